@@ -0,0 +1,65 @@
+#!/usr/bin/env bash
+# Translates a package with a struct named like the error class of one of its error constants, and
+# checks that it fails with one error at the struct, pointing at the constant too. Also checks that
+# an #[error] constant that is not a u64 only gets a warning, and the module still translates.
+# Run from the repository root after `cargo build`:  move-testsuite/expect-fail/abort_error_classes.sh
+set -euo pipefail
+
+MOVE_TO_TS=${MOVE_TO_TS:-$(pwd)/target/debug/move-to-ts}
+WORK=$(mktemp -d)
+trap 'rm -rf "$WORK"' EXIT
+
+package() {
+  local dir=$1
+  mkdir -p "$WORK/$dir/sources"
+  cat > "$WORK/$dir/Move.toml" <<TOML
+[package]
+name = "Errors"
+version = "0.0.1"
+
+[addresses]
+errors = "0xe1"
+TOML
+  cat > "$WORK/$dir/sources/market.move"
+}
+
+package clash <<MOVE
+module errors::market {
+    const E_CLOSED: u64 = 1;
+
+    struct MarketError_E_CLOSED has drop { code: u64 }
+
+    public fun close() { abort E_CLOSED }
+}
+MOVE
+if OUTPUT=$("$MOVE_TO_TS" -p "$WORK/clash" -o "$WORK/clash/ts" 2>&1); then
+  echo "FAIL: translating a struct named like an error class succeeded"
+  exit 1
+fi
+COUNT=$(grep -c "^error" <<< "$OUTPUT" || true)
+if ! grep -qF "Struct MarketError_E_CLOSED takes the name of the error class of E_CLOSED" <<< "$OUTPUT" \
+  || ! grep -qF "E_CLOSED declared here" <<< "$OUTPUT" || [ "$COUNT" -ne 1 ]; then
+  echo "FAIL: expected one error about the struct taking the class name, got:"
+  echo "$OUTPUT"
+  exit 1
+fi
+
+package not_u64 <<MOVE
+module errors::market {
+    #[error]
+    const CLOSED: vector<u8> = b"closed";
+
+    public fun closed(): vector<u8> { CLOSED }
+}
+MOVE
+if ! OUTPUT=$("$MOVE_TO_TS" -p "$WORK/not_u64" -o "$WORK/not_u64/ts" 2>&1); then
+  echo "FAIL: a non-u64 #[error] constant should not fail the module, got:"
+  echo "$OUTPUT"
+  exit 1
+fi
+if ! grep -qF "#[error] constant CLOSED is not a u64 abort code" <<< "$OUTPUT"; then
+  echo "FAIL: expected a warning about the non-u64 #[error] constant, got:"
+  echo "$OUTPUT"
+  exit 1
+fi
+echo "Passed: error classes do not collide with structs, and a non-u64 #[error] is only a warning"
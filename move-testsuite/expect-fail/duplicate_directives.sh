@@ -0,0 +1,83 @@
+#!/usr/bin/env bash
+# Translates packages whose directives would generate the same thing twice, and checks that each
+# fails with one error pointing at both directives, instead of a duplicate member or command that
+# only tsc or commander reports: #[cmd] on functions of two modules named vault generating the
+# same cli command, and #[method] on two functions that --naming camel gives the same member name,
+# which --hide-internal keeps apart as functions by prefixing the private one.
+# A repeated #[method] on one struct is rejected by the Move compiler, with both locations.
+# Run from the repository root after `cargo build`:  move-testsuite/expect-fail/duplicate_directives.sh
+set -euo pipefail
+
+MOVE_TO_TS=${MOVE_TO_TS:-$(pwd)/target/debug/move-to-ts}
+WORK=$(mktemp -d)
+trap 'rm -rf "$WORK"' EXIT
+
+package() {
+  local dir=$1
+  mkdir -p "$WORK/$dir/sources"
+  cat > "$WORK/$dir/Move.toml" <<TOML
+[package]
+name = "Duplicates"
+version = "0.0.1"
+
+[addresses]
+first = "0xd1"
+second = "0xd2"
+TOML
+  cat > "$WORK/$dir/sources/duplicates.move"
+}
+
+expect_error() {
+  local dir=$1 message=$2 previous=$3
+  shift 3
+  if OUTPUT=$("$MOVE_TO_TS" -p "$WORK/$dir" -o "$WORK/$dir/ts" "$@" 2>&1); then
+    echo "FAIL: translating $dir succeeded"
+    exit 1
+  fi
+  COUNT=$(grep -c "^error" <<< "$OUTPUT" || true)
+  if ! grep -qF "$message" <<< "$OUTPUT" || ! grep -qF "$previous" <<< "$OUTPUT" || [ "$COUNT" -ne 1 ]; then
+    echo "FAIL: expected one error about $message for $dir, got:"
+    echo "$OUTPUT"
+    exit 1
+  fi
+}
+
+package cmd <<MOVE
+module first::vault {
+    #[cmd]
+    public entry fun deposit(_account: &signer, _amount: u64) {}
+}
+
+module second::vault {
+    #[cmd(desc=b"Deposit into the second vault")]
+    public entry fun deposit(_account: &signer, _amount: u64) {}
+}
+MOVE
+expect_error cmd "The cli command vault:deposit is generated twice" \
+  "The command is first generated for this directive" -c
+
+package method <<MOVE
+module first::positions {
+    #[method(get_size, getSize)]
+    struct Position has key, store, drop { size: u64 }
+
+    public fun get_size(position: &Position): u64 { position.size }
+
+    fun getSize(position: &Position): u64 { position.size * 2 }
+}
+MOVE
+expect_error method "would both be named getSize" "is a method here" --naming camel --hide-internal
+
+package repeated <<MOVE
+module first::positions {
+    #[method(size)]
+    #[method(size, owner)]
+    struct Position has key, store, drop { size: u64, owner: address }
+
+    fun size(position: &Position): u64 { position.size }
+
+    fun owner(position: &Position): address { position.owner }
+}
+MOVE
+expect_error repeated "Duplicate attribute 'method'" "previously given here"
+echo "Passed: one error for each duplicated directive"
@@ -0,0 +1,80 @@
+#!/usr/bin/env bash
+# Translates packages whose #[query_by_key] directive names a registry that cannot serve it, and
+# checks that each fails with one error saying which check failed: a registry without the key
+# ability, a field that is not a Table or IterableTable, and a table holding values of another
+# struct, the latter two also pointing at the field's declaration. aptos_std comes through the
+# same Econia dependency as move-testsuite, fetched when that is built.
+# Run from the repository root after `cargo build`:  move-testsuite/expect-fail/query_by_key.sh
+set -euo pipefail
+
+MOVE_TO_TS=${MOVE_TO_TS:-$(pwd)/target/debug/move-to-ts}
+WORK=$(mktemp -d)
+trap 'rm -rf "$WORK"' EXIT
+
+package() {
+  local dir=$1
+  mkdir -p "$WORK/$dir/sources"
+  cat > "$WORK/$dir/Move.toml" <<TOML
+[package]
+name = "Registries"
+version = "0.0.1"
+
+[addresses]
+registries = "0xe1"
+
+[dependencies]
+Econia = { git = "https://github.com/econia-labs/econia.git", subdir = "src/move/econia", rev = "711cb00e05b13ca03498b7f413ac21e65a8cc027" }
+TOML
+  cat > "$WORK/$dir/sources/registries.move"
+}
+
+expect_error() {
+  local dir=$1 message=$2 secondary=$3
+  if OUTPUT=$("$MOVE_TO_TS" -p "$WORK/$dir" -o "$WORK/$dir/ts" 2>&1); then
+    echo "FAIL: translating $dir succeeded"
+    exit 1
+  fi
+  COUNT=$(grep -c "^error" <<< "$OUTPUT" || true)
+  if ! grep -qF "$message" <<< "$OUTPUT" || ! grep -qF "$secondary" <<< "$OUTPUT" || [ "$COUNT" -ne 1 ]; then
+    echo "FAIL: expected one error about $message for $dir, got:"
+    echo "$OUTPUT"
+    exit 1
+  fi
+}
+
+package no_key <<MOVE
+module registries::profiles {
+    use aptos_std::table::Table;
+
+    #[query_by_key(registry = Profiles, field = by_owner)]
+    struct Profile has store { score: u64 }
+
+    struct Profiles has store { by_owner: Table<address, Profile> }
+}
+MOVE
+expect_error no_key "Profiles does not have the key ability" "cannot be loaded as a registry"
+
+package not_table <<MOVE
+module registries::profiles {
+    #[query_by_key(registry = Profiles, field = by_owner)]
+    struct Profile has store { score: u64 }
+
+    struct Profiles has key { by_owner: vector<Profile> }
+}
+MOVE
+expect_error not_table "Field by_owner of Profiles is not a Table or IterableTable" "by_owner is declared here"
+
+package other_value <<MOVE
+module registries::profiles {
+    use aptos_std::table::Table;
+
+    #[query_by_key(registry = Profiles, field = by_owner)]
+    struct Profile has store { score: u64 }
+
+    struct Badge has store { level: u8 }
+
+    struct Profiles has key { by_owner: Table<address, Badge> }
+}
+MOVE
+expect_error other_value "The values of Profiles.by_owner are not Profile" "The value type is declared here"
+echo "Passed: one error for each registry #[query_by_key] cannot use"
@@ -0,0 +1,69 @@
+#!/usr/bin/env bash
+# Translates packages whose #[ts_alias] cannot be exported, and checks that each fails with one
+# error at the directive: an old name that is still the name of a function of the module, pointing
+# at that function too, the same old name given by two functions, and an alias that is not
+# assigned a byte string.
+# Run from the repository root after `cargo build`:  move-testsuite/expect-fail/ts_alias.sh
+set -euo pipefail
+
+MOVE_TO_TS=${MOVE_TO_TS:-$(pwd)/target/debug/move-to-ts}
+WORK=$(mktemp -d)
+trap 'rm -rf "$WORK"' EXIT
+
+package() {
+  local dir=$1
+  mkdir -p "$WORK/$dir/sources"
+  cat > "$WORK/$dir/Move.toml" <<TOML
+[package]
+name = "Aliases"
+version = "0.0.1"
+
+[addresses]
+aliases = "0xa1"
+TOML
+  cat > "$WORK/$dir/sources/aliases.move"
+}
+
+expect_error() {
+  local dir=$1 message=$2 secondary=$3
+  if OUTPUT=$("$MOVE_TO_TS" -p "$WORK/$dir" -o "$WORK/$dir/ts" 2>&1); then
+    echo "FAIL: translating $dir succeeded"
+    exit 1
+  fi
+  COUNT=$(grep -c "^error" <<< "$OUTPUT" || true)
+  if ! grep -qF "$message" <<< "$OUTPUT" || ! grep -qF "$secondary" <<< "$OUTPUT" || [ "$COUNT" -ne 1 ]; then
+    echo "FAIL: expected one error about $message for $dir, got:"
+    echo "$OUTPUT"
+    exit 1
+  fi
+}
+
+package existing <<MOVE
+module aliases::counter {
+    #[ts_alias = b"get"]
+    public fun value(): u64 { 1 }
+
+    public fun get(): u64 { 2 }
+}
+MOVE
+expect_error existing "The alias get of value collides with get_" "get declared here"
+
+package twice <<MOVE
+module aliases::counter {
+    #[ts_alias = b"get"]
+    public fun value(): u64 { 1 }
+
+    #[ts_alias = b"get"]
+    public fun amount(): u64 { 2 }
+}
+MOVE
+expect_error twice "collides with get_" "alias get of"
+
+package unassigned <<MOVE
+module aliases::counter {
+    #[ts_alias(get)]
+    public fun value(): u64 { 1 }
+}
+MOVE
+expect_error unassigned "the 'ts_alias' attribute takes the old name" "#[ts_alias(get)]"
+echo "Passed: one error for each #[ts_alias] that cannot be exported"
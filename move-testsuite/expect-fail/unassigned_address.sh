@@ -0,0 +1,47 @@
+#!/usr/bin/env bash
+# Translates a package whose module lives at a named address without a value, and checks that
+# move-to-ts fails with one error naming the address, instead of one per use of it.
+# Run from the repository root after `cargo build`:  move-testsuite/expect-fail/unassigned_address.sh
+set -euo pipefail
+
+MOVE_TO_TS=${MOVE_TO_TS:-$(pwd)/target/debug/move-to-ts}
+WORK=$(mktemp -d)
+trap 'rm -rf "$WORK"' EXIT
+
+mkdir -p "$WORK/sources"
+cat > "$WORK/Move.toml" <<TOML
+[package]
+name = "Unassigned"
+version = "0.0.1"
+
+[addresses]
+Unassigned = "_"
+TOML
+
+cat > "$WORK/sources/Pool.move" <<MOVE
+module Unassigned::Pool {
+    struct Pool has key { reserve: u64 }
+    struct Lp<phantom T> has key { value: u64 }
+
+    public entry fun create(account: &signer) {
+        move_to(account, Pool { reserve: 0 });
+        move_to(account, Lp<Pool> { value: 0 });
+    }
+
+    public fun reserve(addr: address): u64 acquires Pool {
+        borrow_global<Pool>(addr).reserve
+    }
+}
+MOVE
+
+if OUTPUT=$("$MOVE_TO_TS" -p "$WORK" -o "$WORK/ts" 2>&1); then
+  echo "FAIL: translation succeeded"
+  exit 1
+fi
+COUNT=$(grep -c "^error" <<< "$OUTPUT" || true)
+if ! grep -q "named address 'Unassigned' has no value" <<< "$OUTPUT" || [ "$COUNT" -ne 1 ]; then
+  echo "FAIL: expected one error about Unassigned, got:"
+  echo "$OUTPUT"
+  exit 1
+fi
+echo "Passed: one error for the unassigned address"
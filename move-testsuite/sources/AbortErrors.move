@@ -0,0 +1,60 @@
+// aborts with the error constants of the module throw their MoveAbortError subclasses. Constants
+// named E_... or ERROR_..., or marked #[error], are error constants; other aborts, and aborts
+// with a computed code, throw $.abortCode as before. E_SHUT shares the code of ERROR_CLOSED
+module MoveToTsTestsuite::AbortErrors {
+    const E_NOT_OWNER: u64 = 1;
+    const ERROR_CLOSED: u64 = 2;
+    const E_SHUT: u64 = 2;
+    #[error]
+    const EINSUFFICIENT: u64 = 3;
+    const MAX_SIZE: u64 = 100;
+    const E_LABEL: vector<u8> = b"not a code";
+
+    public fun check_owner(owner: address, caller: address) {
+        assert!(owner == caller, E_NOT_OWNER);
+    }
+
+    public fun check_open(closed: bool) {
+        if (closed) {
+            abort ERROR_CLOSED
+        }
+    }
+
+    public fun check_running(shut: bool) {
+        if (shut) {
+            abort E_SHUT
+        }
+    }
+
+    public fun withdraw(balance: u64, amount: u64): u64 {
+        assert!(amount <= balance, EINSUFFICIENT);
+        assert!(amount <= MAX_SIZE, MAX_SIZE);
+        balance - amount
+    }
+
+    public fun check_category(code: u64) {
+        abort (0x10000 + code)
+    }
+
+    public fun label(): vector<u8> {
+        E_LABEL
+    }
+
+    #[test]
+    #[expected_failure(abort_code = 1)]
+    fun test_not_owner() {
+        check_owner(@0x1, @0x2);
+    }
+
+    #[test]
+    #[expected_failure(abort_code = 3)]
+    fun test_insufficient() {
+        withdraw(1, 2);
+    }
+
+    #[test]
+    fun test_withdraw() {
+        check_open(false);
+        assert!(withdraw(5, 2) == 3, 0);
+    }
+}
@@ -0,0 +1,31 @@
+// calls that land in a loop pre-block and in a conditional expression, which have to be awaited
+// from inside the enclosing function when translated with --asynchronous
+module MoveToTsTestsuite::AsyncCalls {
+    fun next(i: u64): u64 {
+        i + 1
+    }
+
+    fun is_even(i: u64): bool {
+        i % 2 == 0
+    }
+
+    fun count_to(limit: u64): u64 {
+        let i = 0;
+        while (next(i) <= limit) {
+            i = next(i);
+        };
+        i
+    }
+
+    fun halve_or_next(i: u64): u64 {
+        let result = if (is_even(i)) i / 2 else next(i);
+        result
+    }
+
+    #[test]
+    fun test_calls_in_nested_positions() {
+        assert!(count_to(5) == 5, 0);
+        assert!(halve_or_next(4) == 2, 1);
+        assert!(halve_or_next(3) == 4, 2);
+    }
+}
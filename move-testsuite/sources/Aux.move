@@ -0,0 +1,53 @@
+// package `aux`, module `aux` and struct `Aux` all share one name
+module aux::aux {
+    struct Aux has copy, drop, store {
+        value: u64,
+    }
+
+    public fun new(value: u64): Aux {
+        Aux { value }
+    }
+
+    public fun value(a: &Aux): u64 {
+        a.value
+    }
+}
+
+module MoveToTsTestsuite::aux {
+    struct Aux has copy, drop, store {
+        doubled: u64,
+    }
+
+    public fun double(value: u64): Aux {
+        Aux { doubled: value * 2 }
+    }
+
+    public fun doubled(a: &Aux): u64 {
+        a.doubled
+    }
+}
+
+// refers to its own Aux, the same-package aux module and the aux package at once
+module MoveToTsTestsuite::AuxUser {
+    use aux::aux;
+    use MoveToTsTestsuite::aux as local_aux;
+
+    struct Aux has copy, drop {
+        external: aux::Aux,
+        local: local_aux::Aux,
+    }
+
+    fun make(value: u64): Aux {
+        Aux {
+            external: aux::new(value),
+            local: local_aux::double(value),
+        }
+    }
+
+    #[test]
+    fun test_three_way_name_collision() {
+        let a = make(3);
+        assert!(aux::value(&a.external) == 3, 0);
+        assert!(local_aux::doubled(&a.local) == 6, 1);
+    }
+}
@@ -0,0 +1,57 @@
+// with --gen-test, integer arithmetic must abort where the VM does: on overflow, on underflow, on
+// division by zero and on shifts by at least the width of the operand. Shifting bits out to the
+// left is not an error and drops them
+module MoveToTsTestsuite::CheckedArithmetic {
+    const MAX_U64: u64 = 18446744073709551615;
+    const MAX_U128: u128 = 340282366920938463463374607431768211455;
+
+    #[test]
+    fun test_in_range() {
+        assert!(MAX_U64 - 1 + 1 == MAX_U64, 0);
+        assert!((MAX_U64 as u128) * (MAX_U64 as u128) < MAX_U128, 1);
+        assert!(255u8 << 1 == 254, 2);
+        assert!(1u64 << 63 >> 63 == 1, 3);
+    }
+
+    #[test]
+    #[expected_failure(arithmetic_error)]
+    fun test_add_overflow() {
+        let a = MAX_U64;
+        a + 1;
+    }
+
+    #[test]
+    #[expected_failure(arithmetic_error)]
+    fun test_mul_overflow() {
+        let a = MAX_U128 / 2;
+        a * 3;
+    }
+
+    #[test]
+    #[expected_failure(arithmetic_error)]
+    fun test_chain_overflow() {
+        let a = 200u8;
+        a + 50 + 5 + 1;
+    }
+
+    #[test]
+    #[expected_failure(arithmetic_error)]
+    fun test_sub_underflow() {
+        let a = 1u64;
+        a - 2;
+    }
+
+    #[test]
+    #[expected_failure(arithmetic_error)]
+    fun test_div_by_zero() {
+        let a = 0u64;
+        10 / a;
+    }
+
+    #[test]
+    #[expected_failure(arithmetic_error)]
+    fun test_shift_too_far() {
+        let a = 8u8;
+        1u8 << a;
+    }
+}
@@ -0,0 +1,35 @@
+// a #[method] on a key struct whose signature uses a struct declared further down the module, so
+// the printer in cli.ts is generated long after this module was translated. Pool.ts should use
+// the bare Quote class, cli.ts should reach both through the package import
+module MoveToTsTestsuite::CliPool {
+    #[method(quote)]
+    struct Pool has key, store, drop {
+        reserve_x: u64,
+        reserve_y: u64,
+    }
+
+    fun quote(pool: &Pool, amount_x: u64): Quote {
+        Quote { amount_x, amount_y: amount_x * pool.reserve_y / pool.reserve_x }
+    }
+
+    struct Quote has copy, store, drop {
+        amount_x: u64,
+        amount_y: u64,
+    }
+
+    #[test]
+    fun test_quote() {
+        let pool = Pool { reserve_x: 2, reserve_y: 6 };
+        let q = quote(&pool, 4);
+        assert!(q.amount_y == 12, 0);
+    }
+}
+
+// translated after CliPool, so it is the current module when the cli printers get generated
+module MoveToTsTestsuite::CliPoolUser {
+    use MoveToTsTestsuite::CliPool::Pool;
+
+    struct Wrapper has key {
+        pool: Pool,
+    }
+}
@@ -0,0 +1,35 @@
+// fields whose TS members would collide: typeTag and typeTag__ both become typeTag__, and with
+// --naming camel so do type and type_. Later fields get a `$N` suffix, and protos stay keyed by the
+// Move names, so packing, unpacking and copying must keep every field
+module MoveToTsTestsuite::CollidingFields {
+    struct Tagged has copy, drop, store {
+        type: u64,
+        type_: u64,
+        typeTag: u64,
+        typeTag__: u64,
+        new: u64,
+    }
+
+    public fun make(a: u64, b: u64): Tagged {
+        Tagged { type: a, type_: b, typeTag: a + 1, typeTag__: b + 1, new: a + b }
+    }
+
+    public fun swapped(t: Tagged): Tagged {
+        let Tagged { type, type_, typeTag, typeTag__, new } = t;
+        Tagged { type: type_, type_: type, typeTag: typeTag__, typeTag__: typeTag, new }
+    }
+
+    #[test]
+    fun test_pack_and_unpack() {
+        let t = make(1, 2);
+        assert!(t.type == 1 && t.type_ == 2, 0);
+        assert!(t.typeTag == 2 && t.typeTag__ == 3, 1);
+        assert!(t.new == 3, 2);
+        let s = swapped(copy t);
+        assert!(s.type == 2 && s.type_ == 1, 3);
+        assert!(s.typeTag == 3 && s.typeTag__ == 2, 4);
+        s.type_ = 5;
+        assert!(t.type_ == 2 && s.type_ == 5, 5);
+        assert!(swapped(s) == Tagged { type: 5, type_: 2, typeTag: 2, typeTag__: 3, new: 3 }, 6);
+    }
+}
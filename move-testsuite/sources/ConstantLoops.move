@@ -0,0 +1,61 @@
+// loops with a constant condition: a while (false) translates to nothing but its pre-block, whose
+// side effects still happen, nothing after a while (true) without a break is translated, and the
+// loop of spin, which nothing ends, is reported as a warning
+module MoveToTsTestsuite::ConstantLoops {
+    public fun never_entered(n: u64): u64 {
+        let calls = 0;
+        while ({ calls = calls + 1; false }) {
+            let doubled = n * 2;
+            n = doubled;
+        };
+        while (false) {
+            n = n + 1;
+        };
+        n + calls
+    }
+
+    public fun first_power_above(limit: u64): u64 {
+        let power = 1;
+        loop {
+            if (power > limit) return power;
+            power = power * 2;
+        }
+    }
+
+    // while (true) has the type of any while loop, so the function still ends with a value,
+    // which is never reached and so neither translated nor hoisted
+    public fun count_down(n: u64): u64 {
+        while (true) {
+            if (n == 0) return 0;
+            n = n - 1;
+        };
+        let unreached = n + 1;
+        unreached
+    }
+
+    // the same for a loop inside a branch, after which nothing of the branch is translated
+    public fun count_down_if(n: u64, enabled: bool): u64 {
+        if (enabled) {
+            while (true) {
+                if (n == 0) return 0;
+                n = n - 1;
+            };
+            let unreached_in_branch = n + 1;
+            n = unreached_in_branch;
+        };
+        n
+    }
+
+    public fun spin() {
+        loop {}
+    }
+
+    #[test]
+    fun test_constant_loops() {
+        assert!(never_entered(3) == 4, 0);
+        assert!(first_power_above(100) == 128, 1);
+        assert!(count_down(5) == 0, 2);
+        assert!(count_down_if(5, true) == 0, 3);
+        assert!(count_down_if(5, false) == 5, 4);
+    }
+}
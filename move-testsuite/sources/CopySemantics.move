@@ -0,0 +1,72 @@
+// with --gen-test, a value stored by move_to, pushed onto a vector or packed into a struct must be
+// a copy of the value the caller keeps, so mutating one afterwards leaves the other as it was.
+// Constants and copied addresses must not be changed through a mutable reference to a copy either
+module MoveToTsTestsuite::CopySemantics {
+    use std::signer;
+    use std::vector;
+
+    struct Holder has key, store, copy, drop {
+        items: vector<u64>,
+        count: u64,
+        owner: address,
+    }
+
+    struct Wrapper has drop {
+        inner: Holder,
+    }
+
+    const SEED: vector<u64> = vector[1, 2];
+    const START: u64 = 10;
+
+    #[test_only]
+    fun holder(owner: address): Holder {
+        Holder { items: SEED, count: START, owner }
+    }
+
+    #[test(account = @0x1234)]
+    fun test_move_to_copy(account: &signer) acquires Holder {
+        let addr = signer::address_of(account);
+        let h = holder(addr);
+        move_to(account, copy h);
+        h.count = 0;
+        vector::push_back(&mut h.items, 3);
+        let stored = borrow_global_mut<Holder>(addr);
+        assert!(stored.count == START, 0);
+        assert!(vector::length(&stored.items) == 2, 1);
+        let snapshot = *stored;
+        stored.count = 1;
+        vector::push_back(&mut stored.items, 3);
+        assert!(snapshot.count == START, 2);
+        assert!(vector::length(&snapshot.items) == 2, 3);
+    }
+
+    #[test(account = @0x1234)]
+    fun test_vector_and_pack_copy(account: &signer) {
+        let h = holder(signer::address_of(account));
+        let v = vector::empty<Holder>();
+        vector::push_back(&mut v, copy h);
+        let w = Wrapper { inner: copy h };
+        h.count = 0;
+        vector::push_back(&mut h.items, 3);
+        assert!(vector::borrow(&v, 0).count == START, 0);
+        assert!(vector::length(&vector::borrow(&v, 0).items) == 2, 1);
+        assert!(w.inner.count == START, 2);
+        assert!(vector::length(&w.inner.items) == 2, 3);
+    }
+
+    #[test]
+    fun test_constants_and_addresses() {
+        let seed = SEED;
+        vector::push_back(&mut seed, 3);
+        assert!(vector::length(&SEED) == 2, 0);
+        let start = START;
+        let r = &mut start;
+        *r = 0;
+        assert!(START == 10, 1);
+        let a = @0x1;
+        let b = a;
+        let rb = &mut b;
+        *rb = @0x2;
+        assert!(a == @0x1, 2);
+    }
+}
@@ -0,0 +1,24 @@
+// module names that differ only in case map to the same capitalized namespace, and to file names
+// that only differ in case, so cpswap is written to cpswap-1.ts
+module MoveToTsTestsuite::cpswap {
+    public fun fee(): u64 {
+        3
+    }
+}
+
+module MoveToTsTestsuite::Cpswap {
+    public fun fee(): u64 {
+        5
+    }
+}
+
+module MoveToTsTestsuite::CpswapUser {
+    use MoveToTsTestsuite::cpswap;
+    use MoveToTsTestsuite::Cpswap;
+
+    #[test]
+    fun test_namespaces_do_not_collide() {
+        assert!(cpswap::fee() == 3, 0);
+        assert!(Cpswap::fee() == 5, 1);
+    }
+}
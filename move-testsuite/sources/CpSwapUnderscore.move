@@ -0,0 +1,23 @@
+// a module name with an underscore next to one without, which capitalize and the file names keep
+// apart, and which sort between Cpswap and cpswap when namespaces are handed out
+module MoveToTsTestsuite::cp_swap {
+    public fun fee(): u64 {
+        30
+    }
+}
+
+module MoveToTsTestsuite::CpSwapUnderscoreUser {
+    use MoveToTsTestsuite::cp_swap;
+    use MoveToTsTestsuite::cpswap;
+
+    public fun total_fee(): u64 {
+        cp_swap::fee() + cpswap::fee()
+    }
+
+    #[test]
+    fun test_underscore_namespaces_do_not_collide() {
+        assert!(cp_swap::fee() == 30, 0);
+        assert!(cpswap::fee() == 3, 1);
+        assert!(total_fee() == 33, 2);
+    }
+}
@@ -0,0 +1,35 @@
+// vector::destroy_empty only matters to Move's resource discipline, so its calls translate to an
+// inline empty check instead of a runtime native, and option::destroy_none reaches it through the
+// translated body of 0x1::option
+module MoveToTsTestsuite::DisciplineNatives {
+    use std::option;
+    use std::vector;
+
+    public fun drain_sum(n: u64): u64 {
+        let v = vector::empty<u64>();
+        let i = 0;
+        while (i < n) {
+            vector::push_back(&mut v, i);
+            i = i + 1;
+        };
+        let sum = 0;
+        while (!vector::is_empty(&v)) {
+            sum = sum + vector::pop_back(&mut v);
+        };
+        vector::destroy_empty(v);
+        option::destroy_none(option::none<u64>());
+        sum
+    }
+
+    #[test]
+    fun test_drain_sum() {
+        assert!(drain_sum(5) == 10, 0);
+        assert!(drain_sum(0) == 0, 1);
+    }
+
+    #[test]
+    #[expected_failure]
+    fun test_destroy_non_empty() {
+        vector::destroy_empty(vector::singleton(1u8));
+    }
+}
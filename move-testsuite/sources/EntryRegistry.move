@@ -0,0 +1,41 @@
+// entry functions with and without a buildPayload_. ALL_ENTRY_FUNCTIONS lists deposit (u64 and
+// vector-u8 params), swap (2 type params, address and vector-vector-u8 params) and rename, but
+// neither set_config, whose struct parameter cannot be passed in a payload, nor the non-entry
+// peek. ALL_STRUCTS lists Config and Vault
+module MoveToTsTestsuite::EntryRegistry {
+    use std::signer;
+
+    struct Config has copy, drop, store {
+        fee_bps: u64,
+    }
+
+    struct Vault has key {
+        amount: u64,
+        memo: vector<u8>,
+    }
+
+    public entry fun deposit(account: &signer, amount: u64, memo: vector<u8>) acquires Vault {
+        let addr = signer::address_of(account);
+        if (exists<Vault>(addr)) {
+            let vault = borrow_global_mut<Vault>(addr);
+            vault.amount = vault.amount + amount;
+            vault.memo = memo;
+        } else {
+            move_to(account, Vault { amount, memo });
+        }
+    }
+
+    public entry fun swap<X, Y>(_account: &signer, _pool: address, _route: vector<vector<u8>>) {
+    }
+
+    public entry fun set_config(_account: &signer, _config: Config) {
+    }
+
+    public entry fun rename(account: &signer, memo: vector<u8>) acquires Vault {
+        borrow_global_mut<Vault>(signer::address_of(account)).memo = memo;
+    }
+
+    public fun peek(addr: address): u64 acquires Vault {
+        borrow_global<Vault>(addr).amount
+    }
+}
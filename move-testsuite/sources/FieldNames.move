@@ -0,0 +1,62 @@
+module MoveToTsTestsuite::FieldNames {
+    // every field here shares its name with a member of the generated class
+    struct Clashing has copy, drop, store, key {
+        typeTag: u64,
+        fields: u64,
+        moduleAddress: address,
+        moduleName: bool,
+        structName: u8,
+        typeParameters: vector<u8>,
+        load: u64,
+        constructor: u64,
+    }
+
+    #[test_only]
+    fun make(): Clashing {
+        Clashing {
+            typeTag: 1,
+            fields: 2,
+            moduleAddress: @MoveToTsTestsuite,
+            moduleName: true,
+            structName: 3,
+            typeParameters: b"tp",
+            load: 4,
+            constructor: 5,
+        }
+    }
+
+    #[test]
+    fun test_borrow_and_mutate() {
+        let c = make();
+        assert!(c.typeTag == 1, 0);
+        assert!(c.constructor == 5, 1);
+        c.typeTag = c.typeTag + c.fields;
+        *&mut c.constructor = 6;
+        assert!(c.typeTag == 3, 2);
+        assert!(c.constructor == 6, 3);
+        assert!(c.load == 4, 4);
+    }
+
+    #[test]
+    fun test_copy_and_unpack() {
+        let c = make();
+        let d = copy c;
+        d.typeTag = 10;
+        assert!(c.typeTag == 1, 0);
+        assert!(c != d, 1);
+        let Clashing { typeTag, fields: _, moduleAddress, moduleName, structName: _, typeParameters, load: _, constructor } = d;
+        assert!(typeTag == 10, 2);
+        assert!(constructor == 5, 3);
+        assert!(moduleAddress == @MoveToTsTestsuite, 4);
+        assert!(moduleName, 5);
+        assert!(typeParameters == b"tp", 6);
+    }
+
+    #[test(admin = @MoveToTsTestsuite)]
+    fun test_global_storage(admin: &signer) acquires Clashing {
+        move_to(admin, make());
+        let c = borrow_global_mut<Clashing>(@MoveToTsTestsuite);
+        c.typeTag = 7;
+        assert!(borrow_global<Clashing>(@MoveToTsTestsuite).typeTag == 7, 0);
+    }
+}
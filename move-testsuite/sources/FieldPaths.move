@@ -0,0 +1,55 @@
+// nested structs, a vector of structs, fields renamed in TS and a Table field, for the
+// getField and getFieldValue path lookups of the generated classes
+module MoveToTsTestsuite::FieldPaths {
+    use std::vector;
+    use aptos_std::table::{Self, Table};
+
+    struct Coin has store, drop {
+        value: u64,
+    }
+
+    struct Reserve has store {
+        coin: Coin,
+        history: vector<Coin>,
+    }
+
+    struct Pool has key {
+        reserve: Reserve,
+        type: u8,
+        positions: Table<address, u64>,
+    }
+
+    // getFieldValue is a member of every struct class, and so is get_field_value with --naming camel
+    struct Lookup has drop {
+        getFieldValue: u64,
+        get_field_value: u64,
+    }
+
+    public fun new_coin(value: u64): Coin {
+        Coin { value }
+    }
+
+    public fun new_pool(value: u64, type: u8): Pool {
+        let history = vector::singleton(Coin { value: 1 });
+        Pool { reserve: Reserve { coin: Coin { value }, history }, type, positions: table::new() }
+    }
+
+    public fun new_lookup(a: u64, b: u64): Lookup {
+        Lookup { getFieldValue: a, get_field_value: b }
+    }
+
+    #[test(owner = @0xb0b)]
+    fun test_new_pool(owner: signer) {
+        let pool = new_pool(7, 2);
+        assert!(pool.reserve.coin.value == 7 && pool.type == 2, 0);
+        move_to(&owner, pool);
+    }
+
+    #[test]
+    fun test_new_lookup() {
+        let lookup = new_lookup(3, 4);
+        assert!(lookup.getFieldValue == 3 && lookup.get_field_value == 4, 1);
+        let Lookup { getFieldValue, get_field_value } = lookup;
+        assert!(getFieldValue + get_field_value == 7, 2);
+    }
+}
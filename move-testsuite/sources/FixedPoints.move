@@ -0,0 +1,19 @@
+// the raw values and decimal digits that typescript/src/tests/fixedPointTests.ts expects from the
+// toDecimalString() generated on FixedPoint32
+module MoveToTsTestsuite::FixedPoints {
+    use std::fixed_point32;
+
+    #[test]
+    fun test_third() {
+        let third = fixed_point32::create_from_rational(1, 3);
+        assert!(fixed_point32::get_raw_value(third) == 1431655765, 0);
+        assert!(fixed_point32::multiply_u64(100000000, third) == 33333333, 1);
+    }
+
+    #[test]
+    fun test_five_halves() {
+        let five_halves = fixed_point32::create_from_rational(5, 2);
+        assert!(fixed_point32::get_raw_value(five_halves) == 10737418240, 0);
+        assert!(fixed_point32::multiply_u64(100000000, five_halves) == 250000000, 1);
+    }
+}
@@ -0,0 +1,28 @@
+// a #[method] on a generic key struct whose wrapped function refers to the struct's type parameter
+// in its return type and in its body, so cli.ts replays it with the type parameter in scope. Type
+// parameters that cannot be resolved are reported at their use instead of panicking the translator
+module MoveToTsTestsuite::GenericCliVault {
+    #[method(receipt_of, holds)]
+    struct Vault<phantom CoinType> has key, store, drop {
+        amount: u64,
+    }
+
+    struct Receipt<phantom CoinType> has copy, store, drop {
+        amount: u64,
+    }
+
+    fun receipt_of<CoinType>(vault: &Vault<CoinType>, scale: u64): Receipt<CoinType> {
+        Receipt<CoinType> { amount: vault.amount * scale }
+    }
+
+    fun holds<CoinType>(vault: &Vault<CoinType>, owner: address): bool {
+        exists<Vault<CoinType>>(owner) && vault.amount > 0
+    }
+
+    #[test]
+    fun test_receipt_of() {
+        let vault = Vault<u8> { amount: 3 };
+        let receipt = receipt_of(&vault, 2);
+        assert!(receipt == Receipt<u8> { amount: 6 }, 0);
+    }
+}
@@ -0,0 +1,19 @@
+// #[cmd] on a generic entry function, the cli command takes one TYPE_ argument per type param,
+// and describes amount_in with the text given for it in the directive
+module MoveToTsTestsuite::GenericCmd {
+    use std::signer;
+
+    struct Swapped<phantom X, phantom Y> has key {
+        amount_in: u64,
+    }
+
+    #[cmd(desc=b"Record a swap from X to Y", amount_in=b"amount of X to swap")]
+    public entry fun swap<X, Y>(user: &signer, amount_in: u64) acquires Swapped {
+        let addr = signer::address_of(user);
+        if (exists<Swapped<X, Y>>(addr)) {
+            borrow_global_mut<Swapped<X, Y>>(addr).amount_in = amount_in;
+        } else {
+            move_to(user, Swapped<X, Y> { amount_in });
+        }
+    }
+}
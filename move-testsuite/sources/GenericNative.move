@@ -0,0 +1,41 @@
+module MoveToTsTestsuite::GenericNative {
+    #[test_only]
+    use aptos_std::table::{Self, Table};
+    #[test_only]
+    use aptos_std::type_info;
+    #[test_only]
+    use std::string::{Self, String};
+
+    // type parameters are declared in the opposite order from the table's <K, V>
+    #[test_only]
+    fun add_swapped<V: drop + store, K: copy + drop>(t: &mut Table<K, V>, k: K, v: V) {
+        table::add<K, V>(t, k, v);
+    }
+
+    #[test_only]
+    fun remove_swapped<V: drop + store, K: copy + drop>(t: &mut Table<K, V>, k: K): V {
+        table::remove<K, V>(t, k)
+    }
+
+    #[test_only]
+    fun names_swapped<V, K>(): (String, String) {
+        (type_info::type_name<K>(), type_info::type_name<V>())
+    }
+
+    #[test]
+    fun test_table_with_swapped_params() {
+        let t = table::new<u64, bool>();
+        add_swapped<bool, u64>(&mut t, 1, true);
+        assert!(table::contains(&t, 1), 0);
+        assert!(*table::borrow(&t, 1), 1);
+        assert!(remove_swapped<bool, u64>(&mut t, 1), 2);
+        table::destroy_empty(t);
+    }
+
+    #[test]
+    fun test_type_names_with_swapped_params() {
+        let (k, v) = names_swapped<bool, u64>();
+        assert!(k == string::utf8(b"u64"), 0);
+        assert!(v == string::utf8(b"bool"), 1);
+    }
+}
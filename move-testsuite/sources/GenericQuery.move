@@ -0,0 +1,20 @@
+// #[query] on a function with two type params where only the second one flows into the result
+// struct, whose output tag must come out as QueryResult<$p[1]>
+module MoveToTsTestsuite::GenericQuery {
+    use std::signer;
+
+    struct Marker<phantom T> has store, drop {}
+
+    struct QueryResult<phantom X> has key, store, drop {
+        amount: u64,
+    }
+
+    #[query]
+    public entry fun query_second<A, B>(user: &signer, amount: u64) acquires QueryResult {
+        let _marker = Marker<A> {};
+        if (exists<QueryResult<B>>(signer::address_of(user))) {
+            move_from<QueryResult<B>>(signer::address_of(user));
+        };
+        move_to<QueryResult<B>>(user, QueryResult<B> { amount })
+    }
+}
@@ -0,0 +1,37 @@
+// typetags used repeatedly, which should be allocated once at module level, and generic ones,
+// which depend on $p and must stay at their use site
+module MoveToTsTestsuite::HoistedTags {
+    use std::signer;
+
+    struct Counter has key {
+        value: u64,
+    }
+
+    struct Tagged<phantom T> has key {
+        value: u64,
+    }
+
+    public fun bump(account: &signer) acquires Counter {
+        let addr = signer::address_of(account);
+        if (!exists<Counter>(addr)) {
+            move_to(account, Counter { value: 0 });
+        };
+        let counter = borrow_global_mut<Counter>(addr);
+        counter.value = counter.value + 1;
+    }
+
+    public fun read(addr: address): u64 acquires Counter {
+        borrow_global<Counter>(addr).value
+    }
+
+    public fun read_tagged<T>(addr: address): u64 acquires Tagged {
+        borrow_global<Tagged<T>>(addr).value
+    }
+
+    #[test(account = @0x123)]
+    fun test_bump(account: signer) acquires Counter {
+        bump(&account);
+        bump(&account);
+        assert!(read(signer::address_of(&account)) == 2, 0);
+    }
+}
@@ -0,0 +1,44 @@
+// else-if chains, which should come out as flat `else if` sequences
+module MoveToTsTestsuite::IfElseChains {
+    public fun four_way(x: u64): u64 {
+        if (x == 0) {
+            10
+        } else if (x == 1) {
+            11
+        } else if (x == 2) {
+            12
+        } else {
+            13
+        }
+    }
+
+    // the first branch is empty, so the chain cannot be negated into a single if
+    public fun empty_branch(x: u64, y: u64): u64 {
+        let result = 0;
+        if (x == y) {
+        } else if (x < y) {
+            result = 1;
+        } else if (x > y + 10) {
+            result = 2;
+        } else {
+            result = 3;
+        };
+        result
+    }
+
+    #[test]
+    fun test_four_way() {
+        assert!(four_way(0) == 10, 0);
+        assert!(four_way(1) == 11, 1);
+        assert!(four_way(2) == 12, 2);
+        assert!(four_way(3) == 13, 3);
+    }
+
+    #[test]
+    fun test_empty_branch() {
+        assert!(empty_branch(5, 5) == 0, 0);
+        assert!(empty_branch(4, 5) == 1, 1);
+        assert!(empty_branch(20, 5) == 2, 2);
+        assert!(empty_branch(6, 5) == 3, 3);
+    }
+}
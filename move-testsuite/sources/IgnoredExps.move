@@ -0,0 +1,38 @@
+// ignored expressions, of which only the calls should be emitted
+module MoveToTsTestsuite::IgnoredExps {
+    struct Counter has key {
+        value: u64,
+    }
+
+    struct Pair has copy, drop {
+        first: u64,
+        second: u64,
+    }
+
+    fun make_pair(first: u64): Pair {
+        Pair { first, second: first + 1 }
+    }
+
+    fun split(x: u64): (u64, u64) {
+        (x / 2, x - x / 2)
+    }
+
+    public fun ignore_tuple(x: u64): u64 {
+        // the tuple returned by split is popped without being bound
+        split(x);
+        x
+    }
+
+    public fun ignore_borrow_of_call(x: u64): u64 {
+        // a borrow of a temporary, only make_pair has to run
+        &make_pair(x).first;
+        (x as u128);
+        x
+    }
+
+    #[test]
+    fun test_ignored() {
+        assert!(ignore_tuple(5) == 5, 0);
+        assert!(ignore_borrow_of_call(7) == 7, 1);
+    }
+}
@@ -0,0 +1,60 @@
+// asserts guarded by a bool constant, in an if on the constant or as the first operand of the
+// condition, are emitted behind $.invariantChecks so that consumers can switch them, and left out
+// with --production. Ifs doing more than assert stay as they are
+module MoveToTsTestsuite::InvariantChecks {
+    const PARANOID: bool = true;
+    const CHECK_FEES: bool = false;
+
+    const E_UNBALANCED: u64 = 1;
+    const E_FEE_TOO_HIGH: u64 = 2;
+
+    struct Pool has drop {
+        reserve_x: u64,
+        reserve_y: u64,
+        fee: u64,
+    }
+
+    // the guarded-if form, with two asserts
+    public fun swap(pool: &mut Pool, amount_in: u64): u64 {
+        let amount_out = amount_in * pool.reserve_y / (pool.reserve_x + amount_in);
+        pool.reserve_x = pool.reserve_x + amount_in;
+        pool.reserve_y = pool.reserve_y - amount_out;
+        if (PARANOID) {
+            assert!(pool.reserve_x > 0, E_UNBALANCED);
+            assert!(pool.reserve_y > 0, E_UNBALANCED);
+        };
+        amount_out
+    }
+
+    // the conjunction form, which keeps reading the constant in its condition
+    public fun set_fee(pool: &mut Pool, fee: u64) {
+        assert!(PARANOID && fee <= 100, E_FEE_TOO_HIGH);
+        pool.fee = fee;
+    }
+
+    // guarded by a constant that is false, so the check does not run
+    public fun set_fee_unchecked(pool: &mut Pool, fee: u64) {
+        if (CHECK_FEES) {
+            assert!(fee <= 100, E_FEE_TOO_HIGH);
+        };
+        pool.fee = fee;
+    }
+
+    // not a guard: the if also changes the pool
+    public fun rebalance(pool: &mut Pool) {
+        if (PARANOID) {
+            pool.reserve_x = pool.reserve_y;
+        };
+    }
+
+    #[test]
+    fun test_invariant_checks() {
+        let pool = Pool { reserve_x: 100, reserve_y: 100, fee: 0 };
+        assert!(swap(&mut pool, 100) == 50, 0);
+        set_fee(&mut pool, 30);
+        // CHECK_FEES is false, so the fee is not checked
+        set_fee_unchecked(&mut pool, 1000);
+        rebalance(&mut pool);
+        assert!(pool.reserve_x == 50 && pool.fee == 1000, 1);
+    }
+}
@@ -0,0 +1,25 @@
+// two #[method]s on one struct, one of them returning a tuple. Their wrappers and the cli show
+// commands are the interesting output with --asynchronous
+module MoveToTsTestsuite::Methods {
+    #[method(total, split)]
+    struct Balance has key, store, drop {
+        free: u64,
+        locked: u64,
+    }
+
+    fun total(balance: &Balance): u64 {
+        balance.free + balance.locked
+    }
+
+    fun split(balance: &Balance, factor: u64): (u64, u64) {
+        (balance.free * factor, balance.locked * factor)
+    }
+
+    #[test]
+    fun test_methods() {
+        let balance = Balance { free: 2, locked: 3 };
+        assert!(total(&balance) == 5, 0);
+        let (free, locked) = split(&balance, 2);
+        assert!(free == 4 && locked == 6, 1);
+    }
+}
@@ -0,0 +1,35 @@
+// nested ifs where only the inner one has an else, which must stay attached to the inner if
+module MoveToTsTestsuite::NestedIfs {
+    public fun inner_else(x: u64, y: u64): u64 {
+        let result = 0;
+        if (x > 0) {
+            if (y > 0) result = 1 else result = 2;
+        };
+        result
+    }
+
+    public fun inner_else_in_else(x: u64, y: u64): u64 {
+        let result = 0;
+        if (x > 0) {
+            result = 5;
+        } else {
+            if (y > 0) result = 6;
+            result = result + 1;
+        };
+        result
+    }
+
+    #[test]
+    fun test_inner_else() {
+        assert!(inner_else(0, 1) == 0, 0);
+        assert!(inner_else(1, 1) == 1, 1);
+        assert!(inner_else(1, 0) == 2, 2);
+    }
+
+    #[test]
+    fun test_inner_else_in_else() {
+        assert!(inner_else_in_else(1, 0) == 5, 0);
+        assert!(inner_else_in_else(0, 1) == 7, 1);
+        assert!(inner_else_in_else(0, 0) == 1, 2);
+    }
+}
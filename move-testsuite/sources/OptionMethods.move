@@ -0,0 +1,27 @@
+// a #[method] returning an Option, which `move-to-ts -c` prints as "None" or {"Some": value}
+// through the accessors generated on 0x1::option::Option
+module MoveToTsTestsuite::OptionMethods {
+    use std::option::{Self, Option};
+
+    #[method(best_bid)]
+    struct Book has key, store, drop {
+        bids: vector<u64>,
+    }
+
+    fun best_bid(book: &Book): Option<u64> {
+        if (std::vector::is_empty(&book.bids)) {
+            option::none()
+        } else {
+            option::some(*std::vector::borrow(&book.bids, 0))
+        }
+    }
+
+    #[test]
+    fun test_best_bid() {
+        let empty = Book { bids: std::vector::empty() };
+        assert!(option::is_none(&best_bid(&empty)), 0);
+        let book = Book { bids: std::vector::singleton(5) };
+        let bid = best_bid(&book);
+        assert!(option::extract(&mut bid) == 5, 1);
+    }
+}
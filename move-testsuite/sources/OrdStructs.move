@@ -0,0 +1,37 @@
+// #[ord] structs get a static comparator and compareTo, ordering by the named field and breaking
+// ties with the fields of then, in their order. Price is compared through its single field
+module MoveToTsTestsuite::OrdStructs {
+    struct Price has store, drop, copy {
+        value: u64,
+    }
+
+    #[ord(price, then=b"timestamp,id")]
+    struct Order has store, drop, copy {
+        price: Price,
+        timestamp: u64,
+        id: u128,
+        owner: address,
+        filled: bool,
+    }
+
+    #[ord(owner)]
+    struct Position has store, drop {
+        owner: address,
+        size: u8,
+    }
+
+    public fun new_order(price: u64, timestamp: u64, id: u128, owner: address): Order {
+        Order { price: Price { value: price }, timestamp, id, owner, filled: false }
+    }
+
+    public fun new_position(owner: address, size: u8): Position {
+        Position { owner, size }
+    }
+
+    #[test]
+    fun test_new_order() {
+        let order = new_order(10, 2, 1, @0x2);
+        assert!(order.price.value == 10 && !order.filled, 0);
+        assert!(new_position(@0x10, 3).size == 3, 1);
+    }
+}
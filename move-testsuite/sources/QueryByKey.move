@@ -0,0 +1,54 @@
+// structs stored in the table field of a registry resource get a static loadByKey through
+// #[query_by_key], for a Table and an IterableTable registry, and for a registry generic over the
+// coin types whose pool values take the same type parameters
+module MoveToTsTestsuite::QueryByKey {
+    use aptos_std::iterable_table::{Self, IterableTable};
+    use aptos_std::table::{Self, Table};
+
+    #[query_by_key(registry = Profiles, field = by_owner)]
+    struct Profile has store, drop {
+        score: u64,
+    }
+
+    struct Profiles has key {
+        by_owner: Table<address, Profile>,
+    }
+
+    #[query_by_key(registry = Pools, field = pools)]
+    struct Pool<phantom X, phantom Y> has store {
+        reserve_x: u64,
+        reserve_y: u64,
+    }
+
+    struct Pools<phantom X, phantom Y> has key {
+        pools: IterableTable<u64, Pool<X, Y>>,
+    }
+
+    struct CoinA {}
+    struct CoinB {}
+
+    public fun add_profile(profiles: &mut Profiles, owner: address, score: u64) {
+        table::add(&mut profiles.by_owner, owner, Profile { score });
+    }
+
+    public fun new_pools<X, Y>(): Pools<X, Y> {
+        Pools<X, Y> { pools: iterable_table::new() }
+    }
+
+    public fun add_pool<X, Y>(pools: &mut Pools<X, Y>, id: u64, reserve_x: u64, reserve_y: u64) {
+        iterable_table::add(&mut pools.pools, id, Pool<X, Y> { reserve_x, reserve_y });
+    }
+
+    #[test(owner = @0xb0b)]
+    fun test_registries(owner: signer) {
+        let profiles = Profiles { by_owner: table::new() };
+        add_profile(&mut profiles, @0xb0b, 5);
+        assert!(table::borrow(&profiles.by_owner, @0xb0b).score == 5, 0);
+        move_to(&owner, profiles);
+
+        let pools = new_pools<CoinA, CoinB>();
+        add_pool(&mut pools, 1, 100, 200);
+        assert!(iterable_table::borrow(&pools.pools, 1).reserve_y == 200, 1);
+        move_to(&owner, pools);
+    }
+}
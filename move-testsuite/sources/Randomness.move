@@ -0,0 +1,23 @@
+// a private #[randomness] entry function gets buildPayload_roll along with
+// requiresRandomness_roll = true, while the plain private entry function next to it gets neither
+module MoveToTsTestsuite::Randomness {
+    use std::signer;
+
+    struct LastRoll has key {
+        sides: u64,
+    }
+
+    #[randomness]
+    entry fun roll(user: &signer, sides: u64) acquires LastRoll {
+        let addr = signer::address_of(user);
+        if (exists<LastRoll>(addr)) {
+            borrow_global_mut<LastRoll>(addr).sides = sides;
+        } else {
+            move_to(user, LastRoll { sides });
+        }
+    }
+
+    entry fun reset(user: &signer) acquires LastRoll {
+        let LastRoll { sides: _ } = move_from<LastRoll>(signer::address_of(user));
+    }
+}
@@ -0,0 +1,97 @@
+// while loops counting a local up to a bound that does not change in the loop are emitted as for
+// loops evaluating the bound once. Loops whose bound the body can change, or that continue past
+// the increment, stay while loops, and all of them must agree with the Move semantics
+module MoveToTsTestsuite::RangeLoops {
+    use std::vector;
+
+    // bound in a local
+    public fun double_all(v: vector<u64>): vector<u64> {
+        let i = 0;
+        let n = vector::length(&v);
+        while (i < n) {
+            let elem = vector::borrow_mut(&mut v, i);
+            *elem = *elem * 2;
+            i = i + 1;
+        };
+        v
+    }
+
+    // bound read through an immutable reference, evaluated once
+    public fun sum_prefix(v: &vector<u64>, skip: u64): u64 {
+        let total = 0;
+        let i = skip;
+        while (i < vector::length(v)) {
+            total = total + *vector::borrow(v, i);
+            i = i + 1;
+        };
+        total
+    }
+
+    // the body grows the vector, so the bound has to be read again in every iteration
+    public fun pad_to_double(v: vector<u64>): vector<u64> {
+        let i = 0;
+        while (i < vector::length(&v)) {
+            if (vector::length(&v) < 6) {
+                vector::push_back(&mut v, 0);
+            };
+            i = i + 1;
+        };
+        v
+    }
+
+    // the bound is behind a mutable reference the body writes through
+    public fun drain_into(from: &mut vector<u64>, to: &mut vector<u64>) {
+        let i = 0;
+        while (i < vector::length(from)) {
+            vector::push_back(to, vector::pop_back(from));
+            i = i + 1;
+        };
+    }
+
+    // continue skips the increment at the end of the body, so this stays a while loop
+    public fun odd_positions(v: &vector<u8>): u64 {
+        let count = 0;
+        let i = 0u8;
+        let n = (vector::length(v) as u8);
+        while (i < n) {
+            if (*vector::borrow(v, (i as u64)) % 2 == 0) {
+                i = i + 1;
+                continue
+            };
+            count = count + 1;
+            i = i + 1;
+        };
+        count
+    }
+
+    public fun nested(rows: u128, cols: u128): u128 {
+        let cells = 0;
+        let r = 0;
+        while (r < rows) {
+            let c = 0;
+            while (c < cols) {
+                cells = cells + r * cols + c;
+                c = c + 1;
+            };
+            r = r + 1;
+        };
+        cells
+    }
+
+    #[test]
+    fun test_range_loops() {
+        let doubled = double_all(vector[1, 2, 3]);
+        assert!(doubled == vector[2, 4, 6], 0);
+        assert!(sum_prefix(&doubled, 1) == 10, 1);
+        assert!(pad_to_double(vector[7, 8, 9]) == vector[7, 8, 9, 0, 0, 0], 2);
+        let from = vector[1, 2, 3, 4];
+        let to = vector[];
+        drain_into(&mut from, &mut to);
+        // two iterations: the bound shrinks as the counter grows
+        assert!(from == vector[1, 2], 3);
+        assert!(to == vector[4, 3], 4);
+        assert!(odd_positions(&vector[1, 2, 3, 5, 6]) == 3, 5);
+        // (0 + 1 + 2) + (3 + 4 + 5)
+        assert!(nested(2, 3) == 15, 6);
+    }
+}
@@ -0,0 +1,40 @@
+// functions taking a vector<signer> cannot get a buildPayload_, since signers only come from the
+// accounts that sign a transaction. Translating them warns instead of panicking, and their bodies
+// are still translated, with the signers as addresses
+module MoveToTsTestsuite::SignerVectors {
+    use std::signer;
+    use std::vector;
+
+    struct Marker has key {
+        count: u64,
+    }
+
+    #[test_only]
+    public entry fun register_all(accounts: vector<signer>) {
+        while (!vector::is_empty(&accounts)) {
+            let account = vector::pop_back(&mut accounts);
+            move_to(&account, Marker { count: 1 });
+        };
+        vector::destroy_empty(accounts);
+    }
+
+    #[test_only]
+    public fun addresses_of(accounts: &vector<signer>): vector<address> {
+        let result = vector::empty<address>();
+        let i = 0;
+        while (i < vector::length(accounts)) {
+            vector::push_back(&mut result, signer::address_of(vector::borrow(accounts, i)));
+            i = i + 1;
+        };
+        result
+    }
+
+    #[test(a = @0x1234, b = @0x5678)]
+    fun test_vector_of_signers(a: signer, b: signer) acquires Marker {
+        let accounts = vector[a, b];
+        let addresses = addresses_of(&accounts);
+        assert!(*vector::borrow(&addresses, 1) == @0x5678, 1);
+        register_all(accounts);
+        assert!(borrow_global<Marker>(@0x1234).count == 1, 2);
+    }
+}
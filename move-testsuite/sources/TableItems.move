@@ -0,0 +1,43 @@
+// structs with 0x1::table::Table fields get tableItemRequest_<field> and getTableItem_<field>, for
+// keys of every kind the table items API takes: integers, addresses, structs, and a type parameter
+// that the instance's type tag resolves
+module MoveToTsTestsuite::TableItems {
+    use aptos_std::table::{Self, Table};
+
+    struct OrderId has copy, drop, store {
+        id: u64,
+        owner: address,
+    }
+
+    struct Book has key {
+        by_id: Table<u64, u128>,
+        by_owner: Table<address, vector<u64>>,
+        orders: Table<OrderId, u64>,
+    }
+
+    struct Registry<K: copy + drop, V: store> has key {
+        entries: Table<K, V>,
+    }
+
+    public fun new_book(): Book {
+        Book { by_id: table::new(), by_owner: table::new(), orders: table::new() }
+    }
+
+    public fun add_order(book: &mut Book, id: u64, owner: address, size: u64) {
+        table::add(&mut book.by_id, id, (size as u128));
+        table::add(&mut book.orders, OrderId { id, owner }, size);
+    }
+
+    public fun new_registry<K: copy + drop, V: store>(): Registry<K, V> {
+        Registry { entries: table::new() }
+    }
+
+    #[test(owner = @0xb0b)]
+    fun test_add_order(owner: signer) {
+        let book = new_book();
+        add_order(&mut book, 7, @0xb0b, 3);
+        assert!(*table::borrow(&book.orders, OrderId { id: 7, owner: @0xb0b }) == 3, 0);
+        move_to(&owner, book);
+        move_to(&owner, new_registry<u64, bool>());
+    }
+}
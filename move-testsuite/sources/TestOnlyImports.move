@@ -0,0 +1,23 @@
+// tests that create their accounts with a #[test_only] helper of aptos_framework, which only exists
+// when packages are compiled for tests, as they are with --gen-test
+module MoveToTsTestsuite::TestOnlyImports {
+    use std::signer;
+    #[test_only]
+    use aptos_framework::account;
+
+    struct Registered has key {
+        owner: address,
+    }
+
+    public fun register(account: &signer) {
+        move_to(account, Registered { owner: signer::address_of(account) });
+    }
+
+    #[test]
+    fun test_register_created_account() acquires Registered {
+        let user = account::create_account_for_test(@0xa11ce);
+        register(&user);
+        assert!(borrow_global<Registered>(@0xa11ce).owner == @0xa11ce, 0);
+        assert!(account::exists_at(@0xa11ce), 1);
+    }
+}
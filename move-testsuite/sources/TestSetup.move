@@ -0,0 +1,31 @@
+// with --gen-test, setup runs once in beforeAll and each test starts from a copy of its state, so
+// test_untouched must still see 0 after test_bump. setup is idempotent so that the Move unit tests,
+// which do not know about #[test_setup], can call it themselves
+module MoveToTsTestsuite::TestSetup {
+    use std::signer;
+
+    struct Counter has key {
+        value: u64,
+    }
+
+    #[test_only, test_setup(admin = @0x1234)]
+    fun setup(admin: &signer) {
+        if (!exists<Counter>(signer::address_of(admin))) {
+            move_to(admin, Counter { value: 0 });
+        }
+    }
+
+    #[test(admin = @0x1234)]
+    fun test_bump(admin: &signer) acquires Counter {
+        setup(admin);
+        let counter = borrow_global_mut<Counter>(signer::address_of(admin));
+        counter.value = counter.value + 1;
+        assert!(counter.value == 1, 0);
+    }
+
+    #[test(admin = @0x1234)]
+    fun test_untouched(admin: &signer) acquires Counter {
+        setup(admin);
+        assert!(borrow_global<Counter>(signer::address_of(admin)).value == 0, 1);
+    }
+}
@@ -0,0 +1,21 @@
+// with --trace, an abort in inner reaches the caller of outer with a frame for each function and
+// its primitive arguments in the moveTrace of the error
+module MoveToTsTestsuite::Traces {
+    public fun inner(amount: u64, owner: address) {
+        if (amount > 0 && owner != @0x0) {
+            abort 7
+        }
+    }
+
+    public fun outer(flag: bool) {
+        if (flag) {
+            inner(5, @0xb0b);
+        }
+    }
+
+    #[test]
+    #[expected_failure(abort_code = 7)]
+    fun test_outer() {
+        outer(true);
+    }
+}
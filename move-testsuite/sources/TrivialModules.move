@@ -0,0 +1,12 @@
+// modules without structs and functions. Their files import only what they use (HexString for
+// moduleAddress, $ for the empty registries, and U64, u64, U8 and u8 for the constants of Limits),
+// have no loadParsers, and the package index does not call one for them
+module MoveToTsTestsuite::Limits {
+    const MAX_FEE_BPS: u64 = 30;
+    const MIN_LIQUIDITY: u64 = 1000;
+    const SEED: vector<u8> = b"limits";
+}
+
+// an empty marker module
+module MoveToTsTestsuite::Marker {
+}
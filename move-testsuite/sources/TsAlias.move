@@ -0,0 +1,23 @@
+// functions renamed since a release keep their old TS names through #[ts_alias], as deprecated
+// exports of the function and of its buildPayload_
+module MoveToTsTestsuite::TsAlias {
+    struct Balance has key {
+        value: u64,
+    }
+
+    #[ts_alias = b"init_balance"]
+    public entry fun create_balance(account: &signer, value: u64) {
+        move_to(account, Balance { value });
+    }
+
+    #[ts_alias = b"get_value"]
+    public fun balance_value(addr: address): u64 acquires Balance {
+        borrow_global<Balance>(addr).value
+    }
+
+    #[test(account = @0x123)]
+    fun test_renamed(account: signer) acquires Balance {
+        create_balance(&account, 7);
+        assert!(balance_value(@0x123) == 7, 0);
+    }
+}
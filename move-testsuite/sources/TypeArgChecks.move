@@ -0,0 +1,25 @@
+// generic functions, an entry function and a generic struct, each of which starts its generated
+// code with a check of the number of type arguments unless --no-runtime-checks is given
+module MoveToTsTestsuite::TypeArgChecks {
+    struct Pair<phantom X, phantom Y> has key, store, drop {
+        value: u64,
+    }
+
+    public fun make<X, Y>(value: u64): Pair<X, Y> {
+        Pair<X, Y> { value }
+    }
+
+    public entry fun publish<X, Y>(account: &signer, value: u64) {
+        move_to(account, make<X, Y>(value));
+    }
+
+    public fun value<X, Y>(pair: &Pair<X, Y>): u64 {
+        pair.value
+    }
+
+    #[test]
+    fun test_make() {
+        let pair = make<u8, u64>(3);
+        assert!(value(&pair) == 3, 0);
+    }
+}
@@ -0,0 +1,38 @@
+// conditionals that yield unit, used as statements for their side effects
+module MoveToTsTestsuite::UnitConditionals {
+    struct Counter has key {
+        value: u64,
+    }
+
+    fun bump(counter: &mut Counter, by: u64) {
+        counter.value = counter.value + by;
+    }
+
+    fun nothing() {}
+
+    public fun bump_if(counter: &mut Counter, cond: bool) {
+        if (cond) bump(counter, 1) else ();
+        if (!cond) nothing();
+        let () = if (cond) nothing() else bump(counter, 10);
+    }
+
+    public fun bump_all(counter: &mut Counter, n: u64) {
+        let i = 0;
+        while (i < n) {
+            if (i % 2 == 0) { bump(counter, i) } else { nothing() };
+            i = i + 1;
+        }
+    }
+
+    #[test]
+    fun test_unit_conditionals() {
+        let counter = Counter { value: 0 };
+        bump_if(&mut counter, true);
+        assert!(counter.value == 1, 0);
+        bump_if(&mut counter, false);
+        assert!(counter.value == 11, 1);
+        bump_all(&mut counter, 5);
+        assert!(counter.value == 17, 2);
+        let Counter { value: _ } = counter;
+    }
+}
@@ -0,0 +1,85 @@
+// constants of type vector<address> and vector<bool>, with none, one and several elements, are
+// arrays of HexString and boolean, and can be passed on to functions that iterate them, including
+// an entry function whose buildPayload_ takes them. Vector literals with none and one element in
+// function bodies, which are not folded into constants, are arrays too
+module MoveToTsTestsuite::VectorConstants {
+    use std::vector;
+
+    const ADMINS: vector<address> = vector[@0x1, @0x2, @0xa11ce];
+    const OWNER: vector<address> = vector[@0xb0b];
+    const NOBODY: vector<address> = vector[];
+    const FLAGS: vector<bool> = vector[true, false, true];
+    const ONE_FLAG: vector<bool> = vector[false];
+    const NO_FLAGS: vector<bool> = vector[];
+
+    struct Admins has key {
+        list: vector<address>,
+    }
+
+    public fun contains_address(list: &vector<address>, addr: address): bool {
+        let i = 0;
+        while (i < vector::length(list)) {
+            if (*vector::borrow(list, i) == addr) {
+                return true
+            };
+            i = i + 1;
+        };
+        false
+    }
+
+    public fun count_true(flags: vector<bool>): u64 {
+        let count = 0;
+        while (!vector::is_empty(&flags)) {
+            if (vector::pop_back(&mut flags)) {
+                count = count + 1;
+            };
+        };
+        count
+    }
+
+    public fun only(addr: address): vector<address> {
+        vector[addr]
+    }
+
+    public fun nobody(): vector<address> {
+        vector[]
+    }
+
+    public fun is_admin(addr: address): bool {
+        contains_address(&ADMINS, addr) || contains_address(&OWNER, addr)
+    }
+
+    public entry fun set_admins(account: &signer, list: vector<address>) {
+        move_to(account, Admins { list });
+    }
+
+    public entry fun set_default_admins(account: &signer) {
+        set_admins(account, ADMINS);
+    }
+
+    #[test]
+    fun test_vector_constants() {
+        assert!(is_admin(@0xa11ce), 0);
+        assert!(is_admin(@0xb0b), 1);
+        assert!(!is_admin(@0xc0ffee), 2);
+        assert!(!contains_address(&NOBODY, @0x1), 3);
+        assert!(count_true(FLAGS) == 2, 4);
+        assert!(count_true(ONE_FLAG) == 0, 5);
+        // each use is a copy, so popping the flags above left the constant alone
+        assert!(vector::length(&FLAGS) == 3, 6);
+        assert!(count_true(NO_FLAGS) == 0, 7);
+        let list = only(@0xb0b);
+        assert!(vector::length(&list) == 1, 8);
+        assert!(contains_address(&list, @0xb0b), 9);
+        assert!(vector::is_empty(&nobody()), 10);
+        vector::push_back(&mut list, @0xa11ce);
+        assert!(vector::length(&list) == 2, 11);
+    }
+
+    #[test(account = @0x123)]
+    fun test_set_default_admins(account: signer) acquires Admins {
+        set_default_admins(&account);
+        let admins = borrow_global<Admins>(@0x123);
+        assert!(contains_address(&admins.list, @0x2), 0);
+    }
+}
@@ -0,0 +1,35 @@
+module MoveToTsTestsuite::Visibility {
+    friend MoveToTsTestsuite::Caller;
+
+    struct Counter has key, store, drop {
+        value: u64,
+    }
+
+    #[method(peek)]
+    struct Holder has key, store, drop {
+        counter: Counter,
+    }
+
+    public entry fun bump(account: &signer) {
+        internal_bump(account);
+    }
+
+    public(friend) fun friend_bump(account: &signer) {
+        internal_bump(account);
+    }
+
+    fun internal_bump(_account: &signer) {
+    }
+
+    fun peek(holder: &Holder): u64 {
+        holder.counter.value
+    }
+}
+
+module MoveToTsTestsuite::Caller {
+    use MoveToTsTestsuite::Visibility;
+
+    public fun call_friend(account: &signer) {
+        Visibility::friend_bump(account);
+    }
+}
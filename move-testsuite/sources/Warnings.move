@@ -0,0 +1,11 @@
+module MoveToTsTestsuite::Warnings {
+    struct Pair has copy, drop, store {
+        first: u64,
+        second: u64,
+    }
+
+    // struct parameters get no buildPayload_, so this is reported as a warning and gets no command
+    #[cmd]
+    public entry fun set_pair(_account: &signer, _pair: Pair) {
+    }
+}
@@ -0,0 +1,36 @@
+// while conditions with a pre-block that unpacks, returns early and aborts, with the body unpacking
+// the same names again
+module MoveToTsTestsuite::WhilePreBlock {
+    struct Pair has copy, drop {
+        first: u64,
+        second: u64,
+    }
+
+    fun pair_at(i: u64): Pair {
+        Pair { first: i, second: i * 2 }
+    }
+
+    public fun sum_until(limit: u64, stop_at: u64): u64 {
+        let i = 0;
+        let total = 0;
+        while ({
+            let Pair { first, second } = pair_at(i);
+            if (first == stop_at) return total + second;
+            assert!(second >= first, 1);
+            first < limit
+        }) {
+            let Pair { first, second } = pair_at(i + 1);
+            total = total + first + second;
+            i = i + 1;
+        };
+        total
+    }
+
+    #[test]
+    fun test_sum_until() {
+        // (1 + 2) + (2 + 4) + (3 + 6)
+        assert!(sum_until(3, 100) == 18, 0);
+        // returns from the pre-block at i == 2, adding its second
+        assert!(sum_until(100, 2) == 13, 1);
+    }
+}
@@ -0,0 +1,108 @@
+#!/usr/bin/env bash
+# Translates a package whose two dependencies name the same address differently (`base_lib` and,
+# through addr_subst, `std_ext`), and checks that the package at that address is emitted once and
+# that both dependencies import it from there. Then checks that --address-alias renames it.
+# Run from the repository root after `cargo build`:  move-testsuite/stress/address_aliases.sh
+set -euo pipefail
+
+MOVE_TO_TS=${MOVE_TO_TS:-$(pwd)/target/debug/move-to-ts}
+WORK=$(mktemp -d)
+trap 'rm -rf "$WORK"' EXIT
+
+mkdir -p "$WORK/base/sources" "$WORK/dep_a/sources" "$WORK/dep_b/sources" "$WORK/root/sources"
+cat > "$WORK/base/Move.toml" <<TOML
+[package]
+name = "Base"
+version = "0.0.1"
+
+[addresses]
+base_lib = "0x777"
+TOML
+cat > "$WORK/base/sources/math.move" <<MOVE
+module base_lib::math {
+    struct Amount has store, drop { value: u64 }
+
+    public fun double(amount: Amount): Amount {
+        Amount { value: amount.value * 2 }
+    }
+}
+MOVE
+
+cat > "$WORK/dep_a/Move.toml" <<TOML
+[package]
+name = "DepA"
+version = "0.0.1"
+
+[addresses]
+dep_a = "0x778"
+
+[dependencies]
+Base = { local = "../base" }
+TOML
+cat > "$WORK/dep_a/sources/use_a.move" <<MOVE
+module dep_a::use_a {
+    use base_lib::math::{Self, Amount};
+
+    public fun twice(amount: Amount): Amount {
+        math::double(math::double(amount))
+    }
+}
+MOVE
+
+cat > "$WORK/dep_b/Move.toml" <<TOML
+[package]
+name = "DepB"
+version = "0.0.1"
+
+[addresses]
+dep_b = "0x779"
+
+[dependencies]
+Base = { local = "../base", addr_subst = { "std_ext" = "base_lib" } }
+TOML
+cat > "$WORK/dep_b/sources/use_b.move" <<MOVE
+module dep_b::use_b {
+    use std_ext::math::{Self, Amount};
+
+    public fun thrice(amount: Amount): Amount {
+        math::double(math::double(math::double(amount)))
+    }
+}
+MOVE
+
+cat > "$WORK/root/Move.toml" <<TOML
+[package]
+name = "AliasRoot"
+version = "0.0.1"
+
+[dependencies]
+DepA = { local = "../dep_a" }
+DepB = { local = "../dep_b" }
+TOML
+
+check() {
+  local out=$1 name=$2 alias=$3
+  if [ ! -f "$out/src/$name/math.ts" ]; then
+    echo "FAIL: expected the package at 0x777 under src/$name"
+    ls "$out/src"
+    exit 1
+  fi
+  if [ -e "$out/src/$alias" ]; then
+    echo "FAIL: the package at 0x777 was also emitted under src/$alias"
+    exit 1
+  fi
+  for module in dep_a/use_a dep_b/use_b; do
+    if ! grep -q "\.\./$name['\"]" "$out/src/$module.ts" || grep -q "\.\./$alias['\"]" "$out/src/$module.ts"; then
+      echo "FAIL: $module.ts should import the package at 0x777 from ../$name"
+      grep "^import" "$out/src/$module.ts"
+      exit 1
+    fi
+  done
+}
+
+"$MOVE_TO_TS" -p "$WORK/root" -o "$WORK/named"
+check "$WORK/named" base_lib std_ext
+
+"$MOVE_TO_TS" -p "$WORK/root" -o "$WORK/renamed" --address-alias base_lib=math_lib
+check "$WORK/renamed" math_lib base_lib
+echo "Passed: one package for an address with two names"
@@ -0,0 +1,71 @@
+#!/usr/bin/env bash
+# Translates a module with a #[method], a #[query] and a resource with --asynchronous, and checks
+# that every async function in the output declares its Promise type, so that the .d.ts files do
+# not fall back to Promise<any>. If tsc is on the PATH, the declarations it emits are checked too.
+# Run from the repository root after `cargo build`:  move-testsuite/stress/async_signatures.sh
+set -euo pipefail
+
+MOVE_TO_TS=${MOVE_TO_TS:-$(pwd)/target/debug/move-to-ts}
+WORK=$(mktemp -d)
+trap 'rm -rf "$WORK"' EXIT
+
+mkdir -p "$WORK/sources"
+cat > "$WORK/Move.toml" <<TOML
+[package]
+name = "AsyncSignatures"
+version = "0.0.1"
+
+[addresses]
+AsyncSignatures = "0x12349"
+TOML
+cat > "$WORK/sources/pool.move" <<MOVE
+module AsyncSignatures::pool {
+    #[method(total)]
+    struct Pool has key, store, drop {
+        a: u64,
+        b: u64,
+    }
+
+    fun total(pool: &Pool): u64 {
+        pool.a + pool.b
+    }
+
+    #[query]
+    public entry fun get_pool(user: &signer, a: u64) {
+        move_to<Pool>(user, Pool { a, b: a })
+    }
+}
+MOVE
+
+"$MOVE_TO_TS" -p "$WORK" -o "$WORK/out" --asynchronous
+OUT="$WORK/out/src/AsyncSignatures/pool.ts"
+
+# signatures may span lines, up to the `{` opening the body
+UNTYPED=$(awk '
+  /async / { sig = ""; open = 1 }
+  open { sig = sig $0; if ($0 ~ /\{[[:space:]]*$/) { open = 0; if (sig !~ /\): Promise<[^>]/ || sig ~ /Promise<any>/) print sig } }
+' "$OUT")
+if [ -n "$UNTYPED" ]; then
+  echo "FAIL: async functions without a concrete Promise type:"
+  echo "$UNTYPED"
+  exit 1
+fi
+
+for expected in \
+  "static async load(repo: AptosParserRepo, client: AptosClient, address: HexString, typeParams: TypeTag\[\]): Promise<Pool> {" \
+  "^): Promise<Pool> {" \
+  "^  *): Promise<U64> {"; do
+  if ! grep -q "$expected" "$OUT"; then
+    echo "FAIL: expected a signature matching: $expected"
+    exit 1
+  fi
+done
+
+if command -v tsc > /dev/null && [ -d "$WORK/out/node_modules" ]; then
+  (cd "$WORK/out" && tsc --declaration --emitDeclarationOnly --outDir "$WORK/dts")
+  if grep -n "Promise<any>" "$WORK/dts/AsyncSignatures/pool.d.ts"; then
+    echo "FAIL: pool.d.ts declares Promise<any>"
+    exit 1
+  fi
+fi
+echo "Passed: async signatures"
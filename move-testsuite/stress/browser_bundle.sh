@@ -0,0 +1,73 @@
+#!/usr/bin/env bash
+# Translates a package with --gen-cli and bundles its index with esbuild for browsers, once with
+# the generated .ext.ts and once after deleting it. The index must not reach the cli, and neither
+# the module files nor the runtime may pull in a Node builtin, which esbuild cannot resolve with
+# --platform=browser.
+# Run from the repository root after `cargo build` and `yarn install` in typescript/:
+#   move-testsuite/stress/browser_bundle.sh
+set -euo pipefail
+
+MOVE_TO_TS=${MOVE_TO_TS:-$(pwd)/target/debug/move-to-ts}
+RUNTIME=$(pwd)/typescript
+ESBUILD=${ESBUILD:-$RUNTIME/node_modules/.bin/esbuild}
+WORK=$(mktemp -d)
+trap 'rm -rf "$WORK"' EXIT
+
+if [ ! -x "$ESBUILD" ]; then
+  echo "FAIL: run yarn install in typescript/ first, or point ESBUILD at an esbuild binary"
+  exit 1
+fi
+
+mkdir -p "$WORK/sources"
+cat > "$WORK/Move.toml" <<TOML
+[package]
+name = "Browser"
+version = "0.0.1"
+
+[addresses]
+browser = "0x7b1"
+TOML
+cat > "$WORK/sources/counter.move" <<MOVE
+module browser::counter {
+    struct Counter has key { value: u64 }
+
+    #[cmd(desc=b"Create a counter")]
+    public entry fun create(account: &signer) {
+        move_to(account, Counter { value: 0 });
+    }
+
+    public fun value(counter: &Counter): u64 { counter.value }
+}
+MOVE
+
+OUT="$WORK/out"
+"$MOVE_TO_TS" -p "$WORK" -o "$OUT" -n browser-bundle -c
+if [ ! -f "$OUT/src/cli.ts" ]; then
+  echo "FAIL: expected a cli.ts"
+  exit 1
+fi
+if grep -qE "\./cli['\"]" "$OUT/src/index.ts" "$OUT/src/browser/index.ts"; then
+  echo "FAIL: an index re-exports the cli"
+  exit 1
+fi
+
+# the generated package resolves its dependencies, and the runtime, from typescript/
+mkdir -p "$OUT/node_modules/@manahippo"
+for dep in "$RUNTIME"/node_modules/*; do
+  if [ "$(basename "$dep")" != "@manahippo" ]; then
+    ln -s "$dep" "$OUT/node_modules/$(basename "$dep")"
+  fi
+done
+ln -s "$RUNTIME" "$OUT/node_modules/@manahippo/move-to-ts"
+
+bundle() {
+  if ! "$ESBUILD" "$OUT/src/index.ts" --bundle --platform=browser --log-level=error \
+    --outfile="$WORK/bundle.js"; then
+    echo "FAIL: the index does not bundle for browsers $1"
+    exit 1
+  fi
+}
+bundle "with counter.ext.ts"
+rm "$OUT/src/browser/counter.ext.ts"
+bundle "without counter.ext.ts"
+echo "Passed: the generated package bundles for browsers"
@@ -0,0 +1,60 @@
+#!/usr/bin/env bash
+# Translates a module with a struct, adds a method to the generated vault.ext.ts by hand, then
+# regenerates with --managed-output and checks that the method survived, that the manifest lists
+# the file as user-owned, and that deleting the file does not break the module's guarded require.
+# Run from the repository root after `cargo build`:  move-testsuite/stress/class_extensions.sh
+set -euo pipefail
+
+MOVE_TO_TS=${MOVE_TO_TS:-$(pwd)/target/debug/move-to-ts}
+WORK=$(mktemp -d)
+trap 'rm -rf "$WORK"' EXIT
+
+mkdir -p "$WORK/sources"
+cat > "$WORK/Move.toml" <<TOML
+[package]
+name = "Extensions"
+version = "0.0.1"
+
+[addresses]
+Extensions = "0x1234a"
+TOML
+cat > "$WORK/sources/vault.move" <<MOVE
+module Extensions::vault {
+    struct Vault has key { amount: u64 }
+
+    public fun amount(vault: &Vault): u64 { vault.amount }
+}
+MOVE
+
+OUT="$WORK/out"
+"$MOVE_TO_TS" -p "$WORK" -o "$OUT" --managed-output
+EXT="$OUT/src/Extensions/vault.ext.ts"
+if [ ! -f "$EXT" ] || ! grep -q "export interface Vault {}" "$OUT/src/Extensions/vault.ts"; then
+  echo "FAIL: expected vault.ext.ts and a merging interface for Vault"
+  exit 1
+fi
+cat >> "$EXT" <<TS
+import { Vault } from "./vault";
+declare module "./vault" { interface Vault { isEmpty(): boolean; } }
+Vault.prototype.isEmpty = function () { return this.amount.toJsNumber() === 0; };
+TS
+
+"$MOVE_TO_TS" -p "$WORK" -o "$OUT" --managed-output
+if ! grep -q "Vault.prototype.isEmpty" "$EXT"; then
+  echo "FAIL: regenerating overwrote the hand-added method"
+  exit 1
+fi
+if ! grep -q "^user-owned: src/Extensions/vault.ext.ts$" "$OUT/.move-to-ts-manifest"; then
+  echo "FAIL: the manifest should list vault.ext.ts as user-owned"
+  cat "$OUT/.move-to-ts-manifest"
+  exit 1
+fi
+if ! "$MOVE_TO_TS" -p "$WORK" -o "$OUT" --check; then
+  echo "FAIL: --check should not report the hand-edited vault.ext.ts"
+  exit 1
+fi
+if ! grep -q 'require("./vault.ext")' "$OUT/src/Extensions/vault.ts"; then
+  echo "FAIL: vault.ts should load vault.ext.ts behind a guard"
+  exit 1
+fi
+echo "Passed: hand-added methods survive regeneration"
@@ -0,0 +1,48 @@
+#!/usr/bin/env bash
+# Translates a package with --gen-cli whose commands a_b:c and a:b-c would both be handled by a TS
+# function named a_b_c, and checks that cli.ts declares two distinct handlers, one for each
+# command, instead of redeclaring a_b_c.
+# Run from the repository root after `cargo build`:  move-testsuite/stress/cli_handler_names.sh
+set -euo pipefail
+
+MOVE_TO_TS=${MOVE_TO_TS:-$(pwd)/target/debug/move-to-ts}
+WORK=$(mktemp -d)
+trap 'rm -rf "$WORK"' EXIT
+
+mkdir -p "$WORK/app/sources"
+cat > "$WORK/app/Move.toml" <<TOML
+[package]
+name = "Names"
+version = "0.0.1"
+
+[addresses]
+names = "0x4a4e"
+TOML
+cat > "$WORK/app/sources/names.move" <<MOVE
+module names::a_b {
+    #[cmd]
+    public entry fun c(_account: &signer) {}
+}
+
+module names::a {
+    #[cmd]
+    public entry fun b_c(_account: &signer) {}
+}
+MOVE
+"$MOVE_TO_TS" -p "$WORK/app" -o "$WORK/app/ts" -c
+CLI="$WORK/app/ts/src/cli.ts"
+expect_count() {
+  local count
+  count=$(grep -cF -- "$1" "$CLI" || true)
+  if [ "$count" -ne "$2" ]; then
+    echo "FAIL: cli.ts has $1 $count times instead of $2"
+    exit 1
+  fi
+}
+expect_count "const a_b_c = async" 1
+expect_count "const a_b_c_2 = async" 1
+expect_count ".command(\"a_b:c\")" 1
+expect_count ".command(\"a:b-c\")" 1
+expect_count ".action(a_b_c)" 1
+expect_count ".action(a_b_c_2)" 1
+echo "Passed: each cli command has its own handler"
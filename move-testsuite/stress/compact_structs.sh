@@ -0,0 +1,55 @@
+#!/usr/bin/env bash
+# Translates a package, move-testsuite and so its whole framework closure by default, with
+# --gen-test both with and without --compact-structs, reports how much smaller the compact output
+# is, and runs the generated jest suite of each against the runtime in typescript/. Both have to
+# pass with the same number of tests.
+# Run from the repository root after `cargo build` and `yarn build` in typescript/, with network
+# access for the yarn install of the generated packages:
+#   [PACKAGE=<dir>] move-testsuite/stress/compact_structs.sh
+set -euo pipefail
+
+MOVE_TO_TS=${MOVE_TO_TS:-$(pwd)/target/debug/move-to-ts}
+PACKAGE=${PACKAGE:-$(pwd)/move-testsuite}
+RUNTIME=$(pwd)/typescript
+WORK=$(mktemp -d)
+trap 'rm -rf "$WORK"' EXIT
+
+if [ ! -f "$RUNTIME/dist/index.d.ts" ]; then
+  echo "FAIL: run yarn install and yarn build in typescript/ first"
+  exit 1
+fi
+
+source_size() {
+  find "$1/src" -name "*.ts" -exec cat {} + | wc -c | tr -d ' '
+}
+
+run_suite() {
+  local mode=$1
+  shift
+  local out="$WORK/$mode"
+  "$MOVE_TO_TS" -p "$PACKAGE" -o "$out" -n "compact-structs-$mode" -t "$@" > "$WORK/$mode.log"
+  (cd "$out" && yarn install --silent > /dev/null)
+  rm -rf "$out/node_modules/@manahippo/move-to-ts"
+  ln -s "$RUNTIME" "$out/node_modules/@manahippo/move-to-ts"
+  if ! (cd "$out" && yarn --silent jest 2> "$WORK/$mode.jest"); then
+    echo "FAIL: the $mode suite does not pass" >&2
+    tail -n 40 "$WORK/$mode.jest" >&2
+    exit 1
+  fi
+  grep "^Tests:" "$WORK/$mode.jest"
+}
+
+full_tests=$(run_suite full)
+compact_tests=$(run_suite compact --compact-structs)
+if [ "$full_tests" != "$compact_tests" ]; then
+  echo "FAIL: the suites ran different tests"
+  echo "  full:    $full_tests"
+  echo "  compact: $compact_tests"
+  exit 1
+fi
+
+full_size=$(source_size "$WORK/full")
+compact_size=$(source_size "$WORK/compact")
+echo "full:    $full_size bytes"
+echo "compact: $compact_size bytes, $(( (full_size - compact_size) * 100 / full_size ))% smaller"
+echo "Passed: $full_tests in both modes"
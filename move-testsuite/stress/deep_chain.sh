@@ -0,0 +1,48 @@
+#!/usr/bin/env bash
+# Translates a generated module holding one expression chain of N additions (10000 by default),
+# then checks that move-to-ts did not crash and that the output is not nested N parentheses deep.
+# Run from the repository root after `cargo build`:  move-testsuite/stress/deep_chain.sh [N]
+set -euo pipefail
+
+N=${1:-10000}
+MOVE_TO_TS=${MOVE_TO_TS:-$(pwd)/target/debug/move-to-ts}
+WORK=$(mktemp -d)
+trap 'rm -rf "$WORK"' EXIT
+
+mkdir -p "$WORK/sources"
+cat > "$WORK/Move.toml" <<TOML
+[package]
+name = "DeepChain"
+version = "0.0.1"
+
+[addresses]
+DeepChain = "0x12347"
+TOML
+
+{
+  echo "module DeepChain::DeepChain {"
+  echo "    public fun total(): u64 {"
+  printf "        1"
+  for ((i = 1; i < N; i++)); do
+    printf " + 1"
+  done
+  echo
+  echo "    }"
+  echo "}"
+} > "$WORK/sources/DeepChain.move"
+
+"$MOVE_TO_TS" -p "$WORK" -o "$WORK/ts"
+
+OUTPUT=$(find "$WORK/ts/src" -name "*.ts" -path "*DeepChain*" | head -n 1)
+DEPTH=$(tr -cd '()' < "$OUTPUT" | awk '{
+  depth = 0; max = 0
+  for (i = 1; i <= length($0); i++) {
+    if (substr($0, i, 1) == "(") { depth++; if (depth > max) max = depth } else depth--
+  }
+  print max
+}')
+if [ "$DEPTH" -gt 16 ]; then
+  echo "FAIL: parentheses nested $DEPTH deep in $OUTPUT"
+  exit 1
+fi
+echo "Passed: $N-node chain translated, parentheses nested $DEPTH deep"
@@ -0,0 +1,108 @@
+#!/usr/bin/env bash
+# Translates a package depending on two others, one of which depends on the third, with
+# --emit-depgraph, and checks the modules and edges of the DOT and JSON graphs: same-package and
+# cross-package edges, and which targets are source modules.
+# Run from the repository root after `cargo build`:  move-testsuite/stress/depgraph.sh
+set -euo pipefail
+
+MOVE_TO_TS=${MOVE_TO_TS:-$(pwd)/target/debug/move-to-ts}
+WORK=$(mktemp -d)
+trap 'rm -rf "$WORK"' EXIT
+
+mkdir -p "$WORK/units/sources" "$WORK/ledger/sources" "$WORK/app/sources"
+cat > "$WORK/units/Move.toml" <<TOML
+[package]
+name = "Units"
+version = "0.0.1"
+
+[addresses]
+units = "0x7a1"
+TOML
+cat > "$WORK/units/sources/units.move" <<MOVE
+module units::amount {
+    struct Amount has store, drop, copy { value: u64 }
+
+    public fun of(value: u64): Amount { Amount { value } }
+}
+MOVE
+
+cat > "$WORK/ledger/Move.toml" <<TOML
+[package]
+name = "Ledger"
+version = "0.0.1"
+
+[addresses]
+ledger = "0x7a2"
+
+[dependencies]
+Units = { local = "../units" }
+TOML
+cat > "$WORK/ledger/sources/ledger.move" <<MOVE
+module ledger::entry {
+    use units::amount::Amount;
+
+    struct Entry has store, drop { amount: Amount }
+
+    public fun new(amount: Amount): Entry { Entry { amount } }
+}
+MOVE
+
+cat > "$WORK/app/Move.toml" <<TOML
+[package]
+name = "App"
+version = "0.0.1"
+
+[addresses]
+app = "0x7a3"
+
+[dependencies]
+Units = { local = "../units" }
+Ledger = { local = "../ledger" }
+TOML
+cat > "$WORK/app/sources/app.move" <<MOVE
+module app::config {
+    const FEE: u64 = 3;
+
+    public fun fee(): u64 { FEE }
+}
+
+module app::book {
+    use app::config;
+    use ledger::entry::{Self, Entry};
+    use units::amount;
+
+    struct Book has key { entries: vector<Entry> }
+
+    public fun record(value: u64): Entry {
+        entry::new(amount::of(value + config::fee()))
+    }
+}
+MOVE
+
+"$MOVE_TO_TS" -p "$WORK/app" -o "$WORK/out" --emit-depgraph "$WORK/deps.dot"
+DOT="$WORK/deps.dot"
+JSON="$WORK/deps.json"
+
+expect() {
+  local file=$1 line=$2
+  if ! grep -qF -- "$line" "$file"; then
+    echo "FAIL: expected in $(basename "$file"): $line"
+    cat "$file"
+    exit 1
+  fi
+}
+
+expect "$DOT" '"0x7a3::book" [package="app", source=true];'
+expect "$DOT" '"0x7a1::amount" [package="units", source=false];'
+expect "$DOT" '"0x7a3::book" -> "0x7a3::config" [kind="same-package", target_source=true];'
+expect "$DOT" '"0x7a3::book" -> "0x7a2::entry" [kind="cross-package", target_source=false, style=dashed];'
+expect "$DOT" '"0x7a3::book" -> "0x7a1::amount" [kind="cross-package", target_source=false, style=dashed];'
+expect "$DOT" '"0x7a2::entry" -> "0x7a1::amount" [kind="cross-package", target_source=false, style=dashed];'
+if [ "$(grep -c -- "->" "$DOT")" != 4 ]; then
+  echo "FAIL: expected 4 edges"
+  cat "$DOT"
+  exit 1
+fi
+expect "$JSON" '{"from":"0x7a2::entry","to":"0x7a1::amount","kind":"cross-package","targetIsSource":false}'
+expect "$JSON" '{"id":"0x7a3::config","package":"app","source":true}'
+echo "Passed: the dependency graph of three packages"
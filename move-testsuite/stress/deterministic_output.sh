@@ -0,0 +1,89 @@
+#!/usr/bin/env bash
+# Translates the same modules twice, once from files and module declarations in reverse order, and
+# checks that the output is identical: temporaries, hoisted declarations and everything else must
+# only depend on the code, not on the order the compiler hands its maps over in. The modules use
+# temporaries, shadowed locals, unpacks and loops, whose declarations are hoisted. The compiler
+# numbers temporaries across functions, so each function renumbers its own from temp$0.
+# SOURCE_DATE_EPOCH is pinned, so that generatedAt in the package index is the same for both runs.
+# Run from the repository root after `cargo build`:  move-testsuite/stress/deterministic_output.sh
+set -euo pipefail
+
+MOVE_TO_TS=${MOVE_TO_TS:-$(pwd)/target/debug/move-to-ts}
+WORK=$(mktemp -d)
+trap 'rm -rf "$WORK"' EXIT
+
+LEDGER='module app::ledger {
+    struct Entry has copy, drop { amount: u64, fee: u64 }
+
+    public fun entry(amount: u64, fee: u64): Entry { Entry { amount, fee } }
+
+    public fun total(entry: &Entry, times: u64): u64 {
+        let sum = 0;
+        let i = 0;
+        while (i < times) {
+            let Entry { amount, fee } = *entry;
+            let amount = if (fee > amount) { 0 } else { amount - fee };
+            sum = sum + amount;
+            i = i + 1;
+        };
+        sum
+    }
+}'
+MATH='module app::math {
+    public fun clamp(a: u64, b: u64, c: u64, d: u64, e: u64, f: u64, g: u64, h: u64, i: u64, j: u64, k: u64): u64 {
+        let (x, y) = if (a > b) { (a - b, c) } else { (b - a, d) };
+        let z = if (x > y) { e + f * g } else { h + i * (j + k) };
+        if (z > 100) { 100 } else { z }
+    }
+}'
+FEES='module app::fees {
+    use app::ledger;
+    use app::math;
+
+    public fun apply(amount: u64, cap: u64): u64 {
+        let total = ledger::total(&ledger::entry(amount, 1), 3);
+        let total = math::clamp(total, cap, 1, 2, 3, 4, 5, 6, 7, 8, 9);
+        total
+    }
+}'
+
+package() {
+  local dir=$1
+  mkdir -p "$WORK/$dir/sources"
+  cat > "$WORK/$dir/Move.toml" <<TOML
+[package]
+name = "Deterministic"
+version = "0.0.1"
+
+[addresses]
+app = "0x7d1"
+TOML
+}
+
+package forward
+printf '%s\n\n%s\n' "$LEDGER" "$MATH" > "$WORK/forward/sources/a_modules.move"
+printf '%s\n' "$FEES" > "$WORK/forward/sources/b_fees.move"
+
+package reverse
+printf '%s\n' "$FEES" > "$WORK/reverse/sources/a_fees.move"
+printf '%s\n\n%s\n' "$MATH" "$LEDGER" > "$WORK/reverse/sources/b_modules.move"
+
+export SOURCE_DATE_EPOCH=1700000000
+"$MOVE_TO_TS" -p "$WORK/forward" -o "$WORK/forward/ts" -n deterministic
+"$MOVE_TO_TS" -p "$WORK/reverse" -o "$WORK/reverse/ts" -n deterministic
+if ! diff -r "$WORK/forward/ts/src" "$WORK/reverse/ts/src"; then
+  echo "FAIL: the output depends on the order of the sources"
+  exit 1
+fi
+if ! grep -q '^ *let .*temp\$' "$WORK/forward/ts/src/app/math.ts"; then
+  echo "FAIL: expected hoisted temporaries in math.ts"
+  exit 1
+fi
+for module in ledger math fees; do
+  if grep -q 'temp\$' "$WORK/forward/ts/src/app/$module.ts" \
+    && ! grep -q '^ *let temp\$0\b' "$WORK/forward/ts/src/app/$module.ts"; then
+    echo "FAIL: expected the temporaries of $module.ts to be numbered from temp\$0"
+    exit 1
+  fi
+done
+echo "Passed: the output does not depend on the order of the sources"
@@ -0,0 +1,36 @@
+#!/usr/bin/env bash
+# Translates move-testsuite with --gen-test and checks that no module, framework ones included,
+# calls the runtime natives of vector::destroy_empty or table::drop_unchecked_box, then runs the
+# generated tests of DisciplineNatives, which build, drain and destroy a vector, against the
+# runtime in typescript/.
+# Run from the repository root after `cargo build` and `yarn build` in typescript/, with network
+# access for the yarn install of the generated package:
+#   move-testsuite/stress/discipline_natives.sh
+set -euo pipefail
+
+MOVE_TO_TS=${MOVE_TO_TS:-$(pwd)/target/debug/move-to-ts}
+RUNTIME=$(pwd)/typescript
+WORK=$(mktemp -d)
+trap 'rm -rf "$WORK"' EXIT
+
+if [ ! -f "$RUNTIME/dist/index.d.ts" ]; then
+  echo "FAIL: run yarn install and yarn build in typescript/ first"
+  exit 1
+fi
+
+OUT="$WORK/out"
+"$MOVE_TO_TS" -p "$(pwd)/move-testsuite" -o "$OUT" -n discipline-natives -t > "$WORK/translate.log"
+if grep -rnE '\$\.(std_vector_destroy_empty|aptos_std_table_drop_unchecked_box)\(' "$OUT/src"; then
+  echo "FAIL: the calls above still go to a runtime native"
+  exit 1
+fi
+
+(cd "$OUT" && yarn install --silent > /dev/null)
+rm -rf "$OUT/node_modules/@manahippo/move-to-ts"
+ln -s "$RUNTIME" "$OUT/node_modules/@manahippo/move-to-ts"
+if ! (cd "$OUT" && yarn --silent jest DisciplineNatives 2> "$WORK/jest.log"); then
+  echo "FAIL: the DisciplineNatives tests do not pass"
+  tail -n 40 "$WORK/jest.log"
+  exit 1
+fi
+echo "Passed: vector::destroy_empty and table::drop_unchecked_box need no runtime native"
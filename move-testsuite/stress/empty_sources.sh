@@ -0,0 +1,48 @@
+#!/usr/bin/env bash
+# Translates a package that only gathers a dependency and has an empty sources directory, then one
+# without a sources directory or dependencies at all, with --gen-cli and --gen-ui. Both runs must
+# succeed, the first one with the dependency translated.
+# Run from the repository root after `cargo build`:  move-testsuite/stress/empty_sources.sh
+set -euo pipefail
+
+MOVE_TO_TS=${MOVE_TO_TS:-$(pwd)/target/debug/move-to-ts}
+WORK=$(mktemp -d)
+trap 'rm -rf "$WORK"' EXIT
+
+mkdir -p "$WORK/dep/sources" "$WORK/meta/sources" "$WORK/bare"
+cat > "$WORK/dep/Move.toml" <<TOML
+[package]
+name = "Dep"
+version = "0.0.1"
+
+[addresses]
+dep = "0x1234b"
+TOML
+cat > "$WORK/dep/sources/counter.move" <<MOVE
+module dep::counter {
+    struct Counter has key { value: u64 }
+
+    public fun value(counter: &Counter): u64 { counter.value }
+}
+MOVE
+cat > "$WORK/meta/Move.toml" <<TOML
+[package]
+name = "Meta"
+version = "0.0.1"
+
+[dependencies]
+Dep = { local = "../dep" }
+TOML
+cat > "$WORK/bare/Move.toml" <<TOML
+[package]
+name = "Bare"
+version = "0.0.1"
+TOML
+
+"$MOVE_TO_TS" -p "$WORK/meta" -o "$WORK/meta_out" --gen-cli
+if [ ! -f "$WORK/meta_out/src/dep/counter.ts" ]; then
+  echo "FAIL: the dependency of a package without source modules should be translated"
+  exit 1
+fi
+"$MOVE_TO_TS" -p "$WORK/bare" -o "$WORK/bare_out" --gen-cli --gen-ui
+echo "Passed: packages without source modules translate cleanly"
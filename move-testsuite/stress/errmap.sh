@@ -0,0 +1,96 @@
+#!/usr/bin/env bash
+# Translates a module with a dozen error constants, named E_..., ERROR_..., ENOT_... or marked
+# #[error], with --emit-errmap and compares the error map against the snapshot in
+# snapshots/errmap_vault.json: doc comments become messages across attributes, two constants
+# sharing a code keep the first by name with a warning for the other, ENOT_... names count only
+# when the module aborts with them, and other constants and modules without errors are left out.
+# Run from the repository root after `cargo build`:  move-testsuite/stress/errmap.sh
+set -euo pipefail
+
+MOVE_TO_TS=${MOVE_TO_TS:-$(pwd)/target/debug/move-to-ts}
+SNAPSHOT=$(pwd)/move-testsuite/stress/snapshots/errmap_vault.json
+WORK=$(mktemp -d)
+trap 'rm -rf "$WORK"' EXIT
+
+mkdir -p "$WORK/sources"
+cat > "$WORK/Move.toml" <<TOML
+[package]
+name = "ErrMap"
+version = "0.0.1"
+
+[addresses]
+errvault = "0xe11"
+TOML
+cat > "$WORK/sources/vault.move" <<MOVE
+module errvault::vault {
+    /// The caller is not the vault owner
+    const E_NOT_OWNER: u64 = 1;
+    /// Shares the code of E_NOT_OWNER, whose name sorts first
+    const E_NOT_OWNER_ALIAS: u64 = 1;
+    /// The vault holds less than the amount withdrawn
+    const E_INSUFFICIENT_BALANCE: u64 = 2;
+    // a plain comment is not a message
+    const E_PAUSED: u64 = 3;
+    /// Deposits are capped per account,
+    /// see MAX_DEPOSIT
+    const ERROR_DEPOSIT_LIMIT: u64 = 4;
+    /// The vault was already created
+    #[error]
+    const ALREADY_EXISTS: u64 = 5;
+    #[error]
+    /// The vault does not exist
+    const MISSING_VAULT: u64 = 6;
+    /// Only the admin may pause the vault
+    const ENOT_AUTHORIZED: u64 = 7;
+    const EFROZEN: u64 = 8;
+    /// The fee is "too high"
+    const E_FEE_TOO_HIGH: u64 = 9;
+
+    /// The balance would overflow
+    const E_OVERFLOW: u64 = 10;
+    /// Amounts must be positive
+    const E_ZERO_AMOUNT: u64 = 11;
+
+    /// Not an abort code, as it is not a u64
+    const E_SMALL: u8 = 12;
+    /// Not an error name
+    const MAX_DEPOSIT: u64 = 1000;
+    /// Named like a framework error, but no abort uses it
+    const EPOCH_INTERVAL: u64 = 3600;
+
+    struct Vault has key { balance: u64, paused: bool }
+
+    public fun withdraw(vault: &mut Vault, amount: u64, small: u8) {
+        assert!(amount > 0, E_ZERO_AMOUNT);
+        assert!(!vault.paused, E_PAUSED);
+        assert!(vault.balance >= amount, E_INSUFFICIENT_BALANCE);
+        assert!(amount <= MAX_DEPOSIT && small < E_SMALL, ERROR_DEPOSIT_LIMIT);
+        vault.balance = vault.balance - amount;
+    }
+
+    public fun pause(vault: &mut Vault, admin: address, frozen: bool, now: u64) {
+        if (admin != @errvault) abort ENOT_AUTHORIZED;
+        assert!(!frozen && now % EPOCH_INTERVAL != 0, EFROZEN);
+        vault.paused = true;
+    }
+}
+MOVE
+cat > "$WORK/sources/math.move" <<MOVE
+module errvault::math {
+    const SCALE: u64 = 100;
+
+    public fun scale(value: u64): u64 { value * SCALE }
+}
+MOVE
+
+"$MOVE_TO_TS" -p "$WORK" -o "$WORK/out" --emit-errmap "$WORK/errmap.json" > "$WORK/run.log" 2>&1
+if ! grep -qF "E_NOT_OWNER_ALIAS shares the abort code 1 with E_NOT_OWNER" "$WORK/run.log"; then
+  echo "FAIL: expected a warning about E_NOT_OWNER_ALIAS sharing the code of E_NOT_OWNER"
+  cat "$WORK/run.log"
+  exit 1
+fi
+if ! diff "$SNAPSHOT" "$WORK/errmap.json"; then
+  echo "FAIL: the error map differs from the snapshot"
+  exit 1
+fi
+echo "Passed: error map matches the snapshot"
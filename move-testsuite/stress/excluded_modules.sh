@@ -0,0 +1,169 @@
+#!/usr/bin/env bash
+# Translates a package with --exclude-module for the dependency module whose structs it only uses
+# in types, one as a phantom type argument and two as field types, one of them generic. The
+# excluded module gets an opaque stub instead of its file, the output compiles with tsc against
+# the runtime in typescript/, and resources of all three kinds parse. A module that calls a
+# function of an excluded module fails to translate.
+# Run from the repository root after `cargo build` and `yarn install` plus `yarn build` in
+# typescript/:  move-testsuite/stress/excluded_modules.sh
+set -euo pipefail
+
+MOVE_TO_TS=${MOVE_TO_TS:-$(pwd)/target/debug/move-to-ts}
+RUNTIME=$(pwd)/typescript
+WORK=$(mktemp -d)
+trap 'rm -rf "$WORK"' EXIT
+
+if [ ! -f "$RUNTIME/dist/index.d.ts" ] || [ ! -x "$RUNTIME/node_modules/.bin/tsc" ]; then
+  echo "FAIL: run yarn install and yarn build in typescript/ first"
+  exit 1
+fi
+
+mkdir -p "$WORK/tokens/sources" "$WORK/app/sources" "$WORK/caller/sources"
+cat > "$WORK/tokens/Move.toml" <<TOML
+[package]
+name = "Tokens"
+version = "0.0.1"
+
+[addresses]
+tokens = "0x70c"
+TOML
+cat > "$WORK/tokens/sources/usdc.move" <<MOVE
+module tokens::usdc {
+    struct USDC {}
+
+    struct Meta has store, drop { decimals: u8, symbol: vector<u8> }
+
+    struct Wrapper<T> has store, drop { inner: T }
+
+    public fun meta(): Meta {
+        Meta { decimals: 6, symbol: b"USDC" }
+    }
+}
+MOVE
+
+cat > "$WORK/app/Move.toml" <<TOML
+[package]
+name = "App"
+version = "0.0.1"
+
+[addresses]
+app = "0x70d"
+
+[dependencies]
+Tokens = { local = "../tokens" }
+TOML
+cat > "$WORK/app/sources/vault.move" <<MOVE
+module app::vault {
+    use tokens::usdc::{Meta, USDC, Wrapper};
+
+    struct Vault<phantom CoinType> has key { value: u64 }
+
+    struct Listing has key { meta: Meta, price: u64 }
+
+    struct Wrapped has key { wrapped: Wrapper<u64> }
+
+    public entry fun open(account: &signer) {
+        move_to(account, Vault<USDC> { value: 0 });
+    }
+
+    public fun list(account: &signer, meta: Meta, price: u64) {
+        move_to(account, Listing { meta, price });
+    }
+}
+MOVE
+
+OUT="$WORK/out"
+"$MOVE_TO_TS" -p "$WORK/app" -o "$OUT" -n excluded-modules --exclude-module tokens::usdc
+if [ -e "$OUT/src/tokens/usdc.ts" ]; then
+  echo "FAIL: tokens::usdc is excluded, so it should not be translated"
+  exit 1
+fi
+STUB="$OUT/src/_stubs/tokens_usdc.ts"
+if [ ! -f "$STUB" ] || ! grep -q "opaque: true" "$STUB"; then
+  echo "FAIL: expected an opaque stub of tokens::usdc in $STUB"
+  exit 1
+fi
+if ! grep -q "new \$.TypeParamIdx(0)" "$STUB" || ! grep -q "^import \* as \$ from" "$STUB"; then
+  echo "FAIL: the stub of the generic Wrapper should import the runtime as \$"
+  exit 1
+fi
+if ! grep -q "^import \* as Tokens_Usdc_Stub from \"../_stubs/tokens_usdc\";" "$OUT/src/app/vault.ts"; then
+  echo "FAIL: vault.ts should import the stub of tokens::usdc"
+  grep "^import" "$OUT/src/app/vault.ts"
+  exit 1
+fi
+
+# the generated package resolves its dependencies, and the runtime, from typescript/
+mkdir -p "$OUT/node_modules/@manahippo"
+for dep in "$RUNTIME"/node_modules/*; do
+  if [ "$(basename "$dep")" != "@manahippo" ]; then
+    ln -s "$dep" "$OUT/node_modules/$(basename "$dep")"
+  fi
+done
+ln -s "$RUNTIME" "$OUT/node_modules/@manahippo/move-to-ts"
+cat > "$OUT/src/check.ts" <<TS
+import { AptosParserRepo, parseTypeTagOrThrow } from "@manahippo/move-to-ts";
+import * as Vault from "./app/vault";
+
+const repo = new AptosParserRepo();
+Vault.loadParsers(repo);
+const vault = repo.parse({ value: "5" }, parseTypeTagOrThrow("0x70d::vault::Vault<0x70c::usdc::USDC>")) as Vault.Vault;
+if (vault.value.toJsNumber() !== 5) {
+  throw new Error("expect a vault of 5");
+}
+const listing = repo.parse(
+  { meta: { decimals: 6, symbol: "0x55534443" }, price: "7" },
+  parseTypeTagOrThrow("0x70d::vault::Listing")
+) as Vault.Listing;
+if (listing.meta.raw.decimals !== 6 || listing.getFieldValue("meta.symbol") !== "0x55534443") {
+  throw new Error("expect the stub to keep the data of meta as is");
+}
+const wrapped = repo.parse(
+  { wrapped: { inner: "9" } },
+  parseTypeTagOrThrow("0x70d::vault::Wrapped")
+) as Vault.Wrapped;
+if (wrapped.getFieldValue("wrapped.inner") !== "9") {
+  throw new Error("expect the generic stub to keep the data of wrapped as is");
+}
+console.log("parsed");
+TS
+if ! "$RUNTIME/node_modules/.bin/tsc" -p "$OUT/tsconfig.json"; then
+  echo "FAIL: the output with a stub does not compile"
+  exit 1
+fi
+if [ "$(cd "$OUT" && node dist/check.js)" != "parsed" ]; then
+  echo "FAIL: the resources did not parse"
+  exit 1
+fi
+
+cat > "$WORK/caller/Move.toml" <<TOML
+[package]
+name = "Caller"
+version = "0.0.1"
+
+[addresses]
+caller = "0x70e"
+
+[dependencies]
+Tokens = { local = "../tokens" }
+TOML
+cat > "$WORK/caller/sources/shop.move" <<MOVE
+module caller::shop {
+    use tokens::usdc::{Self, Meta};
+
+    public fun meta(): Meta {
+        usdc::meta()
+    }
+}
+MOVE
+if "$MOVE_TO_TS" -p "$WORK/caller" -o "$WORK/caller-out" --exclude-module tokens::usdc \
+  > "$WORK/caller.log" 2>&1; then
+  echo "FAIL: a call into an excluded module should not translate"
+  exit 1
+fi
+if ! grep -q "which is not translated with --exclude-module tokens::usdc" "$WORK/caller.log"; then
+  echo "FAIL: expected an error naming the excluded module"
+  cat "$WORK/caller.log"
+  exit 1
+fi
+echo "Passed: structs of excluded modules come from opaque stubs"
@@ -0,0 +1,71 @@
+#!/usr/bin/env bash
+# Translates a package with --gen-cli and --extra-cmd for an entry function of a dependency, and
+# checks that cli.ts gets a command for it next to the #[cmd] of the package. Then checks that an
+# unknown function and a function that is not a public entry get one error each.
+# Run from the repository root after `cargo build`:  move-testsuite/stress/extra_cmd.sh
+set -euo pipefail
+
+MOVE_TO_TS=${MOVE_TO_TS:-$(pwd)/target/debug/move-to-ts}
+WORK=$(mktemp -d)
+trap 'rm -rf "$WORK"' EXIT
+
+mkdir -p "$WORK/partner/sources" "$WORK/app/sources"
+cat > "$WORK/partner/Move.toml" <<TOML
+[package]
+name = "Partner"
+version = "0.0.1"
+
+[addresses]
+partner = "0x7c1"
+TOML
+cat > "$WORK/partner/sources/pool.move" <<MOVE
+module partner::pool {
+    public entry fun swap(_account: &signer, _amount_in: u64, _min_out: u64) {}
+
+    public fun quote(amount_in: u64): u64 { amount_in * 2 }
+}
+MOVE
+
+cat > "$WORK/app/Move.toml" <<TOML
+[package]
+name = "App"
+version = "0.0.1"
+
+[addresses]
+app = "0x7c2"
+
+[dependencies]
+Partner = { local = "../partner" }
+TOML
+cat > "$WORK/app/sources/router.move" <<MOVE
+module app::router {
+    use partner::pool;
+
+    #[cmd]
+    public entry fun route(account: &signer, amount: u64) {
+        pool::swap(account, amount, 0);
+    }
+}
+MOVE
+
+"$MOVE_TO_TS" -p "$WORK/app" -o "$WORK/ts" -c --extra-cmd partner::pool::swap
+CLI="$WORK/ts/src/cli.ts"
+for command in 'command("router:route")' 'command("pool:swap")' 'Calls partner::pool::swap'; do
+  if ! grep -qF "$command" "$CLI"; then
+    echo "FAIL: cli.ts has no $command"
+    exit 1
+  fi
+done
+
+if OUTPUT=$("$MOVE_TO_TS" -p "$WORK/app" -o "$WORK/bad" -c \
+    --extra-cmd 0x7c1::pool::missing --extra-cmd 0x7c1::pool::quote 2>&1); then
+  echo "FAIL: translation with invalid --extra-cmd entries succeeded"
+  exit 1
+fi
+if ! grep -q "0x7c1::pool::missing: .* has no function missing" <<< "$OUTPUT" \
+    || ! grep -q "0x7c1::pool::quote: quote is not a public entry function" <<< "$OUTPUT"; then
+  echo "FAIL: expected one error for each invalid --extra-cmd, got:"
+  echo "$OUTPUT"
+  exit 1
+fi
+echo "Passed: cli commands for entry functions of a dependency"
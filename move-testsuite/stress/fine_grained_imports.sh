@@ -0,0 +1,81 @@
+#!/usr/bin/env bash
+# Translates a package using one module of a dependency with --fine-grained-imports, and checks
+# that it imports that module's file instead of the dependency's index, and nothing of the module
+# it does not use. Without the option, it imports the index.
+# Run from the repository root after `cargo build`:  move-testsuite/stress/fine_grained_imports.sh
+set -euo pipefail
+
+MOVE_TO_TS=${MOVE_TO_TS:-$(pwd)/target/debug/move-to-ts}
+WORK=$(mktemp -d)
+trap 'rm -rf "$WORK"' EXIT
+
+mkdir -p "$WORK/lib/sources" "$WORK/app/sources"
+cat > "$WORK/lib/Move.toml" <<TOML
+[package]
+name = "Lib"
+version = "0.0.1"
+
+[addresses]
+lib = "0x77a"
+TOML
+cat > "$WORK/lib/sources/lib.move" <<MOVE
+module lib::amounts {
+    struct Amount has store, drop { value: u64 }
+
+    public fun new(value: u64): Amount {
+        Amount { value }
+    }
+}
+
+module lib::unused {
+    public fun nothing(): u64 { 0 }
+}
+MOVE
+
+cat > "$WORK/app/Move.toml" <<TOML
+[package]
+name = "App"
+version = "0.0.1"
+
+[addresses]
+app = "0x77b"
+
+[dependencies]
+Lib = { local = "../lib" }
+TOML
+cat > "$WORK/app/sources/wallet.move" <<MOVE
+module app::wallet {
+    use lib::amounts::{Self, Amount};
+
+    struct Wallet has key { amount: Amount }
+
+    public fun make(value: u64): Wallet {
+        Wallet { amount: amounts::new(value) }
+    }
+}
+MOVE
+
+"$MOVE_TO_TS" -p "$WORK/app" -o "$WORK/fine" --fine-grained-imports
+WALLET="$WORK/fine/src/app/wallet.ts"
+if ! grep -q "^import \* as Lib_Amounts from \"../lib/amounts\";" "$WALLET"; then
+  echo "FAIL: wallet.ts should import ../lib/amounts on its own"
+  grep "^import" "$WALLET"
+  exit 1
+fi
+if grep -q "from \"../lib\";\|lib/unused" "$WALLET"; then
+  echo "FAIL: wallet.ts should not import the index of lib, nor lib/unused"
+  grep "^import" "$WALLET"
+  exit 1
+fi
+if ! grep -q "Lib_Amounts\.new_\|Lib_Amounts\.Amount" "$WALLET"; then
+  echo "FAIL: wallet.ts should refer to lib::amounts through Lib_Amounts"
+  exit 1
+fi
+
+"$MOVE_TO_TS" -p "$WORK/app" -o "$WORK/package"
+if ! grep -q "from \"../lib\";" "$WORK/package/src/app/wallet.ts"; then
+  echo "FAIL: without --fine-grained-imports, wallet.ts should import the index of lib"
+  grep "^import" "$WORK/package/src/app/wallet.ts"
+  exit 1
+fi
+echo "Passed: modules of other packages are imported one by one"
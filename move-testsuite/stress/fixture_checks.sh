@@ -0,0 +1,51 @@
+#!/usr/bin/env bash
+# Translates move-testsuite with --strict-construction and --trace, and runs each script in
+# fixture_checks/ against the translated fixtures, so that the runtime helpers are exercised with
+# the classes and functions move-to-ts actually generates rather than hand-written look-alikes,
+# which is all the runtime tests in typescript/src/tests have.
+# The output has to compile with tsc against the runtime in typescript/, which also checks the
+# @ts-expect-error lines of the scripts.
+# Run from the repository root after `cargo build` and `yarn install` plus `yarn build` in
+# typescript/:  move-testsuite/stress/fixture_checks.sh
+set -euo pipefail
+
+MOVE_TO_TS=${MOVE_TO_TS:-$(pwd)/target/debug/move-to-ts}
+RUNTIME=$(pwd)/typescript
+CHECKS=$(pwd)/move-testsuite/stress/fixture_checks
+WORK=$(mktemp -d)
+trap 'rm -rf "$WORK"' EXIT
+
+if [ ! -f "$RUNTIME/dist/index.d.ts" ] || [ ! -x "$RUNTIME/node_modules/.bin/tsc" ]; then
+  echo "FAIL: run yarn install and yarn build in typescript/ first"
+  exit 1
+fi
+
+OUT="$WORK/out"
+"$MOVE_TO_TS" -p "$(pwd)/move-testsuite" -o "$OUT" -n fixture-checks --strict-construction --trace \
+  > "$WORK/translate.log"
+
+# the generated package resolves its dependencies, and the runtime, from typescript/
+mkdir -p "$OUT/node_modules/@manahippo"
+for dep in "$RUNTIME"/node_modules/*; do
+  if [ "$(basename "$dep")" != "@manahippo" ]; then
+    ln -s "$dep" "$OUT/node_modules/$(basename "$dep")"
+  fi
+done
+ln -s "$RUNTIME" "$OUT/node_modules/@manahippo/move-to-ts"
+cp -r "$CHECKS" "$OUT/src/fixture_checks"
+if ! "$RUNTIME/node_modules/.bin/tsc" -p "$OUT/tsconfig.json" > "$WORK/tsc.log"; then
+  echo "FAIL: the output and the fixture checks do not compile"
+  head -n 40 "$WORK/tsc.log"
+  exit 1
+fi
+for check in "$CHECKS"/*.ts; do
+  name=$(basename "$check" .ts)
+  if [ "$name" = "expect" ]; then
+    continue
+  fi
+  if ! (cd "$OUT" && node "dist/fixture_checks/$name.js"); then
+    echo "FAIL: fixture check $name"
+    exit 1
+  fi
+done
+echo "Passed: the runtime helpers work with the translated fixtures"
@@ -0,0 +1,25 @@
+import { hasAbility, isResource, StructInfoType } from "@manahippo/move-to-ts";
+import * as GenericCliVault from "../MoveToTsTestsuite/GenericCliVault";
+import * as TableItems from "../MoveToTsTestsuite/TableItems";
+import { expect } from "./expect";
+
+// Vault<phantom CoinType> has key, store, drop
+const vault: StructInfoType = GenericCliVault.Vault;
+expect(isResource(vault), "expect a key struct to be a resource");
+expect(hasAbility(vault, "store") && !hasAbility(vault, "copy"), "expect Vault to have store but not copy");
+expect(
+  vault.typeParamCount === 1 && vault.phantomTypeParams?.[0] === true,
+  "expect Vault to have one phantom type parameter"
+);
+
+// Receipt<phantom CoinType> has copy, store, drop
+expect(!isResource(GenericCliVault.Receipt), "expect a struct without key not to be a resource");
+expect(hasAbility(GenericCliVault.Receipt, "copy"), "expect Receipt to have copy");
+
+// Registry<K: copy + drop, V: store> has key
+const registry: StructInfoType = TableItems.Registry;
+expect(
+  registry.typeParamCount === 2 && registry.phantomTypeParams?.every(p => !p) === true,
+  "expect Registry to have two type parameters that are not phantom"
+);
+expect(isResource(registry) && !hasAbility(registry, "drop"), "expect Registry to have key but not drop");
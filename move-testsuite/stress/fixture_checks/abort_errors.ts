@@ -0,0 +1,39 @@
+import { abortErrorFromVmStatus, AptosLocalCache, MoveAbortError, registerAbortErrors } from "@manahippo/move-to-ts";
+import { HexString } from "aptos";
+import * as AbortErrors from "../MoveToTsTestsuite/AbortErrors";
+import { expect } from "./expect";
+
+const $c = new AptosLocalCache();
+let caught: unknown = null;
+try {
+  AbortErrors.check_owner_(new HexString("0x1"), new HexString("0x2"), $c);
+}
+catch (e) {
+  caught = e;
+}
+expect(
+  caught instanceof AbortErrors.AbortErrorsError_E_NOT_OWNER && caught instanceof AbortErrors.AbortErrorsError,
+  `expect the classes of the constant and module, got ${caught}`
+);
+expect(caught instanceof MoveAbortError && caught instanceof Error, "expect a MoveAbortError");
+// expected_failure and difftest read the code from the message
+const abort = caught as MoveAbortError;
+expect(abort.message === "1", `expect the code as the message, got ${abort.message}`);
+expect(abort.code.value.toJSNumber() === 1, "expect the code");
+
+// what a failed simulation reports, with the module address padded
+const module = `0x${"0".repeat(59)}12345::AbortErrors`;
+const closed = abortErrorFromVmStatus(`Move abort in ${module}: ERROR_CLOSED(0x2): closed`);
+expect(closed instanceof AbortErrors.AbortErrorsError_ERROR_CLOSED, `expect ERROR_CLOSED, got ${closed}`);
+const shut = abortErrorFromVmStatus(`Move abort in ${module}: E_SHUT(0x2): shut`);
+expect(shut instanceof AbortErrors.AbortErrorsError_E_SHUT, `expect E_SHUT, got ${shut}`);
+expect(abortErrorFromVmStatus(`Move abort in ${module}: 0x2`) === null, "expect a shared code without a name to map to nothing");
+const fromCode = abortErrorFromVmStatus(`Move abort in ${module}: 0x3`);
+expect(fromCode instanceof AbortErrors.AbortErrorsError_EINSUFFICIENT, `expect EINSUFFICIENT, got ${fromCode}`);
+expect(abortErrorFromVmStatus(`Move abort in ${module}: 0x64`) === null, "expect codes of other constants to map to nothing");
+expect(abortErrorFromVmStatus("Move abort in 0x7b::AbortErrors: 0x3") === null, "expect other modules to map to nothing");
+
+// loading the module again does not make its codes ambiguous
+registerAbortErrors([AbortErrors.AbortErrorsError_E_NOT_OWNER]);
+const again = abortErrorFromVmStatus(`Move abort in ${module}: 0x1`);
+expect(again instanceof AbortErrors.AbortErrorsError_E_NOT_OWNER, `expect E_NOT_OWNER, got ${again}`);
@@ -0,0 +1,17 @@
+// shared by the fixture checks, which run with node outside of a test runner
+export function expect(cond: boolean, message: string) {
+  if (!cond) {
+    throw new Error(message);
+  }
+}
+
+export function expectThrows(f: () => unknown, check: (message: string) => boolean, description: string) {
+  let message: string | null = null;
+  try {
+    f();
+  }
+  catch (e) {
+    message = e instanceof Error ? e.message : `${e}`;
+  }
+  expect(message !== null && check(message), `expect ${description}, got ${message ?? "no error"}`);
+}
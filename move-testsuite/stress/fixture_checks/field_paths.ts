@@ -0,0 +1,32 @@
+import { AptosLocalCache, AtomicTypeTag, getFieldValueByPath, U64, U8, u64, u8 } from "@manahippo/move-to-ts";
+import * as FieldPaths from "../MoveToTsTestsuite/FieldPaths";
+import { expect, expectThrows } from "./expect";
+
+const $c = new AptosLocalCache();
+const Coin = FieldPaths.Coin;
+expect(Coin.getField("value").typeTag === AtomicTypeTag.U64, "expect the value field");
+expectThrows(
+  () => Coin.getField("amount"),
+  m => m === "Coin has no field amount, its fields are: value",
+  "an unknown field"
+);
+
+const pool = FieldPaths.new_pool_(u64(7), u8(2), $c);
+// through two levels of nested structs
+const value = getFieldValueByPath(pool, "reserve.coin.value") as U64;
+expect(value.value.toJSNumber() === 7, `expect 7, got ${value}`);
+const coin = FieldPaths.new_coin_(u64(3), $c);
+expect((coin.getFieldValue("value") as U64).value.toJSNumber() === 3, "expect 3");
+// type is a reserved member name, generated as type__
+expect((pool.getFieldValue("type") as U8).value.toJSNumber() === 2, "expect renamed members to be found by field name");
+// a field named like the lookup member itself is generated as getFieldValue__
+const lookup = FieldPaths.new_lookup_(u64(3), u64(4), $c);
+expect((lookup.getFieldValue("getFieldValue") as U64).value.toJSNumber() === 3, "expect the getFieldValue field");
+expect((lookup.getFieldValue("get_field_value") as U64).value.toJSNumber() === 4, "expect the get_field_value field");
+
+const throwsWith = (path: string, message: string) =>
+  expectThrows(() => getFieldValueByPath(pool, path), m => m === message, path);
+throwsWith("reserve.history.value", "Pool.reserve.history is a vector, cannot get its field value");
+throwsWith("positions.handle", "Pool.positions is a table, cannot get its field handle");
+throwsWith("reserve.coin.value.inner", "Pool.reserve.coin.value is not a struct, cannot get its field inner");
+throwsWith("reserve.amount", "Reserve has no field amount, its fields are: coin, history");
@@ -0,0 +1,13 @@
+import { AptosLocalCache, U64 } from "@manahippo/move-to-ts";
+import * as MoveToTsTestsuite from "../MoveToTsTestsuite";
+import * as CpSwapUnderscoreUser from "../MoveToTsTestsuite/CpSwapUnderscoreUser";
+import { expect } from "./expect";
+
+const $c = new AptosLocalCache();
+const fee = (value: U64) => value.value.toJSNumber();
+// Cpswap, cp_swap and cpswap in name order: only cpswap collides, with Cpswap, and gets a suffix
+expect(fee(MoveToTsTestsuite.Cpswap.fee_($c)) === 5, "expect Cpswap under its own name");
+expect(fee(MoveToTsTestsuite.Cp_swap.fee_($c)) === 30, "expect cp_swap as Cp_swap");
+expect(fee(MoveToTsTestsuite.Cpswap$1.fee_($c)) === 3, "expect cpswap as Cpswap$1");
+// the module importing both cp_swap and cpswap refers to each under its own alias
+expect(fee(CpSwapUnderscoreUser.total_fee_($c)) === 33, "expect 30 + 3");
@@ -0,0 +1,31 @@
+import { AptosLocalCache, u128, u64, u8 } from "@manahippo/move-to-ts";
+import { HexString } from "aptos";
+import * as OrdStructs from "../MoveToTsTestsuite/OrdStructs";
+import { expect } from "./expect";
+
+// #[ord(price, then=b"timestamp,id")] on Order, whose price is a single-field struct
+const $c = new AptosLocalCache();
+const owner = new HexString("0x1");
+// both above 2^53, where JS numbers stop telling them apart
+const big = "9007199254740993";
+const bigger = "9007199254740994";
+const order = (price: string, timestamp: number, id: number) =>
+  OrdStructs.new_order_(u64(price), u64(timestamp), u128(id), owner, $c);
+const orders = [order(bigger, 1, 1), order(big, 7, 1), order(big, 3, 2), order(big, 3, 1)];
+orders.sort(OrdStructs.Order.comparator);
+const shown = orders.map(o => `${o.price.value.value}@${o.timestamp.value}#${o.id.value}`).join(",");
+expect(
+  shown === `${big}@3#1,${big}@3#2,${big}@7#1,${bigger}@1#1`,
+  `expect the price first, then the timestamp, then the id, got ${shown}`
+);
+expect(orders[0].compareTo(orders[1]) < 0, "expect compareTo to agree with comparator");
+expect(orders[0].compareTo(order(big, 3, 1)) === 0, "expect equal orders to tie");
+
+// #[ord(owner)] compares addresses by value, not as strings
+const positions = [
+  OrdStructs.new_position_(new HexString("0x10"), u8(1), $c),
+  OrdStructs.new_position_(new HexString("0x2"), u8(2), $c),
+];
+positions.sort(OrdStructs.Position.comparator);
+expect(positions[0].owner.hex() === "0x2", `expect 0x2 before 0x10, got ${positions[0].owner.hex()}`);
+console.log("Passed: #[ord] comparators of OrdStructs");
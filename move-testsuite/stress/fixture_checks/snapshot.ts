@@ -0,0 +1,41 @@
+import { AptosLocalCache, strToU8, StructTag, u64, u8str } from "@manahippo/move-to-ts";
+import { AptosClient, HexString } from "aptos";
+import { getProjectRepo } from "..";
+import * as MoveToTsTestsuite from "../MoveToTsTestsuite";
+import * as EntryRegistry from "../MoveToTsTestsuite/EntryRegistry";
+import { expect } from "./expect";
+
+// the resources of one account as a fullnode returns them, one of a type no parser knows
+const owner = new HexString("0xb0b");
+const snapshot = [
+  {
+    type: { address: "0x12345", module: "EntryRegistry", name: "Vault", generic_type_params: [] },
+    data: { amount: "5", memo: "0x6869" },
+  },
+  { type: { address: "0x12345", module: "Unknown", name: "Thing", generic_type_params: [] }, data: {} },
+];
+const client = {
+  getAccountResources: async (address: HexString) => (address.hex() === owner.hex() ? snapshot : []),
+} as unknown as AptosClient;
+
+const main = async () => {
+  const $c = new AptosLocalCache();
+  const warnings = await MoveToTsTestsuite.snapshotToCache(client, getProjectRepo(), [owner], $c);
+  expect(warnings.length === 1 && warnings[0].includes("Unknown::Thing"), `expect one warning, got ${warnings}`);
+
+  // the translated entry function finds the Vault of the snapshot, and adds to it
+  EntryRegistry.deposit_(owner, u64(3), strToU8("memo"), $c);
+  const amount = EntryRegistry.peek_(owner, $c);
+  expect(amount.value.toJSNumber() === 8, `expect 5 + 3, got ${amount}`);
+  const Vault = EntryRegistry.Vault;
+  const vault = $c.borrow_global<EntryRegistry.Vault>(
+    new StructTag(Vault.moduleAddress, Vault.moduleName, Vault.structName, []),
+    owner
+  );
+  expect(u8str(vault.memo) === "memo", `expect the memo to be replaced, got ${u8str(vault.memo)}`);
+};
+
+main().catch(e => {
+  console.error(e);
+  process.exit(1);
+});
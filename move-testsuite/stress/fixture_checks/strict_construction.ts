@@ -0,0 +1,24 @@
+import { AptosLocalCache, copy, StructTag, u64 } from "@manahippo/move-to-ts";
+import * as FieldPaths from "../MoveToTsTestsuite/FieldPaths";
+import { expect } from "./expect";
+
+// translated with --strict-construction
+const $c = new AptosLocalCache();
+const Coin = FieldPaths.Coin;
+const coinTag = new StructTag(Coin.moduleAddress, Coin.moduleName, Coin.structName, []);
+const coin = FieldPaths.new_coin_(u64(5), $c);
+expect(coin.value.value.toJSNumber() === 5, "expect a value of 5");
+
+// constructing by hand outside of the module does not type-check
+// @ts-expect-error
+const forged = new Coin({ value: u64(1_000_000) }, coinTag);
+expect(forged.value.value.toJSNumber() === 1_000_000, "constructed at runtime all the same");
+
+// __pack checks the fields it is given
+// @ts-expect-error
+Coin.__pack({ amount: u64(1) }, coinTag);
+expect(Coin.__pack({ value: u64(2) }, coinTag).value.value.toJSNumber() === 2, "expect __pack to build a Coin");
+
+// the runtime still constructs copies from plain protos
+const copied = copy(coin);
+expect(copied !== coin && copied.value.value.toJSNumber() === 5, "expect an equal copy");
@@ -0,0 +1,62 @@
+import {
+  AptosLocalCache,
+  getTypeTagFullname,
+  iterableTableItemRequest,
+  StructTag,
+  tableItemRequest,
+  TypeParamIdx,
+  u64,
+  U128,
+} from "@manahippo/move-to-ts";
+import { HexString } from "aptos";
+import * as QueryByKey from "../MoveToTsTestsuite/QueryByKey";
+import * as TableItems from "../MoveToTsTestsuite/TableItems";
+import { expect, expectThrows } from "./expect";
+
+const $c = new AptosLocalCache();
+// older frameworks keep the handle of a table as a u128, newer ones as an address
+const handleOf = (table: { handle: U128 | HexString }) =>
+  table.handle instanceof HexString ? table.handle.hex() : table.handle.value.toString();
+const tagOf = (struct: { moduleAddress: HexString, moduleName: string, structName: string }, typeParams: any[] = []) =>
+  new StructTag(struct.moduleAddress, struct.moduleName, struct.structName, typeParams);
+
+const book = TableItems.new_book_($c);
+const request = book.tableItemRequest_by_id(u64(42));
+expect(request.handle === handleOf(book.by_id), `expect the handle of by_id, got ${request.handle}`);
+expect(request.keyType === "u64" && request.valueType === "u128", `unexpected types ${request.keyType}, ${request.valueType}`);
+expect(request.key === "42", `expect u64 keys as strings, got ${request.key}`);
+
+const ownerRequest = book.tableItemRequest_by_owner(new HexString("0xb0b"));
+expect(ownerRequest.valueType === "vector<u64>", `unexpected value type ${ownerRequest.valueType}`);
+expect(ownerRequest.key === "0xb0b", `expect address keys as hex, got ${ownerRequest.key}`);
+
+// struct keys are sent as objects of their fields
+const orderIdTag = tagOf(TableItems.OrderId);
+const key = TableItems.OrderId.__pack({ id: u64(7), owner: new HexString("0x2") }, orderIdTag);
+const orderRequest = book.tableItemRequest_orders(key);
+expect(orderRequest.keyType === getTypeTagFullname(orderIdTag), `unexpected key type ${orderRequest.keyType}`);
+expect(
+  orderRequest.key.id === "7" && orderRequest.key.owner === "0x2",
+  `expect struct keys as objects, got ${JSON.stringify(orderRequest.key)}`
+);
+
+// Registry<K, V> resolves the types of its entries from the type tag of the instance
+const registry = TableItems.new_registry_($c, [orderIdTag, new StructTag(new HexString("0x1"), "string", "String", [])]);
+const registryRequest = registry.tableItemRequest_entries(key);
+expect(registryRequest.keyType === getTypeTagFullname(orderIdTag), `expect K resolved, got ${registryRequest.keyType}`);
+expect(registryRequest.valueType === "0x1::string::String", `expect V resolved, got ${registryRequest.valueType}`);
+expectThrows(
+  () => tableItemRequest(registry.entries.handle, TableItems.Registry.getField("entries"), [new TypeParamIdx(0), orderIdTag], key),
+  m => m.includes("unresolved type parameters"),
+  "unresolved type parameters to throw"
+);
+
+// IterableTable<u64, Pool<X, Y>> in Pools<X, Y> reaches its handle through its TableWithLength
+const coins = [tagOf(QueryByKey.CoinA), tagOf(QueryByKey.CoinB)];
+const pools = QueryByKey.new_pools_($c, coins);
+const iterRequest = iterableTableItemRequest(pools.pools, QueryByKey.Pools.getField("pools"), coins, u64(1));
+expect(iterRequest.handle === handleOf(pools.pools.inner.inner), `expect the handle of the inner Table, got ${iterRequest.handle}`);
+expect(
+  iterRequest.valueType === `0x1::iterable_table::IterableValue<u64, ${getTypeTagFullname(tagOf(QueryByKey.Pool, coins))}>`,
+  `expect the entries as IterableValue, got ${iterRequest.valueType}`
+);
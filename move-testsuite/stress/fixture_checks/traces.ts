@@ -0,0 +1,21 @@
+import { AptosLocalCache, moveTraceOf } from "@manahippo/move-to-ts";
+import * as Traces from "../MoveToTsTestsuite/Traces";
+import { expect } from "./expect";
+
+// translated with --trace
+const $c = new AptosLocalCache();
+let caught: any;
+try {
+  Traces.outer_(true, $c);
+}
+catch (e) {
+  caught = e;
+}
+expect(caught instanceof Error, "expect the abort to reach the caller");
+// the message stays the abort code, which expected_failure checks match as a substring
+expect(caught.message === "7", `expect the message to stay the abort code, got ${caught.message}`);
+const frames = moveTraceOf(caught);
+expect(frames.length === 2, `expect a frame for inner and outer, got ${frames.join(" | ")}`);
+expect(frames[0].endsWith("::Traces::inner(amount=5, owner=0xb0b)"), `unexpected frame: ${frames[0]}`);
+expect(frames[1].endsWith("::Traces::outer(flag=true)"), `unexpected frame: ${frames[1]}`);
+expect(caught.stack.endsWith(frames.join("\n")), "expect the frames at the end of the stack");
@@ -0,0 +1,13 @@
+import { AptosLocalCache } from "@manahippo/move-to-ts";
+import { HexString } from "aptos";
+import * as VectorConstants from "../MoveToTsTestsuite/VectorConstants";
+import { expect } from "./expect";
+
+const $c = new AptosLocalCache();
+// vector[addr] and vector[] in a function body are arrays, not the element itself or a unit
+const list = VectorConstants.only_(new HexString("0xb0b"), $c);
+expect(Array.isArray(list) && list.length === 1, `expect one address, got ${list}`);
+expect(list[0] instanceof HexString && list[0].hex() === "0xb0b", `expect 0xb0b, got ${list[0]}`);
+const nobody = VectorConstants.nobody_($c);
+expect(Array.isArray(nobody) && nobody.length === 0, `expect no address, got ${nobody}`);
+expect(!VectorConstants.contains_address_(nobody, new HexString("0x1"), $c), "expect 0x1 not to be found");
@@ -0,0 +1,73 @@
+#!/usr/bin/env bash
+# Translates a package with --gen-cli and checks that its commands can run in --interactive mode:
+# arguments are optional for commander, and each command lists its type arguments and arguments
+# with their types and the descriptions given in #[cmd], which the prompts and the summary shown
+# before submitting use, and submitted transactions are waited for unless --no-wait is given. A
+# description that is not a byte string is an error.
+# Run from the repository root after `cargo build`:  move-testsuite/stress/interactive_cli.sh
+set -euo pipefail
+
+MOVE_TO_TS=${MOVE_TO_TS:-$(pwd)/target/debug/move-to-ts}
+WORK=$(mktemp -d)
+trap 'rm -rf "$WORK"' EXIT
+
+package() {
+  local dir=$1
+  mkdir -p "$WORK/$dir/sources"
+  cat > "$WORK/$dir/Move.toml" <<TOML
+[package]
+name = "Vault"
+version = "0.0.1"
+
+[addresses]
+vault = "0x7e1"
+TOML
+  cat > "$WORK/$dir/sources/vault.move"
+}
+
+package app <<MOVE
+module vault::vault {
+    #[cmd(desc=b"Move coins into the vault", amount=b"in octas, 1 APT is 100000000")]
+    public entry fun deposit<CoinType>(_account: &signer, to: address, amount: u64, lock: bool) {}
+
+    #[cmd]
+    public entry fun ping(_account: &signer) {}
+}
+MOVE
+"$MOVE_TO_TS" -p "$WORK/app" -o "$WORK/app/ts" -c
+CLI="$WORK/app/ts/src/cli.ts"
+expect() {
+  if ! grep -qF -- "$1" "$CLI"; then
+    echo "FAIL: cli.ts has no $1"
+    exit 1
+  fi
+}
+expect ".option('-i, --interactive'"
+expect ".option('--no-wait'"
+expect "printTransactionOutcome(txDetails);"
+expect ".argument('[TYPE_CoinType]'"
+expect ".argument('[amount]', \"in octas, 1 APT is 100000000\")"
+expect ".argument('[to]')"
+expect "{name: 'CoinType', type: 'type argument', description: '', parse: (client: AptosClient, value: string) => parseTypeArg('CoinType', value)},"
+expect "{name: 'amount', type: 'u64', description: \"in octas, 1 APT is 100000000\", parse: (client: AptosClient, value: string) => u64(value)},"
+expect "{name: 'lock', type: 'bool', description: \"\""
+expect "const [CoinType, to, amount, lock] = await collectArgs(client, vault_deposit_params, [CoinType\$arg, to\$arg, amount\$arg, lock\$arg]);"
+expect "confirmPayload(client, account, '0x7e1::vault::deposit', vault_deposit_params, [CoinType, to, amount, lock], payload)"
+expect "const vault_ping_params: CliParam[] = [];"
+
+package bad <<MOVE
+module vault::vault {
+    #[cmd(amount=1)]
+    public entry fun deposit(_account: &signer, amount: u64) {}
+}
+MOVE
+if OUTPUT=$("$MOVE_TO_TS" -p "$WORK/bad" -o "$WORK/bad/ts" -c 2>&1); then
+  echo "FAIL: a description that is not a byte string was accepted"
+  exit 1
+fi
+if ! grep -qF "amount needs to be assigned a byte string describing the argument" <<< "$OUTPUT"; then
+  echo "FAIL: expected an error about the description of amount, got:"
+  echo "$OUTPUT"
+  exit 1
+fi
+echo "Passed: cli commands prompt for their arguments in --interactive mode"
@@ -0,0 +1,58 @@
+#!/usr/bin/env bash
+# Translates a generic entry function with --emit-json-schema and compares its schema against the
+# snapshot in snapshots/json_schema_swap_route.json. Functions without a buildPayload_ get none.
+# Run from the repository root after `cargo build`:  move-testsuite/stress/json_schema.sh
+set -euo pipefail
+
+MOVE_TO_TS=${MOVE_TO_TS:-$(pwd)/target/debug/move-to-ts}
+SNAPSHOT=$(pwd)/move-testsuite/stress/snapshots/json_schema_swap_route.json
+WORK=$(mktemp -d)
+trap 'rm -rf "$WORK"' EXIT
+
+mkdir -p "$WORK/sources"
+cat > "$WORK/Move.toml" <<TOML
+[package]
+name = "JsonSchema"
+version = "0.0.1"
+
+[addresses]
+JsonSchema = "0x12349"
+TOML
+cat > "$WORK/sources/router.move" <<MOVE
+module JsonSchema::router {
+    struct Route has drop { hops: vector<vector<u64>> }
+
+    public entry fun swap_route<X, Y, Z, Curve>(
+        account: &signer,
+        amount_in: u64,
+        min_out: u128,
+        path: vector<address>,
+        memo: vector<u8>,
+        hops: vector<vector<u64>>,
+        fee_tier: u8,
+        unwrap: bool
+    ) {
+        let _ = account;
+        let _ = Route { hops };
+        assert!(amount_in > 0 && min_out > 0 && fee_tier < 100 && !unwrap, 1);
+        let _ = path;
+        let _ = memo;
+    }
+
+    public fun route_of(hops: vector<vector<u64>>): Route {
+        Route { hops }
+    }
+}
+MOVE
+
+"$MOVE_TO_TS" -p "$WORK" -o "$WORK/out" --emit-json-schema
+SCHEMAS="$WORK/out/schemas/0x12349/router"
+if ! diff "$SNAPSHOT" "$SCHEMAS/swap_route.json"; then
+  echo "FAIL: the schema of swap_route differs from the snapshot"
+  exit 1
+fi
+if [ -e "$SCHEMAS/route_of.json" ]; then
+  echo "FAIL: route_of has no buildPayload_, so it should have no schema"
+  exit 1
+fi
+echo "Passed: entry function schema matches the snapshot"
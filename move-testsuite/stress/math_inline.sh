@@ -0,0 +1,109 @@
+#!/usr/bin/env bash
+# Translates a package calling the helpers of 0x1::math64 and 0x1::math128, with aptos-stdlib as
+# its dependency, and checks that the calls go to the fast paths of the runtime, or to the
+# translated bodies with --no-math-inline. The generated tests of math64 and math128 run the fast
+# paths against those bodies, and run here with jest together with the tests of the package.
+# APTOS_FRAMEWORK is the aptos-move/framework/aptos-framework directory of an aptos-core checkout
+# recent enough to have math64 and math128, next to which aptos-stdlib is found.
+# Run from the repository root after `cargo build` and `yarn build` in typescript/, with network
+# access for the yarn install of the generated package:
+#   APTOS_FRAMEWORK=<dir> move-testsuite/stress/math_inline.sh
+set -euo pipefail
+
+MOVE_TO_TS=${MOVE_TO_TS:-$(pwd)/target/debug/move-to-ts}
+APTOS_FRAMEWORK=${APTOS_FRAMEWORK:?set APTOS_FRAMEWORK to an aptos-framework package directory}
+SKIPS=$(pwd)/move-testsuite/stress/framework_baseline.skip
+RUNTIME=$(pwd)/typescript
+WORK=$(mktemp -d)
+trap 'rm -rf "$WORK"' EXIT
+
+if [ ! -f "$RUNTIME/dist/index.d.ts" ]; then
+  echo "FAIL: run yarn install and yarn build in typescript/ first"
+  exit 1
+fi
+
+mkdir -p "$WORK/app/sources"
+cat > "$WORK/app/Move.toml" <<TOML
+[package]
+name = "MathInline"
+version = "0.0.1"
+
+[addresses]
+app = "0x7a1"
+
+[dependencies]
+AptosStdlib = { local = "$(cd "$APTOS_FRAMEWORK/../aptos-stdlib" && pwd)" }
+TOML
+cat > "$WORK/app/sources/pricing.move" <<MOVE
+module app::pricing {
+    use aptos_std::math64;
+    use aptos_std::math128;
+
+    public fun geometric_mean(a: u64, b: u64): u64 {
+        math64::sqrt(a * b)
+    }
+
+    public fun compound(base: u128, rate: u128, periods: u128): u128 {
+        base * math128::pow(rate, periods)
+    }
+
+    public fun spread(a: u64, b: u64): u64 {
+        math64::max(a, b) - math64::min(a, b)
+    }
+
+    #[test]
+    fun test_pricing() {
+        assert!(geometric_mean(4, 9) == 6, 0);
+        assert!(compound(3, 2, 10) == 3072, 1);
+        assert!(spread(7, 3) == 4, 2);
+        assert!(math64::average(7, 8) == 7, 3);
+    }
+
+    #[test]
+    #[expected_failure(arithmetic_error)]
+    fun test_pow_overflow() {
+        math64::pow(2, 64);
+    }
+}
+MOVE
+
+skip_args=()
+while IFS= read -r line; do
+  case "$line" in
+    ""|"#"*) ;;
+    *) skip_args+=(--skip-function "$line") ;;
+  esac
+done < "$SKIPS"
+
+"$MOVE_TO_TS" -p "$WORK/app" -o "$WORK/inline" -n math-inline -t ${skip_args[@]+"${skip_args[@]}"} > "$WORK/inline.log"
+PRICING="$WORK/inline/src/app/pricing.ts"
+for helper in math64_sqrt math128_pow math64_max math64_min; do
+  if ! grep -q "\$\.aptos_std_${helper}(" "$PRICING"; then
+    echo "FAIL: pricing.ts should call \$.aptos_std_${helper}"
+    exit 1
+  fi
+done
+for module in math64 math128; do
+  if ! grep -q "test('$module::fast paths'" "$WORK/inline/src/tests/aptos_std/$module.test.ts"; then
+    echo "FAIL: the tests of $module should check its fast paths"
+    exit 1
+  fi
+done
+
+"$MOVE_TO_TS" -p "$WORK/app" -o "$WORK/general" --no-math-inline ${skip_args[@]+"${skip_args[@]}"} > "$WORK/general.log"
+if grep -q "aptos_std_math" "$WORK/general/src/app/pricing.ts"; then
+  echo "FAIL: with --no-math-inline, pricing.ts should call the translated bodies"
+  exit 1
+fi
+
+OUT="$WORK/inline"
+(cd "$OUT" && yarn install --silent > /dev/null)
+rm -rf "$OUT/node_modules/@manahippo/move-to-ts"
+ln -s "$RUNTIME" "$OUT/node_modules/@manahippo/move-to-ts"
+if ! (cd "$OUT" && yarn --silent jest src/tests/aptos_std/math64.test.ts src/tests/aptos_std/math128.test.ts \
+  src/tests/app/pricing.test.ts 2> "$WORK/jest.log"); then
+  echo "FAIL: the fast paths disagree with the translated bodies"
+  tail -n 40 "$WORK/jest.log"
+  exit 1
+fi
+echo "Passed: math helpers go to the fast paths, which agree with the translated bodies"
@@ -0,0 +1,94 @@
+#!/usr/bin/env bash
+# Translates a package into itself with -o . twice, with package.json and --managed-output. The
+# generated package has to land in typescript/ both times with identical contents, and the second
+# run must not remove or change a file, while Move.toml and the Move sources stay untouched and no
+# package.json appears next to Move.toml. Also checks --output-subdir, and that it is refused when
+# it leads into the Move sources, or out of the output directory through a symlink.
+# Run from the repository root after `cargo build`:  move-testsuite/stress/output_in_package.sh
+set -euo pipefail
+
+MOVE_TO_TS=${MOVE_TO_TS:-$(pwd)/target/debug/move-to-ts}
+WORK=$(mktemp -d)
+trap 'rm -rf "$WORK"' EXIT
+
+PACKAGE="$WORK/package"
+mkdir -p "$PACKAGE/sources"
+cat > "$PACKAGE/Move.toml" <<TOML
+[package]
+name = "InPlace"
+version = "0.0.1"
+
+[addresses]
+inplace = "0x1d1"
+TOML
+cat > "$PACKAGE/sources/counter.move" <<MOVE
+module inplace::counter {
+    struct Counter has key { value: u64 }
+
+    public entry fun create(account: &signer) {
+        move_to(account, Counter { value: 0 });
+    }
+}
+MOVE
+
+move_files() {
+  (cd "$PACKAGE" && find Move.toml sources -type f -exec sha256sum {} + | sort)
+}
+
+before=$(move_files)
+run() {
+  (cd "$PACKAGE" && "$MOVE_TO_TS" -o . -n in-place --managed-output "$@") > "$WORK/run.log"
+}
+
+run
+if [ ! -f "$PACKAGE/typescript/package.json" ] || [ ! -d "$PACKAGE/typescript/src" ]; then
+  echo "FAIL: expected the generated package under typescript/"
+  exit 1
+fi
+for entry in package.json tsconfig.json src .move-to-ts-manifest; do
+  if [ -e "$PACKAGE/$entry" ]; then
+    echo "FAIL: $entry was written next to Move.toml"
+    exit 1
+  fi
+done
+cp -r "$PACKAGE/typescript" "$WORK/first"
+
+run
+if grep -q "^Removed" "$WORK/run.log"; then
+  echo "FAIL: the second run pruned files of the first"
+  cat "$WORK/run.log"
+  exit 1
+fi
+if ! diff -r "$WORK/first" "$PACKAGE/typescript"; then
+  echo "FAIL: the second run changed the generated package"
+  exit 1
+fi
+if [ "$before" != "$(move_files)" ]; then
+  echo "FAIL: Move.toml or the Move sources changed"
+  exit 1
+fi
+
+run --output-subdir sdk/ts
+if [ ! -f "$PACKAGE/sdk/ts/package.json" ]; then
+  echo "FAIL: expected the generated package under sdk/ts/ with --output-subdir"
+  exit 1
+fi
+if run --output-subdir sources/ts; then
+  echo "FAIL: an --output-subdir inside the Move sources was accepted"
+  exit 1
+fi
+mkdir "$WORK/outside"
+ln -s "$WORK/outside" "$PACKAGE/escape"
+if run --output-subdir escape/ts; then
+  echo "FAIL: an --output-subdir leading out of the output directory through a symlink was accepted"
+  exit 1
+fi
+if [ -n "$(ls -A "$WORK/outside")" ]; then
+  echo "FAIL: files were written through the symlink"
+  exit 1
+fi
+if [ "$before" != "$(move_files)" ]; then
+  echo "FAIL: Move.toml or the Move sources changed"
+  exit 1
+fi
+echo "Passed: -o . writes the same typescript/ twice and leaves the Move package alone"
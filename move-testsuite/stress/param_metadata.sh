@@ -0,0 +1,70 @@
+#!/usr/bin/env bash
+# Translates entry functions with every kind of parameter and compares their typeParams_ and
+# params_ metadata against the snapshot in snapshots/param_metadata.ts. A function with a
+# parameter buildPayload_ cannot take still gets its metadata, with that parameter marked
+# payloadEligible: false, and a struct parameter is of kind unsupported, while a public function
+# gets none.
+# Run from the repository root after `cargo build`:  move-testsuite/stress/param_metadata.sh
+set -euo pipefail
+
+MOVE_TO_TS=${MOVE_TO_TS:-$(pwd)/target/debug/move-to-ts}
+SNAPSHOT=$(pwd)/move-testsuite/stress/snapshots/param_metadata.ts
+WORK=$(mktemp -d)
+trap 'rm -rf "$WORK"' EXIT
+
+mkdir -p "$WORK/sources"
+cat > "$WORK/Move.toml" <<TOML
+[package]
+name = "ParamMetadata"
+version = "0.0.1"
+
+[addresses]
+ParamMetadata = "0x1234a"
+TOML
+cat > "$WORK/sources/router.move" <<MOVE
+module ParamMetadata::router {
+    struct Route has drop { hops: vector<vector<u64>> }
+
+    public entry fun swap_route<X, Y>(
+        account: &signer,
+        amount_in: u64,
+        path: vector<address>,
+        memo: vector<u8>,
+        hops: vector<vector<u64>>,
+        unwrap: bool
+    ) {
+        let _ = account;
+        let _ = Route { hops };
+        assert!(amount_in > 0 && !unwrap, 1);
+        let _ = path;
+        let _ = memo;
+    }
+
+    public entry fun multi_sign(accounts: vector<signer>, threshold: u8) {
+        assert!(threshold > 0, 1);
+        let _ = accounts;
+    }
+
+    public entry fun set_route(route: Route) {
+        let _ = route;
+    }
+
+    public fun route_of(hops: vector<vector<u64>>): Route {
+        Route { hops }
+    }
+}
+MOVE
+
+"$MOVE_TO_TS" -p "$WORK" -o "$WORK/out"
+MODULE="$WORK/out/src/ParamMetadata/router.ts"
+awk '/^export const (typeParams|params)_/ { keep = 1 } keep { print } /as const;$/ { keep = 0 }' \
+  "$MODULE" > "$WORK/metadata.ts"
+if ! diff "$SNAPSHOT" "$WORK/metadata.ts"; then
+  echo "FAIL: the parameter metadata differs from the snapshot"
+  exit 1
+fi
+if grep -q "params_route_of" "$MODULE"; then
+  echo "FAIL: route_of is not an entry function, so it should have no parameter metadata"
+  exit 1
+fi
+echo "Passed: entry function parameter metadata matches the snapshot"
@@ -0,0 +1,67 @@
+#!/usr/bin/env bash
+# Translates a package with --emit-provenance and --emit-json-schema from outside of its directory,
+# and checks that source_<function> names the Move file relative to the package with a byte range
+# that starts at the function's name and ends with its body, that the JSON Schema carries the same
+# provenance, and that friendModules lists the module's friends.
+# Run from the repository root after `cargo build`:  move-testsuite/stress/provenance.sh
+set -euo pipefail
+
+MOVE_TO_TS=${MOVE_TO_TS:-$(pwd)/target/debug/move-to-ts}
+WORK=$(mktemp -d)
+trap 'rm -rf "$WORK"' EXIT
+
+mkdir -p "$WORK/swap/sources"
+cat > "$WORK/swap/Move.toml" <<TOML
+[package]
+name = "Swap"
+version = "0.0.1"
+
+[addresses]
+swap = "0x5a"
+TOML
+cat > "$WORK/swap/sources/swap.move" <<MOVE
+module swap::pool {
+    friend swap::router;
+
+    struct Pool has key { reserve: u64 }
+
+    public entry fun create(account: &signer, reserve: u64) {
+        move_to(account, Pool { reserve });
+    }
+}
+
+module swap::router {
+    public entry fun noop(_account: &signer) {}
+}
+MOVE
+
+OUT="$WORK/out"
+(cd "$WORK" && "$MOVE_TO_TS" -p swap -o "$OUT" --emit-provenance --emit-json-schema > /dev/null)
+POOL=$(find "$OUT/src" -name pool.ts)
+LINE=$(grep "^export const source_create = " "$POOL" || true)
+PATTERN='^export const source_create = \{ file: "sources/swap.move", start: ([0-9]+), end: ([0-9]+) \} as const;$'
+if [[ ! "$LINE" =~ $PATTERN ]]; then
+  echo "FAIL: unexpected provenance of create: $LINE"
+  exit 1
+fi
+START=${BASH_REMATCH[1]}
+END=${BASH_REMATCH[2]}
+SPAN=$(tail -c +$((START + 1)) "$WORK/swap/sources/swap.move" | head -c $((END - START)))
+if [[ "$SPAN" != create\(* ]] || [[ "$SPAN" != *"Pool { reserve });"*"}" ]]; then
+  echo "FAIL: bytes $START..$END are not the function create:"
+  echo "$SPAN"
+  exit 1
+fi
+
+SCHEMA=$(find "$OUT/schemas" -name create.json)
+if ! grep -qF "\"provenance\": {\"file\":\"sources/swap.move\",\"start\":$START,\"end\":$END}," "$SCHEMA"; then
+  echo "FAIL: the schema of create does not carry the same provenance:"
+  cat "$SCHEMA"
+  exit 1
+fi
+
+if ! grep -qxF 'export const friendModules = ["0x5a::router"] as const;' "$POOL"; then
+  echo "FAIL: expected pool.ts to list router as its friend"
+  exit 1
+fi
+echo "Passed: payload builders and schemas point back to their Move source"
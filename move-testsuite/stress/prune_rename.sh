@@ -0,0 +1,78 @@
+#!/usr/bin/env bash
+# Translates a package with --managed-output, renames one of its modules and translates it again.
+# The manifest of the second run must have exactly one file removed and one added compared to the
+# first, the old module file has to be gone from disk, and neither a user-authored file next to the
+# output nor a file the manifest points to outside of the output directory may be deleted.
+# Run from the repository root after `cargo build`:  move-testsuite/stress/prune_rename.sh
+set -euo pipefail
+
+MOVE_TO_TS=${MOVE_TO_TS:-$(pwd)/target/debug/move-to-ts}
+WORK=$(mktemp -d)
+trap 'rm -rf "$WORK"' EXIT
+
+mkdir -p "$WORK/sources"
+cat > "$WORK/Move.toml" <<TOML
+[package]
+name = "Prune"
+version = "0.0.1"
+
+[addresses]
+Prune = "0x1234c"
+TOML
+cat > "$WORK/sources/stays.move" <<MOVE
+module Prune::stays {
+    public fun one(): u64 { 1 }
+}
+MOVE
+cat > "$WORK/sources/before.move" <<MOVE
+module Prune::before {
+    public fun two(): u64 { 2 }
+}
+MOVE
+
+OUT="$WORK/out"
+generated() {
+  grep -v "^user-owned: " "$OUT/.move-to-ts-manifest" | sort
+}
+
+"$MOVE_TO_TS" -p "$WORK" -o "$OUT" --managed-output > /dev/null
+generated > "$WORK/first"
+echo "// written by hand" > "$OUT/src/notes.ts"
+echo "outside" > "$WORK/outside.txt"
+echo "../outside.txt" >> "$OUT/.move-to-ts-manifest"
+
+rm "$WORK/sources/before.move"
+cat > "$WORK/sources/after.move" <<MOVE
+module Prune::after {
+    public fun two(): u64 { 2 }
+}
+MOVE
+"$MOVE_TO_TS" -p "$WORK" -o "$OUT" --managed-output > "$WORK/run.log"
+generated > "$WORK/second"
+
+removed=$(comm -23 "$WORK/first" "$WORK/second")
+added=$(comm -13 "$WORK/first" "$WORK/second")
+if [ "$removed" != "src/Prune/before.ts" ] || [ "$added" != "src/Prune/after.ts" ]; then
+  echo "FAIL: expected before.ts removed and after.ts added, got"
+  echo "removed: $removed"
+  echo "added: $added"
+  exit 1
+fi
+if [ -e "$OUT/src/Prune/before.ts" ] || [ ! -f "$OUT/src/Prune/after.ts" ]; then
+  echo "FAIL: before.ts should be deleted and after.ts written"
+  exit 1
+fi
+if [ "$(grep -c "^Removed" "$WORK/run.log")" != 1 ]; then
+  echo "FAIL: expected exactly one file pruned"
+  cat "$WORK/run.log"
+  exit 1
+fi
+if [ ! -f "$OUT/src/notes.ts" ]; then
+  echo "FAIL: pruning deleted a file that is not in the manifest"
+  exit 1
+fi
+if [ ! -f "$WORK/outside.txt" ] || ! grep -q "^Ignoring ../outside.txt" "$WORK/run.log"; then
+  echo "FAIL: a manifest entry outside of the output directory should be ignored"
+  exit 1
+fi
+echo "Passed: renaming a module prunes exactly its old file"
@@ -0,0 +1,18 @@
+export const typeParams_multi_sign = [] as const;
+export const params_multi_sign = [
+  { name: "accounts", kind: "vector", inner: { kind: "signer" }, isSigner: false, tsType: "unknown", payloadEligible: false, reason: "Signers cannot be passed in a transaction payload, only the accounts that sign it provide them" },
+  { name: "threshold", kind: "u8", isSigner: false, tsType: "U8 | number | bigint | string", payloadEligible: true },
+] as const;
+export const typeParams_set_route = [] as const;
+export const params_set_route = [
+  { name: "route", kind: "unsupported", isSigner: false, tsType: "unknown", payloadEligible: false, reason: "This type is not supported as parameter of script function" },
+] as const;
+export const typeParams_swap_route = ["X", "Y"] as const;
+export const params_swap_route = [
+  { name: "account", kind: "signer", isSigner: true, tsType: "HexString", payloadEligible: true },
+  { name: "amount_in", kind: "u64", isSigner: false, tsType: "U64 | number | bigint | string", payloadEligible: true },
+  { name: "path", kind: "vector", inner: { kind: "address" }, isSigner: false, tsType: "(HexString | string)[]", payloadEligible: true },
+  { name: "memo", kind: "u8vector", isSigner: false, tsType: "U8[]", payloadEligible: true },
+  { name: "hops", kind: "vector", inner: { kind: "vector", inner: { kind: "u64" } }, isSigner: false, tsType: "U64[][]", payloadEligible: true },
+  { name: "unwrap", kind: "bool", isSigner: false, tsType: "boolean", payloadEligible: true },
+] as const;
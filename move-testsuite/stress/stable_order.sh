@@ -0,0 +1,55 @@
+#!/usr/bin/env bash
+# Translates a module, then the same module with its declarations in reverse order, and checks that
+# both runs produce byte-identical output: declarations are emitted sorted by name, whatever their
+# order in the Move file.
+# Run from the repository root after `cargo build`:  move-testsuite/stress/stable_order.sh
+set -euo pipefail
+
+MOVE_TO_TS=${MOVE_TO_TS:-$(pwd)/target/debug/move-to-ts}
+WORK=$(mktemp -d)
+trap 'rm -rf "$WORK"' EXIT
+
+mkdir -p "$WORK/sources"
+cat > "$WORK/Move.toml" <<TOML
+[package]
+name = "StableOrder"
+version = "0.0.1"
+
+[addresses]
+StableOrder = "0x12348"
+TOML
+
+DECLS=(
+  "const E_ZERO: u64 = 1;"
+  "const E_LIMIT: u64 = 2;"
+  "struct Vault has key { amount: u64 }"
+  "struct Receipt has key, store, drop { amount: u64 }"
+  "#[cmd] public entry fun open(account: &signer) { move_to(account, Vault { amount: 0 }); }"
+  "public fun peek(vault: &Vault): u64 { vault.amount }"
+  "public fun check(amount: u64) { assert!(amount > 0, E_ZERO); assert!(amount < 100, E_LIMIT); }"
+  "public fun receipt(amount: u64): Receipt { check(amount); Receipt { amount } }"
+)
+
+generate() {
+  {
+    echo "module StableOrder::Vault {"
+    for decl in "$@"; do
+      echo "    $decl"
+    done
+    echo "}"
+  } > "$WORK/sources/Vault.move"
+  "$MOVE_TO_TS" -p "$WORK" -o "$WORK/$OUT" --gen-cli
+}
+
+OUT=forward generate "${DECLS[@]}"
+REVERSED=()
+for ((i = ${#DECLS[@]} - 1; i >= 0; i--)); do
+  REVERSED+=("${DECLS[$i]}")
+done
+OUT=reversed generate "${REVERSED[@]}"
+
+if ! diff -r "$WORK/forward/src" "$WORK/reversed/src"; then
+  echo "FAIL: reordering the declarations changed the output"
+  exit 1
+fi
+echo "Passed: reordered declarations produce identical output"
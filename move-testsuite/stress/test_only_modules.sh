@@ -0,0 +1,45 @@
+#!/usr/bin/env bash
+# Translates move-testsuite with --gen-test and checks that std::unit_test, a #[test_only] module,
+# is written under src/tests and left out of the std index, that the output compiles with tsc, and
+# that the tests of TestOnlyImports, which create their account with
+# account::create_account_for_test, pass against the runtime in typescript/.
+# Run from the repository root after `cargo build` and `yarn build` in typescript/, with network
+# access for the yarn install of the generated package:
+#   move-testsuite/stress/test_only_modules.sh
+set -euo pipefail
+
+MOVE_TO_TS=${MOVE_TO_TS:-$(pwd)/target/debug/move-to-ts}
+RUNTIME=$(pwd)/typescript
+WORK=$(mktemp -d)
+trap 'rm -rf "$WORK"' EXIT
+
+if [ ! -f "$RUNTIME/dist/index.d.ts" ] || [ ! -x "$RUNTIME/node_modules/.bin/tsc" ]; then
+  echo "FAIL: run yarn install and yarn build in typescript/ first"
+  exit 1
+fi
+
+OUT="$WORK/out"
+"$MOVE_TO_TS" -p "$(pwd)/move-testsuite" -o "$OUT" -n test-only-modules -t > "$WORK/translate.log"
+if [ ! -f "$OUT/src/tests/std/unit_test.ts" ] || [ -f "$OUT/src/std/unit_test.ts" ]; then
+  echo "FAIL: expected std::unit_test under src/tests only"
+  exit 1
+fi
+if grep -q "unit_test" "$OUT/src/std/index.ts"; then
+  echo "FAIL: the std index refers to the #[test_only] module unit_test"
+  exit 1
+fi
+
+(cd "$OUT" && yarn install --silent > /dev/null)
+rm -rf "$OUT/node_modules/@manahippo/move-to-ts"
+ln -s "$RUNTIME" "$OUT/node_modules/@manahippo/move-to-ts"
+if ! "$RUNTIME/node_modules/.bin/tsc" --noEmit -p "$OUT/tsconfig.json" > "$WORK/tsc.log"; then
+  echo "FAIL: the output does not compile"
+  head -n 40 "$WORK/tsc.log"
+  exit 1
+fi
+if ! (cd "$OUT" && yarn --silent jest TestOnlyImports 2> "$WORK/jest.log"); then
+  echo "FAIL: the TestOnlyImports tests do not pass"
+  tail -n 40 "$WORK/jest.log"
+  exit 1
+fi
+echo "Passed: #[test_only] modules are translated under src/tests for the tests that use them"
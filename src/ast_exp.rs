@@ -1,11 +1,12 @@
+use crate::ast_to_ts::{collect_side_effects, int_width, is_exp_unit};
 use crate::shared::*;
-use crate::utils::rename;
+use crate::utils::{field_member_name, rename};
 use move_compiler::{
     diagnostics::Diagnostic,
-    expansion::ast::ModuleAccess,
+    expansion::ast::{ModuleAccess, ModuleIdent},
     hlir::ast::*,
     naming::ast::{BuiltinTypeName, BuiltinTypeName_, TParam},
-    parser::ast::{BinOp, BinOp_, UnaryOp},
+    parser::ast::{BinOp, BinOp_, FunctionName, StructName, UnaryOp},
 };
 use move_ir_types::location::Loc;
 
@@ -15,49 +16,19 @@ impl AstTsPrinter for Exp {
         let Exp { ty: exp_ty, exp } = self;
         use UnannotatedExp_ as E;
         match &exp.value {
-            E::Unit { case: _ } => {
-                unreachable!("Cannot output Unit as value");
-                //derr!((exp.loc, "Cannot output Unit as value"))
-            }
+            // the value of a void function
+            E::Unit { case: _ } => Ok("undefined".to_string()),
             E::Value(v) => Ok(v.term(c)?),
             E::Move {
                 annotation: _,
                 var: v,
-            } => Ok(rename(v)),
+            } => Ok(c.var_name(v)),
             E::Copy {
                 from_user: _,
                 var: v,
-            } => {
-                // value-types and immutables are copied by default
-                let nocopy = rename(v);
-                let explicit_copy = Ok(format!("$.copy({})", nocopy));
-                match &exp_ty.value {
-                    Type_::Single(single_ty) => match &single_ty.value {
-                        SingleType_::Ref(_, _) => Ok(nocopy),
-                        SingleType_::Base(base_ty) => match &base_ty.value {
-                            BaseType_::Param(_) => explicit_copy,
-                            BaseType_::Apply(_, typename, _) => match &typename.value {
-                                TypeName_::Builtin(builtin) => match &builtin.value {
-                                    // vector needs explicit copy
-                                    BuiltinTypeName_::Vector => explicit_copy,
-                                    BuiltinTypeName_::Bool => Ok(nocopy),
-                                    // immutable types, address, signer do not need explicit copy
-                                    //_ => Ok(nocopy),
-                                    _ => explicit_copy,
-                                },
-                                TypeName_::ModuleType(_, _) => explicit_copy,
-                            },
-                            BaseType_::Unreachable => unreachable!(),
-                            BaseType_::UnresolvedError => unreachable!(),
-                        },
-                    },
-                    Type_::Unit => {
-                        return derr!((exp.loc, "Cannot copy Unit"));
-                    }
-                    Type_::Multiple(_) => explicit_copy,
-                }
-            }
-            E::Constant(c) => Ok(rename(c)),
+            } => copy_term(c.var_name(v), exp_ty, exp.loc, c),
+            // constants are shared by every use, so each use gets its own copy to mutate or move
+            E::Constant(name) => copy_term(rename(name), exp_ty, exp.loc, c),
             E::ModuleCall(mcall) => {
                 // ModuleCall
                 Ok(mcall.term(c)?)
@@ -66,22 +37,37 @@ impl AstTsPrinter for Exp {
                 // BuiltinCall
                 Ok((bf, rhs).term(c)?)
             }
-            E::Vector(_, _, _, elems) => Ok(elems.term(c)?),
+            // the elements are an ExpList only from two on, a single element comes as itself and
+            // none as a unit
+            E::Vector(_, n, _, elems) => match (&elems.exp.value, n) {
+                (_, 0) => Ok("[]".to_string()),
+                (E::ExpList(_), _) => Ok(elems.term(c)?),
+                _ => Ok(format!("[{}]", elems.term(c)?)),
+            },
             E::Pack(s, _tys, fields) => {
                 // ["Pack", "StructFullname", typeParams, fields]
                 // construct a new struct/class value using proto constructor
                 // constructor(proto: any, public typeTag: TypeTag)
+                // protos are keyed by Move field names, like the static fields of the class
                 let inner_fields = comma_term(fields, c, |(fname, _, e), c| {
                     Ok(format!("{}: {}", fname, e.term(c)?))
                 })?;
                 let proto = format!("{{ {} }}", inner_fields);
                 let tag = type_to_typetag(exp_ty, c)?;
-                Ok(format!("new {}({}, {})", s, proto, tag))
+                Ok(c.construct_struct(s, &proto, &tag))
             }
             E::ExpList(es) => {
                 // FIXME: for now just output as [...]
                 // FIXME: what is this, really?
-                Ok(format!("[{}]", comma_term(es, c, |e, c| e.term(c))?))
+                // units take no slot, they would leave holes like [, x]
+                let items = es
+                    .iter()
+                    .filter(|item| !is_unit_item(item))
+                    .collect::<Vec<_>>();
+                if items.is_empty() {
+                    return Ok("undefined".to_string());
+                }
+                Ok(format!("[{}]", comma_term(items, c, |e, c| e.term(c))?))
             }
             E::Dereference(e) => {
                 // dereference on RHS is copy, Dereference on LHS is handled in Mutate
@@ -96,16 +82,17 @@ impl AstTsPrinter for Exp {
                 (l, op, r).term(c)
             }
             E::Borrow(_, e, f) => {
+                let member = match borrowed_struct(e) {
+                    Some((mident, sname)) => c.field_member(&mident, &sname, f),
+                    None => field_member_name(f, c),
+                };
                 if c.is_async() {
-                    Ok(format!("({}).{}", e.term(c)?, rename(f)))
+                    Ok(format!("({}).{}", e.term(c)?, member))
                 } else {
-                    Ok(format!("{}.{}", e.term(c)?, rename(f)))
+                    Ok(format!("{}.{}", e.term(c)?, member))
                 }
             }
-            E::BorrowLocal(_, v) => {
-                Ok(format!("{}", rename(v)))
-                //Ok(rename(v))
-            }
+            E::BorrowLocal(_, v) => Ok(c.var_name(v)),
             E::Cast(e, ty) => {
                 return Ok(format!("{}({})", builtin_cast_name(&ty, c)?, e.term(c)?));
             }
@@ -113,13 +100,178 @@ impl AstTsPrinter for Exp {
             // FIXME: is this really how freeze should behave?
             E::Freeze(e) => e.term(c),
             E::UnresolvedError => {
+                c.reached_unresolved = Some(exp.loc);
                 derr!((exp.loc, "Encountered UnresolvedError"))
             }
-            E::Unreachable => unreachable!(),
+            E::Unreachable => {
+                c.reached_unreachable = true;
+                derr!((exp.loc, "Encountered unreachable expression"))
+            }
         }
     }
 }
 
+/// Module and name of the struct whose field `e`, a reference, is borrowed from
+fn borrowed_struct(e: &Exp) -> Option<(ModuleIdent, StructName)> {
+    match &e.ty.value {
+        Type_::Single(single) => match &single.value {
+            SingleType_::Ref(_, base) => match &base.value {
+                BaseType_::Apply(_, typename, _) => match &typename.value {
+                    TypeName_::ModuleType(mident, sname) => Some((*mident, *sname)),
+                    _ => None,
+                },
+                _ => None,
+            },
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// A copy of `term`, a value of type `ty`, that shares no object with it. Every value Move copies
+/// goes through here: whatever it ends up in (a move_to, a vector, a packed struct) can then be
+/// mutated without touching the original, and the other way around
+fn copy_term(term: String, ty: &Type, loc: Loc, c: &mut Context) -> TermResult {
+    let explicit_copy = Ok(format!("$.copy({})", term));
+    match &ty.value {
+        Type_::Single(single_ty) => match &single_ty.value {
+            SingleType_::Ref(_, _) => Ok(term),
+            SingleType_::Base(base_ty) => match &base_ty.value {
+                BaseType_::Param(_) => explicit_copy,
+                BaseType_::Apply(_, typename, _) => match &typename.value {
+                    TypeName_::Builtin(builtin) => match &builtin.value {
+                        // vector needs explicit copy
+                        BuiltinTypeName_::Vector => explicit_copy,
+                        BuiltinTypeName_::Bool => Ok(term),
+                        // integers and addresses can be mutated in place through $.set
+                        _ => explicit_copy,
+                    },
+                    TypeName_::ModuleType(_, _) => explicit_copy,
+                },
+                BaseType_::Unreachable => {
+                    c.reached_unreachable = true;
+                    derr!((loc, "Cannot copy a value of unreachable type"))
+                }
+                BaseType_::UnresolvedError => {
+                    c.reached_unresolved = Some(loc);
+                    derr!((loc, "Cannot copy a value of unresolved type"))
+                }
+            },
+        },
+        Type_::Unit => derr!((loc, "Cannot copy Unit")),
+        Type_::Multiple(_) => explicit_copy,
+    }
+}
+
+/*
+Getters of 0x1::timestamp and 0x1::block, and the test-only setters of 0x1::timestamp are
+redirected to the clock of AptosDataCache, unless --strict-natives is given
+ */
+pub fn clock_method_name(module: &ModuleIdent, name: &FunctionName) -> Option<&'static str> {
+    if format_address_hex(module.value.address) != "0x1" {
+        return None;
+    }
+    match (
+        module.value.module.to_string().as_str(),
+        name.to_string().as_str(),
+    ) {
+        ("timestamp", "now_microseconds") => Some("nowMicroseconds"),
+        ("timestamp", "now_seconds") => Some("nowSeconds"),
+        ("timestamp", "update_global_time_for_test") => Some("setMicroseconds"),
+        ("timestamp", "update_global_time_for_test_secs") => Some("setSeconds"),
+        ("timestamp", "fast_forward_seconds") => Some("fastForwardSeconds"),
+        ("block", "get_current_block_height") => Some("blockHeight"),
+        _ => None,
+    }
+}
+
+/// Helpers of 0x1::math64 and 0x1::math128 with a fast path in the runtime, and their arity
+pub const MATH_FAST_PATHS: [(&str, usize); 5] = [
+    ("max", 2),
+    ("min", 2),
+    ("average", 2),
+    ("pow", 2),
+    ("sqrt", 1),
+];
+
+pub fn is_math_module(module: &ModuleIdent) -> bool {
+    format_address_hex(module.value.address) == "0x1"
+        && ["math64", "math128"].contains(&module.value.module.to_string().as_str())
+}
+
+/// Runtime function a call to a math helper is translated to, unless --no-math-inline is given.
+/// Calls within the math modules keep going to the translated bodies, which the generated tests
+/// check the fast paths against
+pub fn math_fast_path(module: &ModuleIdent, name: &FunctionName, c: &Context) -> Option<String> {
+    if c.config.no_math_inline || !is_math_module(module) || c.is_current_module(module) {
+        return None;
+    }
+    MATH_FAST_PATHS
+        .iter()
+        .find(|(fname, _)| name.to_string() == *fname)
+        .map(|(fname, _)| format!("aptos_std_{}_{}", module.value.module, fname))
+}
+
+/// Public functions of 0x1::randomness, which are routed to the runtime so they draw from the
+/// cache's AptosRandomness instead of the per-block seed that only exists on chain
+const RANDOMNESS_FUNCTIONS: [&str; 10] = [
+    "u8_integer",
+    "u64_integer",
+    "u128_integer",
+    "u8_range",
+    "u64_range",
+    "u128_range",
+    "bytes",
+    "permutation",
+    "initialize_for_testing",
+    "set_seed",
+];
+
+pub fn is_randomness_call(module: &ModuleIdent, name: &FunctionName) -> bool {
+    format_address_hex(module.value.address) == "0x1"
+        && module.value.module.to_string() == "randomness"
+        && RANDOMNESS_FUNCTIONS.contains(&name.to_string().as_str())
+}
+
+/// Natives that only serve Move's resource discipline, so that calls to them need no runtime
+/// native: whether the value must be an empty vector, otherwise only the arguments are evaluated.
+/// option::destroy_none and the other destroy_* of 0x1::option are Move functions, whose
+/// translated bodies keep their own checks and end up here
+const DISCIPLINE_ONLY_NATIVES: [(&str, &str, bool); 2] = [
+    ("vector", "destroy_empty", true),
+    ("table", "drop_unchecked_box", false),
+];
+
+pub fn discipline_only_native(module: &ModuleIdent, name: &FunctionName) -> Option<bool> {
+    if format_address_hex(module.value.address) != "0x1" {
+        return None;
+    }
+    DISCIPLINE_ONLY_NATIVES
+        .iter()
+        .find(|(mname, fname, _)| {
+            module.value.module.to_string() == *mname && name.to_string() == *fname
+        })
+        .map(|(_, _, checks_empty)| *checks_empty)
+}
+
+/// What is left of a call to a discipline-only native: the empty check, thrown like the runtime
+/// native did, or the side effects of the arguments
+fn discipline_only_term(checks_empty: bool, arguments: &Exp, c: &mut Context) -> TermResult {
+    if checks_empty {
+        return Ok(format!(
+            "({}.length === 0 || $.assert(false, new Error(\"Vector is not empty!\")))",
+            arguments.term(c)?
+        ));
+    }
+    let mut effects = vec![];
+    collect_side_effects(arguments, &mut effects);
+    if effects.is_empty() {
+        return Ok("undefined".to_string());
+    }
+    let effects = comma_term(&effects, c, |e, c| e.term(c))?;
+    Ok(format!("({}, undefined)", effects))
+}
+
 pub fn format_type_args_at_instantiation(type_args: &Vec<BaseType>, c: &mut Context) -> TermResult {
     if type_args.is_empty() {
         return Ok("".to_string());
@@ -149,9 +301,58 @@ impl AstTsPrinter for ModuleCall {
         // ["Call", func, typeArgs, args]
         // func
 
+        if !c.config.strict_natives {
+            if let Some(method) = clock_method_name(module, name) {
+                let args = match &arguments.exp.value {
+                    UnannotatedExp_::Unit { case: _ } => "".to_string(),
+                    UnannotatedExp_::ExpList(items) => {
+                        comma_term(items, c, |item, c| item.term(c))?
+                    }
+                    _ => arguments.term(c)?,
+                };
+                return Ok(format!("$c.getClock().{}({})", method, args));
+            }
+        }
+
+        if is_randomness_call(module, name) {
+            let args = match &arguments.exp.value {
+                UnannotatedExp_::Unit { case: _ } => "".to_string(),
+                UnannotatedExp_::ExpList(items) => {
+                    format!("{}, ", comma_term(items, c, |item, c| item.term(c))?)
+                }
+                _ => format!("{}, ", arguments.term(c)?),
+            };
+            return Ok(format!("$.aptos_framework_randomness_{}({}$c)", name, args));
+        }
+
+        if let Some(fast_path) = math_fast_path(module, name, c) {
+            let args = match &arguments.exp.value {
+                UnannotatedExp_::ExpList(items) => comma_term(items, c, |item, c| item.term(c))?,
+                _ => arguments.term(c)?,
+            };
+            return Ok(format!("$.{}({})", fast_path, args));
+        }
+
+        if let Some(checks_empty) = discipline_only_native(module, name) {
+            return discipline_only_term(checks_empty, arguments, c);
+        }
+
+        // stubs of excluded modules only hold their structs
+        if let Some(entry) = c.excluded_module_entry(module) {
+            return derr!((
+                name.0.loc,
+                format!(
+                    "Calls {}::{}, which is not translated with --exclude-module {}",
+                    module, name, entry
+                )
+            ));
+        }
+
         // every function signature will start with:
         // export function X($c: AptosDataCache, $p: TypeTag[], ...)
-        let func_name = format_qualified_name(module, name, c);
+        let is_awaited = c.await_calls(arguments.exp.loc)?;
+        let ts_name = c.function_ts_name(module, name);
+        let func_name = format_qualified_name(module, &ts_name, c);
         // AptosFramework::TypeInfo::type_of requires us to always send in the type parameters as
         // arguments as they are not always inferrable from regular parameters
         let tparams_ = format_type_args_at_instantiation(type_arguments, c)?;
@@ -160,12 +361,12 @@ impl AstTsPrinter for ModuleCall {
             if type_arguments.is_empty() {
                 Ok(format!(
                     "{}($c)",
-                    format_function_name(&func_name, c.is_async())
+                    format_function_name(&func_name, is_awaited)
                 ))
             } else {
                 Ok(format!(
                     "{}($c, {})",
-                    format_function_name(&func_name, c.is_async()),
+                    format_function_name(&func_name, is_awaited),
                     tparams_
                 ))
             }
@@ -177,7 +378,7 @@ impl AstTsPrinter for ModuleCall {
             };
             Ok(format!(
                 "{}({}, $c{}{})",
-                format_function_name(&func_name, c.is_async()),
+                format_function_name(&func_name, is_awaited),
                 args,
                 if type_arguments.is_empty() { "" } else { ", " },
                 tparams_, // may be ""
@@ -197,8 +398,9 @@ impl AstTsPrinter for (&Box<BuiltinFunction>, &Box<Exp>) {
             args.term(c)?
         };
         use BuiltinFunction_ as F;
-        let await_modifier = if c.is_async() { "await " } else { "" };
-        let await_postfix = if c.is_async() { "_async" } else { "" };
+        let is_awaited = c.await_calls(args.exp.loc)?;
+        let await_modifier = if is_awaited { "await " } else { "" };
+        let await_postfix = if is_awaited { "_async" } else { "" };
         match &builtin_f.value {
             F::MoveTo(bt) => Ok(format!(
                 "{}$c.move_to{}({}, {})",
@@ -242,6 +444,13 @@ impl AstTsPrinter for (&Box<BuiltinFunction>, &Box<Exp>) {
     }
 }
 
+pub fn is_unit_item(item: &ExpListItem) -> bool {
+    match item {
+        ExpListItem::Single(e, _) => is_exp_unit(e),
+        ExpListItem::Splat(_, _, _) => false,
+    }
+}
+
 impl AstTsPrinter for ExpListItem {
     const CTOR_NAME: &'static str = "ExpListItem";
     fn term(&self, c: &mut Context) -> TermResult {
@@ -263,12 +472,8 @@ pub fn base_type_to_tstype(base_ty: &BaseType, c: &mut Context) -> TermResult {
             }
             // when TParam is used within a Type_, it is always used for instantiating a
             // concrete type or at a call site. So just use the hard-coded type-param: tparams_
-            if let Some(idx) = c.get_tparam_index(tp) {
-                Ok(format!("$p[{}]", idx))
-            } else {
-                unreachable!("Non-existent type parameter");
-                //derr!((self.loc, "Non-existent type parameter"))
-            }
+            let idx = c.tparam_index_or_err(tp, base_ty.loc, "base_type_to_tstype")?;
+            Ok(format!("$p[{}]", idx))
         }
         BaseType_::Apply(_abilities_opt, m, ss) => {
             match &m.value {
@@ -359,15 +564,21 @@ impl AstTsPrinter for BuiltinTypeName {
     }
 }
 
-pub fn builtin_cast_name(builtin: &BuiltinTypeName, _c: &mut Context) -> TermResult {
-    match builtin.value {
-        BuiltinTypeName_::Address => derr!((builtin.loc, "Cannot cast to address")),
-        BuiltinTypeName_::Bool => derr!((builtin.loc, "Cannot cast to bool")),
-        BuiltinTypeName_::U8 => Ok("u8".to_string()),
-        BuiltinTypeName_::U64 => Ok("u64".to_string()),
-        BuiltinTypeName_::U128 => Ok("u128".to_string()),
-        BuiltinTypeName_::Signer => derr!((builtin.loc, "Cannot cast to signer")),
-        BuiltinTypeName_::Vector => derr!((builtin.loc, "Cannot cast to vector")),
+/// Casts check that the value fits, unless runtime checks are off
+pub fn builtin_cast_name(builtin: &BuiltinTypeName, c: &mut Context) -> TermResult {
+    let name = match builtin.value {
+        BuiltinTypeName_::Address => return derr!((builtin.loc, "Cannot cast to address")),
+        BuiltinTypeName_::Bool => return derr!((builtin.loc, "Cannot cast to bool")),
+        BuiltinTypeName_::U8 => "u8",
+        BuiltinTypeName_::U64 => "u64",
+        BuiltinTypeName_::U128 => "u128",
+        BuiltinTypeName_::Signer => return derr!((builtin.loc, "Cannot cast to signer")),
+        BuiltinTypeName_::Vector => return derr!((builtin.loc, "Cannot cast to vector")),
+    };
+    if c.emits_runtime_checks() {
+        Ok(name.to_string())
+    } else {
+        Ok(format!("$.{}Unchecked", name))
     }
 }
 
@@ -408,14 +619,18 @@ impl AstTsPrinter for LValue {
         use LValue_ as L;
         match &self.value {
             L::Ignore => Ok("".to_string()), // FIXME: this only works for array/tuple unpack!
-            L::Var(v, _st) => Ok(rename(v)),
-            L::Unpack(_, _, fields) => Ok(format!(
+            L::Var(v, _st) => Ok(c.var_name(v)),
+            L::Unpack(sname, _, fields) => Ok(format!(
                 "{{ {} }}",
                 comma_term_opt(
                     fields,
                     c,
                     |(field, lvalue), c| {
-                        let name = rename(&field);
+                        // structs are only unpacked in the module declaring them
+                        let name = match c.current_module {
+                            Some(mident) => c.field_member(&mident, sname, &field),
+                            None => field_member_name(&field, c),
+                        };
                         let as_name = rename(&lvalue.term(c)?);
                         if as_name.is_empty() {
                             Ok("".to_string())
@@ -501,9 +716,11 @@ pub fn handle_binop_for_base_type(
                     // XXX
                     match &builtin.value {
                         BuiltinTypeName_::Signer | BuiltinTypeName_::Address => {
-                            let js_op = match &binop.value {
-                                BinOp_::Eq => "===",
-                                BinOp_::Neq => "!==",
+                            // compared in canonical form, as the same address may come in
+                            // short or padded form
+                            let negation = match &binop.value {
+                                BinOp_::Eq => "",
+                                BinOp_::Neq => "!",
                                 _ => {
                                     return derr!((
                                         binop.loc,
@@ -512,9 +729,9 @@ pub fn handle_binop_for_base_type(
                                 }
                             };
                             Ok(format!(
-                                "(({}).hex() {} ({}).hex())",
+                                "{}$.isSameAddress({}, {})",
+                                negation,
                                 lhs.term(c)?,
-                                js_op,
                                 rhs.term(c)?
                             ))
                         }
@@ -550,35 +767,13 @@ pub fn handle_binop_for_base_type(
                                 BinOp_::Le => {
                                     Ok(format!("({}).le({})", lhs.term(c)?, rhs.term(c)?))
                                 }
-                                BinOp_::BitOr => {
-                                    Ok(format!("({}).or({})", lhs.term(c)?, rhs.term(c)?))
-                                }
-                                BinOp_::BitAnd => {
-                                    Ok(format!("({}).and({})", lhs.term(c)?, rhs.term(c)?))
-                                }
-                                BinOp_::Xor => {
-                                    Ok(format!("({}).xor({})", lhs.term(c)?, rhs.term(c)?))
-                                }
-                                BinOp_::Add => {
-                                    Ok(format!("({}).add({})", lhs.term(c)?, rhs.term(c)?))
-                                }
-                                BinOp_::Sub => {
-                                    Ok(format!("({}).sub({})", lhs.term(c)?, rhs.term(c)?))
-                                }
-                                BinOp_::Mul => {
-                                    Ok(format!("({}).mul({})", lhs.term(c)?, rhs.term(c)?))
-                                }
-                                BinOp_::Div => {
-                                    Ok(format!("({}).div({})", lhs.term(c)?, rhs.term(c)?))
-                                }
-                                BinOp_::Mod => {
-                                    Ok(format!("({}).mod({})", lhs.term(c)?, rhs.term(c)?))
-                                }
-                                BinOp_::Shl => {
-                                    Ok(format!("({}).shl({})", lhs.term(c)?, rhs.term(c)?))
-                                }
-                                BinOp_::Shr => {
-                                    Ok(format!("({}).shr({})", lhs.term(c)?, rhs.term(c)?))
+                                op if chainable_int_method(&op).is_some() => {
+                                    let bits = int_width(&builtin.value).unwrap();
+                                    Ok(format!(
+                                        "({}){}",
+                                        lhs.term(c)?,
+                                        int_method_call(&op, rhs.term(c)?, bits, c)
+                                    ))
                                 }
                                 _ => {
                                     derr!((
@@ -607,7 +802,8 @@ pub fn handle_binop_for_base_type(
         }
         BaseType_::Param(tp) => {
             let fname = dynamic_binop_name(binop.value);
-            let tparams_ = format!("$p[{}]", c.get_tparam_index(tp).unwrap());
+            let idx = c.tparam_index_or_err(tp, binop.loc, "handle_binop_for_base_type")?;
+            let tparams_ = format!("$p[{}]", idx);
             Ok(format!(
                 "{}({}, {}, {})",
                 fname,
@@ -640,10 +836,108 @@ pub fn handle_binop_for_type(
     }
 }
 
+// bigInt method of an integer binop whose result can be chained on, i.e. another integer
+fn chainable_int_method(binop: &BinOp_) -> Option<&'static str> {
+    match binop {
+        BinOp_::BitOr => Some("or"),
+        BinOp_::BitAnd => Some("and"),
+        BinOp_::Xor => Some("xor"),
+        BinOp_::Add => Some("add"),
+        BinOp_::Sub => Some("sub"),
+        BinOp_::Mul => Some("mul"),
+        BinOp_::Div => Some("div"),
+        BinOp_::Mod => Some("mod"),
+        BinOp_::Shl => Some("shl"),
+        BinOp_::Shr => Some("shr"),
+        _ => None,
+    }
+}
+
+/// `.add(b, { bits: 64 })`. Arithmetic is given the width of its operands, so that it aborts like
+/// the VM on overflow, on division by zero and on shifts by too much, whatever the runtime version.
+/// Without runtime checks the width is left out, and with it the shift masks and overflow checks
+fn int_method_call(binop: &BinOp_, operand: String, bits: u32, c: &Context) -> String {
+    let method = chainable_int_method(binop).unwrap();
+    match binop {
+        BinOp_::BitOr | BinOp_::BitAnd | BinOp_::Xor => format!(".{}({})", method, operand),
+        _ if !c.emits_runtime_checks() => format!(".{}({})", method, operand),
+        _ => format!(".{}({}, {{ bits: {} }})", method, operand, bits),
+    }
+}
+
+/// Width of an integer type, None for other types
+fn int_type_width(ty: &Type) -> Option<u32> {
+    match &ty.value {
+        Type_::Single(single) => match &single.value {
+            SingleType_::Base(base) => match &base.value {
+                BaseType_::Apply(_, typename, _) => match &typename.value {
+                    TypeName_::Builtin(builtin) => int_width(&builtin.value),
+                    _ => None,
+                },
+                _ => None,
+            },
+            SingleType_::Ref(_, _) => None,
+        },
+        _ => None,
+    }
+}
+
+/*
+Left-leaning chains of the same kind of operation, e.g. a + b - c or a && b && c, are walked in a
+loop rather than through term() on each lhs, so that machine-generated chains thousands of nodes
+deep neither overflow the stack nor nest parentheses that deep. Integer chains come out as
+(a).add(b, { bits: 64 }).sub(c, { bits: 64 }), boolean ones as (a && b && c).
+ */
+fn flatten_binop_chain<'a>(
+    lhs: &'a Exp,
+    binop: &'a BinOp,
+    rhs: &'a Exp,
+    links_to: impl Fn(&BinOp, &Exp) -> bool,
+) -> (&'a Exp, Vec<(&'a BinOp, &'a Exp)>) {
+    let mut links = vec![(binop, rhs)];
+    let mut base = lhs;
+    while let UnannotatedExp_::BinopExp(inner_lhs, inner_op, inner_rhs) = &base.exp.value {
+        if !links_to(inner_op, inner_lhs) {
+            break;
+        }
+        links.push((inner_op, inner_rhs));
+        base = inner_lhs;
+    }
+    links.reverse();
+    (base, links)
+}
+
 impl AstTsPrinter for (&Box<Exp>, &BinOp, &Box<Exp>) {
     const CTOR_NAME: &'static str = "BinopExp";
     fn term(&self, c: &mut Context) -> TermResult {
         let (lhs, binop, rhs) = *self;
+        let bits = int_type_width(&lhs.ty);
+        if let (Some(bits), Some(_)) = (bits, chainable_int_method(&binop.value)) {
+            let (base, links) = flatten_binop_chain(lhs, binop, rhs, |op, inner_lhs| {
+                chainable_int_method(&op.value).is_some()
+                    && int_type_width(&inner_lhs.ty) == Some(bits)
+            });
+            if links.len() > 1 {
+                let mut out = format!("({})", base.term(c)?);
+                for (op, operand) in links {
+                    let call = int_method_call(&op.value, operand.term(c)?, bits, c);
+                    out.push_str(&call);
+                }
+                return Ok(out);
+            }
+        }
+        if matches!(binop.value, BinOp_::And | BinOp_::Or) {
+            let (base, links) =
+                flatten_binop_chain(lhs, binop, rhs, |op, _| op.value == binop.value);
+            if links.len() > 1 {
+                let mut out = format!("({}", base.term(c)?);
+                for (op, operand) in links {
+                    out.push_str(&format!(" {} {}", op.term(c)?, operand.term(c)?));
+                }
+                out.push(')');
+                return Ok(out);
+            }
+        }
         handle_binop_for_type(&lhs.ty, binop, lhs, rhs, c)
     }
 }
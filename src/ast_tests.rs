@@ -1,9 +1,11 @@
+use crate::ast_exp::{is_math_module, MATH_FAST_PATHS};
 use crate::shared::*;
 use crate::tsgen_writer::TsgenWriter;
+use crate::utils::COVERAGE_HITS_DIR;
 use itertools::Itertools;
 use move_compiler::{
     diagnostics::{Diagnostic, Diagnostics},
-    expansion::ast::{Attribute, AttributeValue, AttributeValue_, Attribute_},
+    expansion::ast::{Attribute, AttributeValue, AttributeValue_, Attribute_, ModuleIdent},
     hlir::ast::*,
     parser::ast::FunctionName,
 };
@@ -32,6 +34,29 @@ pub fn check_test(
         })
         .map(|(_k, v)| v.clone());
 
+    let setup_attr = func
+        .attributes
+        .key_cloned_iter()
+        .find(|(k, _)| k.to_string() == "test_setup");
+    if let Some((_, setup_attr)) = setup_attr {
+        if let Some((other, _, _)) = &c.test_setup {
+            return derr!((
+                name.0.loc,
+                format!(
+                    "{} is the second #[test_setup] function of this module, after {}",
+                    name, other
+                )
+            ));
+        }
+        if !func.signature.type_parameters.is_empty() {
+            return derr!((
+                setup_attr.loc,
+                "a #[test_setup] function cannot have type parameters"
+            ));
+        }
+        c.test_setup = Some((*name, func.signature.clone(), setup_attr.clone()));
+    }
+
     if !test_attr.is_empty() {
         if test_attr.len() != 1 {
             return derr!((
@@ -40,6 +65,17 @@ pub fn check_test(
             ));
         }
 
+        // a test is never called by other code, so nothing could supply its type arguments
+        if !func.signature.type_parameters.is_empty() {
+            let reason = "has type parameters, which no caller can instantiate";
+            c.add_warning(wdiag!((
+                name.0.loc,
+                format!("test {} is skipped, it {}", name, reason)
+            )));
+            c.skipped_tests.push((*name, reason.to_string()));
+            return Ok(true);
+        }
+
         c.tests.push((
             *name,
             func.signature.clone(),
@@ -65,17 +101,15 @@ pub fn format_attribute_value(val: &AttributeValue, c: &mut Context) -> TermResu
     }
 }
 
-pub fn generate_tests(c: &mut Context) -> Result<(String, String), Diagnostics> {
+pub fn generate_tests(
+    mident: &ModuleIdent,
+    c: &mut Context,
+) -> Result<(String, String), Diagnostics> {
     let mut w = TsgenWriter::new();
-    let output = write_tests(&mut w, c);
+    let output = write_tests(mident, &mut w, c);
     match output {
         Ok(_) => {
-            let mident = c.current_module.unwrap();
-            let filename = format!(
-                "{}/{}.test.ts",
-                format_address(mident.value.address),
-                mident.value.module
-            );
+            let filename = format!("{}.test.ts", c.production_module_path(mident));
             let content = format!("{}", w);
             Ok((filename, content))
         }
@@ -91,6 +125,10 @@ pub fn get_abort_code_from_expected_failure(expected_failure: &Attribute) -> Str
     match &expected_failure.value {
         Attribute_::Parameterized(_, attrs_inner) => {
             for (name, attr) in attrs_inner.key_cloned_iter() {
+                // overflows and the like, which the runtime throws as $.ArithmeticError
+                if name.to_string() == "arithmetic_error" {
+                    return "$.ArithmeticError".to_string();
+                }
                 if name.to_string() == "abort_code" {
                     if let Attribute_::Assigned(_, val) = &attr.value {
                         if let AttributeValue_::Value(v) = &val.value {
@@ -112,21 +150,114 @@ pub fn get_abort_code_from_expected_failure(expected_failure: &Attribute) -> Str
     "".to_string()
 }
 
-pub fn write_tests(w: &mut TsgenWriter, c: &mut Context) -> WriteResult {
-    let mident = c.current_module.unwrap();
+// a fresh cache, set up for reproducible runs
+fn write_new_cache(w: &mut TsgenWriter, c: &Context) {
+    w.writeln("const $c = new $.AptosLocalCache();");
+    if !c.config.strict_natives {
+        // timestamp reads go through the clock, so freeze it for reproducible tests
+        w.writeln("$c.setClock(new $.FrozenClock());");
+    }
+    // seeded so that randomness::set_seed and assertions on drawn values are reproducible
+    w.writeln("$c.setRandomness(new $.SeededRandomness());");
+}
 
+/// Declares the values of the parameters assigned in a #[test] or #[test_setup] attribute, and
+/// returns the arguments of the call
+fn write_test_arguments(
+    attr: &Attribute,
+    sig: &FunctionSignature,
+    w: &mut TsgenWriter,
+    c: &mut Context,
+) -> TermResult {
+    match &attr.value {
+        Attribute_::Name(_) | Attribute_::Assigned(_, _) => {
+            if !sig.parameters.is_empty() {
+                return derr!((attr.loc, "Not all test arguments are assigned value"));
+            }
+        }
+        Attribute_::Parameterized(_, param_vals) => {
+            for (pname, pval) in param_vals.key_cloned_iter() {
+                if let Attribute_::Assigned(_pname, pval) = &pval.value {
+                    w.writeln(format!(
+                        "const {} = {};",
+                        pname,
+                        format_attribute_value(pval, c)?
+                    ));
+                } else {
+                    return derr!((pval.loc, "not sure how to handle this"));
+                }
+            }
+        }
+    }
+    let args = sig
+        .parameters
+        .iter()
+        .map(|(var, _ty)| var.to_string())
+        .join(", ");
+    Ok(if args.is_empty() {
+        args
+    } else {
+        format!("{}, ", args)
+    })
+}
+
+pub fn write_tests(mident: &ModuleIdent, w: &mut TsgenWriter, c: &mut Context) -> WriteResult {
+    // test files mirror the source layout under src/tests, next to the #[test_only] modules
+    let test_path = format!("{}/{}", TESTS_DIR, c.production_module_path(mident));
     w.writeln(format!(
-        "import * as Source from '../../{}/{}'; ",
-        format_address(mident.value.address),
-        mident.value.module
+        "import * as Source from '{}'; ",
+        relative_import_path(&parent_dir(&test_path), &c.module_path(mident))
     ));
     w.writeln("import * as $ from '@manahippo/move-to-ts';");
     w.writeln("import { HexString } from 'aptos';");
 
     w.new_line();
 
+    // listed so that leaving them out is not silent
+    if !c.skipped_tests.is_empty() {
+        w.writeln("// skipped tests:");
+        for (name, reason) in c.skipped_tests.iter() {
+            w.writeln(format!("//   {}::{} {}", mident.value.module, name, reason));
+        }
+        w.new_line();
+    }
+
     let async_modifier = if c.is_async() { "async " } else { "" };
 
+    if c.emits_coverage() {
+        // jest runs each test file in its own module registry, so every file keeps its hits apart
+        w.writeln(format!(
+            "afterAll(() => $.writeCoverageHits({}, {}));",
+            quote(&COVERAGE_HITS_DIR),
+            quote(&format!(
+                "{}::{}",
+                format_address_hex(mident.value.address),
+                mident.value.module
+            ))
+        ));
+        w.new_line();
+    }
+
+    // the #[test_setup] function runs once, each test gets its own copy of the state it left
+    if let Some((name, sig, attr)) = c.test_setup.clone() {
+        w.writeln(format!("// state after {}::{}", mident.value.module, name));
+        w.writeln("let $setup: $.AptosLocalCache;");
+        w.writeln(format!("beforeAll({}() => {{", async_modifier));
+        w.increase_indent();
+        write_new_cache(w, c);
+        let args = write_test_arguments(&attr, &sig, w, c)?;
+        let raw_fname = format!("Source.{}", c.local_function_ts_name(&name));
+        w.writeln(format!(
+            "{}({}$c);",
+            format_function_name(&raw_fname, c.is_async()),
+            args
+        ));
+        w.writeln("$setup = $c;");
+        w.decrease_indent();
+        w.writeln("});");
+        w.new_line();
+    }
+
     // one test runner for each $[test]
     for (name, sig, attr, expect_failure_attr) in c.tests.clone().iter() {
         w.writeln(format!(
@@ -135,35 +266,14 @@ pub fn write_tests(w: &mut TsgenWriter, c: &mut Context) -> WriteResult {
         ));
         w.increase_indent();
 
-        w.writeln("const $c = new $.AptosLocalCache();");
-
-        match &attr.value {
-            Attribute_::Name(_) | Attribute_::Assigned(_, _) => {
-                if !sig.parameters.is_empty() {
-                    return derr!((attr.loc, "Not all test arguments are assigned value"));
-                }
-            }
-            Attribute_::Parameterized(_, param_vals) => {
-                for (pname, pval) in param_vals.key_cloned_iter() {
-                    if let Attribute_::Assigned(_pname, pval) = &pval.value {
-                        w.writeln(format!(
-                            "const {} = {};",
-                            pname,
-                            format_attribute_value(pval, c)?
-                        ));
-                    } else {
-                        return derr!((pval.loc, "not sure how to handle this"));
-                    }
-                }
-            }
+        if c.test_setup.is_some() {
+            w.writeln("const $c = $.cloneCache($setup);");
+        } else {
+            write_new_cache(w, c);
         }
 
-        let args = sig
-            .parameters
-            .iter()
-            .map(|(var, _ty)| var.to_string())
-            .join(", ");
-        let raw_fname = format!("Source.{}", name);
+        let args = write_test_arguments(attr, sig, w, c)?;
+        let raw_fname = format!("Source.{}", c.local_function_ts_name(name));
         if let Some(failure_attr) = expect_failure_attr {
             let abort_code = get_abort_code_from_expected_failure(failure_attr);
             let throw_detector = if c.is_async() {
@@ -172,20 +282,18 @@ pub fn write_tests(w: &mut TsgenWriter, c: &mut Context) -> WriteResult {
                 "toThrow"
             };
             w.writeln(format!(
-                "expect( {}() => {}({}{}$c) ).{}({});",
+                "expect( {}() => {}({}$c) ).{}({});",
                 async_modifier,
                 format_function_name(&raw_fname, c.is_async()),
                 args,
-                if !args.is_empty() { ", " } else { "" },
                 throw_detector,
                 abort_code,
             ));
         } else {
             w.writeln(format!(
-                "{}({}{}$c);",
+                "{}({}$c);",
                 format_function_name(&raw_fname, c.is_async()),
-                args,
-                if !args.is_empty() { ", " } else { "" }
+                args
             ));
         }
 
@@ -194,5 +302,112 @@ pub fn write_tests(w: &mut TsgenWriter, c: &mut Context) -> WriteResult {
         w.new_line();
     }
 
+    let math_functions = math_parity_functions(c);
+    if !math_functions.is_empty() {
+        write_math_parity_test(mident, &math_functions, w, c)?;
+    }
+
+    Ok(())
+}
+
+/// The helpers of the current module, when it is 0x1::math64 or 0x1::math128, whose calls from
+/// other modules go to the fast paths of the runtime, with their arity
+pub fn math_parity_functions(c: &Context) -> Vec<(FunctionName, usize)> {
+    let mident = match c.current_module {
+        Some(mident) if is_math_module(&mident) && !c.config.no_math_inline => mident,
+        _ => return vec![],
+    };
+    let mdef = match c.program.modules.get(&mident) {
+        Some(mdef) => mdef,
+        None => return vec![],
+    };
+    mdef.functions
+        .key_cloned_iter()
+        .filter(|(fname, func)| {
+            MATH_FAST_PATHS.iter().any(|(name, arity)| {
+                fname.to_string() == *name && func.signature.parameters.len() == *arity
+            }) && c.skipped_function(&mident, fname).is_none()
+        })
+        .map(|(fname, func)| (fname, func.signature.parameters.len()))
+        .collect()
+}
+
+/// Runs the fast paths against the translated bodies of this module, which calls from other
+/// modules skip, on values around the powers of two, so that both have to agree on the results
+/// and on which inputs overflow
+fn write_math_parity_test(
+    mident: &ModuleIdent,
+    functions: &[(FunctionName, usize)],
+    w: &mut TsgenWriter,
+    c: &mut Context,
+) -> WriteResult {
+    let module = mident.value.module.to_string();
+    let (int, wide_samples) = if module == "math64" {
+        ("u64", vec!["18446744073709551615"])
+    } else {
+        (
+            "u128",
+            vec![
+                "18446744073709551615",
+                "18446744073709551616",
+                "340282366920938463463374607431768211455",
+            ],
+        )
+    };
+    let samples = [
+        "0",
+        "1",
+        "2",
+        "3",
+        "7",
+        "15",
+        "16",
+        "17",
+        "255",
+        "4294967295",
+        "4294967296",
+        "9223372036854775808",
+    ]
+    .iter()
+    .chain(wide_samples.iter())
+    .map(quote)
+    .join(", ");
+
+    w.writeln(format!("test('{}::fast paths', async () => {{", module));
+    w.increase_indent();
+    write_new_cache(w, c);
+    w.writeln("const outcome = async (f: () => any) => {");
+    w.writeln("  try {");
+    w.writeln("    return (await f()).value.toString();");
+    w.writeln("  } catch (e) {");
+    w.writeln("    if (e instanceof $.ArithmeticError) return 'ArithmeticError';");
+    w.writeln("    throw e;");
+    w.writeln("  }");
+    w.writeln("};");
+    w.writeln(format!(
+        "const samples = [{}].map(v => $.{}(v));",
+        samples, int
+    ));
+    for (fname, arity) in functions.iter() {
+        let general = format!("Source.{}", c.local_function_ts_name(fname));
+        let fast = format!("$.aptos_std_{}_{}", module, fname);
+        let (args, loops) = if *arity == 1 {
+            ("a.copy()", "for (const a of samples) {")
+        } else {
+            (
+                "a.copy(), b.copy()",
+                "for (const a of samples) for (const b of samples) {",
+            )
+        };
+        w.writeln(loops);
+        w.writeln(format!(
+            "  expect(await outcome(() => {}({}))).toBe(await outcome(() => {}({}, $c)));",
+            fast, args, general, args
+        ));
+        w.writeln("}");
+    }
+    w.decrease_indent();
+    w.writeln("});");
+    w.new_line();
     Ok(())
 }
@@ -1,30 +1,33 @@
 use crate::ast_exp::*;
 use crate::ast_tests::check_test;
+use crate::inspect::json_string;
 use crate::shared::*;
 use crate::tsgen_writer::TsgenWriter;
-use crate::utils::{capitalize, get_iterable_table_helper_decl, get_table_helper_decl, rename};
+use crate::utils::{
+    field_member_name, get_iterable_table_helper_decl, get_primary_fungible_store_helper_decl,
+    get_struct_base_helper_decl, get_table_helper_decl, get_token_objects_helper_decl, rename,
+    MODE_MARKER,
+};
 use itertools::Itertools;
-use move_compiler::shared::Name;
+use move_compiler::shared::{unique_map::UniqueMap, Name};
 use move_compiler::{
     diagnostics::{Diagnostic, Diagnostics},
-    expansion::ast::{Attribute_, Attributes, ModuleIdent},
+    expansion::ast::{
+        Address, Attribute, AttributeValue_, Attribute_, Attributes, ModuleAccess_, ModuleIdent,
+    },
     hlir::ast::*,
     naming::ast::{BuiltinTypeName_, StructTypeParameter},
-    parser::ast::{Ability_, ConstantName, FunctionName, StructName, Var},
+    parser::ast::{Ability_, BinOp_, ConstantName, Field, FunctionName, StructName, Var},
 };
 use move_ir_types::location::Loc;
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet};
 
 pub fn translate_module(
     mident: ModuleIdent,
     mdef: &ModuleDefinition,
     c: &mut Context,
 ) -> Result<(String, String), Diagnostics> {
-    let filename = format!(
-        "{}/{}.ts",
-        format_address(mident.value.address),
-        mident.value.module
-    );
+    let filename = format!("{}.ts", c.module_path(&mident));
     c.reset_for_module(mident);
     let content = to_ts_string(&(mident, mdef), c);
     match content {
@@ -37,49 +40,266 @@ pub fn translate_module(
     }
 }
 
-pub fn to_ts_string(v: &impl AstTsPrinter, c: &mut Context) -> Result<String, Diagnostic> {
-    let mut writer = TsgenWriter::new();
-    v.write_ts(&mut writer, c)?;
-    let mut lines = vec![
-        "import * as $ from \"@manahippo/move-to-ts\";".to_string(),
-        "import {AptosDataCache, AptosParserRepo, DummyCache} from \"@manahippo/move-to-ts\";"
-            .to_string(),
-        "import {U8, U64, U128} from \"@manahippo/move-to-ts\";".to_string(),
-        "import {u8, u64, u128} from \"@manahippo/move-to-ts\";".to_string(),
-        "import {TypeParamDeclType, FieldDeclType} from \"@manahippo/move-to-ts\";".to_string(),
-        "import {AtomicTypeTag, StructTag, TypeTag, VectorTag} from \"@manahippo/move-to-ts\";"
-            .to_string(),
-        "import {HexString, AptosClient, AptosAccount} from \"aptos\";".to_string(),
-    ];
-    for package_name in c.package_imports.iter() {
-        lines.push(format!(
-            "import * as {} from \"../{}\";",
-            capitalize(package_name),
-            package_name
-        ));
+/// Modules whose output files would overwrite each other, and modules whose file got a suffix as
+/// its path only differs in case from another's, see compute_renamed_module_files
+pub fn check_module_path_collisions(program: &Program, c: &mut Context) -> Result<(), Diagnostics> {
+    let mut errors = Diagnostics::new();
+    let mut by_path: BTreeMap<String, ModuleIdent> = BTreeMap::new();
+    for (mident, _) in program.modules.key_cloned_iter() {
+        let path = c.module_path(&mident);
+        if let Some(other) = by_path.get(&path) {
+            errors.add(Diagnostic::new(
+                NotTranslatable {},
+                (mident.loc, format!("{} and {} are both written to {}.ts", other, mident, path)),
+                vec![(other.loc, format!("{} declared here", other))],
+                std::iter::empty::<String>(),
+            ));
+            continue;
+        }
+        by_path.insert(path.clone(), mident);
+        if let Some((_, other)) = c.renamed_module_files.get(&mident).cloned() {
+            c.add_warning(wdiag!(
+                (
+                    mident.loc,
+                    format!(
+                        "{} and {} would be written to files whose paths only differ in case, which overwrite each other on case-insensitive file systems, so {} is written to {}.ts",
+                        other, mident, mident, path
+                    )
+                ),
+                (other.loc, format!("{} declared here", other))
+            ));
+        }
+    }
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+// names imported by every module file, grouped by import line
+const RUNTIME_IMPORTS: &[(&[&str], &str)] = &[
+    (
+        &["AptosDataCache", "AptosParserRepo", "DummyCache"],
+        "@manahippo/move-to-ts",
+    ),
+    (&["U8", "U64", "U128"], "@manahippo/move-to-ts"),
+    (&["u8", "u64", "u128"], "@manahippo/move-to-ts"),
+    (
+        &["TypeParamDeclType", "FieldDeclType"],
+        "@manahippo/move-to-ts",
+    ),
+    (
+        &["AtomicTypeTag", "StructTag", "TypeTag", "VectorTag"],
+        "@manahippo/move-to-ts",
+    ),
+    (&["HexString", "AptosClient", "AptosAccount"], "aptos"),
+];
+
+fn uses_identifier(body: &str, ident: &str) -> bool {
+    let is_ident_char = |ch: char| ch.is_ascii_alphanumeric() || ch == '_' || ch == '$';
+    body.match_indices(ident).any(|(idx, _)| {
+        let before = body[..idx].chars().next_back();
+        let after = body[idx + ident.len()..].chars().next();
+        !before.map_or(false, is_ident_char) && !after.map_or(false, is_ident_char)
+    })
+}
+
+/// Modules without structs and functions, e.g. constant-only or marker modules, whose files would
+/// otherwise consist of unused imports
+fn is_trivial_module(c: &Context) -> bool {
+    c.current_module
+        .and_then(|mident| c.program.modules.get(&mident))
+        .map_or(false, |mdef| {
+            mdef.structs.is_empty() && mdef.functions.is_empty()
+        })
+}
+
+// imports and hoisted typetags depend on what the body referenced, so they are only known once
+// it has been written. Trivial modules only import the runtime names their body uses, so that they
+// pass noUnusedLocals
+fn ts_import_header(body: &str, c: &Context) -> String {
+    let trivial = is_trivial_module(c);
+    let mut lines = vec![format!("{}{}", MODE_MARKER, c.output_mode())];
+    if !trivial || body.contains("$.") {
+        lines.push("import * as $ from \"@manahippo/move-to-ts\";".to_string());
+    }
+    for (names, path) in RUNTIME_IMPORTS.iter() {
+        let names = names
+            .iter()
+            .filter(|name| !trivial || uses_identifier(body, name))
+            .join(", ");
+        if !names.is_empty() {
+            lines.push(format!("import {{{}}} from \"{}\";", names, path));
+        }
     }
-    for module_name in c.same_package_imports.iter() {
+    for (path, alias) in c.import_aliases.iter() {
+        lines.push(format!("import * as {} from \"{}\";", alias, path));
+    }
+    if c.config.compact_structs && uses_identifier(body, "StructBase") {
         lines.push(format!(
-            "import * as {} from \"./{}\";",
-            capitalize(module_name),
-            module_name
+            "import {{StructBase}} from \"{}\";",
+            c.helper_import_path("struct_base")
         ));
     }
-    lines.push(format!("{}", writer));
-    Ok(lines.join("\n"))
+    // typetags without type params, allocated once instead of at every use
+    lines.extend(c.hoisted_typetag_decls());
+    lines.push(String::new());
+    lines.join("\n")
+}
+
+pub fn to_ts_string(v: &impl AstTsPrinter, c: &mut Context) -> Result<String, Diagnostic> {
+    let mut writer = TsgenWriter::new();
+    v.write_ts(&mut writer, c)?;
+    let mut content = writer.into_string();
+    // the header goes in front of the body in place, instead of into a second copy of it
+    let header = ts_import_header(&content, c);
+    content.insert_str(0, &header);
+    Ok(content)
 }
 
+/// Table and IterableTable come with typed wrappers, primary_fungible_store with helpers to find
+/// and read primary stores, and with --framework-extras 0x4::token with address derivations and a
+/// loader. Their code is the same for every package, so it is written once to src/_helpers and
+/// the special modules import and re-export it
 pub fn handle_special_module(
     mi: &ModuleIdent,
     _module: &ModuleDefinition,
     w: &mut TsgenWriter,
-    _c: &mut Context,
+    c: &mut Context,
 ) -> WriteResult {
-    if format_address_hex(mi.value.address) == "0x1" {
-        if mi.value.module.to_string() == "table" {
-            w.writeln(get_table_helper_decl());
-        } else if mi.value.module.to_string() == "iterable_table" {
-            w.writeln(get_iterable_table_helper_decl());
+    let address = format_address_hex(mi.value.address);
+    let module = mi.value.module.to_string();
+    let (helper_name, class_name, content) = match (address.as_str(), module.as_str()) {
+        ("0x1", "table") => (
+            "table",
+            "TypedTable",
+            get_table_helper_decl(&c.module_import_path_from_helpers(mi)),
+        ),
+        ("0x1", "iterable_table") => {
+            let option = c
+                .program
+                .modules
+                .key_cloned_iter()
+                .map(|(mident, _)| mident)
+                .find(|mident| {
+                    mident.value.address == mi.value.address
+                        && mident.value.module.to_string() == "option"
+                });
+            let option = match option {
+                Some(option) => option,
+                None => return derr!((mi.loc, "iterable_table requires 0x1::option")),
+            };
+            (
+                "iterable_table",
+                "TypedIterableTable",
+                get_iterable_table_helper_decl(
+                    &c.module_import_path_from_helpers(mi),
+                    &c.module_import_path_from_helpers(&option),
+                ),
+            )
+        }
+        ("0x1", "primary_fungible_store") => {
+            let fungible_asset = c.program.modules.key_cloned_iter().find(|(mident, _)| {
+                mident.value.address == mi.value.address
+                    && mident.value.module.to_string() == "fungible_asset"
+            });
+            let (fungible_asset, fa_module) = match fungible_asset {
+                Some(found) => found,
+                None => {
+                    return derr!((
+                        mi.loc,
+                        "primary_fungible_store requires 0x1::fungible_asset"
+                    ))
+                }
+            };
+            let concurrent_balance = fa_module
+                .structs
+                .key_cloned_iter()
+                .any(|(sname, _)| sname.to_string() == "ConcurrentFungibleBalance");
+            (
+                "primary_fungible_store",
+                "primaryStoreAddress, fetchMetadata, fetchPrimaryBalance",
+                get_primary_fungible_store_helper_decl(
+                    &c.module_import_path_from_helpers(&fungible_asset),
+                    concurrent_balance,
+                ),
+            )
+        }
+        ("0x4", "token") if c.config.framework_extras => {
+            let royalty = c
+                .program
+                .modules
+                .key_cloned_iter()
+                .map(|(mident, _)| mident)
+                .find(|mident| {
+                    mident.value.address == mi.value.address
+                        && mident.value.module.to_string() == "royalty"
+                })
+                .map(|royalty| c.module_import_path_from_helpers(&royalty));
+            (
+                "token_objects",
+                "collectionAddress, tokenAddress, loadToken",
+                get_token_objects_helper_decl(
+                    &c.module_import_path_from_helpers(mi),
+                    royalty.as_deref(),
+                ),
+            )
+        }
+        _ => return Ok(()),
+    };
+    let path = c.helper_import_path(helper_name);
+    w.writeln(format!("import {{{}}} from \"{}\";", class_name, path));
+    w.writeln(format!("export {{{}}};", class_name));
+    c.helper_files
+        .insert(format!("{}.ts", helper_name), content);
+    Ok(())
+}
+
+/// Every module whose address is a named address without a value fails on the first use of the
+/// address. Checked up front, for one error per address instead of one per use
+pub fn check_unassigned_addresses(program: &Program) -> Result<(), Diagnostics> {
+    let mut by_name: BTreeMap<String, (Name, Vec<ModuleIdent>)> = BTreeMap::new();
+    for (mident, _) in program.modules.key_cloned_iter() {
+        if let Address::NamedUnassigned(name) = mident.value.address {
+            by_name
+                .entry(name.to_string())
+                .or_insert_with(|| (name, vec![]))
+                .1
+                .push(mident);
+        }
+    }
+    let mut errors = Diagnostics::new();
+    for (_, (name, midents)) in by_name.iter() {
+        let needed_by = midents.iter().collect::<Vec<_>>();
+        errors.add(Diagnostic::new(
+            NotTranslatable {},
+            (name.loc, unassigned_address_message(name, &needed_by)),
+            std::iter::empty::<(Loc, String)>(),
+            std::iter::empty::<String>(),
+        ));
+    }
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+/// --naming camel must not map two functions of a module to one TS identifier. Fields that collide
+/// are told apart instead, see compute_field_members
+pub fn check_ts_naming(mident: &ModuleIdent, mdef: &ModuleDefinition, c: &Context) -> WriteResult {
+    if !c.uses_ts_naming(mident) {
+        return Ok(());
+    }
+    let mut seen_functions: BTreeMap<String, FunctionName> = BTreeMap::new();
+    for (fname, _) in mdef.functions.key_cloned_iter() {
+        let ts_name = c.function_ts_name(mident, &fname);
+        if let Some(other) = seen_functions.insert(ts_name.clone(), fname) {
+            return derr!(
+                (fname.0.loc, format!("{} and {} both translate to {}", other, fname, ts_name)),
+                (other.0.loc, format!("{} declared here", other))
+            );
         }
     }
     Ok(())
@@ -90,9 +310,13 @@ impl AstTsPrinter for (ModuleIdent, &ModuleDefinition) {
     fn write_ts(&self, w: &mut TsgenWriter, c: &mut Context) -> WriteResult {
         /*
         - imports (handled by TsgenWriter)
+        - module meta
         - constants
         - structs
         - functions
+        - loadParsers
+        - re-exports of the table helpers
+        constants, structs, functions and parsers come sorted by name, as the compiler's maps keep them
          */
         let (name, module) = self;
         let ModuleDefinition {
@@ -100,13 +324,16 @@ impl AstTsPrinter for (ModuleIdent, &ModuleDefinition) {
             attributes: _,
             is_source_module: _,
             dependency_order: _,
-            friends: _,
+            friends,
             structs,
             constants,
             functions,
         } = module;
 
         let package_name = package_name.map_or("".to_string(), |symbol| symbol.to_string());
+        check_ts_naming(name, module, c)?;
+        check_ts_aliases(name, module, c)?;
+        check_abort_error_class_names(name, module, c)?;
 
         // module meta
         w.export_const("packageName", quote(&package_name));
@@ -114,11 +341,39 @@ impl AstTsPrinter for (ModuleIdent, &ModuleDefinition) {
             "moduleAddress",
             format!(
                 "new HexString({})",
-                quote(&format_address_hex(name.value.address))
+                quote(&concrete_address_hex(name, name.loc)?)
             ),
         );
         w.export_const("moduleName", quote(&name.value.module.0));
+        if c.config.emit_provenance {
+            let friend_names = friends
+                .key_cloned_iter()
+                .map(|(friend, _)| {
+                    quote(&format!(
+                        "{}::{}",
+                        format_address_hex(friend.value.address),
+                        friend.value.module
+                    ))
+                })
+                .sorted()
+                .join(", ");
+            w.writeln(format!(
+                "export const friendModules = [{}] as const;",
+                friend_names
+            ));
+        }
         w.new_line();
+        if c.config.strict_construction
+            && structs
+                .iter()
+                .any(|(_, _, sdef)| matches!(sdef.fields, StructFields::Defined(_)))
+        {
+            if c.emits_comments() {
+                w.writeln("// brands the protos of the structs below, only this module names it");
+            }
+            w.writeln("declare const protoBrand: unique symbol;");
+            w.new_line();
+        }
 
         // constants
         for (cname, cdef) in constants.key_cloned_iter() {
@@ -126,6 +381,9 @@ impl AstTsPrinter for (ModuleIdent, &ModuleDefinition) {
         }
         w.new_line();
 
+        // error classes of the error constants
+        write_abort_error_classes(name, constants, w, c)?;
+
         // structs
         for (sname, sdef) in structs.key_cloned_iter() {
             (sname, sdef).write_ts(w, c)?;
@@ -136,33 +394,103 @@ impl AstTsPrinter for (ModuleIdent, &ModuleDefinition) {
             (fname, fdef).write_ts(w, c)?;
         }
 
-        // loadParsers
-        write_load_parsers(name, module, w, c)?;
+        // registries for generic explorers
+        write_registries(structs, functions, w, c)?;
+
+        // loadParsers, which the package index only calls for modules with structs
+        if !structs.is_empty() {
+            write_load_parsers(name, module, w, c)?;
+        }
 
         if c.config.ui {}
 
         // for things like Table, IterableTable
         handle_special_module(name, module, w, c)?;
 
+        if !structs.is_empty() {
+            write_extension_import(name, w, c);
+        }
+
         Ok(())
     }
 }
 
+/// Loads the hand-written extensions of the struct classes, see generate_extension_file. It is
+/// required instead of imported, so that the module still builds once the file is deleted. A
+/// browser bundle that did not find the file replaces the require with one throwing "Dynamic
+/// require", which counts as missing as well
+fn write_extension_import(mident: &ModuleIdent, w: &mut TsgenWriter, c: &Context) {
+    let file_name = c.module_file_name(mident);
+    w.new_line();
+    if c.emits_comments() {
+        w.writeln(format!(
+            "// methods added to the classes above by hand, see {}.ext.ts",
+            file_name
+        ));
+    }
+    w.writeln(format!(
+        "try {{ require(\"./{}.ext\"); }} catch (e: any) {{ if (e?.code !== \"MODULE_NOT_FOUND\" && !String(e?.message).startsWith(\"Dynamic require\")) throw e; }}",
+        file_name
+    ));
+}
+
+/// ALL_STRUCTS lists the struct classes, ALL_ENTRY_FUNCTIONS the entry functions that got a
+/// buildPayload_, with their parameters, in emission order
+fn write_registries(
+    structs: &UniqueMap<StructName, StructDefinition>,
+    functions: &UniqueMap<FunctionName, Function>,
+    w: &mut TsgenWriter,
+    c: &mut Context,
+) -> WriteResult {
+    let struct_names = structs
+        .key_cloned_iter()
+        .map(|(sname, _)| rename(&sname))
+        .join(", ");
+    w.writeln(format!("export const ALL_STRUCTS = [{}];", struct_names));
+
+    w.writeln("export const ALL_ENTRY_FUNCTIONS: $.EntryFunctionDescriptor[] = [");
+    for (fname, func) in functions.key_cloned_iter() {
+        if !has_payload_builder(func) || !script_function_has_valid_parameter(&func.signature) {
+            continue;
+        }
+        let params = func
+            .signature
+            .parameters
+            .iter()
+            .filter(|(_, ty)| !is_type_signer(ty))
+            .map(|(pname, ty)| {
+                Ok(format!(
+                    "{{ name: {}, kind: {} }}",
+                    quote(pname),
+                    quote(&payload_param_kind(ty).map_err(reason_to_diag)?)
+                ))
+            })
+            .collect::<Result<Vec<_>, Diagnostic>>()?
+            .join(", ");
+        w.writeln(format!(
+            "  {{ name: {}, buildPayload: buildPayload_{}, typeParamCount: {}, params: [{}] }},",
+            quote(&fname),
+            c.local_function_ts_name(&fname),
+            func.signature.type_parameters.len(),
+            params
+        ));
+    }
+    w.writeln("];");
+    w.new_line();
+    Ok(())
+}
+
 pub fn write_load_parsers(
     mident: &ModuleIdent,
     module: &ModuleDefinition,
     w: &mut TsgenWriter,
-    _c: &mut Context,
+    c: &mut Context,
 ) -> WriteResult {
     w.writeln("export function loadParsers(repo: AptosParserRepo) {");
 
+    let address = concrete_address_hex(mident, mident.loc)?;
     for (sname, _) in module.structs.key_cloned_iter() {
-        let paramless_name = format!(
-            "{}::{}::{}",
-            format_address_hex(mident.value.address),
-            mident.value.module,
-            sname
-        );
+        let paramless_name = format!("{}::{}::{}", address, mident.value.module, sname);
         w.writeln(format!(
             "  repo.addParser({}, {}.{}Parser);",
             quote(&paramless_name),
@@ -170,12 +498,133 @@ pub fn write_load_parsers(
             sname
         ));
     }
+    // fields of stubbed types are parsed by the stubs
+    for stubbed in c.stub_imports.clone().iter() {
+        let alias = c.add_stub_import(stubbed);
+        w.writeln(format!("  {}.loadParsers(repo);", alias));
+    }
 
     w.writeln("}");
 
     Ok(())
 }
 
+/// Stub of a module excluded with --exclude-module, which translated modules import when they
+/// refer to its structs in types. The classes keep the metadata of the structs, with their fields
+/// marked opaque, and their parsers keep the data as it came in `raw`
+pub fn generate_module_stub(
+    mident: &ModuleIdent,
+    mdef: &ModuleDefinition,
+    c: &mut Context,
+) -> Result<(String, String), Diagnostics> {
+    let filename = format!("{}.ts", c.stub_path(mident));
+    match module_stub_content(mident, mdef, c) {
+        Err(diag) => {
+            let mut diags = Diagnostics::new();
+            diags.add(diag);
+            Err(diags)
+        }
+        Ok(res) => Ok((filename, res)),
+    }
+}
+
+fn module_stub_content(
+    mident: &ModuleIdent,
+    mdef: &ModuleDefinition,
+    c: &mut Context,
+) -> TermResult {
+    let mut w = TsgenWriter::new();
+    let address = concrete_address_hex(mident, mident.loc)?;
+    w.export_const(
+        "moduleAddress",
+        format!("new HexString({})", quote(&address)),
+    );
+    w.export_const("moduleName", quote(&mident.value.module.0));
+    for (sname, sdef) in mdef.structs.key_cloned_iter() {
+        let name = rename(&sname);
+        w.new_line();
+        w.writeln(format!("export class {} ", name));
+        w.short_block(|w| {
+            w.writeln("static moduleAddress = moduleAddress;");
+            w.writeln("static moduleName = moduleName;");
+            w.writeln(format!("static structName: string = {};", quote(&name)));
+            w.writeln(format!(
+                "static typeParameters: TypeParamDeclType[] = [{}];",
+                comma_term(&sdef.type_parameters, c, |tp, c| tp.term(c))?
+            ));
+            w.writeln(format!(
+                "static typeParamCount = {};",
+                sdef.type_parameters.len()
+            ));
+            w.writeln(format!(
+                "static phantomTypeParams: boolean[] = [{}];",
+                sdef.type_parameters
+                    .iter()
+                    .map(|tp| tp.is_phantom)
+                    .join(", ")
+            ));
+            w.writeln(format!(
+                "static abilities: string[] = [{}];",
+                ability_names(&sdef.abilities).iter().map(quote).join(", ")
+            ));
+            w.writeln("static fields: FieldDeclType[] = [");
+            if let StructFields::Defined(fields) = &sdef.fields {
+                for (fname, ty) in fields.iter() {
+                    w.writeln(format!(
+                        "  {{ name: {}, typeTag: {}, opaque: true }},",
+                        quote(&fname),
+                        base_type_to_typetag_builder(ty, &sdef.type_parameters, c)?
+                    ));
+                }
+            }
+            w.writeln("];");
+            w.new_line();
+            w.writeln("constructor(public raw: any, public typeTag: TypeTag) {}");
+            w.new_line();
+            w.writeln(format!(
+                "static {}Parser(data: any, typeTag: TypeTag, _repo: AptosParserRepo): {} {{",
+                name, name
+            ));
+            w.writeln(format!("  return new {}(data, typeTag);", name));
+            w.writeln("}");
+            Ok(())
+        })?;
+        w.new_line();
+    }
+    w.new_line();
+    w.writeln("export function loadParsers(repo: AptosParserRepo) {");
+    for (sname, _) in mdef.structs.key_cloned_iter() {
+        w.writeln(format!(
+            "  repo.addParser({}, {}.{}Parser);",
+            quote(&format!("{}::{}::{}", address, mident.value.module, sname)),
+            rename(&sname),
+            rename(&sname)
+        ));
+    }
+    w.writeln("}");
+
+    let body = w.into_string();
+    let mut lines = vec![format!(
+        "// stub of {}, which is excluded with --exclude-module; generated, do not edit",
+        mident
+    )];
+    // fields whose type uses a type parameter refer to $.TypeParamIdx
+    if body.contains("$.") {
+        lines.push("import * as $ from \"@manahippo/move-to-ts\";".to_string());
+    }
+    for (names, path) in RUNTIME_IMPORTS.iter() {
+        let names = names
+            .iter()
+            .filter(|name| uses_identifier(&body, name))
+            .join(", ");
+        if !names.is_empty() {
+            lines.push(format!("import {{{}}} from \"{}\";", names, path));
+        }
+    }
+    lines.push(String::new());
+    Ok(lines.join("\n") + &body)
+}
+
 impl AstTsPrinter for ConstantName {
     const CTOR_NAME: &'static str = "_ConstantName";
     fn term(&self, _c: &mut Context) -> TermResult {
@@ -214,7 +663,8 @@ pub fn write_simplify_constant_block(
             _ => (),
         }
     }
-    // write block as lambda
+    // write block as lambda. it runs as the module loads, so it cannot be async, and
+    // in_async_scope stays false to reject anything that would need an await inside it
     w.write("( () => ");
     block.write_ts(w, c)?;
     w.write(")()");
@@ -243,6 +693,160 @@ impl AstTsPrinter for (ConstantName, &Constant) {
     }
 }
 
+/// u64 constants named E_... or ERROR_..., or with an #[error] attribute, are abort codes that get
+/// an error class each
+pub(crate) fn is_error_constant(cname: &ConstantName, cdef: &Constant) -> bool {
+    let name = cname.to_string();
+    is_u64_constant(cdef)
+        && (error_attribute(cdef).is_some() || name.starts_with("E_") || name.starts_with("ERROR_"))
+}
+
+fn error_attribute(cdef: &Constant) -> Option<&Attribute> {
+    cdef.attributes
+        .key_cloned_iter()
+        .find(|(name, _)| name.to_string() == "error")
+        .map(|(_, attr)| attr)
+}
+
+pub(crate) fn is_u64_constant(cdef: &Constant) -> bool {
+    match &cdef.signature.value {
+        BaseType_::Apply(_, typename, _) => match &typename.value {
+            TypeName_::Builtin(builtin) => builtin.value == BuiltinTypeName_::U64,
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+fn abort_error_class_name(mident: &ModuleIdent, cname: &ConstantName, c: &Context) -> String {
+    format!("{}Error_{}", c.module_namespace(mident), cname)
+}
+
+/// The error classes share the module file with the struct classes, so no struct may be named
+/// like one of them
+pub fn check_abort_error_class_names(
+    mident: &ModuleIdent,
+    mdef: &ModuleDefinition,
+    c: &Context,
+) -> WriteResult {
+    let errors = mdef
+        .constants
+        .key_cloned_iter()
+        .filter(|(cname, cdef)| is_error_constant(cname, cdef))
+        .collect::<Vec<_>>();
+    if errors.is_empty() {
+        return Ok(());
+    }
+    let base_class = format!("{}Error", c.module_namespace(mident));
+    for (sname, _) in mdef.structs.key_cloned_iter() {
+        let struct_class = rename(&sname);
+        if struct_class == base_class {
+            return derr!((
+                sname.0.loc,
+                format!(
+                    "Struct {} takes the name of the error class of the module's error constants",
+                    sname
+                )
+            ));
+        }
+        if let Some((cname, _)) = errors
+            .iter()
+            .find(|(cname, _)| abort_error_class_name(mident, cname, c) == struct_class)
+        {
+            return derr!(
+                (
+                    sname.0.loc,
+                    format!(
+                        "Struct {} takes the name of the error class of {}",
+                        sname, cname
+                    )
+                ),
+                (cname.0.loc, format!("{} declared here", cname))
+            );
+        }
+    }
+    Ok(())
+}
+
+/// `<Module>Error` and a `<Module>Error_<CONSTANT>` subclass for each error constant, registered
+/// so that failed simulations rethrow their abort as these classes
+fn write_abort_error_classes(
+    mident: &ModuleIdent,
+    constants: &UniqueMap<ConstantName, Constant>,
+    w: &mut TsgenWriter,
+    c: &mut Context,
+) -> WriteResult {
+    let mut errors = vec![];
+    for (cname, cdef) in constants.key_cloned_iter() {
+        if is_error_constant(&cname, cdef) {
+            errors.push(cname);
+        } else if let Some(attr) = error_attribute(cdef) {
+            c.add_warning(wdiag!((
+                attr.loc,
+                format!(
+                    "#[error] constant {} is not a u64 abort code, no error class is generated for it",
+                    cname
+                )
+            )));
+        }
+    }
+    if errors.is_empty() {
+        return Ok(());
+    }
+    let module_id = format!(
+        "{}::{}",
+        concrete_address_hex(mident, mident.loc)?,
+        mident.value.module
+    );
+    let base_class = format!("{}Error", c.module_namespace(mident));
+    w.writeln(format!(
+        "export class {} extends $.MoveAbortError {{}}",
+        base_class
+    ));
+    let mut classes = vec![];
+    for cname in errors.iter() {
+        let class_name = abort_error_class_name(mident, cname, c);
+        w.writeln(format!(
+            "export class {} extends {} {{",
+            class_name, base_class
+        ));
+        w.writeln(format!(
+            "  constructor() {{ super({}, {}, {}); }}",
+            cname.term(c)?,
+            quote(&module_id),
+            quote(cname)
+        ));
+        w.writeln("}");
+        classes.push(class_name);
+    }
+    w.writeln(format!("$.registerAbortErrors([{}]);", classes.join(", ")));
+    w.new_line();
+    Ok(())
+}
+
+/// The error class `abort e` throws, when e is an error constant of the current module
+fn abort_error_class(e: &Exp, c: &Context) -> Result<Option<String>, Diagnostic> {
+    let cname = match &e.exp.value {
+        UnannotatedExp_::Constant(cname) => cname,
+        _ => return Ok(None),
+    };
+    let mident = c.current_module_or_err(e.exp.loc, "abort_error_class")?;
+    let cdef = match c
+        .program
+        .modules
+        .get(&mident)
+        .and_then(|mdef| mdef.constants.get(cname))
+    {
+        Some(cdef) => cdef,
+        None => return Ok(None),
+    };
+    if is_error_constant(cname, cdef) {
+        Ok(Some(abort_error_class_name(&mident, cname, c)))
+    } else {
+        Ok(None)
+    }
+}
+
 impl AstTsPrinter for StructTypeParameter {
     // only used by (StructName, &StructDefinition)
     const CTOR_NAME: &'static str = "StructTypeParameter";
@@ -258,15 +862,39 @@ pub fn handle_special_structs(
     w: &mut TsgenWriter,
     c: &mut Context,
 ) -> WriteResult {
-    if c.current_module.is_none() {
-        return Ok(());
-    }
-    let mident = c.current_module.unwrap();
+    let mident = match c.current_module {
+        Some(mident) => mident,
+        None => return Ok(()),
+    };
     let package_name = format_address(mident.value.address);
+    // fixed_point32 is in std and fixed_point64 in aptos_std, both at 0x1
+    if format_address_hex(mident.value.address) == "0x1" {
+        match (
+            mident.value.module.to_string().as_str(),
+            name.to_string().as_str(),
+        ) {
+            ("fixed_point32", "FixedPoint32") => write_fixed_point_methods(name, 32, w, c),
+            ("fixed_point64", "FixedPoint64") => write_fixed_point_methods(name, 64, w, c),
+            // the value field of aggregator_v2 structs takes the name of a value() accessor
+            ("aggregator_v2", "Aggregator") | ("aggregator_v2", "AggregatorSnapshot") => {
+                w.writeln("read(): U64 | U128 { return $.aggregatorRead(this) as U64 | U128; }");
+            }
+            ("aggregator", "Aggregator") => {
+                w.writeln("value(): U128 { return $.aggregatorV1Read(this); }");
+            }
+            _ => (),
+        }
+    }
+    if c.config.framework_extras {
+        write_token_methods(&mident, name, w, c);
+    }
     if package_name == "std" {
         if mident.value.module.to_string() == "string" && name.to_string() == "String" {
             w.writeln("str(): string { return $.u8str(this.bytes); }");
         }
+        if mident.value.module.to_string() == "option" && name.to_string() == "Option" {
+            write_option_methods(&mident, w, c);
+        }
     } else if package_name == "aptos_std" {
         if mident.value.module.to_string() == "iterable_table"
             && name.to_string() == "IterableTable"
@@ -285,6 +913,92 @@ pub fn handle_special_structs(
     Ok(())
 }
 
+/// Accessors for the legacy 0x3::token::TokenData and the 0x4::token::Token object, named get*
+/// since the fields take the plain names. The royalty of a Token lives in another resource, which
+/// loadToken of --framework-extras attaches
+fn write_token_methods(mident: &ModuleIdent, name: &StructName, w: &mut TsgenWriter, c: &Context) {
+    let uri = field_member_name(&"uri", c);
+    match (
+        format_address_hex(mident.value.address).as_str(),
+        mident.value.module.to_string().as_str(),
+        name.to_string().as_str(),
+    ) {
+        ("0x3", "token", "TokenData") => {
+            w.writeln(format!("getUri(): string {{ return this.{}.str(); }}", uri));
+            w.writeln(format!(
+                "getRoyalty(): Royalty {{ return this.{}; }}",
+                field_member_name(&"royalty", c)
+            ));
+        }
+        ("0x4", "token", "Token") => {
+            w.writeln("$royalty: any = null;");
+            w.writeln(format!("getUri(): string {{ return this.{}.str(); }}", uri));
+            w.writeln("getRoyalty(): any { return this.$royalty; }");
+        }
+        _ => (),
+    }
+}
+
+/// FixedPoint32 and FixedPoint64 hold their value as an integer with 32 or 64 fractional bits
+fn write_fixed_point_methods(name: &StructName, bits: usize, w: &mut TsgenWriter, c: &Context) {
+    let value = field_member_name(&"value", c);
+    let raw_type = if bits == 32 { "u64" } else { "u128" };
+    w.writeln(format!(
+        "toNumber(): number {{ return $.fixedPointToNumber(this.{}, {}); }}",
+        value, bits
+    ));
+    w.writeln(format!(
+        "toDecimalString(precision = 8): string {{ return $.fixedPointToDecimalString(this.{}, {}, precision); }}",
+        value, bits
+    ));
+    w.writeln(format!("static fromNumber(n: number): {} {{", name));
+    w.writeln(format!(
+        "  const proto = {{ value: {}($.fixedPointFromNumber(n, {})) }};",
+        raw_type, bits
+    ));
+    w.writeln(format!(
+        "  return {};",
+        c.construct_struct(
+            name,
+            "proto",
+            &format!(
+                "new StructTag(moduleAddress, moduleName, {}, [])",
+                quote(name)
+            )
+        )
+    ));
+    w.writeln("}");
+}
+
+/// Option is a struct holding a vector of at most one element. The class has no TS generic
+/// parameter for the element, so the accessors take the expected type as a cast instead
+fn write_option_methods(mident: &ModuleIdent, w: &mut TsgenWriter, c: &Context) {
+    let vec = field_member_name(&"vec", c);
+    // same abort as option::extract
+    let has_not_set = c.program.modules.get(mident).map_or(false, |mdef| {
+        mdef.constants
+            .key_cloned_iter()
+            .any(|(cname, _)| cname.to_string() == "EOPTION_NOT_SET")
+    });
+    let not_set = if has_not_set {
+        "EOPTION_NOT_SET"
+    } else {
+        "u64(\"262145\")"
+    };
+    w.writeln(format!("isSome(): boolean {{ return this.{}.length > 0; }}", vec));
+    w.writeln(format!("isNone(): boolean {{ return this.{}.length === 0; }}", vec));
+    w.writeln("unwrap<T = any>(): T {");
+    w.writeln(format!("  if (this.{}.length === 0) {{", vec));
+    w.writeln(format!("    throw $.abortCode({});", not_set));
+    w.writeln("  }");
+    w.writeln(format!("  return this.{}[0] as T;", vec));
+    w.writeln("}");
+    w.writeln(format!(
+        "getOrNull<T = any>(): T | null {{ return this.{}.length > 0 ? this.{}[0] as T : null; }}",
+        vec, vec
+    ));
+}
+
 pub fn handle_struct_show_iter_table_directive(
     sname: &StructName,
     sdef: &StructDefinition,
@@ -295,8 +1009,22 @@ pub fn handle_struct_show_iter_table_directive(
     for (_, pattr) in inner_attrs.key_cloned_iter() {
         match &pattr.value {
             Attribute_::Name(field_name) => {
-                // validate this at end-of-module generation
-                c.add_show_iter_table(&c.current_module.unwrap(), sname, sdef, field_name);
+                // the cli command loads the struct as a resource, which takes the key ability
+                if sdef.abilities.has_ability_(Ability_::Key) {
+                    let mident = c.current_module_or_err(
+                        field_name.loc,
+                        "handle_struct_show_iter_table_directive",
+                    )?;
+                    c.add_show_iter_table(&mident, sname, sdef, field_name);
+                } else {
+                    c.add_warning(wdiag!((
+                        field_name.loc,
+                        format!(
+                            "{} does not have key, so show_iter_table only generates a method and no cli command",
+                            sname
+                        )
+                    )));
+                }
 
                 // generate show method
                 w.new_line();
@@ -358,20 +1086,26 @@ pub fn handle_struct_show_iter_table_directive(
                 let value_ts_type = base_type_to_tstype(&table_targs[1], c)?;
 
                 w.writeln(format!(
-                    "async getIterTableEntries_{}(client: AptosClient, repo: AptosParserRepo) {{",
-                    field_name
+                    "async getIterTableEntries_{}(client: AptosClient, repo: AptosParserRepo): Promise<[{}, {}][]> {{",
+                    field_name, key_ts_type, value_ts_type
                 ));
                 w.writeln(format!("  const cache = new DummyCache();"));
                 w.writeln(format!(
                     "  const tags = (this.typeTag as StructTag).typeParams;"
                 ));
                 w.writeln(format!(
-                    "  const iterTableField = {}.fields.filter(f=>f.name === '{}')[0]",
+                    "  const iterTableField = {}.getField('{}');",
                     sname, field_name
                 ));
                 w.writeln(format!(
                     "  const typedIterTable = this.{}.toTypedIterTable<{},{}>(iterTableField);",
-                    field_name, key_ts_type, value_ts_type,
+                    c.field_member(
+                        &c.current_module_or_err(sname.0.loc, "write_show_iter_table")?,
+                        sname,
+                        field_name
+                    ),
+                    key_ts_type,
+                    value_ts_type,
                 ));
                 w.writeln(format!(
                     "  return await typedIterTable.fetchAll(client, repo);"
@@ -390,16 +1124,337 @@ pub fn handle_struct_show_iter_table_directive(
     Ok(())
 }
 
-pub fn validate_method(
+/// The member path from a value of type `ty` to the integer or address an #[ord] field compares:
+/// empty for integers and addresses, and through the field of structs with a single field
+fn ord_member_path(ty: &BaseType, c: &Context) -> Option<String> {
+    match &ty.value {
+        BaseType_::Apply(_, typename, _) => match &typename.value {
+            TypeName_::Builtin(builtin) => match builtin.value {
+                BuiltinTypeName_::U8
+                | BuiltinTypeName_::U64
+                | BuiltinTypeName_::U128
+                | BuiltinTypeName_::Address => Some("".to_string()),
+                _ => None,
+            },
+            TypeName_::ModuleType(mi, sname) => {
+                let sdef = c.program.modules.get(mi)?.structs.get(sname)?;
+                match &sdef.fields {
+                    StructFields::Defined(fields) if fields.len() == 1 => {
+                        let (field, field_ty) = &fields[0];
+                        ord_member_path(field_ty, c)
+                            .map(|path| format!(".{}{}", c.field_member(mi, sname, field), path))
+                    }
+                    _ => None,
+                }
+            }
+        },
+        _ => None,
+    }
+}
+
+pub fn has_ord_directive(sdef: &StructDefinition) -> bool {
+    sdef.attributes
+        .key_cloned_iter()
+        .any(|(name, _)| name.to_string() == "ord")
+}
+
+/// #[ord(price, then=b"timestamp,id")] orders by price, then by the fields listed in then, which is
+/// a byte string because the arguments of an attribute do not keep their order
+pub fn handle_struct_ord_directive(
     sname: &StructName,
     sdef: &StructDefinition,
-    name: &Name,
-    f: &Function,
+    inner_attrs: &Attributes,
+    w: &mut TsgenWriter,
     c: &mut Context,
 ) -> WriteResult {
-    // expect the fname to be a valid function, whose signature is:
-    // fname<sdef.type_params>(obj: &sdef)
-    let err = derr!((
+    let mident = c.current_module_or_err(sname.0.loc, "handle_struct_ord_directive")?;
+    let fields = match &sdef.fields {
+        StructFields::Defined(fields) => fields,
+        StructFields::Native(_) => {
+            return derr!((sname.0.loc, "cannot order native structs"));
+        }
+    };
+
+    let mut primary = vec![];
+    let mut then = vec![];
+    for (_, pattr) in inner_attrs.key_cloned_iter() {
+        match &pattr.value {
+            Attribute_::Name(field_name) => {
+                primary.push((field_name.loc, field_name.to_string()));
+            }
+            Attribute_::Assigned(key, _) if key.to_string() == "then" => {
+                match extract_attribute_value_string(pattr) {
+                    Some(names) => then.extend(
+                        names
+                            .split(',')
+                            .map(|name| (pattr.loc, name.trim().to_string())),
+                    ),
+                    None => {
+                        return derr!((
+                            pattr.loc,
+                            "then expects a byte string of field names, e.g. then=b\"timestamp,id\""
+                        ));
+                    }
+                }
+            }
+            _ => {
+                return derr!((
+                    pattr.loc,
+                    "ord directive expects a field name, optionally followed by then=b\"<field>,...\""
+                ));
+            }
+        }
+    }
+    if primary.len() != 1 {
+        return derr!((
+            sname.0.loc,
+            format!(
+                "ord directive on {} expects exactly one field name, and the fields that break ties in then=b\"...\"",
+                sname
+            )
+        ));
+    }
+
+    let mut comparisons = vec![];
+    for (loc, field_name) in primary.iter().chain(then.iter()) {
+        let field_ty = match fields
+            .iter()
+            .find(|(f_name, _)| f_name.to_string() == *field_name)
+        {
+            Some((_, field_ty)) => field_ty,
+            None => {
+                return derr!((*loc, format!("Field {} does not exist", field_name)));
+            }
+        };
+        let path = match ord_member_path(field_ty, c) {
+            Some(path) => path,
+            None => {
+                return derr!((
+                    *loc,
+                    format!(
+                        "Field {} cannot be ordered: only integers, addresses and structs with a single orderable field can",
+                        field_name
+                    )
+                ));
+            }
+        };
+        let member = format!("{}{}", c.field_member(&mident, sname, field_name), path);
+        comparisons.push(format!("$.compareValues(a.{}, b.{})", member, member));
+    }
+
+    w.writeln(format!(
+        "static comparator(a: {}, b: {}): number {{",
+        sname, sname
+    ));
+    w.writeln(format!("  return {};", comparisons.join(" || ")));
+    w.writeln("}");
+    w.new_line();
+    w.writeln(format!("compareTo(other: {}): number {{", sname));
+    w.writeln(format!("  return {}.comparator(this, other);", sname));
+    w.writeln("}");
+    Ok(())
+}
+
+/// The module of a 0x1 table type by which its entries are read, for Table and IterableTable
+fn keyed_table_module(ty: &BaseType) -> Option<(&'static str, &Vec<BaseType>)> {
+    match &ty.value {
+        BaseType_::Apply(_, typename, targs) if targs.len() == 2 => match &typename.value {
+            TypeName_::ModuleType(table_mi, table_sname)
+                if format_address_hex(table_mi.value.address) == "0x1" =>
+            {
+                match (
+                    table_mi.value.module.to_string().as_str(),
+                    table_sname.to_string().as_str(),
+                ) {
+                    ("table", "Table") => Some(("table", targs)),
+                    ("iterable_table", "IterableTable") => Some(("iterable_table", targs)),
+                    _ => None,
+                }
+            }
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// #[query_by_key(registry = Registry, field = pairs)] loads the value stored under a key in the
+/// table field of a registry resource of the same module. The registry's type parameters are
+/// passed as $p, and the value's type follows from them through the field's type
+pub fn handle_struct_query_by_key_directive(
+    sname: &StructName,
+    attr: &Attribute,
+    inner_attrs: &Attributes,
+    w: &mut TsgenWriter,
+    c: &mut Context,
+) -> WriteResult {
+    let mident = c.current_module_or_err(sname.0.loc, "handle_struct_query_by_key_directive")?;
+    let mut registry = None;
+    let mut field = None;
+    for (pname, pattr) in inner_attrs.key_cloned_iter() {
+        let name = match &pattr.value {
+            Attribute_::Assigned(_, value) => match &value.value {
+                AttributeValue_::ModuleAccess(ma) => match &ma.value {
+                    ModuleAccess_::Name(n) => Some(*n),
+                    ModuleAccess_::ModuleAccess(m, n) if *m == mident => Some(*n),
+                    ModuleAccess_::ModuleAccess(_, _) => {
+                        return derr!((
+                            value.loc,
+                            format!(
+                                "The registry of {} must be declared in the same module",
+                                sname
+                            )
+                        ));
+                    }
+                },
+                _ => None,
+            },
+            _ => None,
+        };
+        match (pname.to_string().as_str(), name) {
+            ("registry", Some(n)) => registry = Some(n),
+            ("field", Some(n)) => field = Some(n),
+            ("registry", None) | ("field", None) => {
+                return derr!((
+                    pattr.loc,
+                    format!("{} expects a name, e.g. {} = pairs", pname, pname)
+                ));
+            }
+            _ => {
+                return derr!((
+                    pname.loc,
+                    "Unrecognized parameter to query_by_key directive"
+                ));
+            }
+        }
+    }
+    let (registry, field) = match (registry, field) {
+        (Some(registry), Some(field)) => (registry, field),
+        _ => {
+            return derr!((
+                attr.loc,
+                "query_by_key directive expects a registry and a field, e.g. #[query_by_key(registry = Registry, field = pairs)]"
+            ));
+        }
+    };
+
+    let rdef = match c
+        .program
+        .modules
+        .get(&mident)
+        .and_then(|mdef| mdef.structs.get(&StructName(registry)))
+    {
+        Some(rdef) => rdef,
+        None => {
+            return derr!((
+                registry.loc,
+                format!("No struct {} in module {}", registry, mident.value.module)
+            ));
+        }
+    };
+    if !rdef.abilities.has_ability_(Ability_::Key) {
+        return derr!((
+            registry.loc,
+            format!(
+                "{} does not have the key ability, so it cannot be loaded as a registry",
+                registry
+            )
+        ));
+    }
+    let (field_decl, field_ty) = match &rdef.fields {
+        StructFields::Defined(fields) => {
+            match fields
+                .iter()
+                .find(|(f, _)| f.to_string() == field.to_string())
+            {
+                Some((f, ty)) => (f, ty),
+                None => {
+                    return derr!((field.loc, format!("{} has no field {}", registry, field)));
+                }
+            }
+        }
+        StructFields::Native(_) => {
+            return derr!((field.loc, format!("{} is a native struct", registry)));
+        }
+    };
+    let (table_module, targs) = match keyed_table_module(field_ty) {
+        Some(table) => table,
+        None => {
+            return derr!(
+                (
+                    field.loc,
+                    format!(
+                        "Field {} of {} is not a Table or IterableTable",
+                        field, registry
+                    )
+                ),
+                (field_decl.0.loc, format!("{} is declared here", field))
+            );
+        }
+    };
+    // the value's type arguments may be the registry's type parameters, resolved from $p
+    let holds_struct = match &targs[1].value {
+        BaseType_::Apply(_, typename, _) => match &typename.value {
+            TypeName_::ModuleType(value_mi, value_sname) => {
+                *value_mi == mident && value_sname == sname
+            }
+            _ => false,
+        },
+        _ => false,
+    };
+    if !holds_struct {
+        return derr!(
+            (
+                field.loc,
+                format!(
+                    "The values of {}.{} are not {}, so they cannot be loaded into it",
+                    registry, field, sname
+                )
+            ),
+            (targs[1].loc, "The value type is declared here".to_string())
+        );
+    }
+    let key_ts_type = base_type_to_tstype(&targs[0], c)?;
+    let member = c.field_member(&mident, &registry, &field);
+
+    w.writeln(format!(
+        "static async loadByKey(client: AptosClient, repo: AptosParserRepo, registryOwner: HexString, key: {}, $p: TypeTag[]): Promise<{}> {{",
+        key_ts_type, sname
+    ));
+    w.writeln(format!(
+        "  const registry = await {}.load(repo, client, registryOwner, $p);",
+        registry
+    ));
+    if table_module == "table" {
+        w.writeln(format!(
+            "  return await registry.getTableItem_{}(client, repo, key) as {};",
+            field, sname
+        ));
+    } else {
+        w.writeln(format!(
+            "  const request = $.iterableTableItemRequest(registry.{}, {}.getField({}), $p, key);",
+            member,
+            registry,
+            quote(&field)
+        ));
+        w.writeln(format!(
+            "  return await $.fetchIterableTableValue(client, repo, request) as {};",
+            sname
+        ));
+    }
+    w.writeln("}");
+    Ok(())
+}
+
+pub fn validate_method(
+    sname: &StructName,
+    sdef: &StructDefinition,
+    name: &Name,
+    f: &Function,
+    c: &mut Context,
+) -> WriteResult {
+    // expect the fname to be a valid function, whose signature is:
+    // fname<sdef.type_params>(obj: &sdef)
+    let err = derr!((
         name.loc,
         format!(
             "This function should have &{} as its first parameter",
@@ -433,7 +1488,8 @@ pub fn validate_method(
     if let BaseType_::Apply(_, typename, targs) = &base.value {
         match &typename.value {
             TypeName_::ModuleType(mi, sname2) => {
-                if is_same_module(&c.current_module.unwrap(), mi) && *sname == *sname2 {
+                let mident = c.current_module_or_err(name.loc, "validate_method")?;
+                if is_same_module(&mident, mi) && *sname == *sname2 {
                     // check type params are in the right order
                     for (idx, tparam) in targs.iter().enumerate() {
                         match &tparam.value {
@@ -470,12 +1526,16 @@ pub fn handle_struct_method_directive(
     w: &mut TsgenWriter,
     c: &mut Context,
 ) -> WriteResult {
+    // functions whose names only differ in case or underscores can get the same member name
+    let mut members: BTreeMap<String, &Name> = BTreeMap::new();
     for (_, pattr) in inner_attrs.key_cloned_iter() {
         match &pattr.value {
             Attribute_::Name(fname) => {
                 // validate it now
                 let program = c.program.clone();
-                let mdef = program.modules.get(&c.current_module.unwrap()).unwrap();
+                let mident =
+                    c.current_module_or_err(fname.loc, "handle_struct_method_directive")?;
+                let mdef = program.modules.get(&mident).unwrap();
                 let func_opt = mdef.functions.get(&FunctionName(*fname));
 
                 if func_opt.is_none() {
@@ -483,14 +1543,34 @@ pub fn handle_struct_method_directive(
                 }
                 let func = func_opt.unwrap();
                 validate_method(sname, sdef, fname, func, c)?;
+                let member = c.member_ts_name(&mident, fname);
+                if let Some(previous) = members.insert(member.clone(), fname) {
+                    return derr!(
+                        (
+                            fname.loc,
+                            format!(
+                                "The methods {} and {} of {} would both be named {}",
+                                previous, fname, sname, member
+                            )
+                        ),
+                        (previous.loc, format!("{} is a method here", previous))
+                    );
+                }
 
                 // generate method
                 w.new_line();
 
                 let async_modifier = if c.is_async() { "async " } else { "" };
-                w.writeln(format!("{}{}(", async_modifier, fname));
+                // methods are an off-chain convenience, so private functions keep their name here
+                w.writeln(format!("{}{}(", async_modifier, member));
                 write_parameters(&func.signature, w, c, false, true)?;
-                w.writeln(") {");
+                // same return type as the wrapped function, which is a Promise in async mode
+                let ret_type_str = type_to_tstype(&func.signature.return_type, c)?;
+                if c.is_async() {
+                    w.writeln(format!("): Promise<{}> {{", ret_type_str));
+                } else {
+                    w.writeln(format!("): {} {{", ret_type_str));
+                }
                 w.writeln(format!("  const cache = new DummyCache();"));
                 w.writeln(format!(
                     "  const tags = (this.typeTag as StructTag).typeParams;"
@@ -501,7 +1581,7 @@ pub fn handle_struct_method_directive(
                     .join(", ");
                 w.writeln(format!(
                     "  return {}(this, {}{}cache{});",
-                    format_function_name(fname, c.is_async()),
+                    format_function_name(&c.local_function_ts_name(fname), c.is_async()),
                     args_str,
                     if args_str.is_empty() { "" } else { ", " },
                     if func.signature.type_parameters.is_empty() {""} else {", tags"},
@@ -510,13 +1590,7 @@ pub fn handle_struct_method_directive(
 
                 // generate printer for cli
                 if func.signature.return_type.value != Type_::Unit {
-                    c.add_printer_method(
-                        &c.current_module.unwrap(),
-                        sname,
-                        sdef,
-                        fname,
-                        &func.signature,
-                    );
+                    c.add_printer_method(&mident, sname, sdef, fname, &func.signature);
                 }
             }
             _ => {
@@ -531,6 +1605,30 @@ pub fn handle_struct_method_directive(
     Ok(())
 }
 
+/// Validates the form of a struct directive, without generating anything for it
+pub fn check_struct_directive(directive: &str, attr: &Attribute) -> Result<(), Reason> {
+    match directive {
+        "cmd" => Err((attr.loc, "the 'cmd' attribute cannot be used on structs".to_string())),
+        "method" => match &attr.value {
+            Attribute_::Parameterized(_, _) => Ok(()),
+            _ => Err((attr.loc, "the 'method' attribute requires a list of function names as argument (e.g. $[method(show_x_as_y)]".to_string())),
+        },
+        "show_iter_table" => match &attr.value {
+            Attribute_::Parameterized(_, _) => Ok(()),
+            _ => Err((attr.loc, "the 'show' requires a list of function names as argument (e.g. $[show(show_x_as_y)]".to_string())),
+        },
+        "ord" => match &attr.value {
+            Attribute_::Parameterized(_, _) => Ok(()),
+            _ => Err((attr.loc, "the 'ord' attribute requires the field to order by as argument (e.g. #[ord(price, then=b\"timestamp\")])".to_string())),
+        },
+        "query_by_key" => match &attr.value {
+            Attribute_::Parameterized(_, _) => Ok(()),
+            _ => Err((attr.loc, "the 'query_by_key' attribute requires the registry resource and its table field as argument (e.g. #[query_by_key(registry = Registry, field = pairs)])".to_string())),
+        },
+        _ => Ok(()),
+    }
+}
+
 pub fn handle_struct_directives(
     sname: &StructName,
     sdef: &StructDefinition,
@@ -539,39 +1637,132 @@ pub fn handle_struct_directives(
 ) -> WriteResult {
     let attrs = &sdef.attributes;
     for (name, attr) in attrs.key_cloned_iter() {
-        match name.to_string().as_str() {
-            "cmd" => return derr!((attr.loc, "the 'cmd' attribute cannot be used on structs")),
-            "method" => match &attr.value {
-                Attribute_::Parameterized(_, inner_attrs) => {
+        let directive = name.to_string();
+        check_struct_directive(&directive, attr).map_err(reason_to_diag)?;
+        if let Attribute_::Parameterized(_, inner_attrs) = &attr.value {
+            match directive.as_str() {
+                // methods wrap the translated functions, which --payload-only does not emit
+                "method" if c.config.payload_only => (),
+                "method" => {
                     w.new_line();
                     handle_struct_method_directive(sname, sdef, inner_attrs, w, c)?;
                 }
-                _ => {
-                    return derr!((attr.loc, "the 'method' attribute requires a list of function names as argument (e.g. $[method(show_x_as_y)]"))
-                }
-            }
-            "show_iter_table" => match &attr.value {
-                Attribute_::Parameterized(_, inner_attrs) => {
+                "show_iter_table" => {
                     w.new_line();
                     handle_struct_show_iter_table_directive(sname, sdef, inner_attrs, w, c)?;
                 }
-                _ => {
-                    return derr!((attr.loc, "the 'show' requires a list of function names as argument (e.g. $[show(show_x_as_y)]"))
+                "ord" => {
+                    w.new_line();
+                    handle_struct_ord_directive(sname, sdef, inner_attrs, w, c)?;
                 }
+                "query_by_key" => {
+                    w.new_line();
+                    handle_struct_query_by_key_directive(sname, attr, inner_attrs, w, c)?;
+                }
+                _ => (),
             }
-            _ => (),
         }
     }
     Ok(())
 }
 
+/// For each 0x1::table::Table field, `tableItemRequest_<field>` builds what the table items API
+/// needs to read the entry of a key, and `getTableItem_<field>` fetches and parses it. The key and
+/// value types come from the field's type tag with the type parameters of the instance substituted
+fn write_table_item_methods(
+    name: &StructName,
+    fields: &[(Field, BaseType)],
+    w: &mut TsgenWriter,
+    c: &mut Context,
+) -> WriteResult {
+    let mident = c.current_module_or_err(name.0.loc, "write_table_item_methods")?;
+    for (fname, ty) in fields.iter() {
+        let (table_mi, targs) = match &ty.value {
+            BaseType_::Apply(_, typename, targs) => match &typename.value {
+                TypeName_::ModuleType(table_mi, table_sname)
+                    if format_address_hex(table_mi.value.address) == "0x1"
+                        && table_mi.value.module.to_string() == "table"
+                        && table_sname.to_string() == "Table"
+                        && targs.len() == 2 =>
+                {
+                    (table_mi, targs)
+                }
+                _ => continue,
+            },
+            _ => continue,
+        };
+        let key_ts_type = base_type_to_tstype(&targs[0], c)?;
+        let value_ts_type = base_type_to_tstype(&targs[1], c)?;
+        let handle = format!(
+            "this.{}.{}",
+            c.field_member(&mident, name, fname),
+            c.field_member(table_mi, &"Table", &"handle")
+        );
+        w.new_line();
+        w.writeln(format!(
+            "tableItemRequest_{}(key: {}): $.TableItemRequest {{",
+            fname, key_ts_type
+        ));
+        w.writeln(format!(
+            "  return $.tableItemRequest({}, {}.getField({}), (this.typeTag as StructTag).typeParams, key);",
+            handle,
+            name,
+            quote(fname)
+        ));
+        w.writeln("}");
+        w.writeln(format!(
+            "async getTableItem_{}(client: AptosClient, repo: AptosParserRepo, key: {}): Promise<{}> {{",
+            fname, key_ts_type, value_ts_type
+        ));
+        w.writeln(format!(
+            "  return await $.fetchTableItem(client, repo, this.tableItemRequest_{}(key)) as {};",
+            fname, value_ts_type
+        ));
+        w.writeln("}");
+    }
+    Ok(())
+}
+
+/// With --strict-construction, the type of the proto a struct class is constructed from. It carries
+/// the module's brand, so that only `__pack` and code of the module build one
+fn write_struct_proto(
+    name: &StructName,
+    fields: &[(Field, BaseType)],
+    w: &mut TsgenWriter,
+    c: &mut Context,
+) -> WriteResult {
+    w.writeln(format!("export interface {}$Proto {{", name));
+    w.writeln("  readonly [protoBrand]: true;");
+    for (fname, ty) in fields.iter() {
+        w.writeln(format!("  {}: {};", fname, base_type_to_tstype(ty, c)?));
+    }
+    w.writeln("}");
+    Ok(())
+}
+
 impl AstTsPrinter for (StructName, &StructDefinition) {
     const CTOR_NAME: &'static str = "StructDef";
     fn write_ts(&self, w: &mut TsgenWriter, c: &mut Context) -> WriteResult {
         let (name, sdef) = self;
 
         w.new_line();
-        w.writeln(format!("export class {} ", name.term(c)?));
+        if let (true, StructFields::Defined(fields)) = (c.config.strict_construction, &sdef.fields)
+        {
+            write_struct_proto(name, fields, w, c)?;
+        }
+        // with --compact-structs, the constructor, the parser body and the members derived from
+        // the static metadata come from StructBase
+        let compact = c.config.compact_structs && matches!(sdef.fields, StructFields::Defined(_));
+        if compact {
+            c.helper_files
+                .insert("struct_base.ts".to_string(), get_struct_base_helper_decl());
+            w.writeln(format!(
+                "export class {} extends StructBase ",
+                name.term(c)?
+            ));
+        } else {
+            w.writeln(format!("export class {} ", name.term(c)?));
+        }
         w.short_block(|w| {
             w.writeln("static moduleAddress = moduleAddress;");
             w.writeln("static moduleName = moduleName;");
@@ -596,27 +1787,49 @@ impl AstTsPrinter for (StructName, &StructDefinition) {
                 Ok(())
             })?;
             w.writeln("];");
+            // the same, summarized for JS consumers, and the abilities, which tell e.g. whether
+            // the struct can be loaded from an account
+            if !compact {
+                w.writeln(format!("static typeParamCount = {};", sdef.type_parameters.len()));
+                w.writeln(format!(
+                    "static phantomTypeParams: boolean[] = [{}];",
+                    sdef.type_parameters.iter().map(|tp| tp.is_phantom).join(", ")
+                ));
+            }
+            w.writeln(format!(
+                "static abilities: string[] = [{}];",
+                ability_names(&sdef.abilities).iter().map(quote).join(", ")
+            ));
             match &sdef.fields {
                 StructFields::Native(_) => (),
                 StructFields::Defined(fields) => {
 
-                    // 1: static field decls
+                    // 1: static field decls, named as on chain and in protos
+                    let mident = c.current_module_or_err(name.0.loc, "struct class")?;
                     w.writeln("static fields: FieldDeclType[] = [");
-                    w.list(fields, ",", |w, (name, ty)| {
+                    w.list(fields, ",", |w, (fname, ty)| {
+                        let member = c.field_member(&mident, name, &fname);
+                        let member_decl = if member != fname.to_string() {
+                            format!(", memberName: {}", quote(&member))
+                        } else {
+                            "".to_string()
+                        };
                         w.write(format!(
-                            "{{ name: {}, typeTag: {} }}",
-                            quote(&rename(&name)),
-                            base_type_to_typetag_builder(ty, &sdef.type_parameters, c)?
+                            "{{ name: {}, typeTag: {}{} }}",
+                            quote(&fname),
+                            base_type_to_typetag_builder(ty, &sdef.type_parameters, c)?,
+                            member_decl
                         ));
                         Ok(true)
                     })?;
                     w.writeln("];");
                     w.new_line();
 
-                    // 2. actual class fields
+                    // 2. actual class fields, which StructBase's constructor assigns when compact
+                    let field_decl = if compact { "declare " } else { "" };
                     if !fields.is_empty() {
-                        w.list(fields, "", |w, (name, ty)| {
-                            w.write(format!("{}: {};", rename(&name), base_type_to_tstype(ty, c)?));
+                        w.list(fields, "", |w, (fname, ty)| {
+                            w.write(format!("{}{}: {};", field_decl, c.field_member(&mident, name, &fname), base_type_to_tstype(ty, c)?));
                             Ok(true)
                         })?;
                         w.new_line();
@@ -624,46 +1837,106 @@ impl AstTsPrinter for (StructName, &StructDefinition) {
                     }
 
                     // 3. ctor
-                    w.write("constructor(proto: any, public typeTag: TypeTag) {");
-                    w.indent(2, |w| {
-                        // one line for each field
-                        w.list(fields, "", |w, (name, ty)| {
-                            let name = rename(&name);
-                            let tstype = base_type_to_tstype(ty, c)?;
-                            w.write(
-                                format!("this.{} = proto['{}'] as {};", name, name, tstype));
-                            Ok(true)
+                    let proto_type = if c.config.strict_construction {
+                        format!("{}$Proto", name)
+                    } else {
+                        "any".to_string()
+                    };
+                    if !compact {
+                        w.write(format!("constructor(proto: {}, public typeTag: TypeTag) {{", proto_type));
+                        w.indent(2, |w| {
+                            // one line for each field
+                            w.list(fields, "", |w, (fname, ty)| {
+                                let member = c.field_member(&mident, name, &fname);
+                                let tstype = base_type_to_tstype(ty, c)?;
+                                w.write(
+                                    format!("this.{} = proto['{}'] as {};", member, fname, tstype));
+                                Ok(true)
+                            })?;
+                            Ok(())
                         })?;
-                        Ok(())
-                    })?;
-                    w.writeln("}");
+                        w.writeln("}");
+                    } else if c.config.strict_construction {
+                        w.writeln(format!("constructor(proto: {}, typeTag: TypeTag) {{", proto_type));
+                        w.writeln("  super(proto, typeTag);");
+                        w.writeln("}");
+                    }
+                    if c.config.strict_construction {
+                        w.writeln(format!(
+                            "static __pack(fields: Omit<{}, typeof protoBrand>, typeTag: TypeTag): {} {{",
+                            proto_type, name
+                        ));
+                        w.writeln(format!("  return new {}(fields as {}, typeTag);", name, proto_type));
+                        w.writeln("}");
+                    }
 
                     // 4. static Parser
                     w.new_line();
                     w.writeln(format!("static {}Parser(data:any, typeTag: TypeTag, repo: AptosParserRepo) : {} {{", name, name));
-                    w.writeln(format!("  const proto = $.parseStructProto(data, typeTag, repo, {});", name));
-                    w.writeln(format!("  return new {}(proto, typeTag);", name));
-                    w.writeln("}");
+                    let mident = c.current_module_or_err(name.0.loc, "struct parser")?;
+                    let member = format!(
+                        "{}::{}::{}",
+                        concrete_address_hex(&mident, name.0.loc)?,
+                        mident.value.module,
+                        name
+                    );
+                    let tags = "(typeTag as StructTag).typeParams";
+                    if let Some(check) = type_args_check(tags, sdef.type_parameters.len(), &member, c) {
+                        w.writeln(format!("  {}", check));
+                    }
+                    if compact {
+                        w.writeln(format!("  return {}.parseStruct(data, typeTag, repo);", name));
+                        w.writeln("}");
+                    } else {
+                        w.writeln(format!("  const proto = $.parseStructProto(data, typeTag, repo, {});", name));
+                        w.writeln(format!("  return new {}(proto, typeTag);", name));
+                        w.writeln("}");
+
+                        // field metadata by name, and values by dotted path through nested structs
+                        w.new_line();
+                        w.writeln("static getField(name: string): FieldDeclType {");
+                        w.writeln(format!("  return $.getStructField({}, name);", name));
+                        w.writeln("}");
+                        w.writeln("getFieldValue(path: string): unknown {");
+                        w.writeln("  return $.getFieldValueByPath(this, path);");
+                        w.writeln("}");
+                    }
 
                     // 5. resource loader
                     if sdef.abilities.has_ability_(Ability_::Key) {
                         w.new_line();
-                        w.writeln("static async load(repo: AptosParserRepo, client: AptosClient, address: HexString, typeParams: TypeTag[]) {");
+                        w.writeln(format!("static async load(repo: AptosParserRepo, client: AptosClient, address: HexString, typeParams: TypeTag[]): Promise<{}> {{", name));
                         w.writeln(format!("  const result = await repo.loadResource(client, address, {}, typeParams);", name));
                         w.writeln(format!("  return result as unknown as {};", name));
                         w.write("}");
                     }
 
-                    // 6. additional util funcs
+                    // 5. event decoder, for structs with the ability profile of events
+                    if sdef.abilities.has_ability_(Ability_::Drop)
+                        && sdef.abilities.has_ability_(Ability_::Store)
+                        && !sdef.abilities.has_ability_(Ability_::Key)
+                    {
+                        w.new_line();
+                        w.writeln(format!("static fromEvent(event: $.MoveEvent, repo: AptosParserRepo) : {} | null {{", name));
+                        w.writeln(format!("  return $.decodeStructEvent<{}>(event, {}, repo);", name, name));
+                        w.writeln("}");
+                    }
+
+                    // 6. reading the entries of 0x1::table::Table fields
+                    write_table_item_methods(&name, fields, w, c)?;
+
+                    // 7. additional util funcs
                     handle_special_structs(&name, w, c)?;
 
-                    // 7. attribute directives
+                    // 8. attribute directives
                     handle_struct_directives(&name, sdef, w, c)?;
                 }
             };
             Ok(())
         })?;
         w.new_line();
+        // declaration merging hook for members added in the module's .ext.ts
+        w.writeln(format!("export interface {} {{}}", name.term(c)?));
 
         Ok(())
     }
@@ -702,15 +1975,17 @@ pub fn handle_function_cmd_directive(
     _w: &mut TsgenWriter,
     c: &mut Context,
 ) -> WriteResult {
-    if f.entry.is_none() {
-        return derr!((
-            fname.0.loc,
-            "the cmd attribute only works on public entry functions"
-        ));
-    }
     let mut desc = None;
+    let mut param_descs = BTreeMap::new();
     if let Some(params) = inner_attrs {
         for (pname, pattr) in params.key_cloned_iter() {
+            // a parameter of the function, other than a signer, takes the description of its
+            // argument, which the cli prompts with in --interactive mode
+            let is_param = f
+                .signature
+                .parameters
+                .iter()
+                .any(|(var, ty)| var.to_string() == pname.to_string() && !is_type_signer(ty));
             match pname.to_string().as_str() {
                 "desc" => {
                     if let Some(str_desc) = extract_attribute_value_string(pattr) {
@@ -722,13 +1997,45 @@ pub fn handle_function_cmd_directive(
                         ));
                     }
                 }
+                param if is_param => {
+                    if let Some(str_desc) = extract_attribute_value_string(pattr) {
+                        param_descs.insert(param.to_string(), str_desc);
+                    } else {
+                        return derr!((
+                            pattr.loc,
+                            format!(
+                                "{} needs to be assigned a byte string describing the argument (e.g. {}=b\"description\")",
+                                param, param
+                            )
+                        ));
+                    }
+                }
                 _ => {
                     return derr!((pname.loc, "Unrecognized parameter to cmd directive"));
                 }
             }
         }
     }
-    c.add_cmd(&c.current_module.unwrap(), fname, f, desc);
+    // buildPayload_ is still skipped for these, so there would be nothing for the command to call
+    let invalid_params = script_function_invalid_parameters(&f.signature);
+    if !invalid_params.is_empty() {
+        let reasons = invalid_params
+            .into_iter()
+            .map(|(var, (loc, msg))| (loc, format!("{}: {}", var, msg)))
+            .collect::<Vec<_>>();
+        c.add_warning(Diagnostic::new(
+            TranslationWarning {},
+            (
+                fname.0.loc,
+                format!("No cli command is generated for {}", fname),
+            ),
+            reasons,
+            std::iter::empty::<String>(),
+        ));
+        return Ok(());
+    }
+    let mident = c.current_module_or_err(fname.0.loc, "handle_function_cmd_directive")?;
+    c.add_cmd(&mident, fname, f, desc, param_descs);
 
     Ok(())
 }
@@ -740,19 +2047,31 @@ pub fn write_query_function(
     w: &mut TsgenWriter,
     c: &mut Context,
 ) -> WriteResult {
-    let query_fname = format!("query_{}", fname);
+    let query_fname = format!("query_{}", c.local_function_ts_name(fname));
     w.writeln(format!("export async function {}(", query_fname));
     w.increase_indent();
 
     // params
     w.writeln("client: AptosClient,");
-    w.writeln("account: AptosAccount,");
+    w.writeln("account: AptosAccount | $.SimulationKeys,");
     w.writeln("repo: AptosParserRepo,");
-    write_parameters(&f.signature, w, c, true, false)?;
+    write_payload_parameters(&f.signature, w, c)?;
     w.writeln("$p: TypeTag[],");
+    w.writeln("$gas?: $.GasOptions,");
+
+    // type params of the output struct are given at the move_to site in terms of the entry
+    // function's own, so both its tag and its class are resolved against the entry function's $p
+    let mident = c.current_module_or_err(fname.0.loc, "write_query_function")?;
+    let (output_tag, output_ts_type) = c.with_current_function(&mident, &f.signature, |c| {
+        (
+            base_type_to_typetag(return_type, c),
+            base_type_to_tstype(return_type, c),
+        )
+    });
+    let output_ts_type = output_ts_type?;
 
     w.decrease_indent();
-    w.writeln(") {");
+    w.writeln(format!("): Promise<{}> {{", output_ts_type));
 
     let mut param_list = f
         .signature
@@ -766,30 +2085,25 @@ pub fn write_query_function(
         param_list.push("$p".to_string());
     }
 
-    let move_to_err = derr!((return_type.loc, "Expect move_to to contain a struct type"));
-    let output_struct_name = match &return_type.value {
-        BaseType_::Apply(_, tn, _) => match &tn.value {
-            TypeName_::ModuleType(_, name) => name.to_string(),
-            _ => {
-                return move_to_err;
-            },
-        }
-        _ => {
-            return move_to_err;
-        }
+    let is_struct = match &return_type.value {
+        BaseType_::Apply(_, tn, _) => matches!(&tn.value, TypeName_::ModuleType(_, _)),
+        _ => false,
     };
+    if !is_struct {
+        return derr!((return_type.loc, "Expect move_to to contain a struct type"));
+    }
 
     w.increase_indent();
 
     // body
     w.writeln(format!(
         "const payload = buildPayload_{}({});",
-        fname, param_list.join(", ")
+        c.local_function_ts_name(fname),
+        param_list.join(", ")
     ));
-    let output_tag = base_type_to_typetag(return_type, c)?;
-    w.writeln(format!("const outputTypeTag = {};", output_tag));
-    w.writeln("const output = await $.simulatePayloadTx(client, account, payload);");
-    w.writeln(format!("return $.takeSimulationValue<{}>(output, outputTypeTag, repo)", output_struct_name));
+    w.writeln(format!("const outputTypeTag = {};", output_tag?));
+    w.writeln("const output = await $.simulatePayloadTx(client, account, payload, $gas);");
+    w.writeln(format!("return $.takeSimulationValue<{}>(output, outputTypeTag, repo)", output_ts_type));
 
     w.decrease_indent();
     w.writeln("}");
@@ -803,13 +2117,6 @@ pub fn handle_function_query_directive(
     w: &mut TsgenWriter,
     c: &mut Context,
 ) -> WriteResult {
-    if f.entry.is_none() {
-        return derr!((
-            fname.0.loc,
-            "the query attribute only works on public entry functions"
-        ));
-    }
-
     // validate that the function has:
     // move_to<X>(..., x) as last statement
 
@@ -832,7 +2139,11 @@ pub fn handle_function_query_directive(
                         UnannotatedExp_::Builtin(builtin_f, _) => match &builtin_f.value {
                             BuiltinFunction_::MoveTo(base) => {
                                 write_query_function(fname, f, base, w, c)?;
-                                c.add_query(&c.current_module.unwrap(), fname, f);
+                                let mident = c.current_module_or_err(
+                                    fname.0.loc,
+                                    "handle_function_query_directive",
+                                )?;
+                                c.add_query(&mident, fname, f);
                                 Ok(())
                             }
                             _ => err,
@@ -847,40 +2158,104 @@ pub fn handle_function_query_directive(
     }
 }
 
-pub fn handle_function_directives(
+/// Validates the form of a function directive, without generating anything for it
+pub fn check_function_directive(
     fname: &FunctionName,
     f: &Function,
-    w: &mut TsgenWriter,
-    c: &mut Context,
-) -> WriteResult {
-    let attrs = &f.attributes;
-    for (name, attr) in attrs.key_cloned_iter() {
-        match name.to_string().as_str() {
-            "cmd" => match &attr.value {
-                Attribute_::Parameterized(_, inner_attrs) => {
-                    w.new_line();
-                    handle_function_cmd_directive(fname, f, Some(inner_attrs), w, c)?;
-                }
-                Attribute_::Name(_) => {
-                    w.new_line();
-                    handle_function_cmd_directive(fname, f, None, w, c)?;
-                }
-                Attribute_::Assigned(_, _) => {
-                    return derr!((attr.loc, "the 'cmd' attribute cannot be assigned"))
+    directive: &str,
+    attr: &Attribute,
+) -> Result<(), Reason> {
+    match directive {
+        "cmd" => {
+            if let Attribute_::Assigned(_, _) = &attr.value {
+                return Err((attr.loc, "the 'cmd' attribute cannot be assigned".to_string()));
+            }
+            if !is_public_entry(f) {
+                return Err((
+                    fname.0.loc,
+                    "the cmd attribute only works on public entry functions".to_string(),
+                ));
+            }
+        }
+        "query" => {
+            if !matches!(&attr.value, Attribute_::Name(_)) {
+                return Err((attr.loc, "the 'query' attribute has no parameters".to_string()));
+            }
+            if !is_public_entry(f) {
+                return Err((
+                    fname.0.loc,
+                    "the query attribute only works on public entry functions".to_string(),
+                ));
+            }
+        }
+        "method" => {
+            return Err((
+                attr.loc,
+                "the 'method' attribute can only be used on structs".to_string(),
+            ));
+        }
+        "ts_alias" => {
+            let old_name = extract_attribute_value_string(attr).ok_or_else(|| {
+                (
+                    attr.loc,
+                    "the 'ts_alias' attribute takes the old name, e.g. #[ts_alias = b\"old_name\"]"
+                        .to_string(),
+                )
+            })?;
+            if !is_move_identifier(&old_name) {
+                return Err((
+                    attr.loc,
+                    format!("'{}' is not a valid function name", old_name),
+                ));
+            }
+        }
+        "randomness" => {
+            if let Attribute_::Assigned(_, _) = &attr.value {
+                return Err((
+                    attr.loc,
+                    "the 'randomness' attribute cannot be assigned".to_string(),
+                ));
+            }
+            if f.entry.is_none() || is_public(f) {
+                return Err((
+                    fname.0.loc,
+                    "the randomness attribute only works on private entry functions".to_string(),
+                ));
+            }
+        }
+        _ => (),
+    }
+    Ok(())
+}
+
+pub fn handle_function_directives(
+    fname: &FunctionName,
+    f: &Function,
+    w: &mut TsgenWriter,
+    c: &mut Context,
+) -> WriteResult {
+    let attrs = &f.attributes;
+    for (name, attr) in attrs.key_cloned_iter() {
+        let directive = name.to_string();
+        check_function_directive(fname, f, &directive, attr).map_err(reason_to_diag)?;
+        match directive.as_str() {
+            "cmd" => match &attr.value {
+                Attribute_::Parameterized(_, inner_attrs) => {
+                    w.new_line();
+                    handle_function_cmd_directive(fname, f, Some(inner_attrs), w, c)?;
                 }
-            },
-            "query" => match &attr.value {
-                Attribute_::Name(_) => {
+                _ => {
                     w.new_line();
-                    handle_function_query_directive(fname, f, w, c)?;
+                    handle_function_cmd_directive(fname, f, None, w, c)?;
                 }
-                _ => return derr!((attr.loc, "the 'query' attribute has no parameters")),
             },
-            "method" => {
-                return derr!((
-                    attr.loc,
-                    "the 'method' attribute can only be used on structs"
-                ))
+            "query" => {
+                w.new_line();
+                handle_function_query_directive(fname, f, w, c)?;
+            }
+            "ts_alias" => {
+                w.new_line();
+                handle_function_ts_alias_directive(fname, f, attr, w, c)?;
             }
             _ => (),
         }
@@ -888,117 +2263,500 @@ pub fn handle_function_directives(
     Ok(())
 }
 
-impl AstTsPrinter for (FunctionName, &Function) {
-    const CTOR_NAME: &'static str = "FunctionDef";
-    fn write_ts(&self, w: &mut TsgenWriter, c: &mut Context) -> WriteResult {
-        let (name, func) = self;
-        let is_entry = func.entry.is_some();
-        if c.config.test {
-            let is_test = check_test(name, func, c)?;
-            if is_test {
-                w.writeln("// #[test]");
-            }
+fn is_move_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(ch) if ch.is_ascii_alphabetic() || ch == '_' => {
+            chars.all(|ch| ch.is_ascii_alphanumeric() || ch == '_')
+        }
+        _ => false,
+    }
+}
+
+/// The old name given by a function's #[ts_alias], with the attribute's location
+pub fn function_ts_alias(f: &Function) -> Option<(Loc, String)> {
+    f.attributes
+        .key_cloned_iter()
+        .find(|(name, _)| name.to_string() == "ts_alias")
+        .and_then(|(_, attr)| extract_attribute_value_string(attr).map(|old| (attr.loc, old)))
+}
+
+/// An alias must not take the TS name of a function of the module, or of another alias
+pub fn check_ts_aliases(mident: &ModuleIdent, mdef: &ModuleDefinition, c: &Context) -> WriteResult {
+    let mut seen: BTreeMap<String, (Loc, String)> = mdef
+        .functions
+        .key_cloned_iter()
+        .map(|(fname, _)| {
+            let ts_name = c.function_ts_name(mident, &fname);
+            (ts_name, (fname.0.loc, format!("{} declared here", fname)))
+        })
+        .collect();
+    for (fname, f) in mdef.functions.key_cloned_iter() {
+        let (loc, old_name) = match function_ts_alias(f) {
+            Some(alias) => alias,
+            None => continue,
+        };
+        let ts_name = ts_alias_name(mident, &fname, &old_name, c);
+        let label = format!("alias {} of {} declared here", old_name, fname);
+        if let Some((other_loc, other)) = seen.insert(ts_name.clone(), (loc, label)) {
+            return derr!(
+                (
+                    loc,
+                    format!(
+                        "The alias {} of {} collides with {}_",
+                        old_name, fname, ts_name
+                    )
+                ),
+                (other_loc, other)
+            );
         }
-        // yep, regardless of visibility, we always export it
-        let async_modifier = if c.is_async() { "async " } else { "" };
+    }
+    Ok(())
+}
+
+/// TS name of an alias, hidden like the function it stands for
+fn ts_alias_name(
+    mident: &ModuleIdent,
+    fname: &FunctionName,
+    old_name: &str,
+    c: &Context,
+) -> String {
+    let ts_name = c.member_ts_name(mident, &old_name);
+    if c.is_hidden_function(mident, fname) {
+        format!("_{}", ts_name)
+    } else {
+        ts_name
+    }
+}
+
+/// Deprecated exports under the name a function had before it was renamed, so that callers of the
+/// old TS name keep compiling
+pub fn handle_function_ts_alias_directive(
+    fname: &FunctionName,
+    f: &Function,
+    attr: &Attribute,
+    w: &mut TsgenWriter,
+    c: &mut Context,
+) -> WriteResult {
+    let old_name = match extract_attribute_value_string(attr) {
+        Some(old_name) => old_name,
+        None => return Ok(()),
+    };
+    let mident = c.current_module_or_err(fname.0.loc, "ts_alias")?;
+    let old_ts = ts_alias_name(&mident, fname, &old_name, c);
+    let new_ts = c.local_function_ts_name(fname);
+    if !matches!(c.emitted_body(f), EmittedBody::Omitted) {
+        w.writeln(format!("/** @deprecated renamed to {}_ */", new_ts));
+        w.writeln(format!("export const {}_ = {}_;", old_ts, new_ts));
+    }
+    if has_payload_builder(f) && script_function_has_valid_parameter(&f.signature) {
         w.writeln(format!(
-            "export {}function {}_ (",
-            async_modifier,
-            rename(name)
+            "/** @deprecated renamed to buildPayload_{} */",
+            new_ts
         ));
-        // write parameters
-        write_parameters(&func.signature, w, c, false, false)?;
-        // cache & typeTags
-        w.writeln("  $c: AptosDataCache,");
-        let num_tparams = func.signature.type_parameters.len();
-        let tpnames = if num_tparams == 0 {
+        w.writeln(format!(
+            "export const buildPayload_{} = buildPayload_{};",
+            old_ts, new_ts
+        ));
+    }
+    Ok(())
+}
+
+/// Framework address derivations that always go through the runtime, regardless of whether the
+/// framework version being compiled implements them in Move or as natives
+pub fn derived_address_helper(mident: &ModuleIdent, fname: &FunctionName) -> Option<&'static str> {
+    if format_address_hex(mident.value.address) != "0x1" {
+        return None;
+    }
+    match (
+        mident.value.module.to_string().as_str(),
+        fname.to_string().as_str(),
+    ) {
+        ("account", "create_resource_address") => {
+            Some("aptos_framework_account_create_resource_address")
+        }
+        ("object", "create_object_address") => Some("aptos_framework_object_create_object_address"),
+        ("object", "create_user_derived_object_address") => {
+            Some("aptos_framework_object_create_user_derived_object_address")
+        }
+        _ => None,
+    }
+}
+
+/// Bodies of the aggregator natives, simulated with plain arithmetic on the struct. With
+/// --strict-natives they throw instead, since the result can differ from what the VM computes
+fn aggregator_native_body(
+    mident: &ModuleIdent,
+    fname: &FunctionName,
+    params: &[String],
+    c: &Context,
+) -> Option<String> {
+    if format_address_hex(mident.value.address) != "0x1" {
+        return None;
+    }
+    let module = mident.value.module.to_string();
+    let p = |i: usize| params.get(i).cloned().unwrap_or_default();
+    let new_struct = |sname: &str, proto: String| {
+        format!(
+            "return {};",
+            c.construct_struct(
+                &sname,
+                &proto,
+                &format!(
+                    "new StructTag(moduleAddress, moduleName, {}, [$p[0]])",
+                    quote(&sname)
+                )
+            )
+        )
+    };
+    let body = match (module.as_str(), fname.to_string().as_str()) {
+        ("aggregator_v2", "create_aggregator") => new_struct(
+            "Aggregator",
+            format!("{{ value: $.aggregatorZero($p[0]), max_value: {} }}", p(0)),
+        ),
+        ("aggregator_v2", "create_unbounded_aggregator") => new_struct(
+            "Aggregator",
+            "{ value: $.aggregatorZero($p[0]), max_value: $.aggregatorMax($p[0]) }".to_string(),
+        ),
+        ("aggregator_v2", "try_add") => format!("return $.aggregatorTryAdd({}, {});", p(0), p(1)),
+        ("aggregator_v2", "try_sub") => format!("return $.aggregatorTrySub({}, {});", p(0), p(1)),
+        ("aggregator_v2", "is_at_least_impl") => {
+            format!("return $.aggregatorIsAtLeast({}, {});", p(0), p(1))
+        }
+        ("aggregator_v2", "read") | ("aggregator_v2", "read_snapshot") => {
+            format!("return $.aggregatorRead({});", p(0))
+        }
+        ("aggregator_v2", "snapshot") | ("aggregator_v2", "copy_snapshot") => new_struct(
+            "AggregatorSnapshot",
+            format!("{{ value: $.aggregatorRead({}) }}", p(0)),
+        ),
+        ("aggregator_v2", "create_snapshot") => new_struct(
+            "AggregatorSnapshot",
+            format!("{{ value: {}.copy() }}", p(0)),
+        ),
+        ("aggregator", "add") => format!("$.aggregatorV1Add({}, {});", p(0), p(1)),
+        ("aggregator", "sub") => format!("$.aggregatorV1Sub({}, {});", p(0), p(1)),
+        ("aggregator", "read") => format!("return $.aggregatorV1Read({});", p(0)),
+        ("aggregator", "destroy") => "return;".to_string(),
+        _ => return None,
+    };
+    if c.config.strict_natives {
+        return Some(format!(
+            "throw new Error(\"0x1::{}::{} is not simulated with --strict-natives\");",
+            module, fname
+        ));
+    }
+    Some(body)
+}
+
+// names the type parameters $p stands for
+fn tparams_comment(tpnames: &str, c: &Context) -> String {
+    if c.emits_comments() {
+        format!(" /* <{}>*/", tpnames)
+    } else {
+        "".to_string()
+    }
+}
+
+/// Declaration of `foo_`, with its translated body or a stub that throws
+fn write_function_impl(
+    name: &FunctionName,
+    func: &Function,
+    emitted: EmittedBody,
+    w: &mut TsgenWriter,
+    c: &mut Context,
+) -> WriteResult {
+    // tests and test-only functions
+    let is_test = c.config.test && check_test(name, func, c)?;
+    if is_test && c.emits_comments() {
+        w.writeln("// #[test]");
+    }
+    // yep, regardless of visibility, we always export it
+    let async_modifier = if c.is_async() { "async " } else { "" };
+    w.writeln(format!(
+        "export {}function {}_ (",
+        async_modifier,
+        c.local_function_ts_name(name)
+    ));
+    // write parameters
+    write_parameters(&func.signature, w, c, false, false)?;
+    // cache & typeTags
+    w.writeln("  $c: AptosDataCache,");
+    let num_tparams = func.signature.type_parameters.len();
+    let tpnames = if num_tparams == 0 {
+        "".to_string()
+    } else {
+        func.signature
+            .type_parameters
+            .iter()
+            .map(|tp| tp.user_specified_name.to_string())
+            .join(", ")
+    };
+    if num_tparams > 0 {
+        w.writeln(format!("  $p: TypeTag[],{}", tparams_comment(&tpnames, c)));
+    }
+    // marks returnType or void
+    w.write("): ");
+    let ret_type_str = type_to_tstype(&func.signature.return_type, c)?;
+    if c.is_async() {
+        w.write(format!("Promise<{}>", ret_type_str));
+    } else {
+        w.write(ret_type_str);
+    }
+    w.write(" ");
+
+    // add parameters to local frame
+    let mut param_names = BTreeSet::new();
+    for (name, _) in func.signature.parameters.iter() {
+        param_names.insert(name.to_string());
+    }
+    let mident = c.current_module_or_err(name.0.loc, "write_function_impl")?;
+    let args = func
+        .signature
+        .parameters
+        .iter()
+        .map(|(n, _)| rename(&n.to_string()))
+        .join(", ");
+    let args_comma = format!("{}{}", args, if args.is_empty() { "" } else { ", " });
+    let member = format!(
+        "{}::{}::{}",
+        concrete_address_hex(&mident, name.0.loc)?,
+        mident.value.module,
+        name
+    );
+    let check = type_args_check("$p", num_tparams, &member, c);
+    if let EmittedBody::Stub(reason) = emitted {
+        w.short_block(|w| {
+            w.writeln(format!(
+                "throw new Error({});",
+                json_string(&format!("{}::{} {}", mident.value.module, name, reason))
+            ));
+            Ok(())
+        })?;
+        w.new_line();
+        return Ok(());
+    }
+    if let FunctionBody_::Native = &func.body.value {
+        let arg_names = func
+            .signature
+            .parameters
+            .iter()
+            .map(|(n, _)| rename(&n.to_string()))
+            .collect::<Vec<_>>();
+        if let Some(body) = aggregator_native_body(&mident, name, &arg_names, c) {
+            w.short_block(|w| {
+                if let Some(check) = &check {
+                    w.writeln(check);
+                }
+                w.writeln(&body);
+                Ok(())
+            })?;
+            w.new_line();
+            return Ok(());
+        }
+    }
+    match (&func.body.value, derived_address_helper(&mident, name)) {
+        (_, Some(helper)) => {
+            w.short_block(|w| {
+                if let Some(check) = &check {
+                    w.writeln(check);
+                }
+                w.writeln(format!("return $.{}({}$c);", helper, args_comma));
+                Ok(())
+            })?;
+        }
+        (FunctionBody_::Native, None) => {
+            let native_name = format!(
+                "return $.{}_{}_{}",
+                format_address(mident.value.address),
+                mident.value.module,
+                name
+            );
+            let comma_tags = format!(
+                "{}{}",
+                if num_tparams == 0 { "" } else { ", " },
+                if num_tparams == 0 {
+                    "".to_string()
+                } else {
+                    // $p is already in the native's own declaration order: call sites build
+                    // it from their type arguments through base_type_to_typetag
+                    format!(
+                        "[{}]",
+                        (0..num_tparams)
+                            .into_iter()
+                            .map(|u| format!("$p[{}]", u))
+                            .join(", ")
+                    )
+                }
+            );
+            w.short_block(|w| {
+                if let Some(check) = &check {
+                    w.writeln(check);
+                }
+                w.writeln(format!("{}({}$c{});", native_name, args_comma, comma_tags));
+                Ok(())
+            })?;
+        }
+        (FunctionBody_::Defined { locals, body }, None) => {
+            let new_vars = locals
+                .key_cloned_iter()
+                .map(|(name, _)| name)
+                .filter(|name| !param_names.contains(&name.to_string()))
+                .collect::<Vec<_>>();
+            let mut prelude = check.into_iter().collect::<Vec<_>>();
+            if c.emits_coverage() && !is_test {
+                prelude.push(format!("$.hitCoverage({});", quote(&member)));
+                c.coverage_functions.insert(member.clone());
+            }
+            let trace = c
+                .emits_traces()
+                .then(|| trace_rethrow(&member, &func.signature));
+            warn_hanging_loops(name, body, c);
+            write_func_body(body, &new_vars, prelude, trace, w, c)?;
+        }
+    }
+    w.new_line();
+    Ok(())
+}
+
+/// With --trace, what the catch around a function body throws: the caught error, with the
+/// function and the values of its primitive parameters added to its moveTrace
+fn trace_rethrow(member: &String, signature: &FunctionSignature) -> String {
+    let args = signature
+        .parameters
+        .iter()
+        .filter(|(_, ty)| match extract_builtin_type(ty) {
+            Ok((BuiltinTypeName_::Vector, _)) | Ok((BuiltinTypeName_::Signer, _)) => false,
+            Ok(_) => true,
+            Err(_) => false,
+        })
+        .map(|(name, _)| format!("{}: {}", quote(name), rename(name)))
+        .join(", ");
+    format!(
+        "throw $.traceCall($e, {}, {{{}}});",
+        quote(member),
+        if args.is_empty() {
             "".to_string()
         } else {
-            func.signature
-                .type_parameters
-                .iter()
-                .map(|tp| tp.user_specified_name.to_string())
-                .join(", ")
-        };
-        if num_tparams > 0 {
-            w.writeln(format!("  $p: TypeTag[], /* <{}>*/", tpnames));
-        }
-        // marks returnType or void
-        w.write("): ");
-        let ret_type_str = type_to_tstype(&func.signature.return_type, c)?;
-        if c.is_async() {
-            w.write(format!("Promise<{}>", ret_type_str));
-        } else {
-            w.write(ret_type_str);
+            format!(" {} ", args)
+        }
+    )
+}
+
+/// With --keep-going, a function that runs into an UnresolvedError left by the compiler is
+/// declared with a stub and a warning, instead of failing its module. It is written to a buffer
+/// first, so that nothing of the failed attempt ends up in the module
+fn write_function_impl_or_stub(
+    name: &FunctionName,
+    func: &Function,
+    emitted: EmittedBody,
+    w: &mut TsgenWriter,
+    c: &mut Context,
+) -> WriteResult {
+    let mut buffer = TsgenWriter::new();
+    let num_tests = c.tests.len();
+    c.reached_unresolved = None;
+    let result = write_function_impl(name, func, emitted, &mut buffer, c);
+    match (result, c.reached_unresolved.take()) {
+        (Ok(()), _) => {
+            let content = buffer.into_string();
+            for line in content.strip_suffix('\n').unwrap_or(&content).lines() {
+                w.writeln(line);
+            }
+            Ok(())
+        }
+        (Err(_), Some(loc)) => {
+            c.tests.truncate(num_tests);
+            c.add_warning(wdiag!(
+                (
+                    name.0.loc,
+                    format!(
+                        "{} is declared with a stub, it cannot be translated because of a compiler error",
+                        name
+                    )
+                ),
+                (loc, "unresolved by the compiler".to_string())
+            ));
+            let stub = EmittedBody::Stub(
+                "could not be translated, see the warnings of move-to-ts".to_string(),
+            );
+            write_function_impl(name, func, stub, w, c)
         }
-        w.write(" ");
+        (Err(diag), None) => Err(diag),
+    }
+}
 
+impl AstTsPrinter for (FunctionName, &Function) {
+    const CTOR_NAME: &'static str = "FunctionDef";
+    fn write_ts(&self, w: &mut TsgenWriter, c: &mut Context) -> WriteResult {
+        let (name, func) = self;
         // set current_function_signature as we enter body
         c.current_function_signature = Some(func.signature.clone());
-        // add parameters to local frame
-        let mut param_names = BTreeSet::new();
-        for (name, _) in func.signature.parameters.iter() {
-            param_names.insert(name.to_string());
-        }
-        match &func.body.value {
-            FunctionBody_::Native => {
-                let mident = c.current_module.unwrap();
-                let native_name = format!(
-                    "return $.{}_{}_{}",
-                    format_address(mident.value.address),
-                    mident.value.module,
-                    name
-                );
-                let args = func
-                    .signature
-                    .parameters
-                    .iter()
-                    .map(|(n, _)| rename(&n.to_string()))
-                    .join(", ");
-                let args_comma = format!("{}{}", args, if args.is_empty() { "" } else { ", " });
-                let comma_tags = format!(
-                    "{}{}",
-                    if num_tparams == 0 { "" } else { ", " },
-                    if num_tparams == 0 {
-                        "".to_string()
-                    } else {
-                        format!(
-                            "[{}]",
-                            (0..num_tparams)
-                                .into_iter()
-                                .map(|u| format!("$p[{}]", u))
-                                .join(", ")
-                        )
-                    }
-                );
-                w.short_block(|w| {
-                    w.writeln(format!("{}({}$c{});", native_name, args_comma, comma_tags));
-                    Ok(())
-                })?;
+        c.in_async_scope = c.is_async();
+        let mident = c.current_module_or_err(name.0.loc, "function printer")?;
+
+        let emitted = match c.skipped_function(&mident, name) {
+            Some((key, reason)) => {
+                c.applied_skips.insert(key);
+                match c.emitted_body(func) {
+                    EmittedBody::Omitted => EmittedBody::Omitted,
+                    _ => EmittedBody::Stub(format!("is skipped: {}", reason)),
+                }
             }
-            FunctionBody_::Defined { locals, body } => {
-                let new_vars = locals
-                    .key_cloned_iter()
-                    .map(|(name, _)| name)
-                    .filter(|name| !param_names.contains(&name.to_string()))
-                    .collect::<Vec<_>>();
-                write_func_body(body, &new_vars, w, c)?;
+            None => c.emitted_body(func),
+        };
+        match emitted {
+            EmittedBody::Omitted => (),
+            emitted if c.config.keep_going => {
+                write_function_impl_or_stub(name, func, emitted, w, c)?
             }
+            emitted => write_function_impl(name, func, emitted, w, c)?,
+        }
+
+        let num_tparams = func.signature.type_parameters.len();
+        let tpnames = func
+            .signature
+            .type_parameters
+            .iter()
+            .map(|tp| tp.user_specified_name.to_string())
+            .join(", ");
+
+        w.writeln(format!(
+            "export const visibility_{} = {};",
+            c.member_ts_name(&mident, name),
+            quote(&visibility_name(&func.visibility))
+        ));
+        if is_randomness_entry(func) {
+            // the transaction is charged a gas deposit up front, so UIs may want to warn
+            w.writeln(format!(
+                "export const requiresRandomness_{} = true;",
+                c.member_ts_name(&mident, name)
+            ));
         }
         w.new_line();
 
-        if is_entry && script_function_has_valid_parameter(&func.signature) {
+        if has_payload_builder(func) {
+            write_param_metadata(name, func, w, c)?;
+        }
+
+        if has_payload_builder(func) && script_function_has_valid_parameter(&func.signature) {
             // TODO
             // uses entry-func signature, which returns TransactionInfo{toPayload(), send(),
             // sendAndWait()}
             w.new_line();
+            if c.config.emit_examples && c.emits_comments() {
+                write_usage_example(name, func, w, c)?;
+            }
             // yep, regardless of visibility, we always export it
-            w.writeln(format!("export function buildPayload_{} (", name));
+            w.writeln(format!(
+                "export function buildPayload_{} (",
+                c.local_function_ts_name(name)
+            ));
             // write parameters
-            write_parameters(&func.signature, w, c, true, false)?;
+            write_payload_parameters(&func.signature, w, c)?;
             // typeTags
             if num_tparams > 0 {
-                w.writeln(format!("  $p: TypeTag[], /* <{}>*/", tpnames));
+                w.writeln(format!("  $p: TypeTag[],{}", tparams_comment(&tpnames, c)));
             }
             // marks returnType or void
             w.write(") ");
@@ -1010,9 +2768,13 @@ impl AstTsPrinter for (FunctionName, &Function) {
                 .filter(|(_n, ty)| !is_type_signer(ty))
                 .collect::<Vec<_>>();
 
+            let address = concrete_address_hex(&mident, name.0.loc)?;
+            let member = format!("{}::{}::{}", address, mident.value.module, name);
+            let check = type_args_check("$p", num_tparams, &member, c);
             w.short_block(|w| {
-                let mident = c.current_module.unwrap();
-                let address = format_address_hex(mident.value.address);
+                if let Some(check) = &check {
+                    w.writeln(check);
+                }
                 if num_tparams > 0 {
                     w.writeln("const typeParamStrings = $p.map(t=>$.getTypeTagFullname(t));");
                 } else {
@@ -1034,7 +2796,8 @@ impl AstTsPrinter for (FunctionName, &Function) {
                     for (pname, ptype) in params_no_signers.iter() {
                         w.writeln(format!(
                             "    {},",
-                            get_ts_handler_for_script_function_param(pname, ptype)?,
+                            get_ts_handler_for_script_function_param(pname, ptype)
+                                .map_err(reason_to_diag)?,
                         ));
                     }
                     w.writeln("  ]");
@@ -1042,17 +2805,99 @@ impl AstTsPrinter for (FunctionName, &Function) {
                 w.writeln(");");
                 Ok(())
             })?;
+            if let Some((file, start, end)) = function_provenance(name, func, c) {
+                w.writeln(format!(
+                    "export const source_{} = {{ file: {}, start: {}, end: {} }} as const;",
+                    c.local_function_ts_name(name),
+                    json_string(&file),
+                    start,
+                    end
+                ));
+            }
             w.new_line();
+        } else if has_payload_builder(func) {
+            warn_signer_params_without_payload(name, func, c);
         }
 
         handle_function_directives(name, func, w, c)?;
 
         c.current_function_signature = None;
+        c.in_async_scope = false;
 
         Ok(())
     }
 }
 
+/// Where an entry function comes from with --emit-provenance, from its name to the end of its body
+pub fn function_provenance(
+    name: &FunctionName,
+    func: &Function,
+    c: &Context,
+) -> Option<(String, u32, u32)> {
+    if !c.config.emit_provenance {
+        return None;
+    }
+    c.provenance(name.0.loc, func.body.loc)
+}
+
+/// Entry functions that take signers other than as `signer` or `&signer` parameters, e.g. in a
+/// `vector<signer>`, get no buildPayload_. Their bodies are still translated, with the signers as
+/// addresses, so only a warning is due
+fn warn_signer_params_without_payload(fname: &FunctionName, func: &Function, c: &mut Context) {
+    let reasons = func
+        .signature
+        .parameters
+        .iter()
+        .filter(|(_, ty)| !is_type_signer(ty) && single_type_contains_signer(ty))
+        .filter_map(|(var, ty)| {
+            get_ts_handler_for_script_function_param(var, ty)
+                .err()
+                .map(|(loc, msg)| (loc, format!("{}: {}", var, msg)))
+        })
+        .collect::<Vec<_>>();
+    if reasons.is_empty() {
+        return;
+    }
+    c.add_warning(Diagnostic::new(
+        TranslationWarning {},
+        (
+            fname.0.loc,
+            format!("No buildPayload_ is generated for {}", fname),
+        ),
+        reasons,
+        std::iter::empty::<String>(),
+    ));
+}
+
+fn single_type_contains_signer(ty: &SingleType) -> bool {
+    fn base_contains_signer(ty: &BaseType) -> bool {
+        match &ty.value {
+            BaseType_::Apply(_, _, ty_args) => {
+                is_base_type_signer(ty) || ty_args.iter().any(base_contains_signer)
+            }
+            _ => false,
+        }
+    }
+    match &ty.value {
+        SingleType_::Base(base_ty) | SingleType_::Ref(_, base_ty) => base_contains_signer(base_ty),
+    }
+}
+
+/// Throws early when a generated function or parser gets the wrong number of type arguments, which
+/// would otherwise surface as an undefined `$p[i]` further down. Nothing is emitted for members
+/// without type parameters, or with --no-runtime-checks
+fn type_args_check(tags: &str, num_tparams: usize, member: &str, c: &Context) -> Option<String> {
+    if num_tparams == 0 || !c.emits_runtime_checks() {
+        return None;
+    }
+    Some(format!(
+        "if (!{tags} || {tags}.length !== {n}) throw new Error(`expected {n} type arguments for {member}, got ${{{tags} ? {tags}.length : \"none\"}}`);",
+        tags = tags,
+        n = num_tparams,
+        member = member
+    ))
+}
+
 pub fn extract_builtin_from_base_type(
     ty: &BaseType,
 ) -> Result<(&BuiltinTypeName_, &Vec<BaseType>), bool> {
@@ -1072,19 +2917,31 @@ pub fn extract_builtin_type(ty: &SingleType) -> Result<(&BuiltinTypeName_, &Vec<
 }
 
 pub fn script_function_has_valid_parameter(sig: &FunctionSignature) -> bool {
-    for (var, ty) in sig.parameters.iter() {
-        if is_type_signer(ty) {
-            continue;
-        }
-        let ts_handler = get_ts_handler_for_script_function_param(var, ty);
-        if ts_handler.is_err() {
-            return false;
-        }
-    }
-    true
+    script_function_invalid_parameters(sig).is_empty()
+}
+
+/// Parameters that prevent generating buildPayload_ for an entry function, and why
+pub fn script_function_invalid_parameters(sig: &FunctionSignature) -> Vec<(Var, Reason)> {
+    sig.parameters
+        .iter()
+        .filter(|(_, ty)| !is_type_signer(ty))
+        .filter_map(|(var, ty)| {
+            get_ts_handler_for_script_function_param(var, ty)
+                .err()
+                .map(|reason| (*var, reason))
+        })
+        .collect()
 }
 
-pub fn get_ts_handler_for_script_function_param(name: &Var, ty: &SingleType) -> TermResult {
+// signers, also inside vectors, come from the accounts that sign a transaction, not its payload
+const SIGNER_PAYLOAD_ARG_REASON: &str =
+    "Signers cannot be passed in a transaction payload, only the accounts that sign it provide them";
+
+pub fn get_ts_handler_for_script_function_param(
+    name: &Var,
+    ty: &SingleType,
+) -> Result<String, Reason> {
+    let move_name = name.to_string();
     let name = rename(name);
     if let Ok((builtin, ty_args)) = extract_builtin_type(ty) {
         match builtin {
@@ -1092,8 +2949,13 @@ pub fn get_ts_handler_for_script_function_param(name: &Var, ty: &SingleType) ->
             | BuiltinTypeName_::Address
             | BuiltinTypeName_::U8
             | BuiltinTypeName_::U64
-            | BuiltinTypeName_::U128 => Ok(format!("$.payloadArg({})", name)),
-            BuiltinTypeName_::Signer => unreachable!(),
+            | BuiltinTypeName_::U128 => Ok(format!(
+                "$.coercePayloadArg({}, {}, {})",
+                name,
+                quote(&payload_arg_type(builtin)),
+                quote(&move_name)
+            )),
+            BuiltinTypeName_::Signer => Err((ty.loc, SIGNER_PAYLOAD_ARG_REASON.to_string())),
             BuiltinTypeName_::Vector => {
                 // handle vector
                 assert!(ty_args.len() == 1);
@@ -1105,61 +2967,412 @@ pub fn get_ts_handler_for_script_function_param(name: &Var, ty: &SingleType) ->
                         BuiltinTypeName_::Bool
                         | BuiltinTypeName_::Address
                         | BuiltinTypeName_::U64
-                        | BuiltinTypeName_::U128 => {
-                            Ok(format!("{}.map(element => $.payloadArg(element))", name))
+                        | BuiltinTypeName_::U128 => Ok(format!(
+                            "{}.map(element => $.coercePayloadArg(element, {}, {}))",
+                            name,
+                            quote(&payload_arg_type(inner_builtin)),
+                            quote(&move_name)
+                        )),
+                        BuiltinTypeName_::Signer => {
+                            Err((ty_args[0].loc, SIGNER_PAYLOAD_ARG_REASON.to_string()))
                         }
-                        BuiltinTypeName_::Signer => unreachable!(),
                         BuiltinTypeName_::Vector => {
                             assert!(inner_ty_args.len() == 1);
-                            let inner_map = get_ts_handler_for_vector_in_vector(&inner_ty_args[0])?;
+                            let inner_map =
+                                get_ts_handler_for_vector_in_vector(&inner_ty_args[0], &move_name)?;
                             Ok(format!("{}.map({})", name, inner_map))
                         }
                     }
                 } else {
-                    derr!((
+                    Err((
                         ty.loc,
                         "This vector type is not supported as parameter of a script function"
+                            .to_string(),
                     ))
                 }
             }
         }
     } else {
-        derr!((
+        Err((
             ty.loc,
-            "This type is not supported as parameter of script function"
+            "This type is not supported as parameter of script function".to_string(),
         ))
     }
 }
 
-pub fn get_ts_handler_for_vector_in_vector(inner_ty: &BaseType) -> TermResult {
-    if let Ok((builtin, inner_ty_args)) = extract_builtin_from_base_type(inner_ty) {
-        match builtin {
-            BuiltinTypeName_::U8 => Ok(format!("array => $.u8ArrayArg(array)")),
-            BuiltinTypeName_::Bool
-            | BuiltinTypeName_::Address
-            | BuiltinTypeName_::U64
-            | BuiltinTypeName_::U128 => {
-                Ok("array => array.map(ele => $.payloadArg(ele))".to_string())
+/// Name of a primitive parameter type for $.coercePayloadArg
+fn payload_arg_type(builtin: &BuiltinTypeName_) -> &'static str {
+    match builtin {
+        BuiltinTypeName_::Bool => "bool",
+        BuiltinTypeName_::Address => "address",
+        BuiltinTypeName_::U8 => "u8",
+        BuiltinTypeName_::U64 => "u64",
+        BuiltinTypeName_::U128 => "u128",
+        BuiltinTypeName_::Signer | BuiltinTypeName_::Vector => unreachable!(),
+    }
+}
+
+/// Kind tag of an entry function parameter in ALL_ENTRY_FUNCTIONS, e.g. `u64` or `vector-u8`.
+/// Fails like get_ts_handler_for_script_function_param on what a payload cannot carry
+fn payload_param_kind(ty: &SingleType) -> Result<String, Reason> {
+    fn base_kind(ty: &BaseType) -> Result<String, Reason> {
+        match extract_builtin_from_base_type(ty) {
+            Ok((BuiltinTypeName_::Vector, ty_args)) => {
+                Ok(format!("vector-{}", base_kind(&ty_args[0])?))
             }
-            BuiltinTypeName_::Signer => unreachable!(),
-            BuiltinTypeName_::Vector => {
-                assert!(inner_ty_args.len() == 1);
-                let inner_map = get_ts_handler_for_vector_in_vector(&inner_ty_args[0])?;
-                Ok(format!("array => array.map({})", inner_map))
+            Ok((BuiltinTypeName_::Signer, _)) => {
+                Err((ty.loc, SIGNER_PAYLOAD_ARG_REASON.to_string()))
             }
+            Ok((builtin, _)) => Ok(payload_arg_type(builtin).to_string()),
+            Err(_) => Err((
+                ty.loc,
+                "This type is not supported as parameter of script function".to_string(),
+            )),
         }
-    } else {
-        derr!((inner_ty.loc, "Unsupported vector-in-vector type"))
+    }
+    match &ty.value {
+        SingleType_::Base(base_ty) | SingleType_::Ref(_, base_ty) => base_kind(base_ty),
     }
 }
 
-pub fn is_base_type_signer(ty: &BaseType) -> bool {
-    match &ty.value {
-        BaseType_::Apply(_, typename, _) => match &typename.value {
-            TypeName_::Builtin(builtin) => {
-                return builtin.value == BuiltinTypeName_::Signer;
+/// Kind of a parameter in params_<fname>: primitives by their payload type, vector<u8> as
+/// u8vector and other vectors as vector, with the kind of their element as inner. Structs and type
+/// parameters, which buildPayload_ cannot take, are unsupported, and their parameters are always
+/// marked payloadEligible: false with the reason
+fn param_metadata_kind(ty: &BaseType) -> String {
+    match extract_builtin_from_base_type(ty) {
+        Ok((BuiltinTypeName_::Vector, ty_args)) => {
+            match extract_builtin_from_base_type(&ty_args[0]) {
+                Ok((BuiltinTypeName_::U8, _)) => "kind: \"u8vector\"".to_string(),
+                _ => format!(
+                    "kind: \"vector\", inner: {{ {} }}",
+                    param_metadata_kind(&ty_args[0])
+                ),
             }
-            _ => false,
+        }
+        Ok((BuiltinTypeName_::Signer, _)) => "kind: \"signer\"".to_string(),
+        Ok((builtin, _)) => format!("kind: {}", quote(&payload_arg_type(builtin))),
+        Err(_) => "kind: \"unsupported\"".to_string(),
+    }
+}
+
+/// typeParams_<fname> and params_<fname> of an entry function, for UIs and form builders. Every
+/// parameter is listed, classified like the payload handlers do: tsType is what buildPayload_
+/// takes, and parameters it cannot take are marked payloadEligible: false, with the reason and an
+/// unknown tsType
+fn write_param_metadata(
+    name: &FunctionName,
+    func: &Function,
+    w: &mut TsgenWriter,
+    c: &mut Context,
+) -> WriteResult {
+    let ts_name = c.local_function_ts_name(name);
+    w.writeln(format!(
+        "export const typeParams_{} = [{}] as const;",
+        ts_name,
+        func.signature
+            .type_parameters
+            .iter()
+            .map(|tp| quote(&tp.user_specified_name))
+            .join(", ")
+    ));
+    w.writeln(format!("export const params_{} = [", ts_name));
+    for (pname, ty) in func.signature.parameters.iter() {
+        let base_ty = match &ty.value {
+            SingleType_::Base(base_ty) | SingleType_::Ref(_, base_ty) => base_ty,
+        };
+        let is_signer = is_type_signer(ty);
+        let eligibility = if is_signer {
+            "tsType: \"HexString\", payloadEligible: true".to_string()
+        } else {
+            match get_ts_handler_for_script_function_param(pname, ty) {
+                Ok(_) => format!(
+                    "tsType: {}, payloadEligible: true",
+                    json_string(&payload_param_tstype(ty, c)?)
+                ),
+                Err((_, reason)) => format!(
+                    "tsType: \"unknown\", payloadEligible: false, reason: {}",
+                    json_string(&reason)
+                ),
+            }
+        };
+        w.writeln(format!(
+            "  {{ name: {}, {}, isSigner: {}, {} }},",
+            quote(pname),
+            param_metadata_kind(base_ty),
+            is_signer,
+            eligibility
+        ));
+    }
+    w.writeln("] as const;");
+    Ok(())
+}
+
+/// JSON Schema of an entry function parameter for --emit-json-schema, titled with its Move name.
+/// Whatever get_ts_handler_for_script_function_param rejects is rejected for the same reason, so
+/// that a schema exists exactly for the parameters buildPayload_ takes
+pub fn json_schema_for_script_function_param(
+    name: &Var,
+    ty: &SingleType,
+) -> Result<String, Reason> {
+    get_ts_handler_for_script_function_param(name, ty)?;
+    fn base_schema(ty: &BaseType) -> Result<Vec<String>, Reason> {
+        let decimal = |max: &str, builtin: &BuiltinTypeName_| {
+            vec![
+                "\"type\":\"string\"".to_string(),
+                format!("\"pattern\":{}", json_string(&decimal_range_pattern(max))),
+                format!("\"description\":{}", json_string(payload_arg_type(builtin))),
+            ]
+        };
+        match extract_builtin_from_base_type(ty) {
+            // the hex string $.u8ArrayArg turns the bytes into
+            Ok((BuiltinTypeName_::Vector, ty_args))
+                if matches!(
+                    extract_builtin_from_base_type(&ty_args[0]),
+                    Ok((BuiltinTypeName_::U8, _))
+                ) =>
+            {
+                Ok(vec![
+                    "\"type\":\"string\"".to_string(),
+                    "\"pattern\":\"^0x([0-9a-fA-F]{2})*$\"".to_string(),
+                    "\"description\":\"vector<u8>\"".to_string(),
+                ])
+            }
+            Ok((BuiltinTypeName_::Vector, ty_args)) => Ok(vec![
+                "\"type\":\"array\"".to_string(),
+                format!("\"items\":{{{}}}", base_schema(&ty_args[0])?.join(",")),
+            ]),
+            Ok((BuiltinTypeName_::Bool, _)) => Ok(vec!["\"type\":\"boolean\"".to_string()]),
+            Ok((BuiltinTypeName_::Address, _)) => Ok(vec![
+                "\"type\":\"string\"".to_string(),
+                "\"pattern\":\"^(0x)?[0-9a-fA-F]{1,64}$\"".to_string(),
+                "\"description\":\"address\"".to_string(),
+            ]),
+            Ok((builtin @ BuiltinTypeName_::U8, _)) => Ok(decimal(&u8::MAX.to_string(), builtin)),
+            Ok((builtin @ BuiltinTypeName_::U64, _)) => Ok(decimal(&u64::MAX.to_string(), builtin)),
+            Ok((builtin @ BuiltinTypeName_::U128, _)) => {
+                Ok(decimal(&u128::MAX.to_string(), builtin))
+            }
+            Ok((BuiltinTypeName_::Signer, _)) => {
+                Err((ty.loc, SIGNER_PAYLOAD_ARG_REASON.to_string()))
+            }
+            Err(_) => Err((
+                ty.loc,
+                "This type is not supported as parameter of script function".to_string(),
+            )),
+        }
+    }
+    let schema = match &ty.value {
+        SingleType_::Base(base_ty) | SingleType_::Ref(_, base_ty) => base_schema(base_ty)?,
+    };
+    Ok(format!(
+        "{{\"title\":{},{}}}",
+        json_string(&name.to_string()),
+        schema.join(",")
+    ))
+}
+
+/// Regex for the decimals from 0 to `max` without leading zeros, which bounds the integers a
+/// payload carries as strings: shorter numbers, then for each digit of `max` the numbers that share
+/// its prefix and are lower at that digit, and `max` itself
+fn decimal_range_pattern(max: &str) -> String {
+    let len = max.len();
+    let mut alternatives = vec!["0".to_string()];
+    if len > 1 {
+        alternatives.push(format!("[1-9][0-9]{{0,{}}}", len - 2));
+    }
+    for (i, digit) in max.bytes().enumerate() {
+        let lowest = if i == 0 { b'1' } else { b'0' };
+        if digit > lowest {
+            alternatives.push(format!(
+                "{}[{}-{}][0-9]{{{}}}",
+                &max[..i],
+                lowest as char,
+                (digit - 1) as char,
+                len - i - 1
+            ));
+        }
+    }
+    alternatives.push(max.to_string());
+    format!("^({})$", alternatives.join("|"))
+}
+
+/// Payload builders coerce primitive arguments at runtime, so their parameters also take the plain
+/// values a JS caller would pass. Byte vectors and nested vectors keep their strict types
+fn payload_param_tstype(ty: &SingleType, c: &mut Context) -> TermResult {
+    let primitive = |builtin: &BuiltinTypeName_| match builtin {
+        BuiltinTypeName_::Bool => "boolean",
+        BuiltinTypeName_::Address => "HexString | string",
+        BuiltinTypeName_::U8 => "U8 | number | bigint | string",
+        BuiltinTypeName_::U64 => "U64 | number | bigint | string",
+        BuiltinTypeName_::U128 => "U128 | number | bigint | string",
+        BuiltinTypeName_::Signer | BuiltinTypeName_::Vector => unreachable!(),
+    };
+    match extract_builtin_type(ty) {
+        Ok((BuiltinTypeName_::Vector, ty_args)) => match extract_builtin_from_base_type(&ty_args[0]) {
+            Ok((BuiltinTypeName_::U8, _))
+            | Ok((BuiltinTypeName_::Vector, _))
+            | Ok((BuiltinTypeName_::Signer, _))
+            | Err(_) => single_type_to_tstype(ty, c),
+            Ok((builtin, _)) => Ok(format!("({})[]", primitive(builtin))),
+        },
+        Ok((BuiltinTypeName_::Signer, _)) | Err(_) => single_type_to_tstype(ty, c),
+        Ok((builtin, _)) => Ok(primitive(builtin).to_string()),
+    }
+}
+
+/// Parameters of buildPayload_ and query_, which leave out signers
+pub fn write_payload_parameters(
+    sig: &FunctionSignature,
+    w: &mut TsgenWriter,
+    c: &mut Context,
+) -> WriteResult {
+    w.increase_indent();
+    for (name, ty) in sig.parameters.iter().filter(|(_, ty)| !is_type_signer(ty)) {
+        w.writeln(format!(
+            "{}: {},",
+            rename(name),
+            payload_param_tstype(ty, c)?
+        ));
+    }
+    w.decrease_indent();
+    Ok(())
+}
+
+/// Value shown for a parameter of type `ty` in the usage example. Structs and type params have no
+/// literal, so they get a placeholder that names their type, and strings an example string
+pub fn sample_value_for_base_type(ty: &BaseType) -> TermResult {
+    match &ty.value {
+        BaseType_::Param(tp) => {
+            return Ok(format!("/* {} */ ...", tp.user_specified_name));
+        }
+        BaseType_::Apply(_, typename, _) => {
+            if let TypeName_::ModuleType(mident, sname) = &typename.value {
+                return Ok(
+                    if mident.value.module.to_string() == "string" && sname.to_string() == "String"
+                    {
+                        "/* String */ \"example\"".to_string()
+                    } else {
+                        format!("/* {}::{} */ ...", mident.value.module, sname)
+                    },
+                );
+            }
+        }
+        _ => (),
+    }
+    if let Ok((builtin, ty_args)) = extract_builtin_from_base_type(ty) {
+        match builtin {
+            BuiltinTypeName_::Bool => Ok("true".to_string()),
+            BuiltinTypeName_::U8 => Ok("u8(\"1\")".to_string()),
+            BuiltinTypeName_::U64 => Ok("u64(\"1000000\")".to_string()),
+            BuiltinTypeName_::U128 => Ok("u128(\"1000000\")".to_string()),
+            BuiltinTypeName_::Address | BuiltinTypeName_::Signer => {
+                Ok("new HexString(\"0x1\")".to_string())
+            }
+            BuiltinTypeName_::Vector => {
+                assert!(ty_args.len() == 1);
+                match extract_builtin_from_base_type(&ty_args[0]) {
+                    // byte vectors are usually strings
+                    Ok((BuiltinTypeName_::U8, _)) => Ok("$.strToU8(\"example\")".to_string()),
+                    _ => Ok(format!("[{}]", sample_value_for_base_type(&ty_args[0])?)),
+                }
+            }
+        }
+    } else {
+        derr!((ty.loc, "Cannot construct sample value for this type"))
+    }
+}
+
+pub fn sample_value_for_single_type(ty: &SingleType) -> TermResult {
+    match &ty.value {
+        SingleType_::Base(base_ty) => sample_value_for_base_type(base_ty),
+        SingleType_::Ref(_, base_ty) => sample_value_for_base_type(base_ty),
+    }
+}
+
+pub fn write_usage_example(
+    name: &FunctionName,
+    func: &Function,
+    w: &mut TsgenWriter,
+    c: &mut Context,
+) -> WriteResult {
+    let type_args = func
+        .signature
+        .type_parameters
+        .iter()
+        .map(|_| "$.parseTypeTagOrThrow(\"0x1::aptos_coin::AptosCoin\")".to_string())
+        .collect::<Vec<_>>();
+    let mut payload_args = vec![];
+    let mut call_args = vec![];
+    for (_, ty) in func.signature.parameters.iter() {
+        let sample = sample_value_for_single_type(ty)?;
+        if !is_type_signer(ty) {
+            payload_args.push(sample.clone());
+        }
+        call_args.push(sample);
+    }
+    if !type_args.is_empty() {
+        payload_args.push(format!("[{}]", type_args.join(", ")));
+    }
+    call_args.push("$c".to_string());
+    if !type_args.is_empty() {
+        call_args.push(format!("[{}]", type_args.join(", ")));
+    }
+
+    w.writeln("// Example:");
+    w.writeln(format!(
+        "//   const payload = buildPayload_{}(",
+        c.local_function_ts_name(name)
+    ));
+    for arg in payload_args.iter() {
+        w.writeln(format!("//     {},", arg));
+    }
+    w.writeln("//   );");
+    w.writeln("//   await $.sendPayloadTx(client, account, payload);");
+    w.writeln("//   // or execute it locally against an AptosDataCache:");
+    w.writeln(format!(
+        "//   {}{}({});",
+        if c.is_async() { "await " } else { "" },
+        format_function_name(&c.local_function_ts_name(name), false),
+        call_args.join(", ")
+    ));
+    Ok(())
+}
+
+pub fn get_ts_handler_for_vector_in_vector(
+    inner_ty: &BaseType,
+    move_name: &str,
+) -> Result<String, Reason> {
+    if let Ok((builtin, inner_ty_args)) = extract_builtin_from_base_type(inner_ty) {
+        match builtin {
+            BuiltinTypeName_::U8 => Ok(format!("array => $.u8ArrayArg(array)")),
+            BuiltinTypeName_::Bool
+            | BuiltinTypeName_::Address
+            | BuiltinTypeName_::U64
+            | BuiltinTypeName_::U128 => Ok(format!(
+                "array => array.map(ele => $.coercePayloadArg(ele, {}, {}))",
+                quote(&payload_arg_type(builtin)),
+                quote(&move_name)
+            )),
+            BuiltinTypeName_::Signer => Err((inner_ty.loc, SIGNER_PAYLOAD_ARG_REASON.to_string())),
+            BuiltinTypeName_::Vector => {
+                assert!(inner_ty_args.len() == 1);
+                let inner_map = get_ts_handler_for_vector_in_vector(&inner_ty_args[0], move_name)?;
+                Ok(format!("array => array.map({})", inner_map))
+            }
+        }
+    } else {
+        Err((inner_ty.loc, "Unsupported vector-in-vector type".to_string()))
+    }
+}
+
+pub fn is_base_type_signer(ty: &BaseType) -> bool {
+    match &ty.value {
+        BaseType_::Apply(_, typename, _) => match &typename.value {
+            TypeName_::Builtin(builtin) => {
+                return builtin.value == BuiltinTypeName_::Signer;
+            }
+            _ => false,
         },
         _ => false,
     }
@@ -1195,7 +3408,11 @@ pub fn is_empty_block(block: &Block) -> bool {
     } else if block.len() == 1 {
         return match &block[0].value {
             Statement_::Command(cmd) => match &cmd.value {
-                Command_::IgnoreAndPop { pop_num: _, exp } => is_exp_unit(exp),
+                Command_::IgnoreAndPop { pop_num: _, exp } => {
+                    let mut effects = vec![];
+                    collect_side_effects(exp, &mut effects);
+                    effects.is_empty()
+                }
                 _ => false,
             },
             _ => false,
@@ -1257,41 +3474,150 @@ pub fn identify_declared_vars_in_stmt(stmt: &Statement, declared: &mut BTreeSet<
     };
 }
 
+/// Orders the hoisted declarations by name, and temporaries such as temp$2 and temp$10 by number
+fn hoisting_order(name: &str) -> (&str, usize, &str) {
+    let prefix = name.trim_end_matches(|ch: char| ch.is_ascii_digit());
+    let number = name[prefix.len()..].parse().unwrap_or(0);
+    (prefix, number, name)
+}
+
 pub fn identify_declared_vars_in_block(block: &Block, undeclared: &mut BTreeSet<String>) {
     for stmt in block.iter() {
         identify_declared_vars_in_stmt(stmt, undeclared);
     }
 }
 
+fn collect_unpacked_vars(lvalue: &LValue, vars: &mut BTreeSet<String>) {
+    match &lvalue.value {
+        LValue_::Var(var, _) => {
+            vars.insert(var.to_string());
+        }
+        LValue_::Unpack(_, _, fields) => {
+            for (_, lvalue) in fields.iter() {
+                collect_unpacked_vars(lvalue, vars);
+            }
+        }
+        LValue_::Ignore => (),
+    }
+}
+
+/// Variables bound by each unpack in `block`, one set per unpack. Those inside the pre-block of a
+/// while loop also go to `in_pre_blocks`
+fn collect_unpacks_in_block(
+    block: &Block,
+    in_pre_block: bool,
+    unpacks: &mut Vec<BTreeSet<String>>,
+    in_pre_blocks: &mut BTreeSet<String>,
+) {
+    use Statement_ as S;
+    for stmt in block.iter() {
+        match &stmt.value {
+            S::Command(cmd) => {
+                if let Command_::Assign(lvalues, _) = &cmd.value {
+                    for lvalue in lvalues.iter() {
+                        if let LValue_::Unpack(_, _, _) = &lvalue.value {
+                            let mut vars = BTreeSet::new();
+                            collect_unpacked_vars(lvalue, &mut vars);
+                            if in_pre_block {
+                                in_pre_blocks.extend(vars.iter().cloned());
+                            }
+                            unpacks.push(vars);
+                        }
+                    }
+                }
+            }
+            S::IfElse {
+                cond: _,
+                if_block,
+                else_block,
+            } => {
+                collect_unpacks_in_block(if_block, in_pre_block, unpacks, in_pre_blocks);
+                collect_unpacks_in_block(else_block, in_pre_block, unpacks, in_pre_blocks);
+            }
+            S::While { cond, block } => {
+                let (pre_block, _cond_exp) = cond;
+                collect_unpacks_in_block(pre_block, true, unpacks, in_pre_blocks);
+                collect_unpacks_in_block(block, in_pre_block, unpacks, in_pre_blocks);
+            }
+            S::Loop {
+                has_break: _,
+                block,
+            } => collect_unpacks_in_block(block, in_pre_block, unpacks, in_pre_blocks),
+        }
+    }
+}
+
+/// A while loop emits its pre-block and body as one block, with the condition checked in
+/// between, so what the pre-block unpacks must outlive it. Those variables are hoisted with the
+/// other locals and unpacked by assignment, and so is every other unpack sharing a variable with
+/// them, which would otherwise declare it a second time
+pub fn identify_unpack_assigned_vars(block: &Block) -> BTreeSet<String> {
+    let mut unpacks = vec![];
+    let mut assigned = BTreeSet::new();
+    collect_unpacks_in_block(block, false, &mut unpacks, &mut assigned);
+    loop {
+        let before = assigned.len();
+        for vars in unpacks.iter() {
+            if !vars.is_disjoint(&assigned) {
+                assigned.extend(vars.iter().cloned());
+            }
+        }
+        if assigned.len() == before {
+            return assigned;
+        }
+    }
+}
+
+/// `prelude` is written ahead of the body, i.e. the type argument check and the coverage counter.
+/// With a `trace`, the body runs in a try whose catch throws that
 pub fn write_func_body(
     block: &Block,
     new_vars: &Vec<Var>,
+    prelude: Vec<String>,
+    trace: Option<String>,
     w: &mut TsgenWriter,
     c: &mut Context,
 ) -> WriteResult {
     w.writeln("{");
     w.increase_indent();
+    for line in prelude {
+        w.writeln(line);
+    }
+    if trace.is_some() {
+        w.writeln("try {");
+        w.increase_indent();
+    }
 
     let mut declared_vars = BTreeSet::<String>::new();
     identify_declared_vars_in_block(block, &mut declared_vars);
+    declared_vars.extend(dead_locals(block));
+    c.unpack_assigned_vars = identify_unpack_assigned_vars(block);
+    declared_vars.retain(|var| !c.unpack_assigned_vars.contains(var));
 
-    let undeclared = new_vars
+    c.temp_var_names(new_vars);
+    let mut undeclared = new_vars
         .iter()
         .filter(|var| !declared_vars.contains(&var.to_string()))
+        .map(|var| c.var_name(var))
         .collect::<Vec<_>>();
+    // sorted here rather than taken in the order of the locals, so that the declarations are the
+    // same on every run and platform
+    undeclared.sort_by(|a, b| hoisting_order(a).cmp(&hoisting_order(b)));
+    undeclared.dedup();
 
     if undeclared.len() > 0 {
-        w.writeln(format!(
-            "let {};",
-            undeclared
-                .into_iter()
-                .map(|v| rename(&v.to_string()))
-                .join(", ")
-        ));
+        w.writeln(format!("let {};", undeclared.join(", ")));
     }
 
-    for stmt in block.iter() {
-        stmt.write_ts(w, c)?;
+    write_statements(block, w, c)?;
+    c.unpack_assigned_vars.clear();
+    c.temp_numbers.clear();
+
+    if let Some(trace) = trace {
+        w.decrease_indent();
+        w.writeln("} catch ($e) {");
+        w.writeln(format!("  {}", trace));
+        w.writeln("}");
     }
 
     w.decrease_indent();
@@ -1300,15 +3626,184 @@ pub fn write_func_body(
     Ok(())
 }
 
+/// Statements whose types the compiler found to be unreachable become a throw, which also ends
+/// the block since nothing after it runs. So does a loop that never falls through. Each statement
+/// is written to a fork first, so that one found unreachable half-way, e.g. in the condition of a
+/// while, leaves nothing of itself behind
+fn write_statements(block: &Block, w: &mut TsgenWriter, c: &mut Context) -> WriteResult {
+    for stmt in block.iter() {
+        let mut fork = w.fork();
+        match stmt.write_ts(&mut fork, c) {
+            Err(_) if c.reached_unreachable => {
+                c.reached_unreachable = false;
+                w.writeln("throw new Error(\"unreachable\");");
+                break;
+            }
+            res => res?,
+        }
+        w.commit(fork);
+        if is_endless_loop(stmt) {
+            break;
+        }
+    }
+    Ok(())
+}
+
+fn is_bool_literal(exp: &Exp, value: bool) -> bool {
+    match &exp.exp.value {
+        UnannotatedExp_::Value(v) => matches!(v.value, Value_::Bool(b) if b == value),
+        _ => false,
+    }
+}
+
+/// A `loop` without a break, or a while whose condition is the constant true and whose body does
+/// not break out of it, after which nothing runs
+fn is_endless_loop(stmt: &Statement) -> bool {
+    match &stmt.value {
+        Statement_::Loop {
+            has_break: breaks, ..
+        } => !breaks,
+        Statement_::While { cond, block } => {
+            cond.0.is_empty() && is_bool_literal(&cond.1, true) && !has_break(block)
+        }
+        _ => false,
+    }
+}
+
+// a break of the loop itself, like has_continue
+fn has_break(block: &Block) -> bool {
+    use Statement_ as S;
+    block.iter().any(|stmt| match &stmt.value {
+        S::Command(cmd) => matches!(cmd.value, Command_::Break),
+        S::IfElse {
+            cond: _,
+            if_block,
+            else_block,
+        } => has_break(if_block) || has_break(else_block),
+        S::While { .. } | S::Loop { .. } => false,
+    })
+}
+
+// whether a function is left from anywhere in `block`, including from nested loops
+fn has_return_or_abort(block: &Block) -> bool {
+    use Statement_ as S;
+    block.iter().any(|stmt| match &stmt.value {
+        S::Command(cmd) => matches!(cmd.value, Command_::Return { .. } | Command_::Abort(_)),
+        S::IfElse {
+            cond: _,
+            if_block,
+            else_block,
+        } => has_return_or_abort(if_block) || has_return_or_abort(else_block),
+        S::While { cond, block } => has_return_or_abort(&cond.0) || has_return_or_abort(block),
+        S::Loop { block, .. } => has_return_or_abort(block),
+    })
+}
+
+/// Endless loops that neither return nor abort either, at any depth of `block`, which hang
+/// whatever runs the function, e.g. a --gen-test suite, once they are reached
+fn collect_hanging_loops(block: &Block, locs: &mut Vec<Loc>) {
+    use Statement_ as S;
+    for stmt in block.iter() {
+        match &stmt.value {
+            S::Command(_) => (),
+            S::IfElse {
+                cond: _,
+                if_block,
+                else_block,
+            } => {
+                collect_hanging_loops(if_block, locs);
+                collect_hanging_loops(else_block, locs);
+            }
+            S::While { cond, block } => {
+                if is_endless_loop(stmt) && !has_return_or_abort(block) {
+                    locs.push(stmt.loc);
+                }
+                collect_hanging_loops(&cond.0, locs);
+                collect_hanging_loops(block, locs);
+            }
+            S::Loop { block, .. } => {
+                if is_endless_loop(stmt) && !has_return_or_abort(block) {
+                    locs.push(stmt.loc);
+                }
+                collect_hanging_loops(block, locs);
+            }
+        }
+        if is_endless_loop(stmt) {
+            break;
+        }
+    }
+}
+
+pub fn warn_hanging_loops(name: &FunctionName, block: &Block, c: &mut Context) {
+    let mut locs = vec![];
+    collect_hanging_loops(block, &mut locs);
+    for loc in locs {
+        c.add_warning(wdiag!((
+            loc,
+            format!(
+                "This loop in {} has no break, return or abort, so it never ends once reached",
+                name
+            )
+        )));
+    }
+}
+
+/// Splits the locals `block` assigns into those of statements that are written and those of
+/// statements after an endless loop, in `block` or any block nested in it, which are dropped
+fn collect_reachable_locals(
+    block: &Block,
+    live: &mut BTreeSet<String>,
+    dead: &mut BTreeSet<String>,
+) {
+    use Statement_ as S;
+    let mut reached_end = false;
+    for stmt in block.iter() {
+        let single = Block::from([stmt.clone()]);
+        if reached_end {
+            collect_mutated_locals(&single, dead);
+            continue;
+        }
+        match &stmt.value {
+            S::Command(_) => collect_mutated_locals(&single, live),
+            S::IfElse {
+                cond,
+                if_block,
+                else_block,
+            } => {
+                collect_mutated_locals_in_exp(cond, live);
+                collect_reachable_locals(if_block, live, dead);
+                collect_reachable_locals(else_block, live, dead);
+            }
+            S::While { cond, block } => {
+                let (pre_block, cond_exp) = cond;
+                collect_reachable_locals(pre_block, live, dead);
+                collect_mutated_locals_in_exp(cond_exp, live);
+                collect_reachable_locals(block, live, dead);
+            }
+            S::Loop {
+                has_break: _,
+                block,
+            } => collect_reachable_locals(block, live, dead),
+        }
+        reached_end = is_endless_loop(stmt);
+    }
+}
+
+/// Locals assigned only by statements after an endless loop, at any depth of the function body,
+/// which are never written, so they are not hoisted either
+fn dead_locals(block: &Block) -> BTreeSet<String> {
+    let (mut live_vars, mut dead_vars) = (BTreeSet::new(), BTreeSet::new());
+    collect_reachable_locals(block, &mut live_vars, &mut dead_vars);
+    dead_vars.difference(&live_vars).cloned().collect()
+}
+
 impl AstTsPrinter for Block {
     const CTOR_NAME: &'static str = "Block";
     fn write_ts(&self, w: &mut TsgenWriter, c: &mut Context) -> WriteResult {
         w.writeln("{");
         w.increase_indent();
 
-        for stmt in self.iter() {
-            stmt.write_ts(w, c)?;
-        }
+        write_statements(self, w, c)?;
 
         w.decrease_indent();
         w.writeln("}");
@@ -1317,6 +3812,395 @@ impl AstTsPrinter for Block {
     }
 }
 
+/// The else block of an else-if, which is an else block holding nothing but another IfElse
+fn as_else_if(else_block: &Block) -> Option<(&Exp, &Block, &Block)> {
+    if else_block.len() != 1 {
+        return None;
+    }
+    match &else_block[0].value {
+        Statement_::IfElse {
+            cond,
+            if_block,
+            else_block,
+        } => Some((cond, if_block, else_block)),
+        _ => None,
+    }
+}
+
+pub fn write_if_else(
+    cond: &Exp,
+    if_block: &Block,
+    else_block: &Block,
+    w: &mut TsgenWriter,
+    c: &mut Context,
+) -> WriteResult {
+    // branches always go through Block::write_ts, which braces even single statements, so an else
+    // can only attach to the if it was written for
+    let else_if = as_else_if(else_block);
+    if is_empty_block(if_block) && else_if.is_none() {
+        // if-block is empty, negate condition and output else block only
+        w.write(format!("if (!({})) ", cond.term(c)?));
+        return else_block.write_ts(w, c);
+    }
+    w.write(format!("if ({}) ", cond.term(c)?));
+    if_block.write_ts(w, c)?;
+    match else_if {
+        // flatten else-if chains instead of nesting each link in a block
+        Some((inner_cond, inner_if_block, inner_else_block)) => {
+            w.write("else ");
+            write_if_else(inner_cond, inner_if_block, inner_else_block, w, c)
+        }
+        None => {
+            if else_block.len() > 0 {
+                w.write("else ");
+                else_block.write_ts(w, c)?;
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Asserts guarded by a bool constant of the module, written `if (PARANOID) { assert!(...) }` or
+/// `assert!(PARANOID && cond, code)`: the constant, and the asserts, or the assert on the
+/// conjunction
+struct GuardedCheck<'a> {
+    guard: &'a ConstantName,
+    loc: Loc,
+    checks: GuardedAsserts<'a>,
+}
+
+enum GuardedAsserts<'a> {
+    Block(&'a Block),
+    Conjunction(&'a Exp, &'a Block, &'a Block),
+}
+
+/// `assert!(cond, code)`, which the compiler expands to an if with nothing but an abort in its else
+fn is_assert(if_block: &Block, else_block: &Block) -> bool {
+    let aborts = else_block.len() == 1
+        && match &else_block[0].value {
+            Statement_::Command(cmd) => matches!(cmd.value, Command_::Abort(_)),
+            _ => false,
+        };
+    is_empty_block(if_block) && aborts
+}
+
+/// The bool constant of the current module that `exp` reads, if any
+fn bool_constant_guard<'a>(exp: &'a Exp, c: &Context) -> Option<&'a ConstantName> {
+    let cname = match &exp.exp.value {
+        UnannotatedExp_::Constant(cname) => cname,
+        _ => return None,
+    };
+    let mident = c.current_module?;
+    let cdef = c.program.modules.get(&mident)?.constants.get(cname)?;
+    match &cdef.signature.value {
+        BaseType_::Apply(_, typename, _) => match &typename.value {
+            TypeName_::Builtin(builtin) if builtin.value == BuiltinTypeName_::Bool => Some(cname),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn as_guarded_check<'a>(
+    cond: &'a Exp,
+    if_block: &'a Block,
+    else_block: &'a Block,
+    c: &Context,
+) -> Option<GuardedCheck<'a>> {
+    if let Some(guard) = bool_constant_guard(cond, c) {
+        let all_asserts = if_block.iter().all(|stmt| match &stmt.value {
+            Statement_::IfElse {
+                if_block,
+                else_block,
+                ..
+            } => is_assert(if_block, else_block),
+            _ => false,
+        });
+        if !else_block.is_empty() || if_block.is_empty() || !all_asserts {
+            return None;
+        }
+        return Some(GuardedCheck {
+            guard,
+            loc: cond.exp.loc,
+            checks: GuardedAsserts::Block(if_block),
+        });
+    }
+    let lhs = match &cond.exp.value {
+        UnannotatedExp_::BinopExp(lhs, op, _) if op.value == BinOp_::And => lhs,
+        _ => return None,
+    };
+    let guard = bool_constant_guard(lhs, c)?;
+    if !is_assert(if_block, else_block) {
+        return None;
+    }
+    Some(GuardedCheck {
+        guard,
+        loc: cond.exp.loc,
+        checks: GuardedAsserts::Conjunction(cond, if_block, else_block),
+    })
+}
+
+/// Guarded asserts run as in Move unless `$.setInvariantChecks` switched their constant off, named
+/// <address>::<module>::<NAME>, and --production leaves them out. An if on the constant runs when
+/// the constant is true; an assert on a conjunction keeps its whole condition, so that it still
+/// aborts when the constant is false
+fn write_guarded_check(check: GuardedCheck, w: &mut TsgenWriter, c: &mut Context) -> WriteResult {
+    if c.config.production {
+        return Ok(());
+    }
+    let mident = c.current_module_or_err(check.loc, "write_guarded_check")?;
+    let constant = format!(
+        "{}::{}::{}",
+        concrete_address_hex(&mident, check.loc)?,
+        mident.value.module,
+        check.guard
+    );
+    let default = match check.checks {
+        GuardedAsserts::Block(_) => check.guard.term(c)?,
+        GuardedAsserts::Conjunction(..) => "true".to_string(),
+    };
+    w.write(format!(
+        "if ($.invariantChecks({}, {})) ",
+        quote(&constant),
+        default
+    ));
+    match check.checks {
+        GuardedAsserts::Block(block) => block.write_ts(w, c),
+        GuardedAsserts::Conjunction(cond, if_block, else_block) => {
+            w.writeln("{");
+            w.increase_indent();
+            write_if_else(cond, if_block, else_block, w, c)?;
+            w.decrease_indent();
+            w.writeln("}");
+            Ok(())
+        }
+    }
+}
+
+fn collect_mutated_locals_in_exp(exp: &Exp, mutated: &mut BTreeSet<String>) {
+    use UnannotatedExp_ as E;
+    match &exp.exp.value {
+        E::BorrowLocal(true, var) => {
+            mutated.insert(var.to_string());
+        }
+        E::ModuleCall(mcall) => collect_mutated_locals_in_exp(&mcall.arguments, mutated),
+        E::Builtin(_, e)
+        | E::Freeze(e)
+        | E::Vector(_, _, _, e)
+        | E::Dereference(e)
+        | E::UnaryExp(_, e)
+        | E::Borrow(_, e, _)
+        | E::Cast(e, _) => collect_mutated_locals_in_exp(e, mutated),
+        E::BinopExp(lhs, _, rhs) => {
+            collect_mutated_locals_in_exp(lhs, mutated);
+            collect_mutated_locals_in_exp(rhs, mutated);
+        }
+        E::Pack(_, _, fields) => fields
+            .iter()
+            .for_each(|(_, _, e)| collect_mutated_locals_in_exp(e, mutated)),
+        E::ExpList(items) => items.iter().for_each(|item| match item {
+            ExpListItem::Single(e, _) | ExpListItem::Splat(_, e, _) => {
+                collect_mutated_locals_in_exp(e, mutated)
+            }
+        }),
+        _ => (),
+    }
+}
+
+/// Locals that `block` assigns or borrows mutably, at any depth
+fn collect_mutated_locals(block: &Block, mutated: &mut BTreeSet<String>) {
+    use Statement_ as S;
+    for stmt in block.iter() {
+        match &stmt.value {
+            S::Command(cmd) => match &cmd.value {
+                Command_::Assign(lvalues, e) => {
+                    lvalues
+                        .iter()
+                        .for_each(|lvalue| collect_unpacked_vars(lvalue, mutated));
+                    collect_mutated_locals_in_exp(e, mutated);
+                }
+                Command_::Mutate(lhs, rhs) => {
+                    collect_mutated_locals_in_exp(lhs, mutated);
+                    collect_mutated_locals_in_exp(rhs, mutated);
+                }
+                Command_::Abort(e)
+                | Command_::Return { exp: e, .. }
+                | Command_::IgnoreAndPop { pop_num: _, exp: e } => {
+                    collect_mutated_locals_in_exp(e, mutated)
+                }
+                _ => (),
+            },
+            S::IfElse {
+                cond,
+                if_block,
+                else_block,
+            } => {
+                collect_mutated_locals_in_exp(cond, mutated);
+                collect_mutated_locals(if_block, mutated);
+                collect_mutated_locals(else_block, mutated);
+            }
+            S::While { cond, block } => {
+                let (pre_block, cond_exp) = cond;
+                collect_mutated_locals(pre_block, mutated);
+                collect_mutated_locals_in_exp(cond_exp, mutated);
+                collect_mutated_locals(block, mutated);
+            }
+            S::Loop {
+                has_break: _,
+                block,
+            } => collect_mutated_locals(block, mutated),
+        }
+    }
+}
+
+// a continue of the loop itself, which would skip the increment of a while loop but not of a for
+fn has_continue(block: &Block) -> bool {
+    use Statement_ as S;
+    block.iter().any(|stmt| match &stmt.value {
+        S::Command(cmd) => matches!(cmd.value, Command_::Continue),
+        S::IfElse {
+            cond: _,
+            if_block,
+            else_block,
+        } => has_continue(if_block) || has_continue(else_block),
+        S::While { .. } | S::Loop { .. } => false,
+    })
+}
+
+fn is_mut_ref(exp: &Exp) -> bool {
+    match &exp.ty.value {
+        Type_::Single(single) => matches!(single.value, SingleType_::Ref(true, _)),
+        _ => false,
+    }
+}
+
+/// Whether `exp` has the same value in every iteration of a loop that changes `mutated`: it
+/// reads only other locals, through immutable references, and calls nothing but vector::length
+fn is_loop_invariant(exp: &Exp, mutated: &BTreeSet<String>) -> bool {
+    use UnannotatedExp_ as E;
+    match &exp.exp.value {
+        E::Value(_) | E::Constant(_) => true,
+        E::Copy { var, .. } | E::Move { var, .. } => {
+            !is_mut_ref(exp) && !mutated.contains(&var.to_string())
+        }
+        E::BorrowLocal(false, var) => !mutated.contains(&var.to_string()),
+        E::Freeze(e) | E::Dereference(e) | E::UnaryExp(_, e) | E::Cast(e, _) => {
+            is_loop_invariant(e, mutated)
+        }
+        E::Borrow(false, e, _) => is_loop_invariant(e, mutated),
+        E::BinopExp(lhs, _, rhs) => {
+            is_loop_invariant(lhs, mutated) && is_loop_invariant(rhs, mutated)
+        }
+        E::ModuleCall(mcall) => {
+            format_address_hex(mcall.module.value.address) == "0x1"
+                && mcall.module.value.module.to_string() == "vector"
+                && mcall.name.to_string() == "length"
+                && is_loop_invariant(&mcall.arguments, mutated)
+        }
+        _ => false,
+    }
+}
+
+fn is_increment_of(stmt: &Statement, counter: &Var) -> bool {
+    use UnannotatedExp_ as E;
+    let (lvalues, rhs) = match &stmt.value {
+        Statement_::Command(cmd) => match &cmd.value {
+            Command_::Assign(lvalues, rhs) => (lvalues, rhs),
+            _ => return false,
+        },
+        _ => return false,
+    };
+    let assigns_counter = match lvalues.as_slice() {
+        [lvalue] => matches!(&lvalue.value, LValue_::Var(var, _) if var == counter),
+        _ => false,
+    };
+    let adds_one = match &rhs.exp.value {
+        E::BinopExp(lhs, op, one) if op.value == BinOp_::Add => {
+            let reads_counter = match &lhs.exp.value {
+                E::Copy { var, .. } | E::Move { var, .. } => var == counter,
+                _ => false,
+            };
+            let is_one = matches!(
+                &one.exp.value,
+                E::Value(v) if matches!(v.value, Value_::U8(1) | Value_::U64(1) | Value_::U128(1))
+            );
+            reads_counter && is_one
+        }
+        _ => false,
+    };
+    assigns_counter && adds_one
+}
+
+/// The lhs and rhs of the condition of a while loop that counts a local up by one until a bound
+/// that does not change in the loop, e.g. `while (i < n) { ..; i = i + 1 }`. Its pre-block may
+/// only compute that bound
+fn as_range_loop<'a>(cond: &'a (Block, Box<Exp>), block: &Block) -> Option<(&'a Exp, &'a Exp)> {
+    use UnannotatedExp_ as E;
+    let (pre_block, cond_exp) = cond;
+    let (lhs, rhs) = match &cond_exp.exp.value {
+        E::BinopExp(lhs, op, rhs) if op.value == BinOp_::Lt => (lhs, rhs),
+        _ => return None,
+    };
+    let counter = match &lhs.exp.value {
+        E::Copy { var, .. } | E::Move { var, .. } => var,
+        _ => return None,
+    };
+    let mut body = block.clone();
+    let increment = body.pop_back()?;
+    if !is_increment_of(&increment, counter) || has_continue(block) {
+        return None;
+    }
+    // the increment is the only change to the counter
+    let mut mutated = BTreeSet::new();
+    collect_mutated_locals(&body, &mut mutated);
+    if !mutated.insert(counter.to_string()) {
+        return None;
+    }
+    let pre_block_is_invariant = pre_block.iter().all(|stmt| match &stmt.value {
+        Statement_::Command(cmd) => match &cmd.value {
+            Command_::Assign(lvalues, e) => match lvalues.as_slice() {
+                [lvalue] => {
+                    matches!(lvalue.value, LValue_::Var(_, _)) && is_loop_invariant(e, &mutated)
+                }
+                _ => false,
+            },
+            _ => false,
+        },
+        _ => false,
+    });
+    if pre_block_is_invariant && is_loop_invariant(rhs, &mutated) {
+        Some((lhs, rhs))
+    } else {
+        None
+    }
+}
+
+/// A range loop as a for loop, whose pre-block runs and whose bound is evaluated once
+fn write_range_loop(
+    pre_block: &Block,
+    (lhs, rhs): (&Exp, &Exp),
+    block: &Block,
+    w: &mut TsgenWriter,
+    c: &mut Context,
+) -> WriteResult {
+    write_statements(pre_block, w, c)?;
+    let mut body = block.clone();
+    let increment = match body.pop_back() {
+        Some(increment) => increment,
+        None => return derr!((rhs.exp.loc, "Range loop without an increment")),
+    };
+    let mut buffer = TsgenWriter::new();
+    increment.write_ts(&mut buffer, c)?;
+    let increment = buffer.into_string();
+    w.write(format!(
+        "for (const $stop = {}; ({}).lt($stop); {}) ",
+        rhs.term(c)?,
+        lhs.term(c)?,
+        increment.trim().trim_end_matches(';')
+    ));
+    body.write_ts(w, c)
+}
+
 impl AstTsPrinter for Statement {
     const CTOR_NAME: &'static str = "Statement";
     fn write_ts(&self, w: &mut TsgenWriter, c: &mut Context) -> WriteResult {
@@ -1329,25 +4213,19 @@ impl AstTsPrinter for Statement {
                 cond,
                 if_block,
                 else_block,
-            } => {
-                // FIXME in case it's a single statement, need indentation here
-                if !is_empty_block(if_block) {
-                    // if-block is non-empty
-                    w.write(format!("if ({}) ", cond.term(c)?));
-                    if_block.write_ts(w, c)?;
-                    if else_block.len() > 0 {
-                        w.write("else");
-                        else_block.write_ts(w, c)?;
-                    }
-                } else {
-                    // if-block is empty, negate condition and output else block only
-                    w.write(format!("if (!{}) ", cond.term(c)?));
-                    else_block.write_ts(w, c)?;
-                }
-                Ok(())
-            }
+            } => match as_guarded_check(cond, if_block, else_block, c) {
+                Some(check) => write_guarded_check(check, w, c),
+                None => write_if_else(cond, if_block, else_block, w, c),
+            },
             S::While { cond, block } => {
                 let (pre_block, cond_exp) = cond;
+                // the pre-block still runs once, to find that the loop is not entered
+                if is_bool_literal(cond_exp, false) {
+                    return write_statements(pre_block, w, c);
+                }
+                if let Some(range) = as_range_loop(cond, block) {
+                    return write_range_loop(&cond.0, range, block, w, c);
+                }
                 // FIXME need to handle the empty case
                 let has_pre_block = pre_block.len() > 0;
                 w.write(format!(
@@ -1359,12 +4237,12 @@ impl AstTsPrinter for Statement {
                     }
                 ));
                 w.short_block(|w| {
+                    // pre-block and body share one scope, see identify_unpack_assigned_vars
                     if has_pre_block {
-                        pre_block.write_ts(w, c)?;
+                        write_statements(pre_block, w, c)?;
                         w.writeln(format!("if (!({})) break;", cond_exp.term(c)?));
                     }
-                    block.write_ts(w, c)?;
-                    Ok(())
+                    write_statements(block, w, c)
                 })?;
                 Ok(())
             }
@@ -1379,8 +4257,72 @@ impl AstTsPrinter for Statement {
     }
 }
 
+/// Unit, or a tuple of nothing but units, neither of which has a value in TS
 pub fn is_exp_unit(exp: &Exp) -> bool {
-    matches!(exp.exp.value, UnannotatedExp_::Unit { case: _ })
+    match &exp.exp.value {
+        UnannotatedExp_::Unit { case: _ } => true,
+        UnannotatedExp_::ExpList(items) => items.iter().all(is_unit_item),
+        _ => false,
+    }
+}
+
+pub fn int_width(builtin: &BuiltinTypeName_) -> Option<u32> {
+    match builtin {
+        BuiltinTypeName_::U8 => Some(8),
+        BuiltinTypeName_::U64 => Some(64),
+        BuiltinTypeName_::U128 => Some(128),
+        _ => None,
+    }
+}
+
+// casts to a type at least as wide as the source cannot abort
+fn is_widening_cast(e: &Exp, to: &BuiltinTypeName_) -> bool {
+    let from = match &e.ty.value {
+        Type_::Single(single) => match &single.value {
+            SingleType_::Base(base) => extract_builtin_from_base_type(base).ok(),
+            SingleType_::Ref(_, _) => None,
+        },
+        _ => None,
+    };
+    match (from.and_then(|(from, _)| int_width(from)), int_width(to)) {
+        (Some(from), Some(to)) => from <= to,
+        _ => false,
+    }
+}
+
+/// The parts of an ignored expression that still have to run: calls, and anything that may abort.
+/// Wrappers that only pass a value along (freeze, borrows, dereferences, widening casts) are
+/// stripped, and values without side effects are dropped entirely
+pub fn collect_side_effects<'a>(exp: &'a Exp, effects: &mut Vec<&'a Exp>) {
+    use UnannotatedExp_ as E;
+    match &exp.exp.value {
+        E::Unit { case: _ }
+        | E::Value(_)
+        | E::Move { .. }
+        | E::Copy { .. }
+        | E::Constant(_)
+        | E::BorrowLocal(_, _)
+        | E::Spec(_, _) => (),
+        E::Freeze(e) | E::Borrow(_, e, _) | E::Dereference(e) | E::UnaryExp(_, e) => {
+            collect_side_effects(e, effects)
+        }
+        E::Cast(e, ty) if is_widening_cast(e, &ty.value) => collect_side_effects(e, effects),
+        E::ExpList(items) => items.iter().for_each(|item| match item {
+            ExpListItem::Single(e, _) | ExpListItem::Splat(_, e, _) => {
+                collect_side_effects(e, effects)
+            }
+        }),
+        E::Vector(_, _, _, e) => collect_side_effects(e, effects),
+        E::Pack(_, _, fields) => fields
+            .iter()
+            .for_each(|(_, _, e)| collect_side_effects(e, effects)),
+        E::ModuleCall(mcall)
+            if discipline_only_native(&mcall.module, &mcall.name) == Some(false) =>
+        {
+            collect_side_effects(&mcall.arguments, effects)
+        }
+        _ => effects.push(exp),
+    }
 }
 
 impl AstTsPrinter for Command {
@@ -1391,17 +4333,32 @@ impl AstTsPrinter for Command {
         match &self.value {
             C::Assign(lvalues, rhs) => {
                 if is_empty_lvalue_list(lvalues) {
-                    w.writeln(format!("{};", rhs.term(c)?));
+                    // nothing to run for `() = ()`
+                    if !is_exp_unit(rhs) {
+                        w.writeln(format!("{};", rhs.term(c)?));
+                    }
                 } else {
-                    if lvalues.len() == 1 && matches!(lvalues[0].value, LValue_::Unpack(_, _, _)) {
+                    let is_unpack =
+                        lvalues.len() == 1 && matches!(lvalues[0].value, LValue_::Unpack(_, _, _));
+                    let mut unpacked = BTreeSet::new();
+                    if is_unpack {
+                        collect_unpacked_vars(&lvalues[0], &mut unpacked);
+                    }
+                    // a destructuring assignment needs parentheses to not be read as a block
+                    let as_assignment = is_unpack && !unpacked.is_disjoint(&c.unpack_assigned_vars);
+                    if as_assignment {
+                        w.write("(");
+                    } else if is_unpack {
                         w.write("let ");
                     }
                     // using write_ts instead of term to allow prettier printing in case we ever
                     // want to do that
+                    // the rhs goes first so that nothing is written for a statement that fails
+                    let rhs = rhs.term(c)?;
                     lvalues.write_ts(w, c)?;
                     w.write(" = ");
-                    w.write(rhs.term(c)?);
-                    w.writeln(";");
+                    w.write(rhs);
+                    w.writeln(if as_assignment { ");" } else { ";" });
                 }
             }
             C::Mutate(lhs, rhs) => match &lhs.exp.value {
@@ -1416,7 +4373,10 @@ impl AstTsPrinter for Command {
                     w.writeln(format!("$.set({}, {});", lhs.term(c)?, rhs.term(c)?));
                 }
             },
-            C::Abort(e) => w.writeln(format!("throw $.abortCode({});", e.term(c)?)),
+            C::Abort(e) => match abort_error_class(e, c)? {
+                Some(class_name) => w.writeln(format!("throw new {}();", class_name)),
+                None => w.writeln(format!("throw $.abortCode({});", e.term(c)?)),
+            },
             C::Return { from_user: _, exp } => {
                 if is_exp_unit(exp) {
                     w.writeln("return;");
@@ -1427,11 +4387,10 @@ impl AstTsPrinter for Command {
             C::Break => w.writeln("break;"),
             C::Continue => w.writeln("continue;"),
             C::IgnoreAndPop { pop_num: _, exp } => {
-                if is_exp_unit(exp) {
-                    // do nothing..
-                    // w.writeln("/*PopAndIgnore*/");
-                } else {
-                    w.writeln(format!("{};", exp.term(c)?));
+                let mut effects = vec![];
+                collect_side_effects(exp, &mut effects);
+                for effect in effects {
+                    w.writeln(format!("{};", effect.term(c)?));
                 }
             }
             _ => {
@@ -0,0 +1,282 @@
+use crate::shared::{ImportStyle, Layout, MoveToTsOptions, Naming};
+use clap::{ArgEnum, ArgMatches};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+pub const CONFIG_FILE_NAME: &str = "move-to-ts.toml";
+
+/*
+Contents of move-to-ts.toml, which sits next to Move.toml. Keys are the long names of the matching
+command line flags, and a flag given on the command line always wins over the file.
+ */
+#[derive(Deserialize, Serialize, Default)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub struct ConfigFile {
+    pub output_path: Option<PathBuf>,
+    pub output_subdir: Option<PathBuf>,
+    pub gen_test: Option<bool>,
+    pub gen_cli: Option<bool>,
+    pub gen_ui: Option<bool>,
+    pub asynchronous: Option<bool>,
+    pub package_json_name: Option<String>,
+    pub emit_examples: Option<bool>,
+    pub strict_natives: Option<bool>,
+    pub naming: Option<String>,
+    pub external_package_import_style: Option<String>,
+    pub fine_grained_imports: Option<bool>,
+    pub layout: Option<String>,
+    pub hide_internal: Option<bool>,
+    pub deny_warnings: Option<bool>,
+    pub prune: Option<bool>,
+    pub managed_output: Option<bool>,
+    pub coin_registry: Option<String>,
+    pub production: Option<bool>,
+    pub runtime_checks: Option<bool>,
+    pub payload_only: Option<bool>,
+    pub keep_going: Option<bool>,
+    pub no_runtime_checks: Option<bool>,
+    pub no_math_inline: Option<bool>,
+    pub framework_extras: Option<bool>,
+    pub test_coverage: Option<bool>,
+    pub gen_difftest: Option<bool>,
+    pub emit_json_schema: Option<bool>,
+    pub emit_provenance: Option<bool>,
+    pub strict_construction: Option<bool>,
+    pub compact_structs: Option<bool>,
+    pub trace: Option<bool>,
+    pub address_alias: Option<Vec<String>>,
+    pub skip_function: Option<Vec<String>>,
+    pub exclude_module: Option<Vec<String>>,
+    pub extra_cmd: Option<Vec<String>>,
+    pub profile: Option<bool>,
+    pub profile_top: Option<usize>,
+    pub profile_json: Option<PathBuf>,
+    pub emit_depgraph: Option<PathBuf>,
+    pub emit_errmap: Option<PathBuf>,
+}
+
+/// Reads move-to-ts.toml from the package root, if there is one
+pub fn load_config_file(root_path: &Path) -> Result<Option<ConfigFile>, String> {
+    let path = root_path.join(CONFIG_FILE_NAME);
+    if !path.exists() {
+        return Ok(None);
+    }
+    let content = std::fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read {}: {}", path.to_string_lossy(), e))?;
+    toml::from_str(&content)
+        .map(Some)
+        .map_err(|e| format!("Invalid {}: {}", CONFIG_FILE_NAME, e))
+}
+
+fn parse_arg_enum<T: ArgEnum>(key: &str, value: &str) -> Result<T, String> {
+    T::from_str(value, false).map_err(|_| {
+        let possible = T::value_variants()
+            .iter()
+            .filter_map(|v| v.to_possible_value())
+            .map(|v| v.get_name().to_string())
+            .collect::<Vec<_>>();
+        format!(
+            "Invalid {} in {}: {}, expected one of {}",
+            key,
+            CONFIG_FILE_NAME,
+            value,
+            possible.join(", ")
+        )
+    })
+}
+
+fn arg_enum_name<T: ArgEnum>(value: &T) -> String {
+    value.to_possible_value().unwrap().get_name().to_string()
+}
+
+/// Applies the settings of `file` that were not explicitly given on the command line. `id` is
+/// the clap id of each option, i.e. the field name in MoveToTsOptions
+pub fn merge_config_file(
+    options: &mut MoveToTsOptions,
+    file: ConfigFile,
+    matches: &ArgMatches,
+) -> Result<(), String> {
+    let unset = |id: &str| matches.occurrences_of(id) == 0;
+
+    if let (Some(v), true) = (file.output_path, unset("output_path")) {
+        options.output_path = v;
+    }
+    if let (Some(v), true) = (file.output_subdir, unset("output_subdir")) {
+        options.output_subdir = v;
+    }
+    if let (Some(v), true) = (file.gen_test, unset("test")) {
+        options.test = v;
+    }
+    if let (Some(v), true) = (file.gen_cli, unset("cli")) {
+        options.cli = v;
+    }
+    if let (Some(v), true) = (file.gen_ui, unset("ui")) {
+        options.ui = v;
+    }
+    if let (Some(v), true) = (file.asynchronous, unset("asynchronous")) {
+        options.asynchronous = v;
+    }
+    if let (Some(v), true) = (file.package_json_name, unset("package_json_name")) {
+        options.package_json_name = v;
+    }
+    if let (Some(v), true) = (file.emit_examples, unset("emit_examples")) {
+        options.emit_examples = v;
+    }
+    if let (Some(v), true) = (file.strict_natives, unset("strict_natives")) {
+        options.strict_natives = v;
+    }
+    if let (Some(v), true) = (file.naming, unset("naming")) {
+        options.naming = parse_arg_enum::<Naming>("naming", &v)?;
+    }
+    if let (Some(v), true) = (
+        file.external_package_import_style,
+        unset("external_package_import_style"),
+    ) {
+        options.external_package_import_style = ImportStyle::from_str(&v).map_err(|e| {
+            format!(
+                "Invalid external-package-import-style in {}: {}",
+                CONFIG_FILE_NAME, e
+            )
+        })?;
+    }
+    if let (Some(v), true) = (file.fine_grained_imports, unset("fine_grained_imports")) {
+        options.fine_grained_imports = v;
+    }
+    if let (Some(v), true) = (file.layout, unset("layout")) {
+        options.layout = parse_arg_enum::<Layout>("layout", &v)?;
+    }
+    if let (Some(v), true) = (file.hide_internal, unset("hide_internal")) {
+        options.hide_internal = v;
+    }
+    if let (Some(v), true) = (file.deny_warnings, unset("deny_warnings")) {
+        options.deny_warnings = v;
+    }
+    if let (Some(v), true) = (file.prune, unset("prune")) {
+        options.prune = v;
+    }
+    if let (Some(v), true) = (file.managed_output, unset("managed_output")) {
+        options.managed_output = v;
+    }
+    if let (Some(v), true) = (file.coin_registry, unset("coin_registry")) {
+        options.coin_registry = Some(v);
+    }
+    if let (Some(v), true) = (file.production, unset("production")) {
+        options.production = v;
+    }
+    if let (Some(v), true) = (file.runtime_checks, unset("runtime_checks")) {
+        options.runtime_checks = v;
+    }
+    if let (Some(v), true) = (file.payload_only, unset("payload_only")) {
+        options.payload_only = v;
+    }
+    if let (Some(v), true) = (file.keep_going, unset("keep_going")) {
+        options.keep_going = v;
+    }
+    if let (Some(v), true) = (file.no_runtime_checks, unset("no_runtime_checks")) {
+        options.no_runtime_checks = v;
+    }
+    if let (Some(v), true) = (file.no_math_inline, unset("no_math_inline")) {
+        options.no_math_inline = v;
+    }
+    if let (Some(v), true) = (file.framework_extras, unset("framework_extras")) {
+        options.framework_extras = v;
+    }
+    if let (Some(v), true) = (file.test_coverage, unset("test_coverage")) {
+        options.test_coverage = v;
+    }
+    if let (Some(v), true) = (file.gen_difftest, unset("gen_difftest")) {
+        options.gen_difftest = v;
+    }
+    if let (Some(v), true) = (file.emit_json_schema, unset("emit_json_schema")) {
+        options.emit_json_schema = v;
+    }
+    if let (Some(v), true) = (file.emit_provenance, unset("emit_provenance")) {
+        options.emit_provenance = v;
+    }
+    if let (Some(v), true) = (file.strict_construction, unset("strict_construction")) {
+        options.strict_construction = v;
+    }
+    if let (Some(v), true) = (file.compact_structs, unset("compact_structs")) {
+        options.compact_structs = v;
+    }
+    if let (Some(v), true) = (file.trace, unset("trace")) {
+        options.trace = v;
+    }
+    if let (Some(v), true) = (file.address_alias, unset("address_alias")) {
+        options.address_alias = v;
+    }
+    if let (Some(v), true) = (file.skip_function, unset("skip_function")) {
+        options.skip_function = v;
+    }
+    if let (Some(v), true) = (file.exclude_module, unset("exclude_module")) {
+        options.exclude_module = v;
+    }
+    if let (Some(v), true) = (file.extra_cmd, unset("extra_cmd")) {
+        options.extra_cmd = v;
+    }
+    if let (Some(v), true) = (file.profile, unset("profile")) {
+        options.profile = v;
+    }
+    if let (Some(v), true) = (file.profile_top, unset("profile_top")) {
+        options.profile_top = v;
+    }
+    if let (Some(v), true) = (file.profile_json, unset("profile_json")) {
+        options.profile_json = Some(v);
+    }
+    if let (Some(v), true) = (file.emit_depgraph, unset("emit_depgraph")) {
+        options.emit_depgraph = Some(v);
+    }
+    if let (Some(v), true) = (file.emit_errmap, unset("emit_errmap")) {
+        options.emit_errmap = Some(v);
+    }
+    Ok(())
+}
+
+/// The effective configuration in move-to-ts.toml format, for --print-config
+pub fn format_config(options: &MoveToTsOptions) -> String {
+    let config = ConfigFile {
+        output_path: Some(options.output_path.clone()),
+        output_subdir: Some(options.output_subdir.clone()),
+        gen_test: Some(options.test),
+        gen_cli: Some(options.cli),
+        gen_ui: Some(options.ui),
+        asynchronous: Some(options.asynchronous),
+        package_json_name: Some(options.package_json_name.clone()),
+        emit_examples: Some(options.emit_examples),
+        strict_natives: Some(options.strict_natives),
+        naming: Some(arg_enum_name(&options.naming)),
+        external_package_import_style: Some(options.external_package_import_style.to_string()),
+        fine_grained_imports: Some(options.fine_grained_imports),
+        layout: Some(arg_enum_name(&options.layout)),
+        hide_internal: Some(options.hide_internal),
+        deny_warnings: Some(options.deny_warnings),
+        prune: Some(options.prune),
+        managed_output: Some(options.managed_output),
+        coin_registry: options.coin_registry.clone(),
+        production: Some(options.production),
+        runtime_checks: Some(options.runtime_checks),
+        payload_only: Some(options.payload_only),
+        keep_going: Some(options.keep_going),
+        no_runtime_checks: Some(options.no_runtime_checks),
+        no_math_inline: Some(options.no_math_inline),
+        framework_extras: Some(options.framework_extras),
+        test_coverage: Some(options.test_coverage),
+        gen_difftest: Some(options.gen_difftest),
+        emit_json_schema: Some(options.emit_json_schema),
+        emit_provenance: Some(options.emit_provenance),
+        strict_construction: Some(options.strict_construction),
+        compact_structs: Some(options.compact_structs),
+        trace: Some(options.trace),
+        address_alias: Some(options.address_alias.clone()),
+        skip_function: Some(options.skip_function.clone()),
+        exclude_module: Some(options.exclude_module.clone()),
+        extra_cmd: Some(options.extra_cmd.clone()),
+        profile: Some(options.profile),
+        profile_top: Some(options.profile_top),
+        profile_json: options.profile_json.clone(),
+        emit_depgraph: options.emit_depgraph.clone(),
+        emit_errmap: options.emit_errmap.clone(),
+    };
+    toml::to_string(&config).expect("Failed to format configuration")
+}
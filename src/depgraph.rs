@@ -0,0 +1,115 @@
+use crate::inspect::json_string;
+use crate::shared::*;
+use move_compiler::expansion::ast::ModuleIdent;
+
+/*
+The modules each translated module refers to, as recorded by format_qualified_name while the
+modules were translated. Nodes are <address>::<module> with the address in hex; an edge is
+same-package when both ends are emitted under the same package, and its target is a source module
+when it belongs to the translated package rather than one of its dependencies. Written as DOT and
+as JSON with --emit-depgraph.
+ */
+
+struct Edge {
+    from: String,
+    to: String,
+    same_package: bool,
+    target_is_source: bool,
+}
+
+pub(crate) fn module_id(mident: &ModuleIdent) -> String {
+    let address = match format_address_hex(mident.value.address) {
+        hex if hex.is_empty() => format_address(mident.value.address),
+        hex => hex,
+    };
+    format!("{}::{}", address, mident.value.module)
+}
+
+fn is_source_module(mident: &ModuleIdent, c: &Context) -> bool {
+    c.program
+        .modules
+        .get(mident)
+        .map_or(false, |mdef| mdef.is_source_module)
+}
+
+fn edges(c: &Context) -> Vec<Edge> {
+    c.module_dependencies
+        .iter()
+        .flat_map(|(from, targets)| {
+            targets.iter().map(move |to| Edge {
+                from: module_id(from),
+                to: module_id(to),
+                same_package: c.package_name(from) == c.package_name(to),
+                target_is_source: is_source_module(to, c),
+            })
+        })
+        .collect()
+}
+
+fn edge_kind(edge: &Edge) -> &'static str {
+    if edge.same_package {
+        "same-package"
+    } else {
+        "cross-package"
+    }
+}
+
+pub fn format_depgraph_dot(c: &Context) -> String {
+    let mut lines = vec!["digraph modules {".to_string()];
+    for mident in c.visited_modules.iter() {
+        lines.push(format!(
+            "  {} [package={}, source={}];",
+            quote(&module_id(mident)),
+            quote(&c.package_name(mident)),
+            is_source_module(mident, c)
+        ));
+    }
+    for edge in edges(c) {
+        lines.push(format!(
+            "  {} -> {} [kind={}, target_source={}{}];",
+            quote(&edge.from),
+            quote(&edge.to),
+            quote(&edge_kind(&edge)),
+            edge.target_is_source,
+            if edge.same_package {
+                ""
+            } else {
+                ", style=dashed"
+            }
+        ));
+    }
+    lines.push("}".to_string());
+    format!("{}\n", lines.join("\n"))
+}
+
+pub fn format_depgraph_json(c: &Context) -> String {
+    let modules = c
+        .visited_modules
+        .iter()
+        .map(|mident| {
+            format!(
+                "{{\"id\":{},\"package\":{},\"source\":{}}}",
+                json_string(&module_id(mident)),
+                json_string(&c.package_name(mident)),
+                is_source_module(mident, c)
+            )
+        })
+        .collect::<Vec<_>>();
+    let edges = edges(c)
+        .iter()
+        .map(|edge| {
+            format!(
+                "{{\"from\":{},\"to\":{},\"kind\":{},\"targetIsSource\":{}}}",
+                json_string(&edge.from),
+                json_string(&edge.to),
+                json_string(edge_kind(edge)),
+                edge.target_is_source
+            )
+        })
+        .collect::<Vec<_>>();
+    format!(
+        "{{\"modules\":[{}],\"edges\":[{}]}}\n",
+        modules.join(","),
+        edges.join(",")
+    )
+}
@@ -0,0 +1,235 @@
+use crate::ast_to_ts::{is_error_constant, is_u64_constant};
+use crate::depgraph::module_id;
+use crate::inspect::json_string;
+use crate::shared::*;
+use move_compiler::diagnostics::{Diagnostic, FilesSourceText};
+use move_compiler::hlir::ast::{
+    Block, Command_, Constant, Exp, ExpListItem, FunctionBody_, ModuleDefinition, Statement_,
+    UnannotatedExp_, Value_,
+};
+use move_compiler::parser::ast::ConstantName;
+use move_ir_types::location::Loc;
+use std::collections::{BTreeMap, BTreeSet};
+
+/*
+The abort codes of the translated modules, for explorers and wallets that only see the code of a
+failed transaction. Error constants are the u64 constants is_error_constant selects, plus the
+framework convention of names like ENOT_FOUND that the module aborts with, so that a constant like
+EPOCH_INTERVAL is left out. Each is paired with the doc comment (///) right above it, skipping
+attributes. Written as JSON with --emit-errmap, keyed by <address>::<module> and then by code;
+when two constants share a code, the first by name wins and the others get a warning.
+ */
+
+struct ErrorEntry {
+    name: String,
+    message: Option<String>,
+}
+
+fn is_framework_error_name(name: &str) -> bool {
+    name.len() > 1
+        && name.starts_with('E')
+        && name
+            .chars()
+            .all(|ch| ch.is_ascii_uppercase() || ch.is_ascii_digit() || ch == '_')
+}
+
+fn is_errmap_constant(
+    cname: &ConstantName,
+    cdef: &Constant,
+    abort_constants: &BTreeSet<String>,
+) -> bool {
+    let name = cname.to_string();
+    is_error_constant(cname, cdef)
+        || (is_u64_constant(cdef)
+            && is_framework_error_name(&name)
+            && abort_constants.contains(&name))
+}
+
+// constants named anywhere in the code of an abort, e.g. abort error::not_found(ENOT_FOUND), or of
+// an assert!, which aborts the same way
+fn collect_constants_in_exp(exp: &Exp, constants: &mut BTreeSet<String>) {
+    use UnannotatedExp_ as E;
+    match &exp.exp.value {
+        E::Constant(cname) => {
+            constants.insert(cname.to_string());
+        }
+        E::ModuleCall(mcall) => collect_constants_in_exp(&mcall.arguments, constants),
+        E::Builtin(_, e)
+        | E::Freeze(e)
+        | E::Vector(_, _, _, e)
+        | E::Dereference(e)
+        | E::UnaryExp(_, e)
+        | E::Borrow(_, e, _)
+        | E::Cast(e, _) => collect_constants_in_exp(e, constants),
+        E::BinopExp(lhs, _, rhs) => {
+            collect_constants_in_exp(lhs, constants);
+            collect_constants_in_exp(rhs, constants);
+        }
+        E::Pack(_, _, fields) => fields
+            .iter()
+            .for_each(|(_, _, e)| collect_constants_in_exp(e, constants)),
+        E::ExpList(items) => items.iter().for_each(|item| match item {
+            ExpListItem::Single(e, _) | ExpListItem::Splat(_, e, _) => {
+                collect_constants_in_exp(e, constants)
+            }
+        }),
+        _ => (),
+    }
+}
+
+fn collect_abort_constants(block: &Block, constants: &mut BTreeSet<String>) {
+    use Statement_ as S;
+    for stmt in block.iter() {
+        match &stmt.value {
+            S::Command(cmd) => {
+                if let Command_::Abort(e) = &cmd.value {
+                    collect_constants_in_exp(e, constants);
+                }
+            }
+            S::IfElse {
+                cond: _,
+                if_block,
+                else_block,
+            } => {
+                collect_abort_constants(if_block, constants);
+                collect_abort_constants(else_block, constants);
+            }
+            S::While { cond, block } => {
+                collect_abort_constants(&cond.0, constants);
+                collect_abort_constants(block, constants);
+            }
+            S::Loop { block, .. } => collect_abort_constants(block, constants),
+        }
+    }
+}
+
+fn module_abort_constants(mdef: &ModuleDefinition) -> BTreeSet<String> {
+    let mut constants = BTreeSet::new();
+    for (_, f) in mdef.functions.key_cloned_iter() {
+        if let FunctionBody_::Defined { body, .. } = &f.body.value {
+            collect_abort_constants(body, &mut constants);
+        }
+    }
+    constants
+}
+
+fn constant_code(cdef: &Constant) -> Option<u64> {
+    let (_, block) = &cdef.value;
+    if block.len() != 1 {
+        return None;
+    }
+    match &block[0].value {
+        Statement_::Command(cmd) => match &cmd.value {
+            Command_::Return { exp, .. } => match &exp.exp.value {
+                UnannotatedExp_::Value(value) => match &value.value {
+                    Value_::U64(code) => Some(*code),
+                    _ => None,
+                },
+                _ => None,
+            },
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// the /// lines above the constant, with only attributes in between
+fn doc_comment(cdef: &Constant, files: &FilesSourceText) -> Option<String> {
+    let (_, source) = files.get(&cdef.loc.file_hash())?;
+    let before = source.get(..cdef.loc.start() as usize)?;
+    let mut lines = vec![];
+    // the first line is the part of the constant's own line before it
+    for line in before.rsplit('\n') {
+        let line = line.trim();
+        if let Some(doc) = line.strip_prefix("///") {
+            lines.push(doc.trim());
+        } else if !((line.is_empty() && lines.is_empty()) || line.starts_with("#[")) {
+            break;
+        }
+    }
+    if lines.is_empty() {
+        return None;
+    }
+    lines.reverse();
+    Some(lines.join("\n"))
+}
+
+fn module_errors(
+    mdef: &ModuleDefinition,
+    files: &FilesSourceText,
+    warnings: &mut Vec<Diagnostic>,
+) -> BTreeMap<u64, ErrorEntry> {
+    let abort_constants = module_abort_constants(mdef);
+    let mut constants = mdef.constants.key_cloned_iter().collect::<Vec<_>>();
+    constants.sort_by_key(|(cname, _)| cname.to_string());
+    let mut errors: BTreeMap<u64, ErrorEntry> = BTreeMap::new();
+    for (cname, cdef) in constants {
+        if !is_errmap_constant(&cname, cdef, &abort_constants) {
+            continue;
+        }
+        let code = match constant_code(cdef) {
+            Some(code) => code,
+            None => continue,
+        };
+        match errors.get(&code) {
+            Some(first) => warnings.push(wdiag!((
+                cname.0.loc,
+                format!(
+                    "{} shares the abort code {} with {}, the error map only lists {}",
+                    cname, code, first.name, first.name
+                )
+            ))),
+            None => {
+                errors.insert(
+                    code,
+                    ErrorEntry {
+                        name: cname.to_string(),
+                        message: doc_comment(cdef, files),
+                    },
+                );
+            }
+        }
+    }
+    errors
+}
+
+/// The error map, with a warning for each error constant it leaves out as its code is taken
+pub fn format_errmap_json(c: &mut Context, files: &FilesSourceText) -> String {
+    let program = c.program.clone();
+    let mut modules = BTreeMap::new();
+    let mut warnings = vec![];
+    for (mident, mdef) in program.modules.key_cloned_iter() {
+        if c.is_excluded_module(&mident) {
+            continue;
+        }
+        let errors = module_errors(mdef, files, &mut warnings);
+        if !errors.is_empty() {
+            modules.insert(module_id(&mident), errors);
+        }
+    }
+    for warning in warnings {
+        c.add_warning(warning);
+    }
+    let modules = modules
+        .iter()
+        .map(|(id, errors)| {
+            let errors = errors
+                .iter()
+                .map(|(code, entry)| {
+                    let message = match &entry.message {
+                        Some(message) => json_string(message),
+                        None => "null".to_string(),
+                    };
+                    format!(
+                        "{}:{{\"name\":{},\"message\":{}}}",
+                        json_string(&code.to_string()),
+                        json_string(&entry.name),
+                        message
+                    )
+                })
+                .collect::<Vec<_>>();
+            format!("{}:{{{}}}", json_string(id), errors.join(","))
+        })
+        .collect::<Vec<_>>();
+    format!("{{{}}}\n", modules.join(","))
+}
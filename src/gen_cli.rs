@@ -1,17 +1,19 @@
-use crate::ast_to_ts::is_type_signer;
+use crate::ast_to_ts::{has_ord_directive, is_type_signer, script_function_invalid_parameters};
+use crate::inspect::{format_single_type, json_string};
 use crate::shared::*;
 use crate::utils::{capitalize, rename};
 use itertools::Itertools;
 use move_compiler::diagnostics::{Diagnostic, Diagnostics};
 use move_compiler::expansion::ast::ModuleIdent;
 use move_compiler::hlir::ast::{
-    BaseType, BaseType_, FunctionSignature, SingleType, SingleType_, StructDefinition, TypeName_,
+    BaseType, BaseType_, FunctionSignature, Program, SingleType, SingleType_, StructDefinition,
+    StructFields, TypeName_,
 };
 use move_compiler::naming::ast::BuiltinTypeName_;
 use move_compiler::parser::ast::{Ability_, StructName};
 use move_compiler::shared::Name;
 use move_ir_types::location::Loc;
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet};
 use std::fmt;
 
 pub fn vector_type_ts_parser(name: &String, element_type: &BaseType) -> TermResult {
@@ -66,7 +68,10 @@ pub fn stype_to_ts_parser(name: &String, loc: Loc, stype: &SingleType) -> TermRe
                 BuiltinTypeName_::U128 => Ok(format!("u128({})", name)),
                 BuiltinTypeName_::Bool => Ok(format!("{}=='true'", name)),
                 BuiltinTypeName_::Address => Ok(format!("new HexString({})", name)),
-                BuiltinTypeName_::Signer => unreachable!(),
+                BuiltinTypeName_::Signer => derr!((
+                    stype.loc,
+                    "signers cannot be passed at entry function invocation"
+                )),
                 BuiltinTypeName_::Vector => {
                     assert!(targs.len() == 1);
                     vector_type_ts_parser(name, &targs[0])
@@ -80,15 +85,16 @@ pub fn stype_to_ts_parser(name: &String, loc: Loc, stype: &SingleType) -> TermRe
 pub fn format_qualified_payload_fname_and_import(
     mident: &ModuleIdent,
     name: &impl fmt::Display,
+    c: &Context,
 ) -> (String, String) {
     // name exists in a different package, use fully qualified name
-    let package_name = format_address(mident.value.address);
+    let package_name = c.package_name(mident);
     (
         format!(
             "{}.{}.buildPayload_{}",
             capitalize(&package_name),
-            capitalize(&mident.value.module),
-            name
+            c.module_namespace(mident),
+            c.function_ts_name(mident, name)
         ),
         package_name,
     )
@@ -97,21 +103,248 @@ pub fn format_qualified_payload_fname_and_import(
 pub fn format_qualified_fname_and_import(
     mident: &ModuleIdent,
     name: &impl fmt::Display,
+    c: &Context,
 ) -> (String, String) {
     // name exists in a different package, use fully qualified name
-    let package_name = format_address(mident.value.address);
+    let package_name = c.package_name(mident);
     (
         format!(
             "{}.{}.query_{}",
             capitalize(&package_name),
-            capitalize(&mident.value.module),
-            name
+            c.module_namespace(mident),
+            c.function_ts_name(mident, name)
         ),
         package_name,
     )
 }
 
-pub fn generate_command(cmd: &CmdParams) -> Result<(String, String), Diagnostic> {
+/// Finds the registry resource named by --coin-registry, given as <address>::<module> or
+/// <address>::<module>::<Struct>. Without a struct name, the module must hold exactly one
+/// non-generic key struct
+pub fn find_coin_registry(
+    program: &Program,
+    spec: &str,
+) -> Result<(ModuleIdent, StructName), String> {
+    let parts = spec.split("::").collect::<Vec<_>>();
+    let (address, module, struct_name) = match parts.as_slice() {
+        [address, module] => (*address, *module, None),
+        [address, module, sname] => (*address, *module, Some(*sname)),
+        _ => {
+            return Err(format!(
+                "Invalid --coin-registry {}, expected <address>::<module>[::<Struct>]",
+                spec
+            ))
+        }
+    };
+    let hex_digits = address.trim_start_matches("0x").trim_start_matches('0');
+    let normalized_hex = if hex_digits.is_empty() {
+        "0x0".to_string()
+    } else {
+        format!("0x{}", hex_digits.to_lowercase())
+    };
+    let (mident, mdef) = program
+        .modules
+        .key_cloned_iter()
+        .find(|(mi, _)| {
+            mi.value.module.to_string() == module
+                && (format_address(mi.value.address) == address
+                    || format_address_hex(mi.value.address) == normalized_hex)
+        })
+        .ok_or_else(|| format!("Coin registry module {}::{} not found", address, module))?;
+    let candidates = mdef
+        .structs
+        .key_cloned_iter()
+        .filter(|(sname, sdef)| {
+            sdef.abilities.has_ability_(Ability_::Key)
+                && sdef.type_parameters.is_empty()
+                && struct_name.map_or(true, |name| sname.to_string() == name)
+        })
+        .map(|(sname, _)| sname)
+        .collect::<Vec<_>>();
+    match candidates.as_slice() {
+        [sname] => Ok((mident, *sname)),
+        [] => Err(format!(
+            "No non-generic key struct {}found in coin registry module {}::{}",
+            struct_name.map(|name| format!("{} ", name)).unwrap_or_default(),
+            address,
+            module
+        )),
+        _ => Err(format!(
+            "Coin registry module {}::{} has several key structs ({}), pick one with {}::{}::<Struct>",
+            address,
+            module,
+            candidates.iter().map(|s| s.to_string()).join(", "),
+            address,
+            module
+        )),
+    }
+}
+
+/// Emitted into cli.ts when --coin-registry is given. Symbols are read from every nested value of
+/// the registry resource that has both a `symbol` and a `token_type` (TypeInfo) field
+pub fn generate_coin_resolver(registry_class: &str) -> String {
+    format!(
+        r###"
+// symbol -> coin type, read from {} once per run
+let coinSymbols: Map<string, string> | null = null;
+
+const collectCoinSymbols = (value: any, out: Map<string, string>) => {{
+  if (value === null || typeof value !== 'object' || value instanceof HexString) {{
+    return;
+  }}
+  if (Array.isArray(value)) {{
+    value.forEach(element => collectCoinSymbols(element, out));
+    return;
+  }}
+  if (value.symbol !== undefined && value.token_type !== undefined) {{
+    const symbol = u8str(value.symbol.bytes ?? value.symbol);
+    const info = value.token_type;
+    out.set(symbol, `${{info.account_address.toShortString()}}::${{u8str(info.module_name)}}::${{u8str(info.struct_name)}}`);
+    return;
+  }}
+  Object.values(value).forEach(field => collectCoinSymbols(field, out));
+}}
+
+const loadCoinSymbols = async (client: AptosClient) => {{
+  if (coinSymbols === null) {{
+    const registry = await {}.load(getProjectRepo(), client, {}.moduleAddress, []);
+    coinSymbols = new Map();
+    collectCoinSymbols(registry, coinSymbols);
+  }}
+  return coinSymbols;
+}}
+
+const editDistance = (a: string, b: string) => {{
+  let previous = Array.from({{length: b.length + 1}}, (_, j) => j);
+  for (let i = 1; i <= a.length; i++) {{
+    const current = [i];
+    for (let j = 1; j <= b.length; j++) {{
+      const substitution = previous[j - 1] + (a[i - 1] === b[j - 1] ? 0 : 1);
+      current.push(Math.min(previous[j] + 1, current[j - 1] + 1, substitution));
+    }}
+    previous = current;
+  }}
+  return previous[b.length];
+}}
+
+// accepts a full type, or a symbol registered in the coin registry
+const resolveTypeArg = async (client: AptosClient, name: string, value: string) => {{
+  if (value.includes('::')) {{
+    return parseTypeArg(name, value);
+  }}
+  const symbols = await loadCoinSymbols(client);
+  const fullname = symbols.get(value);
+  if (fullname !== undefined) {{
+    return parseTypeArg(name, fullname);
+  }}
+  const nearMisses = Array.from(symbols.keys())
+    .filter(symbol => symbol.toUpperCase() === value.toUpperCase() || editDistance(symbol.toUpperCase(), value.toUpperCase()) <= 2);
+  const hint = nearMisses.length > 0 ? `, did you mean ${{nearMisses.join(', ')}}?` : '';
+  throw new Error(`Unknown coin symbol for ${{name}}: ${{value}}${{hint}}`);
+}}
+"###,
+        registry_class, registry_class, registry_class
+    )
+}
+
+fn cmd_command_name(cmd: &CmdParams) -> String {
+    format!(
+        "{}:{}",
+        cmd.mi.value.module,
+        cmd.fname.to_string().replace("_", "-")
+    )
+}
+
+fn printer_command_name(sname: &StructName, fname: &Name) -> String {
+    format!("{}:{}", sname, fname.to_string().replace("_", "-"))
+}
+
+fn query_command_name(query: &CmdParams) -> String {
+    format!(
+        "{}:query-{}",
+        query.mi.value.module,
+        query.fname.to_string().replace("_", "-")
+    )
+}
+
+fn iter_table_command_name(sname: &StructName, field_name: &Name) -> String {
+    format!("show-entries-{}-{}", sname, field_name).replace("_", "-")
+}
+
+fn cmd_handler_name(cmd: &CmdParams) -> String {
+    format!("{}_{}", cmd.mi.value.module, cmd.fname)
+}
+
+fn printer_handler_name(sname: &StructName, fname: &Name) -> String {
+    format!("{}_{}", sname, fname)
+}
+
+fn query_handler_name(query: &CmdParams) -> String {
+    format!("{}_{}", query.mi.value.module, query.fname)
+}
+
+fn iter_table_handler_name(sname: &StructName, field_name: &Name) -> String {
+    format!("show_entries_{}_{}", sname, field_name)
+}
+
+/// Directives of different items can generate commands of the same name, e.g. #[cmd] on
+/// functions of modules with the same name at different addresses, or #[method] on structs with
+/// the same name, which cli.ts would declare twice
+fn claim_command_name(
+    claimed: &mut BTreeMap<String, Loc>,
+    command_name: String,
+    loc: Loc,
+) -> Result<(), Diagnostics> {
+    match claimed.get(&command_name) {
+        Some(previous) => {
+            let res: Result<(), Diagnostic> = derr!(
+                (
+                    loc,
+                    format!("The cli command {} is generated twice", command_name)
+                ),
+                (
+                    *previous,
+                    "The command is first generated for this directive".to_string()
+                )
+            );
+            res.map_err(|diag| {
+                let mut diags = Diagnostics::new();
+                diags.add(diag);
+                diags
+            })
+        }
+        None => {
+            claimed.insert(command_name, loc);
+            Ok(())
+        }
+    }
+}
+
+/// The TS functions behind the commands join module or struct and function names with '_', so
+/// e.g. a_b::c and a::b_c would both declare a_b_c. A handler name already taken gets the first
+/// free numeric suffix instead. Command names always contain ':' or '-', which identifiers
+/// cannot, so both kinds of names share one map
+fn claim_handler_name(
+    claimed: &mut BTreeMap<String, Loc>,
+    handler_name: String,
+    loc: Loc,
+) -> String {
+    let mut name = handler_name.clone();
+    let mut suffix = 2;
+    while claimed.contains_key(&name) {
+        name = format!("{}_{}", handler_name, suffix);
+        suffix += 1;
+    }
+    claimed.insert(name.clone(), loc);
+    name
+}
+
+pub fn generate_command(
+    cmd: &CmdParams,
+    func_name: &str,
+    resolve_symbols: bool,
+    c: &Context,
+) -> Result<(String, String), Diagnostic> {
     let type_param_names = cmd
         .func
         .signature
@@ -132,19 +365,49 @@ pub fn generate_command(cmd: &CmdParams) -> Result<(String, String), Diagnostic>
     let mut all_params = vec![];
     all_params.extend(type_param_names.clone());
     all_params.extend(param_names_no_signer.clone());
+    // every argument may be missing, for --interactive to prompt for it
     let param_decl = all_params
         .iter()
-        .map(|pname| format!("{}: string", pname))
+        .map(|pname| format!("{}$arg: string | undefined", pname))
         .join(", ");
     let mut param_parsers = vec![];
     let mut arguments = vec![];
+    // what --interactive prompts with, and how it checks the answers
+    let mut param_metadata = vec![];
     for tparam in cmd.func.signature.type_parameters.iter() {
         let tname = tparam.user_specified_name;
-        param_parsers.push(format!(
-            "  const {}_ = parseTypeTagOrThrow({});",
-            tname, tname
+        let parse = if resolve_symbols {
+            format!("resolveTypeArg(client, '{}', value)", tname)
+        } else {
+            format!("parseTypeArg('{}', value)", tname)
+        };
+        param_metadata.push(format!(
+            "  {{name: '{}', type: 'type argument', description: '', parse: (client: AptosClient, value: string) => {}}},",
+            tname, parse
         ));
-        arguments.push(format!("  .argument('<TYPE_{}>')", tname));
+        if resolve_symbols {
+            param_parsers.push(format!(
+                "  const {}_ = await resolveTypeArg(client, '{}', {});",
+                tname, tname, tname
+            ));
+            arguments.push(format!(
+                "  .argument('[TYPE_{}]', 'type argument {}, a full type or a registered coin symbol such as APT')",
+                tname, tname
+            ));
+        } else {
+            param_parsers.push(format!(
+                "  const {}_ = parseTypeArg('{}', {});",
+                tname, tname, tname
+            ));
+            arguments.push(format!(
+                "  .argument('[TYPE_{}]', 'type argument {}, a full type such as 0x1::aptos_coin::AptosCoin')",
+                tname, tname
+            ));
+        }
+    }
+    if !type_param_names.is_empty() {
+        // positional arguments are matched in order, so any extra one means a miscount
+        arguments.push("  .allowExcessArguments(false)".to_string());
     }
     for (pname, ptype) in param_no_signers {
         param_parsers.push(format!(
@@ -152,9 +415,29 @@ pub fn generate_command(cmd: &CmdParams) -> Result<(String, String), Diagnostic>
             pname,
             stype_to_ts_parser(&pname.to_string(), pname.0.loc, ptype)?
         ));
-        arguments.push(format!("  .argument('<{}>')", pname));
+        let description = cmd
+            .param_descs
+            .get(&pname.to_string())
+            .cloned()
+            .unwrap_or_default();
+        param_metadata.push(format!(
+            "  {{name: '{}', type: '{}', description: {}, parse: (client: AptosClient, value: string) => {}}},",
+            pname,
+            format_single_type(ptype),
+            json_string(&description),
+            stype_to_ts_parser(&"value".to_string(), pname.0.loc, ptype)?
+        ));
+        if description.is_empty() {
+            arguments.push(format!("  .argument('[{}]')", pname));
+        } else {
+            arguments.push(format!(
+                "  .argument('[{}]', {})",
+                pname,
+                json_string(&description)
+            ));
+        }
     }
-    let (payload_builder, package_name) = format_qualified_payload_fname_and_import(&cmd.mi, &cmd.fname);
+    let (payload_builder, package_name) = format_qualified_payload_fname_and_import(&cmd.mi, &cmd.fname, c);
     let payload = format!(
         "{}({}{})",
         payload_builder,
@@ -181,15 +464,49 @@ pub fn generate_command(cmd: &CmdParams) -> Result<(String, String), Diagnostic>
         }
     );
     let miname = cmd.mi.value.module;
-    let func_name = format!("{}_{}", miname, cmd.fname);
-    let command_name = format!("{}:{}", miname, cmd.fname.to_string().replace("_", "-"));
-    let description = cmd.desc.clone().unwrap_or_default();
+    let command_name = cmd_command_name(cmd);
+    let mut description = cmd.desc.clone().unwrap_or_default();
+    if !type_param_names.is_empty() {
+        if !description.is_empty() {
+            description.push_str(". ");
+        }
+        description.push_str(&format!("Type arguments: {}", type_param_names.join(", ")));
+    }
+    let full_name = format!(
+        "{}::{}::{}",
+        format_address_hex(cmd.mi.value.address),
+        miname,
+        cmd.fname
+    );
+    let collect_args = if all_params.is_empty() {
+        "".to_string()
+    } else {
+        format!(
+            "\n  const [{}] = await collectArgs(client, {}_params, [{}]);",
+            all_params.join(", "),
+            func_name,
+            all_params
+                .iter()
+                .map(|pname| format!("{}$arg", pname))
+                .join(", ")
+        )
+    };
+    let param_list = if param_metadata.is_empty() {
+        "[]".to_string()
+    } else {
+        format!("[\n{}\n]", param_metadata.join("\n"))
+    };
     let action_body = format!(
         r###"
+const {}_params: CliParam[] = {};
+
 const {} = async ({}) => {{
-  const {{client, account}} = readConfig(program);
+  const {{client, account}} = readConfig(program);{}
 {}
   const payload = {};
+  if (program.opts().interactive && !await confirmPayload(client, account, '{}', {}_params, [{}], payload)) {{
+    return;
+  }}
   await sendPayloadTx(client, account, payload);
 }}
 
@@ -199,10 +516,16 @@ program
 {}
   .action({});
 "###,
+        func_name,
+        param_list,
         func_name,
         param_decl,
+        collect_args,
         param_parsers.join("\n"),
         payload,
+        full_name,
+        func_name,
+        all_params.join(", "),
         command_name,
         description,
         arguments.join("\n"),
@@ -215,14 +538,15 @@ program
 pub fn format_qualified_sname_and_import(
     mident: &ModuleIdent,
     name: &StructName,
+    c: &Context,
 ) -> (String, String) {
     // name exists in a different package, use fully qualified name
-    let package_name = format_address(mident.value.address);
+    let package_name = c.package_name(mident);
     (
         format!(
             "{}.{}.{}",
             capitalize(&package_name),
-            capitalize(&mident.value.module),
+            c.module_namespace(mident),
             rename(name)
         ),
         package_name,
@@ -235,6 +559,8 @@ pub fn generate_printer(
     sdef: &StructDefinition,
     fname: &Name,
     fsig: &FunctionSignature,
+    cmd_func_name: &str,
+    c: &Context,
 ) -> Result<(String, String), Diagnostic> {
     let mut arg_decls = vec![];
     for tp in sdef.type_parameters.iter() {
@@ -244,12 +570,12 @@ pub fn generate_printer(
         arg_decls.push(format!("{}: string", name));
     }
 
-    let (struct_qualified_name, package_name) = format_qualified_sname_and_import(mi, sname);
+    let (struct_qualified_name, package_name) = format_qualified_sname_and_import(mi, sname, c);
 
     let type_tags_inner = sdef
         .type_parameters
         .iter()
-        .map(|tp| format!("parseTypeTagOrThrow({})", tp.param.user_specified_name))
+        .map(|tp| format!("parseTypeArg('{}', {})", tp.param.user_specified_name, tp.param.user_specified_name))
         .join(", ");
 
     let mut arguments = vec![];
@@ -269,8 +595,7 @@ pub fn generate_printer(
         param_handlers.push(stype_to_ts_parser(&name.to_string(), name.0.loc, ty)?);
     }
 
-    let cmd_func_name = format!("{}_{}", sname, fname);
-    let command_name = format!("{}:{}", sname, fname.to_string().replace("_", "-"));
+    let command_name = printer_command_name(sname, fname);
 
     let body = format!(
         r###"
@@ -279,7 +604,7 @@ const {} = async (owner: string, {}) => {{
   const repo = getProjectRepo();
   const owner_ = new HexString(owner);
   const value = await {}.load(repo, client, owner_, [{}])
-  print(value.{}({}));
+  print({}value.{}({}));
 }}
 
 program
@@ -292,7 +617,9 @@ program
         arg_decls.join(", "),
         struct_qualified_name,
         type_tags_inner,
-        fname,
+        // methods return a Promise in async mode
+        if c.is_async() { "await " } else { "" },
+        c.member_ts_name(mi, fname),
         param_handlers.join(", "),
         command_name,
         arguments.join("\n"),
@@ -304,6 +631,8 @@ program
 
 pub fn generate_query_printer(
     query: &CmdParams,
+    cmd_func_name: &str,
+    c: &Context,
 ) -> Result<(String, String), Diagnostic> {
     let mut arg_decls = vec![];
     for tp in query.func.signature.type_parameters.iter() {
@@ -313,13 +642,15 @@ pub fn generate_query_printer(
     for (name, _) in params_no_signer.clone() {
         arg_decls.push(format!("{}: string", name));
     }
+    // commander passes the options after the arguments
+    arg_decls.push("options: {readOnly?: boolean}".to_string());
 
-    let (query_func_name, package_name) = format_qualified_fname_and_import(&query.mi, &query.fname);
+    let (query_func_name, package_name) = format_qualified_fname_and_import(&query.mi, &query.fname, c);
 
     let type_tags_inner = query.func.signature
         .type_parameters
         .iter()
-        .map(|tp| format!("parseTypeTagOrThrow({})", tp.user_specified_name))
+        .map(|tp| format!("parseTypeArg('{}', {})", tp.user_specified_name, tp.user_specified_name))
         .join(", ");
 
     let mut arguments = vec![];
@@ -339,13 +670,12 @@ pub fn generate_query_printer(
         param_handlers.push(stype_to_ts_parser(&name.to_string(), name.0.loc, ty)?);
     }
 
-    let cmd_func_name = format!("{}_{}", query.mi.value.module, query.fname);
-    let command_name = format!("{}:query-{}", query.mi.value.module, query.fname.to_string().replace("_", "-"));
+    let command_name = query_command_name(query);
 
     let body = format!(
         r###"
 const {} = async ({}) => {{
-  const {{client, account}} = readConfig(program);
+  const {{client, account}} = options.readOnly ? readReadOnlyConfig(program) : readConfig(program);
   const repo = getProjectRepo();
   const value = await {}(client, account, repo, {}{}[{}])
   print(value);
@@ -354,6 +684,7 @@ const {} = async ({}) => {{
 program
   .command("{}")
 {}
+  .option('--read-only', 'simulate with the public key of the profile, without loading its private key')
   .action({})
 "###,
         cmd_func_name,
@@ -370,26 +701,54 @@ program
     Ok((body, package_name))
 }
 
+/// Whether the values of the IterableTable in `field_name` are structs with an #[ord] directive,
+/// whose comparator the printer can sort the entries with
+fn iter_table_values_have_ord(sdef: &StructDefinition, field_name: &Name, c: &Context) -> bool {
+    let table_ty = match &sdef.fields {
+        StructFields::Defined(fields) => fields
+            .iter()
+            .find(|(f_name, _)| f_name.to_string() == field_name.to_string())
+            .map(|(_, ty)| ty),
+        StructFields::Native(_) => None,
+    };
+    let value_ty = match table_ty.map(|ty| &ty.value) {
+        Some(BaseType_::Apply(_, _, targs)) if targs.len() == 2 => &targs[1],
+        _ => return false,
+    };
+    match &value_ty.value {
+        BaseType_::Apply(_, typename, _) => match &typename.value {
+            TypeName_::ModuleType(value_mi, value_sname) => c
+                .program
+                .modules
+                .get(value_mi)
+                .and_then(|mdef| mdef.structs.get(value_sname))
+                .map_or(false, has_ord_directive),
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
 pub fn generate_iter_table_printer(
     mi: &ModuleIdent,
     sname: &StructName,
     sdef: &StructDefinition,
     field_name: &Name,
+    action_name: &str,
+    c: &Context,
 ) -> (String, String) {
-    let action_name = format!("show_entries_{}_{}", sname, field_name);
-
     let type_param_decls = sdef
         .type_parameters
         .iter()
         .map(|tparam| format!("{}: string", tparam.param.user_specified_name))
         .join(", ");
 
-    let (struct_qualified_name, package_name) = format_qualified_sname_and_import(mi, sname);
+    let (struct_qualified_name, package_name) = format_qualified_sname_and_import(mi, sname, c);
 
     let type_tags_inner = sdef
         .type_parameters
         .iter()
-        .map(|tp| format!("parseTypeTagOrThrow({})", tp.param.user_specified_name))
+        .map(|tp| format!("parseTypeArg('{}', {})", tp.param.user_specified_name, tp.param.user_specified_name))
         .join(", ");
 
     let arguments = sdef
@@ -398,16 +757,19 @@ pub fn generate_iter_table_printer(
         .map(|tp| (format!("  .argument('<TYPE_{}>')", tp.param.user_specified_name)))
         .join("\n");
 
-    let command_name = action_name.to_string().replace("_", "-");
+    let command_name = iter_table_command_name(sname, field_name);
+
+    // with an #[ord] on the values, --sort orders the entries with their comparator
+    let sortable = iter_table_values_have_ord(sdef, field_name, c);
 
     let body = format!(
         r###"
-const {} = async (owner: string{}) => {{
+const {} = async (owner: string{}{}) => {{
   const {{client}} = readConfig(program);
   const repo = getProjectRepo();
   const owner_ = new HexString(owner);
   const value = await {}.load(repo, client, owner_, [{}])
-  const entries = await value.getIterTableEntries_{}(client, repo);
+  const entries = await value.getIterTableEntries_{}(client, repo);{}
   for (const entry of entries) {{
     console.log();
     console.log(`Entry:`);
@@ -418,7 +780,7 @@ const {} = async (owner: string{}) => {{
 
 program
   .command("{}")
-  .argument("<ADDRESS:owner>"){}
+  .argument("<ADDRESS:owner>"){}{}
   .action({})
 "###,
         action_name,
@@ -427,26 +789,150 @@ program
         } else {
             "".to_string()
         },
+        if sortable {
+            ", options: {sort?: boolean}"
+        } else {
+            ""
+        },
         struct_qualified_name,
         type_tags_inner,
         field_name,
+        if sortable {
+            "\n  if (options.sort) {\n    entries.sort((a, b) => a[1].compareTo(b[1]));\n  }"
+        } else {
+            ""
+        },
         command_name,
         if arguments.len() > 0 {
             format!("\n{}", arguments)
         } else {
             "".to_ascii_lowercase()
         },
+        if sortable {
+            "\n  .option(\"--sort\", \"sort the entries by the #[ord] of their values\")"
+        } else {
+            ""
+        },
         action_name,
     );
     (body, package_name)
 }
 
-pub fn generate_cli(ctx: &Context) -> Result<(String, String), Diagnostics> {
+/// Adds a command for each --extra-cmd, as if its function had a #[cmd] directive, unless it has
+/// one already. Each entry naming no translated function, or a function that transactions cannot
+/// call, is one error
+pub fn add_extra_cmds(ctx: &mut Context) -> Result<(), Vec<String>> {
+    let program = ctx.program.clone();
+    let mut errors = vec![];
+    for spec in ctx.config.extra_cmd.clone().iter() {
+        let parts = spec.split("::").collect::<Vec<_>>();
+        if parts.len() != 3 || parts.iter().any(|part| part.is_empty()) {
+            errors.push(format!(
+                "Invalid --extra-cmd {}, expected <ADDRESS>::<MODULE>::<FUNCTION>",
+                spec
+            ));
+            continue;
+        }
+        let module = program.modules.key_cloned_iter().find(|(mident, _)| {
+            !ctx.is_excluded_module(mident)
+                && mident.value.module.to_string() == parts[1]
+                && (format_address_hex(mident.value.address) == parts[0]
+                    || format_address(mident.value.address) == parts[0])
+        });
+        let (mident, mdef) = match module {
+            Some(module) => module,
+            None => {
+                errors.push(format!(
+                    "--extra-cmd {}: no module {}::{} is translated",
+                    spec, parts[0], parts[1]
+                ));
+                continue;
+            }
+        };
+        let function = mdef
+            .functions
+            .key_cloned_iter()
+            .find(|(fname, _)| fname.to_string() == parts[2]);
+        let (fname, func) = match function {
+            Some(function) => function,
+            None => {
+                errors.push(format!(
+                    "--extra-cmd {}: {} has no function {}",
+                    spec, mident, parts[2]
+                ));
+                continue;
+            }
+        };
+        if !is_public_entry(func) {
+            errors.push(format!(
+                "--extra-cmd {}: {} is not a public entry function",
+                spec, fname
+            ));
+            continue;
+        }
+        let invalid_params = script_function_invalid_parameters(&func.signature);
+        if !invalid_params.is_empty() {
+            errors.push(format!(
+                "--extra-cmd {}: {} cannot be called from a transaction ({})",
+                spec,
+                fname,
+                invalid_params
+                    .iter()
+                    .map(|(var, (_, msg))| format!("{}: {}", var, msg))
+                    .join("; ")
+            ));
+            continue;
+        }
+        if ctx
+            .cmds
+            .iter()
+            .any(|cmd| cmd.mi == mident && cmd.fname == fname)
+        {
+            continue;
+        }
+        ctx.add_cmd(
+            &mident,
+            &fname,
+            func,
+            Some(format!("Calls {}", spec)),
+            BTreeMap::new(),
+        );
+    }
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+pub fn generate_cli(
+    ctx: &mut Context,
+    coin_registry: Option<&(ModuleIdent, StructName)>,
+) -> Result<(String, String), Diagnostics> {
     let mut commands = vec![];
     let mut printers = vec![];
     let mut imported_packages = BTreeSet::new();
+    let coin_resolver = match coin_registry {
+        Some((mident, sname)) => {
+            let (registry_class, package_name) = format_qualified_sname_and_import(mident, sname, ctx);
+            imported_packages.insert(package_name);
+            generate_coin_resolver(&registry_class)
+        }
+        None => "".to_string(),
+    };
+    let mut command_names = BTreeMap::new();
     for cmd_param in ctx.cmds.iter() {
-        let command_res = generate_command(cmd_param);
+        claim_command_name(
+            &mut command_names,
+            cmd_command_name(cmd_param),
+            cmd_param.fname.0.loc,
+        )?;
+        let handler_name = claim_handler_name(
+            &mut command_names,
+            cmd_handler_name(cmd_param),
+            cmd_param.fname.0.loc,
+        );
+        let command_res = generate_command(cmd_param, &handler_name, coin_registry.is_some(), ctx);
         if let Ok((cmd_str, package_name)) = command_res {
             commands.push(cmd_str);
             imported_packages.insert(package_name);
@@ -457,12 +943,26 @@ pub fn generate_cli(ctx: &Context) -> Result<(String, String), Diagnostics> {
             return Err(diags);
         }
     }
-    for method in ctx.printer_methods.iter() {
+    for method in ctx.printer_methods.clone().iter() {
         let (mi, sname, sdef, fname, fsig) = method;
         // if sdef is a resource type, generate printer for it
         if sdef.abilities.has_ability_(Ability_::Key) {
-            let printer_res = generate_printer(mi, sname, sdef, fname, fsig);
+            // validate_method checked that the method declares the struct's type parameters in
+            // order, so its signature resolves them for the struct too
+            let handler_name = claim_handler_name(
+                &mut command_names,
+                printer_handler_name(sname, fname),
+                fname.loc,
+            );
+            let printer_res = ctx.with_current_function(mi, fsig, |c| {
+                generate_printer(mi, sname, sdef, fname, fsig, &handler_name, c)
+            });
             if let Ok((printer_body, package_name)) = printer_res {
+                claim_command_name(
+                    &mut command_names,
+                    printer_command_name(sname, fname),
+                    fname.loc,
+                )?;
                 printers.push(printer_body);
                 imported_packages.insert(package_name);
             } else {
@@ -477,7 +977,17 @@ pub fn generate_cli(ctx: &Context) -> Result<(String, String), Diagnostics> {
         }
     }
     for query in ctx.queries.iter() {
-        let command_res = generate_query_printer(query);
+        claim_command_name(
+            &mut command_names,
+            query_command_name(query),
+            query.fname.0.loc,
+        )?;
+        let handler_name = claim_handler_name(
+            &mut command_names,
+            query_handler_name(query),
+            query.fname.0.loc,
+        );
+        let command_res = generate_query_printer(query, &handler_name, ctx);
         if let Ok((cmd_str, package_name)) = command_res {
             commands.push(cmd_str);
             imported_packages.insert(package_name);
@@ -488,28 +998,47 @@ pub fn generate_cli(ctx: &Context) -> Result<(String, String), Diagnostics> {
             return Err(diags);
         }
     }
-    for show_iter_table in ctx.all_shows_iter_tables.iter() {
+    for show_iter_table in ctx.all_shows_iter_tables.clone().iter() {
         let (mi, sname, sdef, field_name) = show_iter_table;
-        let (printer_body, package_name) = generate_iter_table_printer(mi, sname, sdef, field_name);
+        claim_command_name(
+            &mut command_names,
+            iter_table_command_name(sname, field_name),
+            field_name.loc,
+        )?;
+        let handler_name = claim_handler_name(
+            &mut command_names,
+            iter_table_handler_name(sname, field_name),
+            field_name.loc,
+        );
+        let (printer_body, package_name) = ctx.with_current_module(mi, |c| {
+            generate_iter_table_printer(mi, sname, sdef, field_name, &handler_name, c)
+        });
         printers.push(printer_body);
         imported_packages.insert(package_name);
     }
     let package_imports = imported_packages
         .iter()
-        .map(|name| format!("import * as {} from './{}';", capitalize(name), name))
+        .map(|name| {
+            format!(
+                "import * as {} from '{}';",
+                capitalize(name),
+                ctx.package_import_path(&ctx.package_dir_by_name(name), false)
+            )
+        })
         .join("\n");
     let filename = "cli.ts".to_string();
     let content = format!(
         r###"
-import {{ AptosParserRepo, getTypeTagFullname, StructTag, parseTypeTagOrThrow, u8, u64, u128, print, strToU8, u8str, DummyCache }} from "@manahippo/move-to-ts";
+import {{ AptosParserRepo, getTypeTagFullname, StructTag, parseTypeTagOrThrow, u8, u64, u128, print, strToU8, u8str, DummyCache, SimulationKeys, simulatePayloadTx }} from "@manahippo/move-to-ts";
 import {{ AptosAccount, AptosClient, HexString, Types }} from "aptos";
 import {{ Command }} from "commander";
 import {{ getProjectRepo }} from "./";
 import * as fs from "fs";
+import * as readline from "readline";
 import * as yaml from "yaml";
 {}
 
-export const readConfig = (program: Command) => {{
+const readProfile = (program: Command) => {{
   const {{config, profile}} = program.opts();
   const ymlContent = fs.readFileSync(config, {{encoding: "utf-8"}});
   const result = yaml.parse(ymlContent);
@@ -520,21 +1049,154 @@ export const readConfig = (program: Command) => {{
   if (!result.profiles[profile]) {{
     throw new Error(`Expect a ${{profile}} profile to be present in yaml config`);
   }}
-  const url = result.profiles[profile].rest_url;
-  const privateKeyStr = result.profiles[profile].private_key;
-  if (!url) {{
+  if (!result.profiles[profile].rest_url) {{
     throw new Error(`Expect rest_url to be present in ${{profile}} profile`);
   }}
+  return {{profile, values: result.profiles[profile]}};
+}}
+
+export const readConfig = (program: Command) => {{
+  const {{profile, values}} = readProfile(program);
+  const privateKeyStr = values.private_key;
   if (!privateKeyStr) {{
     throw new Error(`Expect private_key to be present in ${{profile}} profile`);
   }}
   const privateKey = new HexString(privateKeyStr);
-  const client = new AptosClient(result.profiles[profile].rest_url);
+  const client = new AptosClient(values.rest_url);
   const account = new AptosAccount(privateKey.toUint8Array());
   console.log(`Using address ${{account.address().hex()}}`);
   return {{client, account}};
 }}
 
+// public key and address only, for simulations that should not touch the private key
+export const readReadOnlyConfig = (program: Command) => {{
+  const {{profile, values}} = readProfile(program);
+  if (!values.public_key || !values.account) {{
+    throw new Error(`Expect public_key and account to be present in ${{profile}} profile`);
+  }}
+  const client = new AptosClient(values.rest_url);
+  const account: SimulationKeys = {{publicKey: values.public_key, address: new HexString(values.account)}};
+  console.log(`Using address ${{account.address.hex()}} (read-only)`);
+  return {{client, account}};
+}}
+
+const parseTypeArg = (name: string, value: string) => {{
+  try {{
+    return parseTypeTagOrThrow(value);
+  }} catch (e) {{
+    throw new Error(`Invalid type argument for ${{name}}: ${{value}}`);
+  }}
+}}
+{}
+
+// an argument of a command, as --interactive prompts for it
+interface CliParam {{
+  name: string;
+  type: string;
+  description: string;
+  parse: (client: AptosClient, value: string) => any;
+}}
+
+const prompt = (question: string) => new Promise<string>(resolve => {{
+  const rl = readline.createInterface({{input: process.stdin, output: process.stdout}});
+  rl.question(question, answer => {{
+    rl.close();
+    resolve(answer.trim());
+  }});
+}});
+
+// asks again until the answer parses the way the argument would
+const promptArg = async (client: AptosClient, param: CliParam) => {{
+  const hint = param.description ? ` - ${{param.description}}` : '';
+  while (true) {{
+    const value = await prompt(`${{param.name}} (${{param.type}})${{hint}}: `);
+    try {{
+      if (param.type === 'bool' && value !== 'true' && value !== 'false') {{
+        throw new Error('expected true or false');
+      }}
+      await param.parse(client, value);
+      return value;
+    }} catch (e) {{
+      console.log(`Invalid ${{param.type}}: ${{(e as Error).message}}`);
+    }}
+  }}
+}}
+
+// the arguments given on the command line, with the missing ones prompted for in --interactive mode
+const collectArgs = async (client: AptosClient, params: CliParam[], values: (string | undefined)[]) => {{
+  const collected: string[] = [];
+  for (const [i, param] of params.entries()) {{
+    const value = values[i];
+    if (value !== undefined) {{
+      collected.push(value);
+    }} else if (program.opts().interactive) {{
+      collected.push(await promptArg(client, param));
+    }} else {{
+      program.error(`error: missing required argument '${{param.name}}'`);
+    }}
+  }}
+  return collected;
+}}
+
+const confirmPayload = async (
+  client: AptosClient,
+  account: AptosAccount,
+  fullname: string,
+  params: CliParam[],
+  values: string[],
+  payload: Types.TransactionPayload
+) => {{
+  console.log(`Function: ${{fullname}}`);
+  params.forEach((param, i) => console.log(`  ${{param.name}} (${{param.type}}): ${{values[i]}}`));
+  try {{
+    const tx = await simulatePayloadTx(client, account, payload);
+    console.log(tx.success ? `Estimated gas: ${{tx.gas_used}}` : `Estimated gas: unknown, the simulation failed with ${{tx.vm_status}}`);
+  }} catch (e) {{
+    console.log(`Estimated gas: unknown, the simulation failed: ${{(e as Error).message}}`);
+  }}
+  return (await prompt('Submit? [y/N] ')).toLowerCase() === 'y';
+}}
+
+// a value of the translated modules decoded into its class through the parsers loadParsers
+// registered, or null for types that have none
+const decodeWithRepo = (repo: AptosParserRepo, type: string, data: any) => {{
+  let tag;
+  try {{
+    tag = parseTypeTagOrThrow(type);
+  }} catch (e) {{
+    return null;
+  }}
+  if (!(tag instanceof StructTag) || !repo.getParserFromTypeTag(tag)) {{
+    return null;
+  }}
+  return repo.parse(data, tag);
+}}
+
+const printTransactionOutcome = (tx: Types.UserTransaction) => {{
+  const repo = getProjectRepo();
+  console.log(tx.success ? `Succeeded: ${{tx.vm_status}}` : `Failed: ${{tx.vm_status}}`);
+  console.log(`Gas used: ${{tx.gas_used}}`);
+  for (const event of tx.events) {{
+    const decoded = decodeWithRepo(repo, event.type, event.data);
+    if (decoded) {{
+      console.log(`Event ${{event.type}}`);
+      print(decoded);
+    }}
+  }}
+  for (const change of tx.changes) {{
+    if (change.type !== 'write_resource') {{
+      continue;
+    }}
+    const {{address, data}} = change as Types.WriteResource;
+    const decoded = decodeWithRepo(repo, data.type, data.data);
+    if (decoded) {{
+      console.log(`Resource ${{data.type}} at ${{address}}`);
+      print(decoded);
+    }}
+  }}
+}}
+
+// prints the hash, and unless --no-wait is given waits for the transaction and prints its outcome
 export async function sendPayloadTx(
   client: AptosClient,
   account: AptosAccount,
@@ -544,9 +1206,13 @@ export async function sendPayloadTx(
   const txnRequest = await client.generateTransaction(account.address(), payload, {{max_gas_amount: `${{max_gas}}`}});
   const signedTxn = await client.signTransaction(account, txnRequest);
   const txnResult = await client.submitTransaction(signedTxn);
+  console.log(`Submitted ${{txnResult.hash}}`);
+  if (!program.opts().wait) {{
+    return;
+  }}
   await client.waitForTransaction(txnResult.hash);
   const txDetails = (await client.getTransactionByHash(txnResult.hash)) as Types.UserTransaction;
-  console.log(txDetails);
+  printTransactionOutcome(txDetails);
 }}
 
 const program = new Command();
@@ -556,6 +1222,8 @@ program
   .description('Move TS CLI generated by move-to-ts')
   .requiredOption('-c, --config <path>', 'path to your aptos config.yml (generated with "aptos init")')
   .option('-p, --profile <PROFILE>', 'aptos config profile to use', 'default')
+  .option('-i, --interactive', 'prompt for missing arguments of commands, and confirm transactions before submitting')
+  .option('--no-wait', 'do not wait for submitted transactions, only print their hash')
 
 {}
 
@@ -564,6 +1232,7 @@ program
 program.parse();
 "###,
         package_imports,
+        coin_resolver,
         commands.join("\n"),
         printers.join("\n"),
     );
@@ -0,0 +1,145 @@
+use crate::ast_to_ts::{is_type_signer, script_function_has_valid_parameter, write_parameters};
+use crate::shared::*;
+use crate::tsgen_writer::TsgenWriter;
+use crate::utils::rename;
+use itertools::Itertools;
+use move_compiler::diagnostics::Diagnostics;
+use move_compiler::expansion::ast::ModuleIdent;
+use move_compiler::hlir::ast::Function;
+use move_compiler::parser::ast::FunctionName;
+
+/// Entry functions of the current module that a single account can submit and that have a
+/// buildPayload_ to hand to the node
+fn difftest_functions(mident: &ModuleIdent, c: &Context) -> Vec<(FunctionName, Function)> {
+    let mdef = match c.program.modules.get(mident) {
+        Some(mdef) => mdef,
+        None => return vec![],
+    };
+    let functions = mdef
+        .functions
+        .key_cloned_iter()
+        .filter(|(_, func)| {
+            has_payload_builder(func)
+                && script_function_has_valid_parameter(&func.signature)
+                && func
+                    .signature
+                    .parameters
+                    .iter()
+                    .filter(|(_, ty)| is_type_signer(ty))
+                    .count()
+                    <= 1
+        })
+        .map(|(name, func)| (name, func.clone()));
+    functions.collect()
+}
+
+/*
+Cases for $.runDiffTest, one builder per entry function. The builder takes the account that sends
+the call, the accounts whose resources are compared, and the arguments of buildPayload_; the signer
+of the translated call is the sending account.
+ */
+pub fn generate_difftest(
+    mident: &ModuleIdent,
+    c: &mut Context,
+) -> Result<Option<(String, String)>, Diagnostics> {
+    let functions = difftest_functions(mident, c);
+    if functions.is_empty() {
+        return Ok(None);
+    }
+    let mut w = TsgenWriter::new();
+    match write_difftest(mident, &functions, &mut w, c) {
+        Ok(_) => {
+            let filename = format!("{}.difftest.ts", c.module_path(mident));
+            Ok(Some((filename, format!("{}", w))))
+        }
+        Err(diag) => {
+            let mut diags = Diagnostics::new();
+            diags.add(diag);
+            Err(diags)
+        }
+    }
+}
+
+fn write_difftest(
+    mident: &ModuleIdent,
+    functions: &[(FunctionName, Function)],
+    w: &mut TsgenWriter,
+    c: &mut Context,
+) -> WriteResult {
+    // difftest files mirror the source layout under src/difftest
+    let module_path = c.module_path(mident);
+    w.writeln(format!(
+        "import * as Source from '{}{}';",
+        "../".repeat(module_path.matches('/').count() + 1),
+        module_path
+    ));
+    w.writeln("import * as $ from '@manahippo/move-to-ts';");
+    w.writeln("import { U8, U64, U128, TypeTag } from '@manahippo/move-to-ts';");
+    w.writeln("import { AptosAccount, HexString } from 'aptos';");
+    w.new_line();
+
+    let async_modifier = if c.is_async() { "async " } else { "" };
+    let address = format_address_hex(mident.value.address);
+    for (name, func) in functions.iter() {
+        let num_tparams = func.signature.type_parameters.len();
+        let local_name = c.local_function_ts_name(name);
+        w.writeln(format!("export function {}Case(", local_name));
+        w.writeln("  account: AptosAccount,");
+        w.writeln("  addresses: HexString[],");
+        write_parameters(&func.signature, w, c, true, false)?;
+        if num_tparams > 0 {
+            w.writeln("  $p: TypeTag[],");
+        }
+        w.writeln(") : $.DiffTestCase {");
+        w.increase_indent();
+
+        let payload_args = func
+            .signature
+            .parameters
+            .iter()
+            .filter(|(_, ty)| !is_type_signer(ty))
+            .map(|(pname, _)| rename(pname))
+            .chain((num_tparams > 0).then(|| "$p".to_string()))
+            .join(", ");
+        let call_args = func
+            .signature
+            .parameters
+            .iter()
+            .map(|(pname, ty)| {
+                if is_type_signer(ty) {
+                    "account.address()".to_string()
+                } else {
+                    rename(pname)
+                }
+            })
+            .chain(std::iter::once("$c".to_string()))
+            .chain((num_tparams > 0).then(|| "$p".to_string()))
+            .join(", ");
+
+        w.writeln("return {");
+        w.increase_indent();
+        w.writeln(format!(
+            "name: {},",
+            quote(&format!("{}::{}::{}", address, mident.value.module, name))
+        ));
+        w.writeln(format!(
+            "payload: Source.buildPayload_{}({}),",
+            local_name, payload_args
+        ));
+        w.writeln(format!(
+            "runLocal: {}($c: $.AptosLocalCache) => {{ {}({}); }},",
+            async_modifier,
+            format_function_name(&format!("Source.{}", local_name), c.is_async()),
+            call_args
+        ));
+        w.writeln("addresses,");
+        w.decrease_indent();
+        w.writeln("};");
+
+        w.decrease_indent();
+        w.writeln("}");
+        w.new_line();
+    }
+
+    Ok(())
+}
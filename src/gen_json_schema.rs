@@ -0,0 +1,144 @@
+use crate::ast_to_ts::{
+    function_provenance, is_type_signer, json_schema_for_script_function_param,
+};
+use crate::inspect::json_string;
+use crate::shared::*;
+use crate::tsgen_writer::TsgenWriter;
+use itertools::Itertools;
+use move_compiler::diagnostics::Diagnostics;
+use move_compiler::expansion::ast::ModuleIdent;
+use move_compiler::hlir::ast::Function;
+use move_compiler::parser::ast::FunctionName;
+
+/*
+One JSON Schema document per entry function that gets a buildPayload_, describing its inputs for
+form builders: `typeArguments`, one type string per type parameter, and `arguments`, keyed by the
+Move names of the parameters other than signers. With --emit-provenance, `provenance` holds the
+source file and byte range of the function like source_<fn> does. Written to
+schemas/<address>/<module>/<fn>.json
+ */
+pub fn generate_json_schemas(
+    mident: &ModuleIdent,
+    c: &Context,
+) -> Result<Vec<(String, String)>, Diagnostics> {
+    let mdef = match c.program.modules.get(mident) {
+        Some(mdef) => mdef,
+        None => return Ok(vec![]),
+    };
+    let functions = mdef
+        .functions
+        .key_cloned_iter()
+        .filter(|(_, func)| has_payload_builder(func))
+        .map(|(name, func)| (name, func.clone()));
+    let mut files = vec![];
+    for (name, func) in functions {
+        let address = concrete_address_hex(mident, name.0.loc).map_err(|diag| {
+            let mut diags = Diagnostics::new();
+            diags.add(diag);
+            diags
+        })?;
+        if let Some(content) = write_json_schema(mident, &address, &name, &func, c) {
+            let filename = format!("{}/{}/{}.json", address, mident.value.module, name);
+            files.push((filename, content));
+        }
+    }
+    Ok(files)
+}
+
+// None for functions with a parameter that buildPayload_ cannot take either
+fn write_json_schema(
+    mident: &ModuleIdent,
+    address: &str,
+    name: &FunctionName,
+    func: &Function,
+    c: &Context,
+) -> Option<String> {
+    let params = func
+        .signature
+        .parameters
+        .iter()
+        .filter(|(_, ty)| !is_type_signer(ty))
+        .map(|(pname, ty)| {
+            json_schema_for_script_function_param(pname, ty)
+                .ok()
+                .map(|schema| (pname.to_string(), schema))
+        })
+        .collect::<Option<Vec<_>>>()?;
+    let tparams = func
+        .signature
+        .type_parameters
+        .iter()
+        .map(|tp| tp.user_specified_name.to_string())
+        .collect::<Vec<_>>();
+
+    let mut w = TsgenWriter::new();
+    w.writeln("{");
+    w.increase_indent();
+    w.writeln("\"$schema\": \"https://json-schema.org/draft/2020-12/schema\",");
+    w.writeln(format!(
+        "\"title\": {},",
+        json_string(&format!("{}::{}::{}", address, mident.value.module, name))
+    ));
+    if let Some((file, start, end)) = function_provenance(name, func, c) {
+        w.writeln(format!(
+            "\"provenance\": {{\"file\":{},\"start\":{},\"end\":{}}},",
+            json_string(&file),
+            start,
+            end
+        ));
+    }
+    w.writeln("\"type\": \"object\",");
+    w.writeln("\"properties\": {");
+    w.increase_indent();
+    if !tparams.is_empty() {
+        let items = tparams
+            .iter()
+            .map(|tp| {
+                format!(
+                    "{{\"title\":{},\"type\":\"string\",\"description\":\"Move type, e.g. 0x1::aptos_coin::AptosCoin\"}}",
+                    json_string(tp)
+                )
+            })
+            .join(",");
+        w.writeln(format!(
+            "\"typeArguments\": {{\"type\":\"array\",\"prefixItems\":[{}],\"minItems\":{n},\"maxItems\":{n}}},",
+            items,
+            n = tparams.len()
+        ));
+    }
+    w.writeln("\"arguments\": {");
+    w.increase_indent();
+    w.writeln("\"type\": \"object\",");
+    w.writeln("\"properties\": {");
+    w.increase_indent();
+    for (i, (pname, schema)) in params.iter().enumerate() {
+        let comma = if i + 1 < params.len() { "," } else { "" };
+        w.writeln(format!("{}: {}{}", json_string(pname), schema, comma));
+    }
+    w.decrease_indent();
+    w.writeln("},");
+    w.writeln(format!(
+        "\"required\": [{}],",
+        params
+            .iter()
+            .map(|(pname, _)| json_string(pname))
+            .join(", ")
+    ));
+    w.writeln("\"additionalProperties\": false");
+    w.decrease_indent();
+    w.writeln("}");
+    w.decrease_indent();
+    w.writeln("},");
+    w.writeln(format!(
+        "\"required\": [{}\"arguments\"],",
+        if tparams.is_empty() {
+            ""
+        } else {
+            "\"typeArguments\", "
+        }
+    ));
+    w.writeln("\"additionalProperties\": false");
+    w.decrease_indent();
+    w.writeln("}");
+    Some(format!("{}\n", w.into_string().trim_end()))
+}
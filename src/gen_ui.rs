@@ -2,9 +2,12 @@ use crate::ast_to_ts::is_type_signer;
 use crate::gen_cli::{format_qualified_payload_fname_and_import, stype_to_ts_parser};
 use crate::shared::*;
 use crate::tsgen_writer::TsgenWriter;
+use crate::utils::rename;
 use itertools::Itertools;
 use move_compiler::diagnostics::{Diagnostic, Diagnostics};
-use move_compiler::hlir::ast::{BaseType, BaseType_, SingleType, SingleType_, TypeName_};
+use move_compiler::hlir::ast::{
+    BaseType, BaseType_, SingleType, SingleType_, StructFields, TypeName_,
+};
 use move_compiler::naming::ast::{BuiltinTypeName_, TParam};
 use move_compiler::parser::ast::Var;
 use move_ir_types::location::Loc;
@@ -35,7 +38,7 @@ pub fn base_type_to_typetag_builder(base_ty: &BaseType, tparams: &Vec<TParam>) -
                 BuiltinTypeName_::Signer => Ok("AtomicTypeTag.Signer".to_string()),
             },
             TypeName_::ModuleType(mident, sname) => {
-                let address = format_address_hex(mident.value.address);
+                let address = concrete_address_hex(mident, base_ty.loc)?;
                 let modname = mident.value.module;
                 let mut tparam_parts = vec![];
                 for base in ss.iter() {
@@ -77,7 +80,7 @@ pub fn write_cmd_arg(
     Ok(())
 }
 
-pub fn write_command(cmd: &CmdParams, w: &mut TsgenWriter) -> TermResult {
+pub fn write_command(cmd: &CmdParams, w: &mut TsgenWriter, c: &Context) -> TermResult {
     w.writeln("{");
     w.increase_indent();
 
@@ -146,7 +149,7 @@ pub fn write_command(cmd: &CmdParams, w: &mut TsgenWriter) -> TermResult {
             stype_to_ts_parser(&format!("{}_", pname), pname.0.loc, ptype)?
         ));
     }
-    let (payload_builder, package_name) = format_qualified_payload_fname_and_import(&cmd.mi, &cmd.fname);
+    let (payload_builder, package_name) = format_qualified_payload_fname_and_import(&cmd.mi, &cmd.fname, c);
     w.writeln(format!(
         "const payload = {}({}{});",
         payload_builder,
@@ -182,6 +185,7 @@ pub fn write_module(
     cmds: &Vec<&CmdParams>,
     all_imported_packages: &mut BTreeSet<String>,
     w: &mut TsgenWriter,
+    c: &Context,
 ) -> WriteResult {
     w.writeln("{");
     w.increase_indent();
@@ -192,7 +196,7 @@ pub fn write_module(
     w.increase_indent();
 
     for cmd in cmds.iter() {
-        all_imported_packages.insert(write_command(cmd, w)?);
+        all_imported_packages.insert(write_command(cmd, w, c)?);
     }
 
     w.decrease_indent();
@@ -209,6 +213,7 @@ pub fn write_package(
     module_cmds: &Vec<(&String, &Vec<&CmdParams>)>,
     all_imported_packages: &mut BTreeSet<String>,
     w: &mut TsgenWriter,
+    c: &Context,
 ) -> WriteResult {
     w.writeln("{");
     w.increase_indent();
@@ -217,12 +222,37 @@ pub fn write_package(
     w.writeln("modules: [");
     w.increase_indent();
     for (module, cmds) in module_cmds.into_iter() {
-        write_module(name, module, cmds, all_imported_packages, w)?;
+        write_module(name, module, cmds, all_imported_packages, w, c)?;
     }
     w.decrease_indent();
     w.writeln("],");
 
+    // structs of the listed modules, the nav only offers the resources among them
     w.writeln("shows: [");
+    w.increase_indent();
+    for (_, cmds) in module_cmds.iter() {
+        let mi = cmds[0].mi;
+        let mdef = match c.program.modules.get(&mi) {
+            Some(mdef) => mdef,
+            None => continue,
+        };
+        for (sname, sdef) in mdef.structs.key_cloned_iter() {
+            // native structs get no parser
+            if let StructFields::Native(_) = sdef.fields {
+                continue;
+            }
+            w.writeln(format!(
+                "{{ module: {}, name: {}, type: \"show\", struct: {}$_.{}.{} }},",
+                quote(&mi.value.module),
+                quote(&sname),
+                name,
+                c.module_namespace(&mi),
+                rename(&sname)
+            ));
+            all_imported_packages.insert(name.clone());
+        }
+    }
+    w.decrease_indent();
     w.writeln("],");
     w.decrease_indent();
     w.writeln("},");
@@ -235,7 +265,7 @@ pub fn generate_ui(ctx: &mut Context) -> Result<Vec<(String, String)>, Diagnosti
 import React, { useState } from 'react';
 import { Input } from 'semantic-ui-react';
 import ReactDOM from 'react-dom/client';
-import { AptosParserRepo, getTypeTagFullname, StructTag, TypeTag, AtomicTypeTag, VectorTag, parseTypeTagOrThrow, u8, u64, u128, print, strToU8, u8str, DummyCache }
+import { AptosParserRepo, getTypeTagFullname, StructTag, TypeTag, AtomicTypeTag, VectorTag, parseTypeTagOrThrow, u8, u64, u128, print, strToU8, u8str, DummyCache, isResource, StructInfoType }
 from
 "@manahippo/move-to-ts";
 import './index.css';
@@ -259,6 +289,7 @@ interface IShow {
   module: string;
   name: string;
   type: "show",
+  struct: StructInfoType;
 }
 
 interface IModule {
@@ -308,7 +339,7 @@ const App = () => {
           {pkg.modules.map(NavModule)}
         </div>
         <div>
-          {pkg.shows.map(NavShow)}
+          {pkg.shows.filter(show => isResource(show.struct)).map(NavShow)}
         </div>
       </div>
     )
@@ -379,7 +410,14 @@ const App = () => {
 
   const ShowContainer = (show: IShow) => {
     return (
-      <h1 className="ui header">{`Show: ${show.name}`}</h1>
+      <div className="ShowContainer">
+        <h1 className="ui header">{`Show: ${show.name}`}</h1>
+        <div className="ShowContent">
+          <div>Module: {show.module}</div>
+          {Array.from({length: show.struct.typeParamCount ?? 0}, (_, i) => TypeArgInput(`T${i}`))}
+          <Input label="address" placeholder="0x..."></Input>
+        </div>
+      </div>
     )
   }
 
@@ -406,7 +444,7 @@ const App = () => {
     let mut package_names: BTreeSet<String> = BTreeSet::new();
     let mut packaged_cmds: BTreeMap<(String, String), Vec<&CmdParams>> = BTreeMap::new();
     for cmd in ctx.cmds.iter() {
-        let pkg_name = format_address(cmd.mi.value.address);
+        let pkg_name = ctx.package_name(&cmd.mi);
         package_names.insert(pkg_name.clone());
         let module_name = cmd.mi.value.module.to_string();
         if let Some(inner_cmds) = packaged_cmds.get_mut(&(pkg_name.clone(), module_name.clone())) {
@@ -435,6 +473,7 @@ const App = () => {
             &package_modules,
             &mut all_imported_packages,
             &mut writer,
+            ctx,
         ) {
             let mut diags = Diagnostics::new();
             diags.add(diag);
@@ -479,7 +518,13 @@ export async function sendPayloadTx(
 
     let imports = all_imported_packages
         .iter()
-        .map(|pname| format!("import * as {}$_ from './{}';", pname, pname))
+        .map(|pname| {
+            format!(
+                "import * as {}$_ from '{}';",
+                pname,
+                ctx.package_import_path(&ctx.package_dir_by_name(pname), false)
+            )
+        })
         .join("\n");
 
     let index_tsx = format!("{}\n{}", imports, writer.to_string());
@@ -0,0 +1,327 @@
+use crate::ast_to_ts::{
+    check_function_directive, check_struct_directive, script_function_invalid_parameters,
+};
+use crate::shared::{ability_names, format_address, has_payload_builder, visibility_name};
+use move_compiler::expansion::ast::{Attribute_, ModuleIdent};
+use move_compiler::hlir::ast::{
+    BaseType, BaseType_, Function, Program, SingleType, SingleType_, StructDefinition, TypeName_,
+};
+use move_compiler::parser::ast::{FunctionName, StructName};
+
+/*
+Summary of what move-to-ts would generate for a package, computed from the HLIR without writing
+any files. Used by the `inspect` subcommand.
+ */
+
+pub struct EntryFunctionReport {
+    pub name: String,
+    pub type_params: Vec<String>,
+    pub params: Vec<(String, String)>,
+    pub directives: Vec<String>,
+    // parameters for which buildPayload_ cannot be generated, with the reason
+    pub problems: Vec<String>,
+}
+
+pub struct StructReport {
+    pub name: String,
+    pub type_params: Vec<String>,
+    pub abilities: Vec<String>,
+    pub directives: Vec<String>,
+    pub problems: Vec<String>,
+}
+
+pub struct ModuleReport {
+    pub name: String,
+    pub entry_functions: Vec<EntryFunctionReport>,
+    pub structs: Vec<StructReport>,
+}
+
+#[derive(Default)]
+pub struct InspectReport {
+    pub modules: Vec<ModuleReport>,
+    pub num_cmds: usize,
+    pub num_queries: usize,
+    pub num_methods: usize,
+}
+
+pub fn inspect_program(program: &Program) -> InspectReport {
+    let mut report = InspectReport::default();
+    for (mident, mdef) in program.modules.key_cloned_iter() {
+        let mut module = ModuleReport {
+            name: format_module_name(&mident),
+            entry_functions: vec![],
+            structs: vec![],
+        };
+        for (fname, func) in mdef.functions.key_cloned_iter() {
+            if func.entry.is_none() {
+                continue;
+            }
+            let entry = inspect_function(&fname, func, &mut report);
+            module.entry_functions.push(entry);
+        }
+        for (sname, sdef) in mdef.structs.key_cloned_iter() {
+            let sreport = inspect_struct(&sname, sdef, &mut report);
+            module.structs.push(sreport);
+        }
+        report.modules.push(module);
+    }
+    report
+}
+
+fn format_module_name(mident: &ModuleIdent) -> String {
+    format!(
+        "{}::{}",
+        format_address(mident.value.address),
+        mident.value.module
+    )
+}
+
+fn inspect_function(
+    fname: &FunctionName,
+    func: &Function,
+    report: &mut InspectReport,
+) -> EntryFunctionReport {
+    let mut directives = vec![];
+    let mut problems = vec![];
+    for (name, attr) in func.attributes.key_cloned_iter() {
+        let directive = name.to_string();
+        match check_function_directive(fname, func, &directive, attr) {
+            Err((_, msg)) => problems.push(msg),
+            Ok(()) => match directive.as_str() {
+                "cmd" => report.num_cmds += 1,
+                "query" => report.num_queries += 1,
+                _ => (),
+            },
+        }
+        directives.push(directive);
+    }
+    if !has_payload_builder(func) {
+        problems.push(format!(
+            "{} function, buildPayload_ is only generated for public or #[randomness] entry functions",
+            visibility_name(&func.visibility)
+        ));
+    }
+    for (var, (_, msg)) in script_function_invalid_parameters(&func.signature) {
+        problems.push(format!("{}: {}", var, msg));
+    }
+    EntryFunctionReport {
+        name: fname.to_string(),
+        type_params: func
+            .signature
+            .type_parameters
+            .iter()
+            .map(|tp| tp.user_specified_name.to_string())
+            .collect(),
+        params: func
+            .signature
+            .parameters
+            .iter()
+            .map(|(var, ty)| (var.to_string(), format_single_type(ty)))
+            .collect(),
+        directives,
+        problems,
+    }
+}
+
+fn inspect_struct(
+    sname: &StructName,
+    sdef: &StructDefinition,
+    report: &mut InspectReport,
+) -> StructReport {
+    let mut directives = vec![];
+    let mut problems = vec![];
+    for (name, attr) in sdef.attributes.key_cloned_iter() {
+        let directive = name.to_string();
+        match check_struct_directive(&directive, attr) {
+            Err((_, msg)) => problems.push(msg),
+            Ok(()) => {
+                if let ("method", Attribute_::Parameterized(_, inner_attrs)) =
+                    (directive.as_str(), &attr.value)
+                {
+                    report.num_methods += inner_attrs.len();
+                }
+            }
+        }
+        directives.push(directive);
+    }
+    let abilities = ability_names(&sdef.abilities)
+        .iter()
+        .map(|name| name.to_string())
+        .collect();
+    StructReport {
+        name: sname.to_string(),
+        type_params: sdef
+            .type_parameters
+            .iter()
+            .map(|tp| tp.param.user_specified_name.to_string())
+            .collect(),
+        abilities,
+        directives,
+        problems,
+    }
+}
+
+fn format_base_type(ty: &BaseType) -> String {
+    match &ty.value {
+        BaseType_::Param(tp) => tp.user_specified_name.to_string(),
+        BaseType_::Apply(_, typename, targs) => {
+            let name = match &typename.value {
+                TypeName_::Builtin(builtin) => builtin.value.to_string(),
+                TypeName_::ModuleType(mident, sname) => {
+                    format!("{}::{}", mident.value.module, sname)
+                }
+            };
+            if targs.is_empty() {
+                name
+            } else {
+                let args = targs.iter().map(format_base_type).collect::<Vec<_>>();
+                format!("{}<{}>", name, args.join(", "))
+            }
+        }
+        BaseType_::Unreachable | BaseType_::UnresolvedError => "_".to_string(),
+    }
+}
+
+pub fn format_single_type(ty: &SingleType) -> String {
+    match &ty.value {
+        SingleType_::Base(b) => format_base_type(b),
+        SingleType_::Ref(true, b) => format!("&mut {}", format_base_type(b)),
+        SingleType_::Ref(false, b) => format!("&{}", format_base_type(b)),
+    }
+}
+
+pub fn format_report_text(report: &InspectReport) -> String {
+    let mut out = String::new();
+    for module in report.modules.iter() {
+        out.push_str(&format!("module {}\n", module.name));
+        for func in module.entry_functions.iter() {
+            let tparams = if func.type_params.is_empty() {
+                "".to_string()
+            } else {
+                format!("<{}>", func.type_params.join(", "))
+            };
+            let params = func
+                .params
+                .iter()
+                .map(|(name, ty)| format!("{}: {}", name, ty))
+                .collect::<Vec<_>>();
+            out.push_str(&format!(
+                "  entry fun {}{}({})",
+                func.name,
+                tparams,
+                params.join(", ")
+            ));
+            for directive in func.directives.iter() {
+                out.push_str(&format!(" #[{}]", directive));
+            }
+            out.push('\n');
+            for problem in func.problems.iter() {
+                out.push_str(&format!("    ! {}\n", problem));
+            }
+        }
+        for sdef in module.structs.iter() {
+            let tparams = if sdef.type_params.is_empty() {
+                "".to_string()
+            } else {
+                format!("<{}>", sdef.type_params.join(", "))
+            };
+            out.push_str(&format!("  struct {}{}", sdef.name, tparams));
+            if !sdef.abilities.is_empty() {
+                out.push_str(&format!(" has {}", sdef.abilities.join(", ")));
+            }
+            for directive in sdef.directives.iter() {
+                out.push_str(&format!(" #[{}]", directive));
+            }
+            out.push('\n');
+            for problem in sdef.problems.iter() {
+                out.push_str(&format!("    ! {}\n", problem));
+            }
+        }
+    }
+    out.push_str(&format!(
+        "\n{} cmd, {} query, {} method\n",
+        report.num_cmds, report.num_queries, report.num_methods
+    ));
+    out
+}
+
+pub(crate) fn json_string(s: &str) -> String {
+    let mut out = String::from("\"");
+    for ch in s.chars() {
+        match ch {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+fn json_string_list(items: &[String]) -> String {
+    let items = items.iter().map(|s| json_string(s)).collect::<Vec<_>>();
+    format!("[{}]", items.join(","))
+}
+
+pub fn format_report_json(report: &InspectReport) -> String {
+    let modules = report
+        .modules
+        .iter()
+        .map(|module| {
+            let funcs = module
+                .entry_functions
+                .iter()
+                .map(|func| {
+                    let params = func
+                        .params
+                        .iter()
+                        .map(|(name, ty)| {
+                            format!(
+                                "{{\"name\":{},\"type\":{}}}",
+                                json_string(name),
+                                json_string(ty)
+                            )
+                        })
+                        .collect::<Vec<_>>();
+                    format!(
+                        "{{\"name\":{},\"typeParams\":{},\"params\":[{}],\"directives\":{},\"problems\":{}}}",
+                        json_string(&func.name),
+                        json_string_list(&func.type_params),
+                        params.join(","),
+                        json_string_list(&func.directives),
+                        json_string_list(&func.problems)
+                    )
+                })
+                .collect::<Vec<_>>();
+            let structs = module
+                .structs
+                .iter()
+                .map(|sdef| {
+                    format!(
+                        "{{\"name\":{},\"typeParams\":{},\"abilities\":{},\"directives\":{},\"problems\":{}}}",
+                        json_string(&sdef.name),
+                        json_string_list(&sdef.type_params),
+                        json_string_list(&sdef.abilities),
+                        json_string_list(&sdef.directives),
+                        json_string_list(&sdef.problems)
+                    )
+                })
+                .collect::<Vec<_>>();
+            format!(
+                "{{\"name\":{},\"entryFunctions\":[{}],\"structs\":[{}]}}",
+                json_string(&module.name),
+                funcs.join(","),
+                structs.join(",")
+            )
+        })
+        .collect::<Vec<_>>();
+    format!(
+        "{{\"modules\":[{}],\"totals\":{{\"cmd\":{},\"query\":{},\"method\":{}}}}}",
+        modules.join(","),
+        report.num_cmds,
+        report.num_queries,
+        report.num_methods
+    )
+}
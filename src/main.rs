@@ -1,60 +1,62 @@
 mod ast_exp;
 mod ast_tests;
+mod config;
+mod depgraph;
+mod errmap;
+mod output;
+mod profile;
 pub mod ast_to_ts;
 pub mod gen_cli;
+pub mod gen_difftest;
+pub mod gen_json_schema;
 pub mod gen_ui;
+pub mod inspect;
 mod shared;
 pub mod tsgen_writer;
 pub mod utils;
 
-use crate::gen_cli::generate_cli;
+use crate::gen_cli::{add_extra_cmds, find_coin_registry, generate_cli};
 use crate::gen_ui::{gen_public_html, generate_ui};
-use crate::shared::is_same_package;
+use crate::output::OutputFiles;
+use crate::profile::Profile;
 use crate::utils::{generate_index, generate_topmost_index};
-use clap::Parser;
+use clap::{CommandFactory, FromArgMatches};
 use move_command_line_common::address::NumericalAddress;
 use move_command_line_common::parser::NumberFormat;
-use move_compiler::diagnostics::unwrap_or_report_diagnostics;
+use move_compiler::diagnostics::{
+    report_diagnostics, report_warnings, unwrap_or_report_diagnostics, Diagnostics, FilesSourceText,
+};
+use move_compiler::hlir::ast::Program;
 use move_compiler::shared::PackagePaths;
 use move_compiler::*;
 use move_package::compilation::package_layout::CompiledPackageLayout;
 use move_package::source_package::layout::SourcePackageLayout;
-use shared::{Context, MoveToTsOptions};
+use shared::{
+    package_relative_paths, parse_address_aliases, parse_excluded_modules, parse_skipped_functions,
+    Context, ImportStyle, Layout, MoveToTsCommand, MoveToTsOptions, HELPERS_DIR,
+};
 use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
 use std::process;
 use std::rc::Rc;
+use std::time::Instant;
 
-fn write_file(root_path: &PathBuf, pair: (String, String)) {
-    let (filename, content) = pair;
-    let path_to_save = root_path.join(filename);
-    let parent = path_to_save.parent().unwrap();
-    std::fs::create_dir_all(&parent).expect("Failed to create directory");
-    std::fs::write(path_to_save, content).expect("Failed to write file to output");
-}
-
-fn build(path: &Path, config: &MoveToTsOptions) {
+/// Compiles the package at path (and its dependencies) down to HLIR, reporting diagnostics if any.
+/// Returns the source files, the program and the project root.
+fn compile_package(
+    path: &Path,
+    config: &MoveToTsOptions,
+) -> (FilesSourceText, Rc<Program>, PathBuf) {
     let build_config = move_package::BuildConfig::default();
     let resolution_graph = build_config
         .resolution_graph_for_package(path)
         .expect("Failed to build resolution graph for package");
-    /*
-    1. Go through the compilation pipeline to report diagnostics if any, otherwise retain AST
-       from the typing stage
-    2. feed typing AST through move-tsgen to get files
-    3. write files
-    4. write jest .test.ts files if --test is given
-    5. write cli and ui if --gen-cli and --gen-tui is given
-    6. write package.json and tsconfig.json if --generate-package is given
-    7. generate various index.ts for packages
-     */
     let root_package = &resolution_graph.package_table[&resolution_graph.root_package.package.name];
     let project_root = match &resolution_graph.build_options.install_dir {
         Some(under_path) => under_path.clone(),
         None => resolution_graph.root_package_path.clone(),
     };
 
-    // 1
     let mut named_address_mapping = BTreeMap::new();
 
     resolution_graph
@@ -84,10 +86,13 @@ fn build(path: &Path, config: &MoveToTsOptions) {
         })
         .collect::<Vec<_>>();
 
-    let sources = vec![format!(
-        "{}/sources",
-        root_package.package_path.to_string_lossy()
-    )];
+    let root_sources = format!("{}/sources", root_package.package_path.to_string_lossy());
+    // a package that only gathers dependencies may come without a sources directory
+    let sources = if Path::new(&root_sources).is_dir() {
+        vec![root_sources]
+    } else {
+        vec![]
+    };
 
     let mut source_package_paths = vec![PackagePaths {
         name: Some(root_package.source_package.package.name),
@@ -97,6 +102,8 @@ fn build(path: &Path, config: &MoveToTsOptions) {
 
     source_package_paths.append(&mut dependencies);
 
+    // every package is compiled as a source package, so in test mode the #[test_only] members of
+    // dependencies exist too, e.g. account::create_account_for_test
     let flags = if config.test {
         Flags::testing()
     } else {
@@ -116,6 +123,33 @@ fn build(path: &Path, config: &MoveToTsOptions) {
     let (_, hlir_program) = hlir_compiler.into_ast();
     let hlir_program = Rc::new(hlir_program);
 
+    (files, hlir_program, project_root)
+}
+
+fn build(path: &Path, config: &MoveToTsOptions) {
+    /*
+    1. Go through the compilation pipeline to report diagnostics if any, otherwise retain AST
+       from the typing stage
+    2. feed typing AST through move-tsgen to get files
+    3. collect files, which are only written once everything else succeeded
+    4. write jest .test.ts files if --test is given
+    5. write cli and ui if --gen-cli and --gen-tui is given
+    6. write package.json and tsconfig.json if --generate-package is given
+    7. generate various index.ts for packages, and check that everything but the cli and the
+       tests bundles for browsers
+    8. report warnings, which only fail the run with --deny-warnings
+    9. write the collected files, or compare them against the output directory with --check
+    10. report where the time went with --profile or --profile-json, and write --emit-depgraph
+        and --emit-errmap
+     */
+
+    let mut profile = Profile::new();
+
+    // 1
+    let start = Instant::now();
+    let (files, hlir_program, project_root) = compile_package(path, config);
+    profile.add_pass("compile", start.elapsed());
+
     // run the full pipeline to check errors/warnings
     // move package doesn't provide a way to save intermediate program ast, so rerunning the
     // entire pipeline to check all errors. We need to make a PR upstream to avoid doing repeat
@@ -131,51 +165,161 @@ fn build(path: &Path, config: &MoveToTsOptions) {
      */
 
     // 2 & 3
-    let build_root_path = if config.output_path.clone().into_os_string().is_empty() {
+    let output_root = if config.output_path.clone().into_os_string().is_empty() {
         project_root
             .join(CompiledPackageLayout::Root.path())
             .join("typescript")
     } else {
         config.output_path.clone()
     };
+    // with -o . or any other directory that holds the Move package, the generated package goes to
+    // --output-subdir, so that every run writes, prunes and checks the same files and never
+    // touches Move.toml or the Move sources
+    let build_root_path = if output::overlaps_package(&output_root, path) {
+        let nested = output::nested_output_root(&output_root, path, &config.output_subdir)
+            .unwrap_or_else(|msg| {
+                println!("{}", msg);
+                process::exit(-1);
+            });
+        println!(
+            "The output directory holds the Move package, writing to {}",
+            nested.to_string_lossy()
+        );
+        nested
+    } else {
+        output_root
+    };
     let mut ctx = Context::new(config, hlir_program.clone());
+    if config.emit_provenance {
+        ctx.source_paths = package_relative_paths(&files);
+    }
+    let mut output = OutputFiles::new();
+    unwrap_or_report_diagnostics(&files, ast_to_ts::check_unassigned_addresses(&hlir_program));
+    unwrap_or_report_diagnostics(
+        &files,
+        ast_to_ts::check_module_path_collisions(&hlir_program, &mut ctx),
+    );
+    if hlir_program.modules.is_empty() {
+        println!("Nothing to translate: neither the package nor its dependencies declare modules");
+    }
     for (mident, mdef) in hlir_program.modules.key_cloned_iter() {
+        if ctx.is_excluded_module(&mident) {
+            continue;
+        }
+
         // 2
+        let start = Instant::now();
         let result = ast_to_ts::translate_module(mident, mdef, &mut ctx);
+        let duration = start.elapsed();
 
         let (filename, content) = unwrap_or_report_diagnostics(&files, result);
+        profile.add_module(mident.to_string(), duration, content.len());
 
         // 3
-        write_file(&build_root_path.join("src"), (filename, content));
+        output.add("src", (filename, content));
+        if let Some((sname, _)) = mdef.structs.key_cloned_iter().next() {
+            let file = utils::generate_extension_file(&mident, &sname.to_string(), &ctx);
+            output.add_user_owned("src", file);
+        }
 
         // 4 tests
-        if config.test && !ctx.tests.is_empty() {
-            let test_res = ast_tests::generate_tests(&mut ctx);
+        let has_tests = !(ctx.tests.is_empty()
+            && ctx.skipped_tests.is_empty()
+            && ast_tests::math_parity_functions(&ctx).is_empty());
+        if config.test && has_tests {
+            let start = Instant::now();
+            let test_res = ast_tests::generate_tests(&mident, &mut ctx);
+            profile.add_pass("tests", start.elapsed());
             let (filename, content) = unwrap_or_report_diagnostics(&files, test_res);
-            write_file(&build_root_path.join("src/tests"), (filename, content));
+            output.add("src/tests", (filename, content));
+        }
+
+        // differential test cases, which call the translated entry functions
+        if config.gen_difftest && !config.payload_only {
+            let start = Instant::now();
+            let result = gen_difftest::generate_difftest(&mident, &mut ctx);
+            profile.add_pass("difftest", start.elapsed());
+            if let Some(file) = unwrap_or_report_diagnostics(&files, result) {
+                output.add("src/difftest", file);
+            }
+        }
+
+        // input schemas of the entry functions, for form builders
+        if config.emit_json_schema {
+            let start = Instant::now();
+            let result = gen_json_schema::generate_json_schemas(&mident, &ctx);
+            profile.add_pass("json-schema", start.elapsed());
+            for file in unwrap_or_report_diagnostics(&files, result) {
+                output.add("schemas", file);
+            }
+        }
+    }
+
+    // opaque stubs of the excluded modules whose structs translated modules refer to
+    for mident in ctx.stubbed_modules.clone().iter() {
+        if let Some(mdef) = hlir_program.modules.get(mident) {
+            let result = ast_to_ts::generate_module_stub(mident, mdef, &mut ctx);
+            let (filename, content) = unwrap_or_report_diagnostics(&files, result);
+            output.add("src", (filename, content));
+        }
+    }
+
+    // helpers of the special modules, shared by every package in the output tree
+    for (filename, content) in ctx.helper_files.iter() {
+        output.add(
+            &format!("src/{}", HELPERS_DIR),
+            (filename.clone(), content.clone()),
+        );
+    }
+
+    if config.cli || config.ui {
+        if let Err(errors) = add_extra_cmds(&mut ctx) {
+            for error in errors.iter() {
+                println!("{}", error);
+            }
+            process::exit(1);
         }
     }
 
-    // 5
-    if config.cli {
-        let (filename, content) = unwrap_or_report_diagnostics(&files, generate_cli(&ctx));
-        write_file(&build_root_path.join("src"), (filename, content));
+    // 5, skipped when there is nothing to offer, e.g. for a package without source modules
+    let has_commands = !(ctx.cmds.is_empty()
+        && ctx.printer_methods.is_empty()
+        && ctx.queries.is_empty()
+        && ctx.all_shows_iter_tables.is_empty());
+    if config.cli && !has_commands {
+        println!(
+            "No cli is generated, as nothing has a #[cmd], #[method], #[query] or \
+             #[show_iter_table] directive"
+        );
+    }
+    if config.cli && has_commands {
+        let coin_registry = config.coin_registry.as_ref().map(|spec| {
+            find_coin_registry(&hlir_program, spec).unwrap_or_else(|msg| {
+                println!("{}", msg);
+                process::exit(1);
+            })
+        });
+        let start = Instant::now();
+        let result = generate_cli(&mut ctx, coin_registry.as_ref());
+        profile.add_pass("cli", start.elapsed());
+        let (filename, content) = unwrap_or_report_diagnostics(&files, result);
+        output.add("src", (filename, content));
     }
 
-    if config.ui {
-        let files = unwrap_or_report_diagnostics(&files, generate_ui(&mut ctx));
+    if config.ui && ctx.cmds.is_empty() {
+        println!("No ui is generated, as no function has a #[cmd] directive");
+    }
+    if config.ui && !ctx.cmds.is_empty() {
+        let start = Instant::now();
+        let result = generate_ui(&mut ctx);
+        profile.add_pass("ui", start.elapsed());
+        let files = unwrap_or_report_diagnostics(&files, result);
         for (filename, content) in files.iter() {
-            write_file(
-                &build_root_path.join("src"),
-                (filename.clone(), content.clone()),
-            );
+            output.add("src", (filename.clone(), content.clone()));
         }
 
         let (filename, content) = gen_public_html();
-        write_file(
-            &build_root_path.join("public"),
-            (filename.clone(), content.clone()),
-        );
+        output.add("public", (filename, content));
     }
 
     // 6
@@ -183,43 +327,160 @@ fn build(path: &Path, config: &MoveToTsOptions) {
         // package.json
         let (filename, content) =
             utils::generate_package_json(config.package_json_name.clone(), config.cli, config.ui);
-        write_file(&build_root_path, (filename, content));
+        output.add("", (filename, content));
 
         // tsconfig.json
         let (filename, content) = utils::generate_ts_config();
-        write_file(&build_root_path, (filename, content));
+        output.add("", (filename, content));
 
         // jest.config.js
         if config.test {
-            let (filename, content) = utils::generate_jest_config();
-            write_file(&build_root_path, (filename, content));
+            let (filename, content) = utils::generate_jest_config(ctx.emits_coverage());
+            output.add("", (filename, content));
+        }
+        if ctx.emits_coverage() {
+            for file in utils::generate_jest_coverage_hooks() {
+                output.add("", file);
+            }
         }
     }
 
+    if ctx.emits_coverage() {
+        output.add("", utils::generate_coverage_functions(&ctx));
+    }
+
     // 7
-    for (package_name, address) in ctx.visited_packages.iter() {
+    let start = Instant::now();
+    for package_name in ctx.visited_packages.keys() {
         let modules = ctx
             .visited_modules
             .iter()
-            .filter(|mi| is_same_package(mi.value.address, *address))
+            .filter(|mi| ctx.package_name(mi) == *package_name && !ctx.in_tests_subtree(mi))
             .collect::<Vec<_>>();
 
-        let (filename, content) = generate_index(package_name, &modules);
-        write_file(&build_root_path.join("src"), (filename, content));
+        let (filename, content) = generate_index(package_name, &modules, &ctx);
+        output.add("src", (filename, content));
+
+        // each package gets published on its own, so it has to declare the siblings it imports
+        if let ImportStyle::Scoped(scope) = &config.external_package_import_style {
+            let package_dir = ctx.package_dir_by_name(package_name);
+            let deps = ctx.package_dependencies.get(&package_dir);
+            let (filename, content) =
+                utils::generate_scoped_package_json(scope, &package_dir, deps);
+            output.add("src", (filename, content));
+        }
     }
 
     // cannot generat topmost index.ts when generating a React playground
-    if !config.ui {
+    if !config.ui && !ctx.visited_packages.is_empty() {
         let package_names = ctx.visited_packages.keys().collect::<Vec<_>>();
-        write_file(
-            &build_root_path.join("src"),
-            generate_topmost_index(&package_names),
+        output.add("src", generate_topmost_index(&package_names, &ctx));
+    }
+    profile.add_pass("index", start.elapsed());
+    let node_only_uses = output.node_only_uses();
+    if !node_only_uses.is_empty() {
+        println!("Generated files meant to run in browsers use Node-only APIs:");
+        for line in node_only_uses.iter() {
+            println!("  {}", line);
+        }
+        process::exit(1);
+    }
+
+    // before 8, as it warns about the codes it leaves out
+    let errmap = config
+        .emit_errmap
+        .as_ref()
+        .map(|_| errmap::format_errmap_json(&mut ctx, &files));
+
+    // 8
+    let warnings = std::mem::replace(&mut ctx.warnings, Diagnostics::new());
+    if config.deny_warnings && !warnings.is_empty() {
+        report_diagnostics(&files, warnings);
+    }
+    report_warnings(&files, warnings);
+    // an entry that matches nothing is left over from a function that translates by now
+    let mut stale_entries = false;
+    for (function, reason) in ctx.skipped_functions.iter() {
+        if ctx.applied_skips.contains(function) {
+            println!("Skipped {}: {}", function, reason);
+        } else {
+            println!(
+                "--skip-function {} matches no translated function",
+                function
+            );
+            stale_entries = true;
+        }
+    }
+    for entry in ctx.excluded_modules.iter() {
+        let matched = hlir_program
+            .modules
+            .key_cloned_iter()
+            .any(|(mident, _)| ctx.excluded_module_entry(&mident).as_ref() == Some(entry));
+        if !matched {
+            println!("--exclude-module {} matches no module", entry);
+            stale_entries = true;
+        }
+    }
+    if config.deny_warnings && stale_entries {
+        process::exit(1);
+    }
+
+    // 9
+    let start = Instant::now();
+    let report = if config.check {
+        let report = output.check(&build_root_path);
+        profile.add_pass("check", start.elapsed());
+        Some(report)
+    } else {
+        output.write_all(&build_root_path, config.prune || config.managed_output);
+        profile.add_pass("write", start.elapsed());
+        None
+    };
+
+    // 10
+    if config.profile {
+        let table = profile::format_profile_text(&profile, config.profile_top);
+        print!("{}", table);
+    }
+    if let Some(profile_path) = &config.profile_json {
+        std::fs::write(profile_path, profile::format_profile_json(&profile))
+            .expect("Failed to write profile");
+    }
+    if let Some(depgraph_path) = &config.emit_depgraph {
+        std::fs::write(depgraph_path, depgraph::format_depgraph_dot(&ctx))
+            .expect("Failed to write dependency graph");
+        std::fs::write(
+            depgraph_path.with_extension("json"),
+            depgraph::format_depgraph_json(&ctx),
         )
+        .expect("Failed to write dependency graph");
+    }
+    if let (Some(errmap_path), Some(errmap)) = (&config.emit_errmap, errmap) {
+        std::fs::write(errmap_path, errmap).expect("Failed to write error map");
+    }
+
+    if let Some(report) = report {
+        if !report.is_up_to_date() {
+            print!("{}", report.format());
+            process::exit(1);
+        }
+        println!("Generated files are up to date");
+    }
+}
+
+fn inspect(path: &Path, config: &MoveToTsOptions, json: bool) {
+    let (_files, hlir_program, _) = compile_package(path, config);
+    let report = inspect::inspect_program(&hlir_program);
+    if json {
+        println!("{}", inspect::format_report_json(&report));
+    } else {
+        print!("{}", inspect::format_report_text(&report));
     }
 }
 
 fn main() {
-    let args = MoveToTsOptions::parse();
+    let matches = MoveToTsOptions::command().get_matches();
+    let mut args = MoveToTsOptions::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
 
     let root = SourcePackageLayout::try_find_root(&args.package_path);
     if root.is_err() {
@@ -228,6 +489,52 @@ fn main() {
     }
     let root_path = root.unwrap();
     std::env::set_current_dir(&root_path).unwrap();
-    println!("Working from {}", root_path.to_string_lossy());
-    build(&root_path, &args);
+    let merged = config::load_config_file(&root_path)
+        .and_then(|file| match file {
+            Some(file) => config::merge_config_file(&mut args, file, &matches),
+            None => Ok(()),
+        });
+    if let Err(msg) = merged {
+        println!("{}", msg);
+        process::exit(-1);
+    }
+    if args.print_config {
+        print!("{}", config::format_config(&args));
+        return;
+    }
+    if args.payload_only && args.test {
+        println!("--payload-only does not emit the functions that --gen-test would call");
+        process::exit(-1);
+    }
+    // scoped packages are published from their own directories, which the flat layout lacks
+    if args.layout == Layout::Flat
+        && matches!(args.external_package_import_style, ImportStyle::Scoped(_))
+    {
+        println!("--layout flat has no package directories to publish scoped packages from");
+        process::exit(-1);
+    }
+    if let Err(msg) = parse_address_aliases(&args.address_alias) {
+        println!("{}", msg);
+        process::exit(-1);
+    }
+    if let Err(msg) = parse_skipped_functions(&args.skip_function) {
+        println!("{}", msg);
+        process::exit(-1);
+    }
+    if let Err(msg) = parse_excluded_modules(&args.exclude_module) {
+        println!("{}", msg);
+        process::exit(-1);
+    }
+    if let Err(msg) = output::check_output_subdir(&args.output_subdir) {
+        println!("{}", msg);
+        process::exit(-1);
+    }
+    // keep stdout parseable when the inspect report is printed as JSON
+    if !matches!(args.command, Some(MoveToTsCommand::Inspect { json: true })) {
+        println!("Working from {}", root_path.to_string_lossy());
+    }
+    match &args.command {
+        Some(MoveToTsCommand::Inspect { json }) => inspect(&root_path, &args, *json),
+        None => build(&root_path, &args),
+    }
 }
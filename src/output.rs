@@ -0,0 +1,490 @@
+use crate::utils::{HELPERS_VERSION_MARKER, MODE_MARKER};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Component, Path, PathBuf};
+
+/*
+Files produced by a run, keyed by their path relative to the output root. Nothing touches the
+filesystem until the whole translation went through, so that --check can compare instead of write.
+ */
+#[derive(Default)]
+pub struct OutputFiles {
+    pub files: BTreeMap<PathBuf, String>,
+    // files users edit, e.g. the .ext.ts of a module, only written where there is none yet
+    pub user_owned: BTreeMap<PathBuf, String>,
+}
+
+// directories under the output root that only hold generated files
+const GENERATED_DIRS: [&str; 2] = ["src", "public"];
+
+// Node builtins, which a bundler targeting browsers cannot resolve
+const NODE_BUILTINS: [&str; 12] = [
+    "child_process",
+    "crypto",
+    "fs",
+    "http",
+    "https",
+    "net",
+    "os",
+    "path",
+    "readline",
+    "stream",
+    "url",
+    "util",
+];
+
+// what a Move package keeps next to Move.toml, which --output-subdir must stay clear of
+const MOVE_PACKAGE_ENTRIES: [&str; 7] = [
+    "Move.toml",
+    "build",
+    "doc_templates",
+    "examples",
+    "scripts",
+    "sources",
+    "tests",
+];
+
+// lines of diff shown per changed file by --check
+const MAX_DIFF_LINES: usize = 200;
+
+// lists the files written by the last run, relative to the output root
+pub const MANIFEST_FILE_NAME: &str = ".move-to-ts-manifest";
+
+// marks the manifest lines of user-owned files, which are never pruned
+const USER_OWNED_PREFIX: &str = "user-owned: ";
+
+/// Files written by the previous run, if it left a manifest. User-owned files are left out, so
+/// that neither --prune nor --check ever treats them as generated. So are entries that lead out of
+/// the output directory, which no run writes, from a manifest edited by hand or checked in from
+/// elsewhere
+pub fn read_manifest(root_path: &Path) -> Option<BTreeSet<PathBuf>> {
+    let content = std::fs::read_to_string(root_path.join(MANIFEST_FILE_NAME)).ok()?;
+    let mut paths = BTreeSet::new();
+    for line in content.lines() {
+        if line.is_empty() || line.starts_with(USER_OWNED_PREFIX) {
+            continue;
+        }
+        let path = PathBuf::from(line);
+        if is_below_root(&path) {
+            paths.insert(path);
+        } else {
+            println!(
+                "Ignoring {} in {}, which is not below the output directory",
+                line, MANIFEST_FILE_NAME
+            );
+        }
+    }
+    Some(paths)
+}
+
+// a relative path without `..`, the only kind of path a run writes
+fn is_below_root(path: &Path) -> bool {
+    path.components().next().is_some()
+        && path
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)))
+}
+
+/// Whether the output directory holds the Move package at `package_root`, i.e. is its root or one
+/// of its ancestors, in which case a package.json or --prune at the output root would reach
+/// Move.toml and the Move sources
+pub fn overlaps_package(output_root: &Path, package_root: &Path) -> bool {
+    // an output directory that does not exist yet holds nothing
+    match (output_root.canonicalize(), package_root.canonicalize()) {
+        (Ok(output_root), Ok(package_root)) => package_root.starts_with(output_root),
+        _ => false,
+    }
+}
+
+/// --output-subdir has to be a relative path without `..`, so that it can only lead below the
+/// output directory
+pub fn check_output_subdir(subdir: &Path) -> Result<(), String> {
+    if is_below_root(subdir) {
+        Ok(())
+    } else {
+        Err(format!(
+            "--output-subdir must be a relative path below the output directory, got {}",
+            subdir.to_string_lossy()
+        ))
+    }
+}
+
+/// `path` with the symlinks of its longest existing ancestor resolved, and the rest appended
+fn resolve_existing(path: &Path) -> Option<PathBuf> {
+    let mut missing = vec![];
+    let mut existing = path;
+    loop {
+        if let Ok(resolved) = existing.canonicalize() {
+            return Some(
+                missing
+                    .iter()
+                    .rev()
+                    .fold(resolved, |path, name| path.join(name)),
+            );
+        }
+        missing.push(existing.file_name()?);
+        existing = existing.parent()?;
+    }
+}
+
+/// The directory generated files go to when the output directory holds the Move package at
+/// `package_root`. A subdirectory that exists may be a symlink, e.g. one checked into the package,
+/// so the path is resolved before checking that it stays below the output directory and leads
+/// away from the files of the Move package
+pub fn nested_output_root(
+    output_root: &Path,
+    package_root: &Path,
+    subdir: &Path,
+) -> Result<PathBuf, String> {
+    let nested = output_root.join(subdir);
+    let (resolved, output_root, package_root) = match (
+        resolve_existing(&nested),
+        output_root.canonicalize(),
+        package_root.canonicalize(),
+    ) {
+        (Some(resolved), Ok(output_root), Ok(package_root)) => {
+            (resolved, output_root, package_root)
+        }
+        _ => {
+            return Err(format!(
+                "Cannot resolve --output-subdir {}",
+                nested.to_string_lossy()
+            ))
+        }
+    };
+    if resolved == output_root || !resolved.starts_with(&output_root) {
+        return Err(format!(
+            "--output-subdir {} leads out of the output directory to {}",
+            subdir.to_string_lossy(),
+            resolved.to_string_lossy()
+        ));
+    }
+    if package_root.starts_with(&resolved) {
+        return Err(format!(
+            "--output-subdir {} holds the Move package",
+            subdir.to_string_lossy()
+        ));
+    }
+    if let Some(Component::Normal(first)) = resolved
+        .strip_prefix(&package_root)
+        .ok()
+        .and_then(|inner| inner.components().next())
+    {
+        let first = first.to_string_lossy();
+        if MOVE_PACKAGE_ENTRIES.contains(&first.as_ref()) {
+            return Err(format!(
+                "--output-subdir {} would write into the {} of the Move package",
+                subdir.to_string_lossy(),
+                first
+            ));
+        }
+    }
+    Ok(nested)
+}
+
+impl OutputFiles {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&mut self, dir: &str, pair: (String, String)) {
+        let (filename, content) = pair;
+        self.files.insert(Path::new(dir).join(filename), content);
+    }
+
+    /// A file that is created with `content` once and belongs to the user from then on
+    pub fn add_user_owned(&mut self, dir: &str, pair: (String, String)) {
+        let (filename, content) = pair;
+        self.user_owned
+            .insert(Path::new(dir).join(filename), content);
+    }
+
+    /// Writes all files along with a manifest of them. With `prune`, files listed in the previous
+    /// manifest that this run did not produce are deleted; anything not in it is left alone
+    pub fn write_all(&self, root_path: &Path, prune: bool) {
+        let previous = read_manifest(root_path);
+        for (path, content) in self.files.iter() {
+            let path_to_save = root_path.join(path);
+            let parent = path_to_save.parent().unwrap();
+            std::fs::create_dir_all(&parent).expect("Failed to create directory");
+            std::fs::write(path_to_save, content).expect("Failed to write file to output");
+        }
+        for (path, content) in self.user_owned.iter() {
+            let path_to_save = root_path.join(path);
+            if path_to_save.exists() {
+                continue;
+            }
+            let parent = path_to_save.parent().unwrap();
+            std::fs::create_dir_all(&parent).expect("Failed to create directory");
+            std::fs::write(path_to_save, content).expect("Failed to write file to output");
+        }
+        if let (true, Some(previous)) = (prune, previous) {
+            for path in previous.iter().filter(|p| !self.files.contains_key(*p)) {
+                let path_to_remove = root_path.join(path);
+                if std::fs::remove_file(&path_to_remove).is_ok() {
+                    println!("Removed {}", path.to_string_lossy());
+                    remove_empty_parents(root_path, &path_to_remove);
+                }
+            }
+        }
+        let manifest = self
+            .files
+            .keys()
+            .map(|path| format!("{}\n", path.to_string_lossy()))
+            .chain(
+                self.user_owned
+                    .keys()
+                    .map(|path| format!("{}{}\n", USER_OWNED_PREFIX, path.to_string_lossy())),
+            )
+            .collect::<String>();
+        std::fs::write(root_path.join(MANIFEST_FILE_NAME), manifest)
+            .expect("Failed to write file to output");
+    }
+
+    /// Lines of the generated files meant to run in browsers, i.e. all but the cli and the tests,
+    /// that import a Node builtin or read the process environment, as `<path>:<line>: <text>`
+    pub fn node_only_uses(&self) -> Vec<String> {
+        let mut uses = vec![];
+        for (path, content) in self.files.iter() {
+            if !runs_in_browsers(path) {
+                continue;
+            }
+            for (i, line) in content.lines().enumerate() {
+                if is_node_only_use(line) {
+                    uses.push(format!(
+                        "{}:{}: {}",
+                        path.to_string_lossy(),
+                        i + 1,
+                        line.trim()
+                    ));
+                }
+            }
+        }
+        uses
+    }
+
+    /// Compares the files against what is on disk under `root_path`, without writing anything.
+    /// User-owned files are neither compared nor stale
+    pub fn check(&self, root_path: &Path) -> CheckReport {
+        let mut report = CheckReport::default();
+        for (path, content) in self.files.iter() {
+            match std::fs::read_to_string(root_path.join(path)) {
+                Err(_) => report.added.push(path.clone()),
+                Ok(existing) if existing != *content => {
+                    if let Some(note) = marker_drift(path, &existing, content) {
+                        report.drift.push(note);
+                    }
+                    let diff = unified_diff(path, &existing, content);
+                    report.changed.push((path.clone(), diff));
+                }
+                Ok(_) => (),
+            }
+        }
+        // the manifest tells generated files apart from user-authored ones, without one
+        // everything under the generated directories counts
+        let on_disk = read_manifest(root_path).unwrap_or_else(|| {
+            let mut on_disk = BTreeSet::new();
+            for dir in GENERATED_DIRS.iter() {
+                collect_files(root_path, Path::new(dir), &mut on_disk);
+            }
+            on_disk
+        });
+        report.stale = on_disk
+            .into_iter()
+            .filter(|path| !self.files.contains_key(path) && !self.user_owned.contains_key(path))
+            .collect();
+        report
+    }
+}
+
+fn runs_in_browsers(path: &Path) -> bool {
+    let is_source = matches!(
+        path.extension().and_then(|e| e.to_str()),
+        Some("ts") | Some("tsx")
+    );
+    is_source
+        && path.starts_with("src")
+        && path != Path::new("src/cli.ts")
+        && !path.starts_with("src/tests")
+        && !path.starts_with("src/difftest")
+}
+
+fn is_node_only_use(line: &str) -> bool {
+    line.contains("process.env")
+        || line.contains("\"node:")
+        || line.contains("'node:")
+        || NODE_BUILTINS.iter().any(|name| {
+            [
+                format!("from \"{}\"", name),
+                format!("from '{}'", name),
+                format!("require(\"{}\")", name),
+                format!("require('{}')", name),
+            ]
+            .iter()
+            .any(|pattern| line.contains(pattern.as_str()))
+        })
+}
+
+fn first_line_marker<'a>(content: &'a str, marker: &str) -> Option<&'a str> {
+    content
+        .lines()
+        .next()?
+        .strip_prefix(marker)
+        .map(|v| v.trim())
+}
+
+// explains a change that comes from the first line recording how the file was generated, i.e.
+// the move-to-ts version of a shared helper or the mode of a module
+fn marker_drift(path: &Path, existing: &str, content: &str) -> Option<String> {
+    let path = path.to_string_lossy();
+    if let (Some(old), Some(new)) = (
+        first_line_marker(existing, HELPERS_VERSION_MARKER),
+        first_line_marker(content, HELPERS_VERSION_MARKER),
+    ) {
+        if old != new {
+            return Some(format!(
+                "{} was generated by move-to-ts {}, this is {}",
+                path, old, new
+            ));
+        }
+    }
+    if let (Some(old), Some(new)) = (
+        first_line_marker(existing, MODE_MARKER),
+        first_line_marker(content, MODE_MARKER),
+    ) {
+        if old != new {
+            return Some(format!(
+                "{} was generated in {} mode, this run uses {} mode",
+                path, old, new
+            ));
+        }
+    }
+    None
+}
+
+fn remove_empty_parents(root_path: &Path, removed: &Path) {
+    let mut dir = removed.parent();
+    while let Some(current) = dir {
+        // remove_dir fails on directories that are not empty, which ends the walk
+        if current == root_path || std::fs::remove_dir(current).is_err() {
+            break;
+        }
+        dir = current.parent();
+    }
+}
+
+fn collect_files(root_path: &Path, relative: &Path, out: &mut BTreeSet<PathBuf>) {
+    let entries = match std::fs::read_dir(root_path.join(relative)) {
+        Ok(entries) => entries,
+        Err(_) => return,
+    };
+    for entry in entries.flatten() {
+        let relative_entry = relative.join(entry.file_name());
+        if entry.path().is_dir() {
+            collect_files(root_path, &relative_entry, out);
+        } else {
+            out.insert(relative_entry);
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct CheckReport {
+    pub added: Vec<PathBuf>,
+    pub changed: Vec<(PathBuf, String)>,
+    // on disk, but no longer generated
+    pub stale: Vec<PathBuf>,
+    // changed files that were generated by another move-to-ts version or in another mode
+    pub drift: Vec<String>,
+}
+
+impl CheckReport {
+    pub fn is_up_to_date(&self) -> bool {
+        self.added.is_empty() && self.changed.is_empty() && self.stale.is_empty()
+    }
+
+    pub fn format(&self) -> String {
+        let mut out = String::new();
+        for path in self.added.iter() {
+            out.push_str(&format!("added:   {}\n", path.to_string_lossy()));
+        }
+        for (path, _) in self.changed.iter() {
+            out.push_str(&format!("changed: {}\n", path.to_string_lossy()));
+        }
+        for path in self.stale.iter() {
+            out.push_str(&format!("stale:   {}\n", path.to_string_lossy()));
+        }
+        if !self.drift.is_empty() {
+            out.push('\n');
+        }
+        for note in self.drift.iter() {
+            out.push_str(&format!("note: {}\n", note));
+        }
+        for (_, diff) in self.changed.iter() {
+            out.push('\n');
+            out.push_str(diff);
+        }
+        out.push_str(&format!(
+            "\n{} added, {} changed, {} stale\n",
+            self.added.len(),
+            self.changed.len(),
+            self.stale.len()
+        ));
+        out
+    }
+}
+
+/// Single-hunk unified diff covering everything between the common prefix and suffix of the two
+/// versions. Not minimal, but enough to spot what changed, and cut off after MAX_DIFF_LINES
+pub fn unified_diff(path: &Path, old: &str, new: &str) -> String {
+    const CONTEXT: usize = 3;
+    let old_lines = old.lines().collect::<Vec<_>>();
+    let new_lines = new.lines().collect::<Vec<_>>();
+    let prefix = old_lines
+        .iter()
+        .zip(new_lines.iter())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let suffix = old_lines[prefix..]
+        .iter()
+        .rev()
+        .zip(new_lines[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let start = prefix.saturating_sub(CONTEXT);
+    let old_end = (old_lines.len() - suffix + CONTEXT).min(old_lines.len());
+    let new_end = (new_lines.len() - suffix + CONTEXT).min(new_lines.len());
+
+    let mut body = vec![];
+    for line in old_lines[start..prefix].iter() {
+        body.push(format!(" {}", line));
+    }
+    for line in old_lines[prefix..old_lines.len() - suffix].iter() {
+        body.push(format!("-{}", line));
+    }
+    for line in new_lines[prefix..new_lines.len() - suffix].iter() {
+        body.push(format!("+{}", line));
+    }
+    for line in old_lines[old_lines.len() - suffix..old_end].iter() {
+        body.push(format!(" {}", line));
+    }
+
+    let path = path.to_string_lossy();
+    let mut out = format!(
+        "--- a/{}\n+++ b/{}\n@@ -{},{} +{},{} @@\n",
+        path,
+        path,
+        start + 1,
+        old_end - start,
+        start + 1,
+        new_end - start
+    );
+    let total = body.len();
+    for line in body.into_iter().take(MAX_DIFF_LINES) {
+        out.push_str(&line);
+        out.push('\n');
+    }
+    if total > MAX_DIFF_LINES {
+        out.push_str(&format!("... {} more lines\n", total - MAX_DIFF_LINES));
+    }
+    out
+}
@@ -0,0 +1,149 @@
+use crate::inspect::json_string;
+use std::time::Duration;
+
+/*
+Where a run spent its time: each translate_module call, and the passes around them. Collected by
+the driver and printed with --profile or written with --profile-json.
+ */
+
+pub struct ModuleTiming {
+    pub name: String,
+    pub duration: Duration,
+    // size of the generated file
+    pub bytes: usize,
+}
+
+#[derive(Default)]
+pub struct Profile {
+    pub modules: Vec<ModuleTiming>,
+    // pass name -> time spent in it, in the order the passes first ran
+    pub passes: Vec<(String, Duration)>,
+}
+
+impl Profile {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds `duration` to the time spent in `pass`
+    pub fn add_pass(&mut self, pass: &str, duration: Duration) {
+        match self.passes.iter_mut().find(|(name, _)| name == pass) {
+            Some((_, total)) => *total += duration,
+            None => self.passes.push((pass.to_string(), duration)),
+        }
+    }
+
+    pub fn add_module(&mut self, name: String, duration: Duration, bytes: usize) {
+        self.modules.push(ModuleTiming {
+            name,
+            duration,
+            bytes,
+        });
+    }
+
+    pub fn total(&self) -> Duration {
+        let modules = self.modules.iter().map(|m| m.duration).sum::<Duration>();
+        let passes = self.passes.iter().map(|(_, d)| *d).sum::<Duration>();
+        modules + passes
+    }
+
+    /// Modules from slowest to fastest
+    pub fn slowest_modules(&self) -> Vec<&ModuleTiming> {
+        let mut modules = self.modules.iter().collect::<Vec<_>>();
+        modules.sort_by(|a, b| b.duration.cmp(&a.duration).then(a.name.cmp(&b.name)));
+        modules
+    }
+}
+
+fn format_millis(duration: Duration) -> String {
+    format!("{:.1}ms", duration.as_secs_f64() * 1000.0)
+}
+
+pub fn format_profile_text(profile: &Profile, top: usize) -> String {
+    let slowest = profile.slowest_modules();
+    let shown = slowest.iter().take(top).collect::<Vec<_>>();
+    let name_width = shown
+        .iter()
+        .map(|m| m.name.len())
+        .chain(profile.passes.iter().map(|(name, _)| name.len()))
+        .max()
+        .unwrap_or(0)
+        .max("module".len());
+
+    let mut out = format!(
+        "{:<width$}  {:>10}  {:>10}\n",
+        "module",
+        "time",
+        "bytes",
+        width = name_width
+    );
+    for module in shown.iter() {
+        out.push_str(&format!(
+            "{:<width$}  {:>10}  {:>10}\n",
+            module.name,
+            format_millis(module.duration),
+            module.bytes,
+            width = name_width
+        ));
+    }
+    if slowest.len() > shown.len() {
+        out.push_str(&format!(
+            "... {} more modules\n",
+            slowest.len() - shown.len()
+        ));
+    }
+    out.push('\n');
+    out.push_str(&format!(
+        "{:<width$}  {:>10}\n",
+        "pass",
+        "time",
+        width = name_width
+    ));
+    for (name, duration) in profile.passes.iter() {
+        out.push_str(&format!(
+            "{:<width$}  {:>10}\n",
+            name,
+            format_millis(*duration),
+            width = name_width
+        ));
+    }
+    out.push_str(&format!(
+        "\n{} modules, {} bytes, {} total\n",
+        profile.modules.len(),
+        profile.modules.iter().map(|m| m.bytes).sum::<usize>(),
+        format_millis(profile.total())
+    ));
+    out
+}
+
+pub fn format_profile_json(profile: &Profile) -> String {
+    let modules = profile
+        .slowest_modules()
+        .iter()
+        .map(|m| {
+            format!(
+                "{{\"name\":{},\"micros\":{},\"bytes\":{}}}",
+                json_string(&m.name),
+                m.duration.as_micros(),
+                m.bytes
+            )
+        })
+        .collect::<Vec<_>>();
+    let passes = profile
+        .passes
+        .iter()
+        .map(|(name, duration)| {
+            format!(
+                "{{\"name\":{},\"micros\":{}}}",
+                json_string(name),
+                duration.as_micros()
+            )
+        })
+        .collect::<Vec<_>>();
+    format!(
+        "{{\"modules\":[{}],\"passes\":[{}],\"totalMicros\":{}}}",
+        modules.join(","),
+        passes.join(","),
+        profile.total().as_micros()
+    )
+}
@@ -4,18 +4,20 @@ use itertools::Itertools;
 use move_compiler::{
     diagnostics::{
         codes::{Category, DiagnosticCode, Severity},
-        Diagnostic,
+        Diagnostic, Diagnostics, FilesSourceText,
+    },
+    expansion::ast::{
+        AbilitySet, Address, Attribute, AttributeValue_, Attribute_, ModuleIdent, Visibility,
     },
-    expansion::ast::{Address, Attribute, AttributeValue_, Attribute_, ModuleIdent},
     hlir::ast::*,
     naming::ast::{BuiltinTypeName_, StructTypeParameter, TParam},
-    parser::ast::{FunctionName, StructName},
+    parser::ast::{Ability_, FunctionName, StructName},
     shared::Name,
 };
 use move_ir_types::location::Loc;
-use std::collections::{BTreeMap, BTreeSet};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::fmt;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::rc::Rc;
 
 #[derive(Copy, Clone)]
@@ -32,6 +34,57 @@ impl DiagnosticCode for NotTranslatable {
     }
 }
 
+/// A bug of the translator itself, e.g. a pass that runs without the module it expects, reported
+/// instead of panicking so that the run still ends with its diagnostics
+#[derive(Copy, Clone)]
+pub struct InternalError {}
+
+impl DiagnosticCode for InternalError {
+    const CATEGORY: Category = Category::TypeSafety;
+    fn severity(self) -> Severity {
+        Severity::Bug
+    }
+
+    fn code_and_message(self) -> (u8, &'static str) {
+        (3, "Internal Error")
+    }
+}
+
+/// Something that translates, but probably not the way the user intended. Reported without
+/// failing the run, unless --deny-warnings is given
+#[derive(Copy, Clone)]
+pub struct TranslationWarning {}
+
+impl DiagnosticCode for TranslationWarning {
+    const CATEGORY: Category = Category::TypeSafety;
+    fn severity(self) -> Severity {
+        Severity::Warning
+    }
+
+    fn code_and_message(self) -> (u8, &'static str) {
+        (2, "Translation Warning")
+    }
+}
+
+macro_rules! wdiag {
+    ($primary: expr $(,)?) => {{
+        Diagnostic::new(
+            TranslationWarning{},
+            $primary,
+            std::iter::empty::<(Loc, String)>(),
+            std::iter::empty::<String>(),
+        )
+    }};
+    ($primary: expr, $($secondary: expr),+ $(,)?) => {{
+        Diagnostic::new(
+            TranslationWarning{},
+            $primary,
+            vec![$($secondary, )*],
+            std::iter::empty::<String>(),
+        )
+    }};
+}
+
 macro_rules! derr {
     ($primary: expr $(,)?) => {{
         Err(Diagnostic::new(
@@ -51,9 +104,105 @@ macro_rules! derr {
     }};
 }
 
+/// Why something cannot be translated. Unlike a Diagnostic, this can be collected and reported
+/// without aborting generation (e.g. by `inspect`)
+pub type Reason = (Loc, String);
+
+pub fn reason_to_diag(reason: Reason) -> Diagnostic {
+    Diagnostic::new(
+        NotTranslatable {},
+        reason,
+        std::iter::empty::<(Loc, String)>(),
+        std::iter::empty::<String>(),
+    )
+}
+
+#[derive(clap::Subcommand, Clone)]
+pub enum MoveToTsCommand {
+    /// Print translatable modules, entry functions and structs without generating anything
+    Inspect {
+        /// print the summary as JSON
+        #[clap(long = "inspect-json")]
+        json: bool,
+    },
+}
+
+#[derive(clap::ArgEnum, Clone, Copy, PartialEq, Eq)]
+pub enum Naming {
+    Camel,
+    Preserve,
+}
+
+/// Directory scheme of the generated sources
+#[derive(clap::ArgEnum, Clone, Copy, PartialEq, Eq)]
+pub enum Layout {
+    // one directory per package, named after its named address when it has one
+    Named,
+    // one directory per package, named after its hex address
+    Hex,
+    // all modules in one directory, file names prefixed by their package
+    Flat,
+}
+
+/// How generated files import packages other than their own
+#[derive(Clone, PartialEq, Eq)]
+pub enum ImportStyle {
+    // sibling directories of the output tree, e.g. "../std"
+    Relative,
+    // separately published packages under a scope, e.g. "@scope/std"
+    Scoped(String),
+}
+
+impl std::str::FromStr for ImportStyle {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == "relative" {
+            return Ok(ImportStyle::Relative);
+        }
+        match s.strip_prefix("scoped:") {
+            Some(scope) if !scope.trim_end_matches('/').is_empty() => {
+                Ok(ImportStyle::Scoped(scope.trim_end_matches('/').to_string()))
+            }
+            _ => Err(format!("expected relative or scoped:<prefix>, got {}", s)),
+        }
+    }
+}
+
+impl fmt::Display for ImportStyle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ImportStyle::Relative => write!(f, "relative"),
+            ImportStyle::Scoped(scope) => write!(f, "scoped:{}", scope),
+        }
+    }
+}
+
+impl ImportStyle {
+    /// Import path of `package_name`, where `relative_dir` is how a relative import reaches the
+    /// directory holding all packages from the importing file
+    pub fn package_path(&self, package_dir: &str, relative_dir: &str) -> String {
+        match self {
+            ImportStyle::Relative => format!("{}{}", relative_dir, package_dir),
+            ImportStyle::Scoped(scope) => format!("{}/{}", scope, package_dir),
+        }
+    }
+}
+
+/// What a function's `foo_` looks like in the generated module
+#[derive(Clone, PartialEq, Eq)]
+pub enum EmittedBody {
+    Translated,
+    // declared with a body that throws, saying why
+    Stub(String),
+    Omitted,
+}
+
 #[derive(Parser, Clone)]
 #[clap(author, version, about)]
 pub struct MoveToTsOptions {
+    #[clap(subcommand)]
+    pub command: Option<MoveToTsCommand>,
     /// Path to a package which the command should be run with respect to.
     #[clap(
         long = "path",
@@ -71,6 +220,14 @@ pub struct MoveToTsOptions {
         default_value = ""
     )]
     pub output_path: PathBuf,
+    /// subdirectory of the output directory that generated files go to when it holds the Move
+    /// package, e.g. with -o .
+    #[clap(
+        long = "output-subdir",
+        parse(from_os_str),
+        default_value = "typescript"
+    )]
+    pub output_subdir: PathBuf,
     /// generate #[test] functions
     #[clap(long = "gen-test", short = 't')]
     pub test: bool,
@@ -84,31 +241,252 @@ pub struct MoveToTsOptions {
     /// generate package.json
     #[clap(long = "package-json-name", short = 'n', default_value = "")]
     pub package_json_name: String,
+    /// emit a commented usage example above each buildPayload_ function
+    #[clap(long = "emit-examples")]
+    pub emit_examples: bool,
+    /// read time and block height from on-chain resources instead of the cache's clock, and do
+    /// not simulate aggregators
+    #[clap(long = "strict-natives")]
+    pub strict_natives: bool,
+    /// naming of generated functions, methods and fields
+    #[clap(long = "naming", arg_enum, default_value = "preserve")]
+    pub naming: Naming,
+    /// import other packages relatively, or as scoped npm packages (relative|scoped:<prefix>),
+    /// which cannot be combined with --layout flat
+    #[clap(long = "external-package-import-style", default_value = "relative")]
+    pub external_package_import_style: ImportStyle,
+    /// import the modules of other packages one by one instead of their package's index, so that
+    /// bundlers can leave out the modules that are not used
+    #[clap(long = "fine-grained-imports")]
+    pub fine_grained_imports: bool,
+    /// directory scheme of the generated sources
+    #[clap(long = "layout", arg_enum, default_value = "named")]
+    pub layout: Layout,
+    /// prefix functions that are not public with `_`
+    #[clap(long = "hide-internal")]
+    pub hide_internal: bool,
+    /// print the configuration merged from move-to-ts.toml and the command line, then exit
+    #[clap(long = "print-config")]
+    pub print_config: bool,
+    /// fail on translation warnings, not only on errors
+    #[clap(long = "deny-warnings")]
+    pub deny_warnings: bool,
+    /// compare the output directory against what would be generated, without writing anything
+    #[clap(long = "check")]
+    pub check: bool,
+    /// delete files written by the previous run that this run no longer generates
+    #[clap(long = "prune")]
+    pub prune: bool,
+    /// the output directory is owned by move-to-ts, implies --prune
+    #[clap(long = "managed-output")]
+    pub managed_output: bool,
+    /// let cli commands take coin symbols as type arguments, looked up in a registry resource
+    /// (<address>::<module>[::<Struct>])
+    #[clap(long = "coin-registry")]
+    pub coin_registry: Option<String>,
+    /// declare functions that cannot be translated because of a compiler error with a stub,
+    /// instead of failing their module
+    #[clap(long = "keep-going")]
+    pub keep_going: bool,
+    /// only emit struct classes, parsers, payload builders and queries, no function bodies
+    #[clap(long = "payload-only")]
+    pub payload_only: bool,
+    /// leave comments, asserts guarded by a bool constant and runtime checks out of the generated
+    /// code, for bundles shipped to browsers
+    #[clap(long = "production")]
+    pub production: bool,
+    /// keep the runtime checks that --production leaves out
+    #[clap(long = "runtime-checks")]
+    pub runtime_checks: bool,
+    /// do not check the number of type arguments passed to generated functions and parsers, the
+    /// range of casts, or overflows and shift amounts of arithmetic
+    #[clap(long = "no-runtime-checks")]
+    pub no_runtime_checks: bool,
+    /// translate calls to the helpers of 0x1::math64 and 0x1::math128 as calls to their bodies
+    /// instead of the runtime's fast paths
+    #[clap(long = "no-math-inline")]
+    pub no_math_inline: bool,
+    /// emit address derivations, loaders and accessors for the token (0x3) and digital asset (0x4)
+    /// modules when they are translated
+    #[clap(long = "framework-extras")]
+    pub framework_extras: bool,
+    /// with --gen-test, count the calls of each translated function and write coverage.json
+    #[clap(long = "test-coverage")]
+    pub test_coverage: bool,
+    /// generate cases that run each entry function both translated and in a node simulation, for
+    /// $.runDiffTest to compare
+    #[clap(long = "gen-difftest")]
+    pub gen_difftest: bool,
+    /// write a JSON Schema of the inputs of each entry function that gets a buildPayload_ to
+    /// schemas/<address>/<module>/<function>.json
+    #[clap(long = "emit-json-schema")]
+    pub emit_json_schema: bool,
+    /// export the source file and byte range of each entry function that gets a buildPayload_ as
+    /// source_<function>, and the module's friends as friendModules
+    #[clap(long = "emit-provenance")]
+    pub emit_provenance: bool,
+    /// type struct constructors with a proto only the declaring module can build, so that code
+    /// outside of it constructs structs through `__pack`
+    #[clap(long = "strict-construction")]
+    pub strict_construction: bool,
+    /// let struct classes extend a StructBase generated once under src/_helpers instead of each
+    /// repeating its constructor, parser and field accessors
+    #[clap(long = "compact-structs")]
+    pub compact_structs: bool,
+    /// append the Move function and its primitive arguments to the message of errors thrown
+    /// through each generated function; has no effect with --production
+    #[clap(long = "trace")]
+    pub trace: bool,
+    /// emit the package declared under ALIAS as NAME, e.g. when dependencies give the same
+    /// address different names (ALIAS=NAME, repeatable)
+    #[clap(long = "address-alias")]
+    pub address_alias: Vec<String>,
+    /// declare FUNCTION with a body that throws REASON instead of translating it, for functions
+    /// known not to translate yet (<ADDRESS>::<MODULE>::<FUNCTION>=<REASON>, repeatable)
+    #[clap(long = "skip-function")]
+    pub skip_function: Vec<String>,
+    /// leave MODULE out of the output. Translated modules may still refer to its structs in
+    /// types, which then come from an opaque stub in src/_stubs (<ADDRESS>::<MODULE>, repeatable)
+    #[clap(long = "exclude-module")]
+    pub exclude_module: Vec<String>,
+    /// generate a cli command for FUNCTION as if it had a #[cmd] directive, for public entry
+    /// functions of packages whose source cannot be changed (<ADDRESS>::<MODULE>::<FUNCTION>,
+    /// repeatable)
+    #[clap(long = "extra-cmd")]
+    pub extra_cmd: Vec<String>,
+    /// print the slowest modules and the time spent in each pass
+    #[clap(long = "profile")]
+    pub profile: bool,
+    /// number of modules listed by --profile
+    #[clap(long = "profile-top", default_value = "20")]
+    pub profile_top: usize,
+    /// write the timings of --profile as JSON to this file
+    #[clap(long = "profile-json", parse(from_os_str))]
+    pub profile_json: Option<PathBuf>,
+    /// write the graph of the modules each translated module refers to as DOT to this file, and
+    /// as JSON next to it with a .json extension
+    #[clap(long = "emit-depgraph", parse(from_os_str))]
+    pub emit_depgraph: Option<PathBuf>,
+    /// write the abort codes of the translated modules, with the name and doc comment of their
+    /// error constants, as JSON to this file
+    #[clap(long = "emit-errmap", parse(from_os_str))]
+    pub emit_errmap: Option<PathBuf>,
+    /// render every typetag instead of looking struct typetags up in Context, for tests that
+    /// compare the output with and without the cache
+    #[clap(long = "no-typetag-cache", hide = true)]
+    pub no_typetag_cache: bool,
 }
 
-use crate::utils::{capitalize, rename};
+use crate::utils::{
+    base_field_member_name, capitalize, rename, sanitize_path_component, to_camel_case,
+};
 pub(crate) use derr;
+pub(crate) use wdiag;
 use move_command_line_common::address::NumericalAddress;
+use move_command_line_common::files::FileHash;
 
 pub struct CmdParams {
     pub mi: ModuleIdent,
     pub fname: FunctionName,
     pub func: Function,
     pub desc: Option<String>,
+    // descriptions of parameters, from #[cmd(<param>=b"...")]
+    pub param_descs: BTreeMap<String, String>,
 }
 
+// directory under src holding helpers shared by all packages of an output tree
+pub const HELPERS_DIR: &str = "_helpers";
+pub const STUBS_DIR: &str = "_stubs";
+// under src, the test files, and with --gen-test the #[test_only] modules
+pub const TESTS_DIR: &str = "tests";
+
+// identifiers every generated module file declares or imports by name
+const RESERVED_FILE_NAMES: &[&str] = &[
+    "$",
+    "AptosDataCache",
+    "AptosParserRepo",
+    "DummyCache",
+    "U8",
+    "U64",
+    "U128",
+    "TypeParamDeclType",
+    "FieldDeclType",
+    "AtomicTypeTag",
+    "StructTag",
+    "TypeTag",
+    "VectorTag",
+    "HexString",
+    "AptosClient",
+    "AptosAccount",
+    "packageName",
+    "moduleAddress",
+    "moduleName",
+    "TypedTable",
+    "TypedIterableTable",
+];
+
 pub struct Context {
     pub program: Rc<Program>,
     pub current_module: Option<ModuleIdent>,
     pub current_function_signature: Option<FunctionSignature>,
-    // modules imported from same package
-    pub same_package_imports: BTreeSet<String>,
-    // external packages imported
-    pub package_imports: BTreeSet<String>,
+    // whether code being emitted runs inside a generated async function, false for e.g. the
+    // initializers of constants
+    pub in_async_scope: bool,
+    // locals of the current function whose unpacks are emitted as assignments to hoisted
+    // variables instead of `let` declarations
+    pub unpack_assigned_vars: BTreeSet<String>,
+    // compiler temporaries of the current function, numbered from 0 in the order the compiler
+    // created them, see temp_var_names
+    pub temp_numbers: BTreeMap<String, usize>,
+    // set along with the error for an expression or type the compiler marked unreachable, which
+    // write_statements turns into a throw
+    pub reached_unreachable: bool,
+    // location of the UnresolvedError behind the current error, which --keep-going turns into a
+    // stub
+    pub reached_unresolved: Option<Loc>,
+    // rendered typetags of struct types, see typetag_cache_key
+    pub typetag_cache: BTreeMap<String, String>,
+    pub typetag_builder_cache: BTreeMap<String, String>,
+    // typetag -> name of the module-level constant holding it, for the current module
+    pub hoisted_typetags: BTreeMap<String, String>,
+    // file name -> content, for the files under src/_helpers shared by all packages
+    pub helper_files: BTreeMap<String, String>,
+    // import path -> alias, for every module or package imported by the current file
+    pub import_aliases: BTreeMap<String, String>,
+    // alias -> import path, the reverse of import_aliases
+    pub import_alias_owners: BTreeMap<String, String>,
+    // namespace each module is exported under by its package's index.ts
+    pub module_namespaces: BTreeMap<ModuleIdent, String>,
+    // module -> (file name, module keeping the unsuffixed name), see compute_renamed_module_files
+    pub renamed_module_files: BTreeMap<ModuleIdent, (String, ModuleIdent)>,
+    // (module, struct) -> Move field name -> TS member, see compute_field_members
+    pub field_members: BTreeMap<(ModuleIdent, String), BTreeMap<String, String>>,
+    // package directory -> directories of other packages its modules import
+    pub package_dependencies: BTreeMap<String, BTreeSet<String>>,
+    // identifiers declared by the current file, which import aliases must avoid
+    pub local_names: BTreeSet<String>,
     // all modules
     pub visited_modules: BTreeSet<ModuleIdent>,
-    // external packages imported
+    // modules each module refers to, for --emit-depgraph
+    pub module_dependencies: BTreeMap<ModuleIdent, BTreeSet<ModuleIdent>>,
+    // package name -> address of the first module translated under it
     pub visited_packages: BTreeMap<String, Address>,
+    // address each module is declared under. A reference may spell it with any name its package
+    // gave to the same number, and only the declaration is the same everywhere
+    pub declared_addresses: BTreeMap<ModuleIdent, Address>,
+    // --address-alias, alias -> canonical name
+    pub address_aliases: BTreeMap<String, String>,
+    // --skip-function, <address>::<module>::<function> -> reason
+    pub skipped_functions: BTreeMap<String, String>,
+    // the entries of skipped_functions that matched a translated function
+    pub applied_skips: BTreeSet<String>,
+    // --exclude-module entries, and the excluded modules that translated modules refer to
+    pub excluded_modules: BTreeSet<String>,
+    pub stubbed_modules: BTreeSet<ModuleIdent>,
+    // the stubs the current module imports, whose parsers its loadParsers adds
+    pub stub_imports: BTreeSet<ModuleIdent>,
+    // source files relative to the root of their package, for --emit-provenance
+    pub source_paths: HashMap<FileHash, String>,
     // configs
     pub config: MoveToTsOptions,
     // unit test info
@@ -118,6 +496,12 @@ pub struct Context {
         Attribute,
         Option<Attribute>,
     )>,
+    // unit tests left out of the test file, with the reason
+    pub skipped_tests: Vec<(FunctionName, String)>,
+    // functions counted by --test-coverage, as 0x..::module::function
+    pub coverage_functions: BTreeSet<String>,
+    // the #[test_setup] function, whose state every test of the module starts from
+    pub test_setup: Option<(FunctionName, FunctionSignature, Attribute)>,
     // cmd info
     pub cmds: Vec<CmdParams>,
     // query info
@@ -132,17 +516,249 @@ pub struct Context {
     )>,
     // all show_iter_table directives collected
     pub all_shows_iter_tables: Vec<(ModuleIdent, StructName, StructDefinition, Name)>,
+    // warnings of all modules, reported once generation is done
+    pub warnings: Diagnostics,
 }
 
-pub fn is_same_package(a1: Address, a2: Address) -> bool {
-    match a1 {
-        // Address eq implementation ignores name, but we cannot ignore that
-        Address::Numerical(name, num) => match a2 {
-            Address::Numerical(name2, num2) => name == name2 && num == num2,
-            _ => false,
-        },
-        Address::NamedUnassigned(_) => a1 == a2,
+/// Capitalized module names can collide (e.g. `coin_list` and `Coin_list`), so within each
+/// package, modules are visited in name order and later ones get a `$N` suffix on collision
+pub fn compute_module_namespaces(c: &Context) -> BTreeMap<ModuleIdent, String> {
+    let mut by_package: BTreeMap<String, Vec<ModuleIdent>> = BTreeMap::new();
+    for (mident, _) in c.program.modules.key_cloned_iter() {
+        by_package
+            .entry(c.package_name(&mident))
+            .or_insert_with(Vec::new)
+            .push(mident);
     }
+    let mut namespaces = BTreeMap::new();
+    for (_, mut modules) in by_package.into_iter() {
+        modules.sort_by_key(|mi| mi.value.module.to_string());
+        let mut taken = BTreeSet::new();
+        for mident in modules {
+            let preferred = capitalize(&mident.value.module);
+            let mut namespace = preferred.clone();
+            let mut suffix = 1;
+            while taken.contains(&namespace) {
+                namespace = format!("{}${}", preferred, suffix);
+                suffix += 1;
+            }
+            taken.insert(namespace.clone());
+            namespaces.insert(mident, namespace);
+        }
+    }
+    namespaces
+}
+
+/// Files of modules whose names only differ in case, e.g. `Vault` and `vault`, would overwrite each
+/// other on case-insensitive file systems. Such modules are visited in path order and each later
+/// path gets a `-N` suffix, which no Move identifier contains. Modules written to the very same
+/// path are left alone, for check_module_path_collisions to report
+pub fn compute_renamed_module_files(c: &Context) -> BTreeMap<ModuleIdent, (String, ModuleIdent)> {
+    let mut by_lowercase_path: BTreeMap<String, Vec<(String, ModuleIdent)>> = BTreeMap::new();
+    for (mident, _) in c.program.modules.key_cloned_iter() {
+        let path = c.module_path(&mident);
+        by_lowercase_path
+            .entry(path.to_lowercase())
+            .or_insert_with(Vec::new)
+            .push((path, mident));
+    }
+    let mut renamed = BTreeMap::new();
+    for (_, mut modules) in by_lowercase_path.into_iter() {
+        modules.sort_by_key(|(path, mident)| (path.clone(), mident.to_string()));
+        let (first_path, first) = modules[0].clone();
+        let mut paths = vec![first_path];
+        for (path, mident) in modules.into_iter().skip(1) {
+            let suffix = match paths.iter().position(|taken| *taken == path) {
+                Some(0) => continue,
+                Some(idx) => idx,
+                None => {
+                    paths.push(path);
+                    paths.len() - 1
+                }
+            };
+            let file_name = format!("{}-{}", c.module_file_name(&mident), suffix);
+            renamed.insert(mident, (file_name, first));
+        }
+    }
+    renamed
+}
+
+/// Fields of a struct can translate to the same member, e.g. `type` and `type_` with --naming
+/// camel, so in declaration order, later ones get a `$N` suffix on collision. Move identifiers
+/// cannot contain `$`, so a suffixed member never collides with another field's
+pub fn compute_field_members(
+    c: &Context,
+) -> BTreeMap<(ModuleIdent, String), BTreeMap<String, String>> {
+    let mut field_members = BTreeMap::new();
+    for (mident, mdef) in c.program.modules.key_cloned_iter() {
+        let ts_naming = c.uses_ts_naming(&mident);
+        for (sname, sdef) in mdef.structs.key_cloned_iter() {
+            let fields = match &sdef.fields {
+                StructFields::Defined(fields) => fields,
+                StructFields::Native(_) => continue,
+            };
+            let mut taken = BTreeSet::new();
+            let mut members = BTreeMap::new();
+            for (field, _) in fields.iter() {
+                let preferred = base_field_member_name(field, ts_naming);
+                let mut member = preferred.clone();
+                let mut suffix = 1;
+                while taken.contains(&member) {
+                    member = format!("{}${}", preferred, suffix);
+                    suffix += 1;
+                }
+                taken.insert(member.clone());
+                members.insert(field.to_string(), member);
+            }
+            field_members.insert((mident, sname.to_string()), members);
+        }
+    }
+    field_members
+}
+
+/// Parses the ALIAS=NAME pairs of --address-alias
+pub fn parse_address_aliases(specs: &[String]) -> Result<BTreeMap<String, String>, String> {
+    let mut aliases = BTreeMap::new();
+    for spec in specs.iter() {
+        match spec.split_once('=') {
+            Some((alias, name)) if !alias.is_empty() && !name.is_empty() && alias != name => {
+                if let Some(previous) = aliases.insert(alias.to_string(), name.to_string()) {
+                    return Err(format!(
+                        "Invalid --address-alias {}, {} is already an alias of {}",
+                        spec, alias, previous
+                    ));
+                }
+            }
+            _ => {
+                return Err(format!(
+                    "Invalid --address-alias {}, expected <ALIAS>=<NAME>",
+                    spec
+                ))
+            }
+        }
+    }
+    Ok(aliases)
+}
+
+/// Parses the <ADDRESS>::<MODULE>::<FUNCTION>=<REASON> entries of --skip-function
+pub fn parse_skipped_functions(specs: &[String]) -> Result<BTreeMap<String, String>, String> {
+    let mut skipped = BTreeMap::new();
+    for spec in specs.iter() {
+        match spec.split_once('=') {
+            Some((function, reason))
+                if function.split("::").filter(|part| !part.is_empty()).count() == 3
+                    && function.split("::").count() == 3
+                    && !reason.trim().is_empty() =>
+            {
+                if skipped
+                    .insert(function.to_string(), reason.trim().to_string())
+                    .is_some()
+                {
+                    return Err(format!(
+                        "Invalid --skip-function {}, {} is skipped more than once",
+                        spec, function
+                    ));
+                }
+            }
+            _ => {
+                return Err(format!(
+                    "Invalid --skip-function {}, expected <ADDRESS>::<MODULE>::<FUNCTION>=<REASON>",
+                    spec
+                ))
+            }
+        }
+    }
+    Ok(skipped)
+}
+
+/// Directory of a path relative to src, empty at the top
+pub fn parent_dir(path: &str) -> String {
+    match path.rfind('/') {
+        Some(i) => path[..i].to_string(),
+        None => "".to_string(),
+    }
+}
+
+/// Resolves an import path written from `dir` to a path relative to src
+fn join_relative(dir: &str, path: &str) -> String {
+    let mut parts = dir
+        .split('/')
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>();
+    for part in path.split('/') {
+        match part {
+            "." | "" => (),
+            ".." => {
+                parts.pop();
+            }
+            _ => parts.push(part),
+        }
+    }
+    parts.join("/")
+}
+
+/// Import path of `target` from a file in `dir`, both relative to src
+pub fn relative_import_path(dir: &str, target: &str) -> String {
+    let from = dir
+        .split('/')
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>();
+    let to = target.split('/').collect::<Vec<_>>();
+    let common = from
+        .iter()
+        .zip(to.iter())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let up = if from.len() == common {
+        "./".to_string()
+    } else {
+        "../".repeat(from.len() - common)
+    };
+    format!("{}{}", up, to[common..].join("/"))
+}
+
+/// Paths of the compiled source files relative to the directory with the Move.toml of their
+/// package, with `/` separators, so that provenance does not depend on where a package or the
+/// dependency cache is checked out
+pub fn package_relative_paths(files: &FilesSourceText) -> HashMap<FileHash, String> {
+    files
+        .iter()
+        .map(|(hash, (name, _))| {
+            let path = Path::new(name.as_str());
+            let relative = path
+                .ancestors()
+                .skip(1)
+                .find(|dir| dir.join("Move.toml").is_file())
+                .and_then(|root| path.strip_prefix(root).ok())
+                .unwrap_or(path);
+            let relative = relative
+                .components()
+                .map(|part| part.as_os_str().to_string_lossy())
+                .join("/");
+            (*hash, relative)
+        })
+        .collect()
+}
+
+/// Parses the <ADDRESS>::<MODULE> entries of --exclude-module
+pub fn parse_excluded_modules(specs: &[String]) -> Result<BTreeSet<String>, String> {
+    let mut excluded = BTreeSet::new();
+    for spec in specs.iter() {
+        let parts = spec.split("::").collect::<Vec<_>>();
+        if parts.len() != 2 || parts.iter().any(|part| part.is_empty()) {
+            return Err(format!(
+                "Invalid --exclude-module {}, expected <ADDRESS>::<MODULE>",
+                spec
+            ));
+        }
+        if !excluded.insert(spec.clone()) {
+            return Err(format!(
+                "Invalid --exclude-module {}, given more than once",
+                spec
+            ));
+        }
+    }
+    Ok(excluded)
 }
 
 pub fn is_same_module(mi1: &ModuleIdent, mi2: &ModuleIdent) -> bool {
@@ -151,51 +767,506 @@ pub fn is_same_module(mi1: &ModuleIdent, mi2: &ModuleIdent) -> bool {
 
 impl Context {
     pub fn new(config: &MoveToTsOptions, program: Rc<Program>) -> Self {
-        Self {
+        let declared_addresses = program
+            .modules
+            .key_cloned_iter()
+            .map(|(mident, _)| (mident, mident.value.address))
+            .collect();
+        // validated by the caller
+        let address_aliases = parse_address_aliases(&config.address_alias).unwrap_or_default();
+        let skipped_functions = parse_skipped_functions(&config.skip_function).unwrap_or_default();
+        let excluded_modules = parse_excluded_modules(&config.exclude_module).unwrap_or_default();
+        let mut c = Self {
             program,
             current_module: None,
             current_function_signature: None,
-            same_package_imports: BTreeSet::new(),
-            package_imports: BTreeSet::new(),
+            in_async_scope: false,
+            unpack_assigned_vars: BTreeSet::new(),
+            temp_numbers: BTreeMap::new(),
+            typetag_cache: BTreeMap::new(),
+            typetag_builder_cache: BTreeMap::new(),
+            hoisted_typetags: BTreeMap::new(),
+            reached_unreachable: false,
+            reached_unresolved: None,
+            helper_files: BTreeMap::new(),
+            import_aliases: BTreeMap::new(),
+            import_alias_owners: BTreeMap::new(),
+            module_namespaces: BTreeMap::new(),
+            renamed_module_files: BTreeMap::new(),
+            field_members: BTreeMap::new(),
+            package_dependencies: BTreeMap::new(),
+            local_names: BTreeSet::new(),
             visited_modules: BTreeSet::new(),
+            module_dependencies: BTreeMap::new(),
             visited_packages: BTreeMap::new(),
+            declared_addresses,
+            address_aliases,
+            skipped_functions,
+            applied_skips: BTreeSet::new(),
+            excluded_modules,
+            stubbed_modules: BTreeSet::new(),
+            stub_imports: BTreeSet::new(),
+            source_paths: HashMap::new(),
             config: config.clone(),
             tests: vec![],
+            skipped_tests: vec![],
+            test_setup: None,
+            coverage_functions: BTreeSet::new(),
             cmds: vec![],
             queries: vec![],
             printer_methods: vec![],
             all_shows_iter_tables: vec![],
-        }
+            warnings: Diagnostics::new(),
+        };
+        c.module_namespaces = compute_module_namespaces(&c);
+        c.renamed_module_files = compute_renamed_module_files(&c);
+        c.field_members = compute_field_members(&c);
+        c
     }
 
     pub fn reset_for_module(&mut self, mname: ModuleIdent) {
         self.current_module = Some(mname);
-        self.same_package_imports.clear();
-        self.package_imports.clear();
+        self.import_aliases.clear();
+        self.import_alias_owners.clear();
+        self.stub_imports.clear();
+        self.hoisted_typetags.clear();
+        self.reached_unreachable = false;
+        self.reached_unresolved = None;
+        self.local_names = RESERVED_FILE_NAMES.iter().map(|s| s.to_string()).collect();
+        if let Some(mdef) = self.program.modules.get(&mname) {
+            for (sname, _) in mdef.structs.key_cloned_iter() {
+                self.local_names.insert(sname.to_string());
+            }
+            for (cname, _) in mdef.constants.key_cloned_iter() {
+                self.local_names.insert(cname.to_string());
+            }
+        }
         self.tests.clear();
+        self.skipped_tests.clear();
+        self.test_setup = None;
         // additive
         self.visited_modules.insert(mname);
-        self.visited_packages
-            .insert(format_address(mname.value.address), mname.value.address);
+        if !self.in_tests_subtree(&mname) {
+            self.visited_packages
+                .entry(self.package_name(&mname))
+                .or_insert(mname.value.address);
+        }
+    }
+
+    /// Address `mident` is declared under, whatever name the reference used
+    pub fn declared_address(&self, mident: &ModuleIdent) -> Address {
+        match self.declared_addresses.get(mident) {
+            Some(address) => *address,
+            None => mident.value.address,
+        }
+    }
+
+    /// Name of the package holding `mident`, the same for every reference to it
+    pub fn package_name(&self, mident: &ModuleIdent) -> String {
+        self.canonical_name(format_address(self.declared_address(mident)))
+    }
+
+    fn canonical_name(&self, name: String) -> String {
+        match self.address_aliases.get(&name) {
+            Some(canonical) => canonical.clone(),
+            None => name,
+        }
+    }
+
+    /// The module being translated. Every pass over a module sets it first, so its absence is a
+    /// bug, reported at `loc` with the code path that ran into it
+    pub fn current_module_or_err(&self, loc: Loc, path: &str) -> Result<ModuleIdent, Diagnostic> {
+        self.current_module.ok_or_else(|| {
+            Diagnostic::new(
+                InternalError {},
+                (
+                    loc,
+                    format!("{} ran without a module being translated", path),
+                ),
+                std::iter::empty::<(Loc, String)>(),
+                std::iter::empty::<String>(),
+            )
+        })
     }
 
     pub fn is_current_package(&self, other: &ModuleIdent) -> bool {
-        is_same_package(
-            self.current_module.unwrap().value.address,
-            other.value.address,
-        )
+        self.current_module.map_or(false, |mident| {
+            self.package_name(&mident) == self.package_name(other)
+        })
     }
 
     pub fn is_current_module(&self, other: &ModuleIdent) -> bool {
-        self.current_module.unwrap() == *other
+        self.current_module == Some(*other)
+    }
+
+    /// Runs `f` as if `mident` was the module being translated. The CLI replays what directives
+    /// collected after all modules went through, when current_module is whichever came last
+    pub fn with_current_module<T>(
+        &mut self,
+        mident: &ModuleIdent,
+        f: impl FnOnce(&Self) -> T,
+    ) -> T {
+        let previous = self.current_module.replace(*mident);
+        let result = f(self);
+        self.current_module = previous;
+        result
+    }
+
+    pub fn add_same_package_import(&mut self, mident: &ModuleIdent) -> String {
+        let namespace = self.module_namespace(mident);
+        self.add_import(format!("./{}", self.module_file_name(mident)), namespace)
     }
 
-    pub fn add_same_package_import(&mut self, modname: String) {
-        self.same_package_imports.insert(modname);
+    /// Directory (or, for the flat layout, file prefix) holding a package's sources
+    pub fn package_dir(&self, address: Address) -> String {
+        let dir = match (self.config.layout, address) {
+            (Layout::Hex, Address::Numerical(_, hex)) => {
+                hex.value.into_inner().to_hex_literal()
+            }
+            (_, Address::Numerical(Some(name), _)) => self.canonical_name(name.to_string()),
+            (_, Address::Numerical(None, hex)) => hex.value.into_inner().to_hex_literal(),
+            (_, Address::NamedUnassigned(name)) => self.canonical_name(name.to_string()),
+        };
+        sanitize_path_component(&dir)
+    }
+
+    pub fn package_dir_by_name(&self, package_name: &String) -> String {
+        match self.visited_packages.get(package_name) {
+            Some(address) => self.package_dir(*address),
+            None => package_name.clone(),
+        }
+    }
+
+    /// Recorded in the first line of each module file, so that --check can tell files generated
+    /// with different options apart
+    pub fn output_mode(&self) -> &'static str {
+        match (self.config.production, self.config.payload_only) {
+            (false, false) => "default",
+            (true, false) => "production",
+            (false, true) => "payload-only",
+            (true, true) => "production payload-only",
+        }
+    }
+
+    /// With --payload-only nothing is emitted for function bodies. Adding --production keeps
+    /// entry and query functions translated and declares the others with a stub
+    pub fn emitted_body(&self, func: &Function) -> EmittedBody {
+        if !self.config.payload_only {
+            return EmittedBody::Translated;
+        }
+        if !self.config.production {
+            return EmittedBody::Omitted;
+        }
+        let is_query = func
+            .attributes
+            .key_cloned_iter()
+            .any(|(name, _)| name.to_string() == "query");
+        if func.entry.is_some() || is_query {
+            EmittedBody::Translated
+        } else {
+            EmittedBody::Stub("is not translated with --payload-only".to_string())
+        }
+    }
+
+    /// The --skip-function entry for a function, spelled with either the hex or the name of its
+    /// address, and the reason it is skipped
+    pub fn skipped_function(
+        &self,
+        mident: &ModuleIdent,
+        name: &FunctionName,
+    ) -> Option<(String, String)> {
+        let addresses = [
+            format_address_hex(mident.value.address),
+            format_address(mident.value.address),
+        ];
+        addresses.iter().find_map(|address| {
+            let key = format!("{}::{}::{}", address, mident.value.module, name);
+            self.skipped_functions
+                .get(&key)
+                .map(|reason| (key, reason.clone()))
+        })
+    }
+
+    /// The --exclude-module entry for a module, spelled with either the hex or the name of its
+    /// address
+    pub fn excluded_module_entry(&self, mident: &ModuleIdent) -> Option<String> {
+        let addresses = [
+            format_address_hex(mident.value.address),
+            format_address(mident.value.address),
+        ];
+        addresses
+            .iter()
+            .map(|address| format!("{}::{}", address, mident.value.module))
+            .find(|key| self.excluded_modules.contains(key))
+    }
+
+    pub fn is_excluded_module(&self, mident: &ModuleIdent) -> bool {
+        self.excluded_module_entry(mident).is_some()
     }
 
-    pub fn add_package_import(&mut self, modname: String) {
-        self.package_imports.insert(modname);
+    /// Path of an excluded module's stub, without extension, relative to src
+    pub fn stub_path(&self, mident: &ModuleIdent) -> String {
+        format!(
+            "{}/{}_{}",
+            STUBS_DIR,
+            self.package_dir(self.declared_address(mident)),
+            sanitize_path_component(&mident.value.module.to_string())
+        )
+    }
+
+    /// Imports the stub of an excluded module into the current module, and has it generated
+    pub fn add_stub_import(&mut self, mident: &ModuleIdent) -> String {
+        self.stubbed_modules.insert(*mident);
+        self.stub_imports.insert(*mident);
+        let path = match self.config.layout {
+            Layout::Flat => format!("./{}", self.stub_path(mident)),
+            Layout::Named | Layout::Hex => format!("../{}", self.stub_path(mident)),
+        };
+        let preferred = format!(
+            "{}_{}_Stub",
+            capitalize(&self.package_name(mident)),
+            self.module_namespace(mident)
+        );
+        self.add_import(path, preferred)
+    }
+
+    /// Source file, relative to its package, and byte range of a declaration spanning from
+    /// `start` to `end`, for --emit-provenance
+    pub fn provenance(&self, start: Loc, end: Loc) -> Option<(String, u32, u32)> {
+        let file = self.source_paths.get(&start.file_hash())?;
+        Some((file.clone(), start.start(), end.end()))
+    }
+
+    pub fn emits_comments(&self) -> bool {
+        !self.config.production
+    }
+
+    pub fn emits_coverage(&self) -> bool {
+        self.config.test && self.config.test_coverage
+    }
+
+    pub fn emits_traces(&self) -> bool {
+        self.config.trace && !self.config.production
+    }
+
+    /// Type argument counts, cast ranges, and overflows and shift amounts of arithmetic are checked
+    /// unless --no-runtime-checks is given, or --production without --runtime-checks
+    pub fn emits_runtime_checks(&self) -> bool {
+        !self.config.no_runtime_checks && (!self.config.production || self.config.runtime_checks)
+    }
+
+    /// Name of a module's file, without extension, relative to its package's directory
+    pub fn module_file_name(&self, mident: &ModuleIdent) -> String {
+        if let Some((file_name, _)) = self.renamed_module_files.get(mident) {
+            return file_name.clone();
+        }
+        match self.config.layout {
+            Layout::Flat => format!(
+                "{}_{}",
+                self.package_dir(self.declared_address(mident)),
+                sanitize_path_component(&mident.value.module.to_string())
+            ),
+            Layout::Named | Layout::Hex => {
+                sanitize_path_component(&mident.value.module.to_string())
+            }
+        }
+    }
+
+    /// Path of a module's file, without extension, relative to src. #[test_only] modules, which
+    /// only exist with --gen-test, go to the tests subtree
+    pub fn module_path(&self, mident: &ModuleIdent) -> String {
+        let path = self.production_module_path(mident);
+        if self.in_tests_subtree(mident) {
+            format!("{}/{}", TESTS_DIR, path)
+        } else {
+            path
+        }
+    }
+
+    /// Whether a module is written under src/tests, and left out of the package indexes
+    pub fn in_tests_subtree(&self, mident: &ModuleIdent) -> bool {
+        self.config.test
+            && self.program.modules.get(mident).map_or(false, |mdef| {
+                mdef.attributes
+                    .key_cloned_iter()
+                    .any(|(name, _)| name.to_string() == "test_only")
+            })
+    }
+
+    /// Where a module's file would go if it was not #[test_only], which the paths of its imports
+    /// are first worked out from, and which its test file mirrors
+    pub fn production_module_path(&self, mident: &ModuleIdent) -> String {
+        match self.config.layout {
+            Layout::Flat => self.module_file_name(mident),
+            Layout::Named | Layout::Hex => format!(
+                "{}/{}",
+                self.package_dir(self.declared_address(mident)),
+                self.module_file_name(mident)
+            ),
+        }
+    }
+
+    /// Path of a package's index, without extension, relative to src
+    pub fn package_index_path(&self, package_dir: &String) -> String {
+        match self.config.layout {
+            Layout::Flat => package_dir.clone(),
+            Layout::Named | Layout::Hex => format!("{}/index", package_dir),
+        }
+    }
+
+    /// Import path of another package's index, from a module file or from the top of src
+    pub fn package_import_path(&self, package_dir: &String, from_module_file: bool) -> String {
+        let relative_dir = match self.config.layout {
+            Layout::Named | Layout::Hex if from_module_file => "../",
+            _ => "./",
+        };
+        self.config
+            .external_package_import_style
+            .package_path(package_dir, relative_dir)
+    }
+
+    /// Import path of a file under src/_helpers, from a module file
+    pub fn helper_import_path(&self, name: &str) -> String {
+        let path = match self.config.layout {
+            Layout::Flat => format!("./{}/{}", HELPERS_DIR, name),
+            Layout::Named | Layout::Hex => format!("../{}/{}", HELPERS_DIR, name),
+        };
+        self.rebase_import(path)
+    }
+
+    /// Import paths are worked out from where the current module's file would go if it was not
+    /// #[test_only]. Relative ones are made relative to where it goes instead
+    pub fn rebase_import(&self, path: String) -> String {
+        match self.current_module {
+            Some(mident) if self.in_tests_subtree(&mident) && path.starts_with('.') => {
+                let from = parent_dir(&self.production_module_path(&mident));
+                let target = join_relative(&from, &path);
+                relative_import_path(&parent_dir(&self.module_path(&mident)), &target)
+            }
+            _ => path,
+        }
+    }
+
+    /// Imports a #[test_only] module from the current module, by its file under src/tests.
+    /// Outside of a module, e.g. in the cli, the import is written from src
+    pub fn add_test_only_import(&mut self, mident: &ModuleIdent) -> String {
+        let from = match self.current_module {
+            Some(current) => parent_dir(&self.production_module_path(&current)),
+            None => "".to_string(),
+        };
+        let path = relative_import_path(&from, &self.module_path(mident));
+        let preferred = if self.is_current_package(mident) {
+            self.module_namespace(mident)
+        } else {
+            format!(
+                "{}_{}",
+                capitalize(&self.package_name(mident)),
+                self.module_namespace(mident)
+            )
+        };
+        self.add_import(path, preferred)
+    }
+
+    /// Import path of a module's file, from a file under src/_helpers
+    pub fn module_import_path_from_helpers(&self, mident: &ModuleIdent) -> String {
+        format!("../{}", self.module_path(mident))
+    }
+
+    pub fn module_namespace(&self, mident: &ModuleIdent) -> String {
+        match self.module_namespaces.get(mident) {
+            Some(namespace) => namespace.clone(),
+            None => capitalize(&mident.value.module),
+        }
+    }
+
+    /// Records that the current module refers to `mident`
+    pub fn add_module_dependency(&mut self, mident: &ModuleIdent) {
+        if let Some(current) = self.current_module {
+            self.module_dependencies
+                .entry(current)
+                .or_insert_with(BTreeSet::new)
+                .insert(*mident);
+        }
+    }
+
+    fn add_package_dependency(&mut self, package_dir: &String) {
+        // outside of a module there is no package to record the dependency of
+        if let Some(current) = self.current_module {
+            let current_dir = self.package_dir(self.declared_address(&current));
+            self.package_dependencies
+                .entry(current_dir)
+                .or_insert_with(BTreeSet::new)
+                .insert(package_dir.clone());
+        }
+    }
+
+    pub fn add_package_import(&mut self, mident: &ModuleIdent) -> String {
+        let package_dir = self.package_dir(self.declared_address(mident));
+        self.add_package_dependency(&package_dir);
+        let path = self.package_import_path(&package_dir, true);
+        let package_name = self.package_name(mident);
+        self.add_import(path, capitalize(&package_name))
+    }
+
+    /// Import path of a module of another package, from a module file
+    pub fn module_import_path(&self, mident: &ModuleIdent) -> String {
+        match self.config.layout {
+            Layout::Flat => format!("./{}", self.module_file_name(mident)),
+            Layout::Named | Layout::Hex => {
+                let package_dir = self.package_dir(self.declared_address(mident));
+                format!(
+                    "{}/{}",
+                    self.package_import_path(&package_dir, true),
+                    self.module_file_name(mident)
+                )
+            }
+        }
+    }
+
+    /// With --fine-grained-imports, a module of another package is imported on its own, as
+    /// <Package>_<Module>, instead of through the index of its package
+    pub fn add_module_import(&mut self, mident: &ModuleIdent) -> String {
+        let package_dir = self.package_dir(self.declared_address(mident));
+        self.add_package_dependency(&package_dir);
+        let preferred = format!(
+            "{}_{}",
+            capitalize(&self.package_name(mident)),
+            self.module_namespace(mident)
+        );
+        self.add_import(self.module_import_path(mident), preferred)
+    }
+
+    /// Returns the alias under which `path` is imported in the current file, picking one that
+    /// does not collide with other imports or local identifiers on first use
+    pub fn add_import(&mut self, path: String, preferred: String) -> String {
+        let path = self.rebase_import(path);
+        if let Some(alias) = self.import_aliases.get(&path) {
+            return alias.clone();
+        }
+        let mut alias = preferred.clone();
+        let mut suffix = 1;
+        while self.local_names.contains(&alias) || self.import_alias_owners.contains_key(&alias) {
+            alias = format!("{}${}", preferred, suffix);
+            suffix += 1;
+        }
+        self.import_aliases.insert(path.clone(), alias.clone());
+        self.import_alias_owners.insert(alias.clone(), path);
+        alias
+    }
+
+    /// Name of the module-level constant holding `tag`, declared by hoisted_typetag_decls
+    pub fn hoist_typetag(&mut self, tag: String) -> String {
+        let next_name = format!("$tag_{}", self.hoisted_typetags.len());
+        self.hoisted_typetags.entry(tag).or_insert(next_name).clone()
+    }
+
+    pub fn hoisted_typetag_decls(&self) -> Vec<String> {
+        self.hoisted_typetags
+            .iter()
+            .map(|(tag, name)| (name, tag))
+            .sorted_by_key(|(name, _)| name[5..].parse::<usize>().unwrap())
+            .map(|(name, tag)| format!("const {} = {};", name, tag))
+            .collect()
     }
 
     pub fn get_tparam_index(&self, tparam: &TParam) -> Option<usize> {
@@ -212,18 +1283,72 @@ impl Context {
         }
     }
 
+    /// Position of `tparam` in the `$p` of the function being translated, with the type parameter
+    /// and the code path that asked for it reported at `loc` when it is not one of its parameters
+    pub fn tparam_index_or_err(
+        &self,
+        tparam: &TParam,
+        loc: Loc,
+        path: &str,
+    ) -> Result<usize, Diagnostic> {
+        if let Some(idx) = self.get_tparam_index(tparam) {
+            return Ok(idx);
+        }
+        let name = tparam.user_specified_name;
+        let msg = match &self.current_function_signature {
+            None => format!(
+                "{} needs type parameter {} outside of a function signature",
+                path, name
+            ),
+            Some(sig) => format!(
+                "{} needs type parameter {}, which is not among the type parameters <{}> of the \
+                 function being translated",
+                path,
+                name,
+                sig.type_parameters
+                    .iter()
+                    .map(|tp| tp.user_specified_name.to_string())
+                    .join(", ")
+            ),
+        };
+        Err(Diagnostic::new(
+            InternalError {},
+            (loc, msg),
+            std::iter::empty::<(Loc, String)>(),
+            std::iter::empty::<String>(),
+        ))
+    }
+
+    /// Runs `f` with `sig` as the signature of the function being translated in `mident`, as the
+    /// passes that replay a function outside of its body need for its type parameters to resolve
+    pub fn with_current_function<T>(
+        &mut self,
+        mident: &ModuleIdent,
+        sig: &FunctionSignature,
+        f: impl FnOnce(&mut Self) -> T,
+    ) -> T {
+        let previous_module = self.current_module.replace(*mident);
+        let previous_signature = self.current_function_signature.replace(sig.clone());
+        let result = f(self);
+        self.current_module = previous_module;
+        self.current_function_signature = previous_signature;
+        result
+    }
+
     pub fn add_cmd(
         &mut self,
         mi: &ModuleIdent,
         fname: &FunctionName,
         func: &Function,
         desc: Option<String>,
+        param_descs: BTreeMap<String, String>,
     ) {
         self.cmds.push(CmdParams {
             mi: mi.clone(),
             fname: fname.clone(),
             func: func.clone(),
             desc: desc,
+            param_descs,
         });
     }
 
@@ -233,6 +1358,7 @@ impl Context {
             fname: fname.clone(),
             func: func.clone(),
             desc: None,
+            param_descs: BTreeMap::new(),
         });
     }
 
@@ -253,6 +1379,10 @@ impl Context {
         ));
     }
 
+    pub fn add_warning(&mut self, warning: Diagnostic) {
+        self.warnings.add(warning);
+    }
+
     pub fn add_show_iter_table(
         &mut self,
         mi: &ModuleIdent,
@@ -271,6 +1401,123 @@ impl Context {
     pub fn is_async(&self) -> bool {
         return self.config.asynchronous;
     }
+
+    /// Whether calls emitted at this point are awaited. With --asynchronous, a call outside of
+    /// an async scope could neither be awaited nor used synchronously, so it is rejected
+    pub fn await_calls(&self, loc: Loc) -> Result<bool, Diagnostic> {
+        if !self.is_async() {
+            Ok(false)
+        } else if self.in_async_scope {
+            Ok(true)
+        } else {
+            derr!((
+                loc,
+                "Cannot translate a call outside of a function body with --asynchronous"
+            ))
+        }
+    }
+
+    /// Whether identifiers declared by `mident` follow --naming. Framework modules always keep
+    /// their Move names, as the runtime and built-in helpers refer to them
+    pub fn uses_ts_naming(&self, mident: &ModuleIdent) -> bool {
+        self.config.naming == Naming::Camel && format_address_hex(mident.value.address) != "0x1"
+    }
+
+    /// Constructs struct `sname` of the current module from `proto`, which a pack site writes as an
+    /// object literal, and which --strict-construction only lets through `__pack`
+    pub fn construct_struct(&self, sname: &impl fmt::Display, proto: &str, tag: &str) -> String {
+        if self.config.strict_construction {
+            format!("{}.__pack({}, {})", sname, proto, tag)
+        } else {
+            format!("new {}({}, {})", sname, proto, tag)
+        }
+    }
+
+    /// TS member holding `field` of struct `sname` declared in `mident`
+    pub fn field_member(
+        &self,
+        mident: &ModuleIdent,
+        sname: &impl fmt::Display,
+        field: &impl fmt::Display,
+    ) -> String {
+        self.field_members
+            .get(&(*mident, sname.to_string()))
+            .and_then(|members| members.get(&field.to_string()))
+            .cloned()
+            .unwrap_or_else(|| base_field_member_name(field, self.uses_ts_naming(mident)))
+    }
+
+    /// TS name of a member named after a function declared in `mident`, e.g. a #[method]
+    pub fn member_ts_name(&self, mident: &ModuleIdent, name: &impl fmt::Display) -> String {
+        if self.uses_ts_naming(mident) {
+            rename(&to_camel_case(&name.to_string()))
+        } else {
+            rename(name)
+        }
+    }
+
+    /// Whether --hide-internal applies to a function declared in `mident`
+    pub fn is_hidden_function(&self, mident: &ModuleIdent, name: &impl fmt::Display) -> bool {
+        if !self.config.hide_internal {
+            return false;
+        }
+        let name = name.to_string();
+        match self.program.modules.get(mident) {
+            Some(mdef) => mdef
+                .functions
+                .key_cloned_iter()
+                .any(|(fname, func)| fname.to_string() == name && !is_public(func)),
+            None => false,
+        }
+    }
+
+    /// Numbers the compiler temporaries among `locals` per function. The compiler numbers them
+    /// with a counter that does not restart for every function, so its numbers depend on what
+    /// else it compiled before, e.g. the order of the source files
+    pub fn temp_var_names(&mut self, locals: &[Var]) {
+        let mut temps = locals
+            .iter()
+            .filter_map(|var| {
+                let name = var.to_string();
+                let number = name.strip_prefix("%#")?.parse::<usize>().ok()?;
+                Some((number, name))
+            })
+            .collect::<Vec<_>>();
+        temps.sort();
+        self.temp_numbers = temps
+            .into_iter()
+            .enumerate()
+            .map(|(i, (_, name))| (name, i))
+            .collect();
+    }
+
+    /// TS name of a local of the current function
+    pub fn var_name(&self, var: &impl fmt::Display) -> String {
+        let name = var.to_string();
+        match self.temp_numbers.get(&name) {
+            Some(number) => format!("temp${}", number),
+            None => rename(&name),
+        }
+    }
+
+    /// TS name of a function declared in `mident`, without the trailing `_`
+    pub fn function_ts_name(&self, mident: &ModuleIdent, name: &impl fmt::Display) -> String {
+        let ts_name = self.member_ts_name(mident, name);
+        if self.is_hidden_function(mident, name) {
+            format!("_{}", ts_name)
+        } else {
+            ts_name
+        }
+    }
+
+    /// TS name of a function declared in the current module. Outside of a module no member can
+    /// collide with it, so it is only renamed
+    pub fn local_function_ts_name(&self, name: &impl fmt::Display) -> String {
+        match self.current_module {
+            Some(mident) => self.function_ts_name(&mident, name),
+            None => rename(name),
+        }
+    }
 }
 
 pub trait AstTsPrinter {
@@ -290,6 +1537,42 @@ pub trait AstTsPrinter {
 pub type TermResult = Result<String, Diagnostic>;
 pub type WriteResult = Result<(), Diagnostic>;
 
+/// Visibility as exposed in the generated `visibility_` metadata. "package" is reserved for
+/// public(package), which is not parsed by the compiler version we build against
+pub fn visibility_name(visibility: &Visibility) -> &'static str {
+    match visibility {
+        Visibility::Public(_) => "public",
+        Visibility::Friend(_) => "friend",
+        Visibility::Internal => "private",
+    }
+}
+
+pub fn is_public(func: &Function) -> bool {
+    matches!(func.visibility, Visibility::Public(_))
+}
+
+/// Public entry functions get buildPayload_, cmd, query and ui surfaces. Private entry functions
+/// can be submitted as transactions just the same, but are internal to their module, so they are
+/// left out unless marked #[randomness], see has_payload_builder
+pub fn is_public_entry(func: &Function) -> bool {
+    func.entry.is_some() && is_public(func)
+}
+
+/// Entry functions marked #[randomness]. They have to be private so that no other module can test
+/// the outcome and abort, but can still be submitted as transactions
+pub fn is_randomness_entry(func: &Function) -> bool {
+    func.entry.is_some()
+        && func
+            .attributes
+            .key_cloned_iter()
+            .any(|(name, _)| name.to_string() == "randomness")
+}
+
+/// Functions that get a buildPayload_
+pub fn has_payload_builder(func: &Function) -> bool {
+    is_public_entry(func) || is_randomness_entry(func)
+}
+
 pub fn quote(quoted: &impl fmt::Display) -> String {
     format!("\"{}\"", quoted)
 }
@@ -318,6 +1601,20 @@ pub fn comma_term<T, F: Fn(T, &mut Context) -> TermResult>(
     comma_term_opt(items, c, f, true)
 }
 
+/// Names of the abilities in `abilities`, in the order they are usually declared
+pub fn ability_names(abilities: &AbilitySet) -> Vec<&'static str> {
+    [
+        (Ability_::Copy, "copy"),
+        (Ability_::Drop, "drop"),
+        (Ability_::Store, "store"),
+        (Ability_::Key, "key"),
+    ]
+    .iter()
+    .filter(|(ability, _)| abilities.has_ability_(*ability))
+    .map(|(_, name)| *name)
+    .collect()
+}
+
 pub fn format_address(address: Address) -> String {
     // this one prefers Name if it exists
     match address {
@@ -328,13 +1625,31 @@ pub fn format_address(address: Address) -> String {
 }
 
 pub fn format_address_hex(address: Address) -> String {
-    // this one prefers Name if it exists
+    // short form (0x1), which is the canonical form produced by $.normalizeAddress at runtime
     match address {
         Address::Numerical(_, hex) => hex.value.into_inner().to_hex_literal(),
         Address::NamedUnassigned(_name) => "".to_string(),
     }
 }
 
+/// Hex form of a module's address, for the places that need its value. Unassigned addresses are
+/// normally reported up front by check_unassigned_addresses
+pub fn concrete_address_hex(mident: &ModuleIdent, loc: Loc) -> TermResult {
+    match mident.value.address {
+        Address::Numerical(_, hex) => Ok(hex.value.into_inner().to_hex_literal()),
+        Address::NamedUnassigned(name) => derr!((loc, unassigned_address_message(&name, &[mident]))),
+    }
+}
+
+pub fn unassigned_address_message(name: &Name, needed_by: &[&ModuleIdent]) -> String {
+    format!(
+        "The named address '{}' has no value, but {} need{} it. Assign it under [addresses] or [dev-addresses] in Move.toml",
+        name,
+        needed_by.iter().map(|mident| mident.to_string()).join(", "),
+        if needed_by.len() == 1 { "s" } else { "" }
+    )
+}
+
 pub fn ts_format_numerical_address(numerical: &NumericalAddress) -> TermResult {
     Ok(format!(
         "new HexString(\"{}\")",
@@ -362,19 +1677,33 @@ pub fn format_qualified_name(
     let name = rename(name);
     if c.is_current_module(mident) {
         // name exists in same module, no qualifier needed
-        name
+        return name;
+    }
+    if c.is_excluded_module(mident) {
+        // the module is not translated, its structs come from an opaque stub
+        let alias = c.add_stub_import(mident);
+        return format!("{}.{}", alias, name);
+    }
+    c.add_module_dependency(mident);
+    if c.in_tests_subtree(mident) {
+        // #[test_only] modules are in no package index
+        let alias = c.add_test_only_import(mident);
+        format!("{}.{}", alias, name)
     } else if c.is_current_package(mident) {
         // name exists in same package, just add module name as qualifier
-        c.add_same_package_import(mident.value.module.to_string());
-        format!("{}.{}", capitalize(&mident.value.module), name)
+        let alias = c.add_same_package_import(mident);
+        format!("{}.{}", alias, name)
+    } else if c.config.fine_grained_imports {
+        // name exists in a different package, whose module is imported by itself
+        let alias = c.add_module_import(mident);
+        format!("{}.{}", alias, name)
     } else {
         // name exists in a different package, use fully qualified name
-        let package_name = format_address(mident.value.address);
-        c.add_package_import(package_name.clone());
+        let alias = c.add_package_import(mident);
         format!(
             "{}.{}.{}",
-            capitalize(&package_name),
-            capitalize(&mident.value.module),
+            alias,
+            c.module_namespace(mident),
             name
         )
     }
@@ -385,10 +1714,74 @@ pub fn format_function_name(fname: &impl fmt::Display, is_async: bool) -> String
     format!("{}{}_", await_modifier, fname)
 }
 
+/*
+Rendered typetags of struct types are memoized in Context, as the same few (Coin<X>, String,
+TypeInfo) are rendered thousands of times across a large package. The key spells out the type with
+type params replaced by their index, which is all the rendered string depends on: `$p[1]` means the
+same thing in every function, so entries need no invalidation when current_function_signature
+changes. Builtins are cheaper to render than to look up and are not cached. The hidden
+--no-typetag-cache renders everything, so tests/typetag_cache.rs can compare both outputs.
+ */
+fn typetag_cache_key(
+    base_ty: &BaseType,
+    param_index: &impl Fn(&TParam) -> Option<usize>,
+) -> Option<String> {
+    match &base_ty.value {
+        BaseType_::Param(tp) => param_index(tp).map(|idx| format!("#{}", idx)),
+        BaseType_::Apply(_, typename, ss) => {
+            let name = match &typename.value {
+                TypeName_::Builtin(builtin) => builtin.value.to_string(),
+                TypeName_::ModuleType(mident, sname) => format!(
+                    "{}::{}::{}",
+                    format_address_hex(mident.value.address),
+                    mident.value.module,
+                    sname
+                ),
+            };
+            let mut args = vec![];
+            for ty in ss.iter() {
+                args.push(typetag_cache_key(ty, param_index)?);
+            }
+            Some(format!("{}<{}>", name, args.join(",")))
+        }
+        _ => None,
+    }
+}
+
+fn is_struct_type(base_ty: &BaseType) -> bool {
+    match &base_ty.value {
+        BaseType_::Apply(_, typename, _) => matches!(&typename.value, TypeName_::ModuleType(_, _)),
+        _ => false,
+    }
+}
+
 pub fn base_type_to_typetag_builder(
     base_ty: &BaseType,
     tparams: &Vec<StructTypeParameter>,
     c: &mut Context,
+) -> TermResult {
+    if !is_struct_type(base_ty) || c.config.no_typetag_cache {
+        return render_typetag_builder(base_ty, tparams, c);
+    }
+    let key = typetag_cache_key(base_ty, &|tp: &TParam| {
+        tparams
+            .iter()
+            .position(|tp2| tp2.param.user_specified_name == tp.user_specified_name)
+    });
+    if let Some(rendered) = key.as_ref().and_then(|key| c.typetag_builder_cache.get(key)) {
+        return Ok(rendered.clone());
+    }
+    let rendered = render_typetag_builder(base_ty, tparams, c)?;
+    if let Some(key) = key {
+        c.typetag_builder_cache.insert(key, rendered.clone());
+    }
+    Ok(rendered)
+}
+
+fn render_typetag_builder(
+    base_ty: &BaseType,
+    tparams: &Vec<StructTypeParameter>,
+    c: &mut Context,
 ) -> TermResult {
     match &base_ty.value {
         BaseType_::Param(tp) => {
@@ -414,7 +1807,7 @@ pub fn base_type_to_typetag_builder(
                 BuiltinTypeName_::Signer => Ok("AtomicTypeTag.Signer".to_string()),
             },
             TypeName_::ModuleType(mident, sname) => {
-                let address = format_address_hex(mident.value.address);
+                let address = concrete_address_hex(mident, base_ty.loc)?;
                 let modname = mident.value.module;
                 let tparams = format!(
                     "[{}]",
@@ -433,17 +1826,43 @@ pub fn base_type_to_typetag_builder(
     }
 }
 
+/// Typetag of `base_ty` at a use site in the module file. Tags that do not depend on the type
+/// params of the enclosing function are allocated once, as module-level constants
 pub fn base_type_to_typetag(base_ty: &BaseType, c: &mut Context) -> TermResult {
+    let tag = cached_typetag(base_ty, c)?;
+    if tag.starts_with("new ") && !tag.contains("$p[") {
+        Ok(c.hoist_typetag(tag))
+    } else {
+        Ok(tag)
+    }
+}
+
+fn cached_typetag(base_ty: &BaseType, c: &mut Context) -> TermResult {
+    if !is_struct_type(base_ty) || c.config.no_typetag_cache {
+        return render_typetag(base_ty, c);
+    }
+    let key = typetag_cache_key(base_ty, &|tp: &TParam| c.get_tparam_index(tp));
+    if let Some(rendered) = key.as_ref().and_then(|key| c.typetag_cache.get(key)) {
+        return Ok(rendered.clone());
+    }
+    let rendered = render_typetag(base_ty, c)?;
+    if let Some(key) = key {
+        c.typetag_cache.insert(key, rendered.clone());
+    }
+    Ok(rendered)
+}
+
+fn render_typetag(base_ty: &BaseType, c: &mut Context) -> TermResult {
     match &base_ty.value {
         BaseType_::Param(tp) => {
-            let idx = c.get_tparam_index(tp).unwrap();
+            let idx = c.tparam_index_or_err(tp, base_ty.loc, "base_type_to_typetag")?;
             Ok(format!("$p[{}]", idx))
         }
         BaseType_::Apply(_, typename, ss) => match &typename.value {
             TypeName_::Builtin(builtin) => match &builtin.value {
                 BuiltinTypeName_::Vector => {
                     assert!(ss.len() == 1);
-                    let inner_builder = base_type_to_typetag(&ss[0], c)?;
+                    let inner_builder = cached_typetag(&ss[0], c)?;
                     Ok(format!("new VectorTag({})", inner_builder))
                 }
                 BuiltinTypeName_::Bool => Ok("AtomicTypeTag.Bool".to_string()),
@@ -454,9 +1873,9 @@ pub fn base_type_to_typetag(base_ty: &BaseType, c: &mut Context) -> TermResult {
                 BuiltinTypeName_::Signer => Ok("AtomicTypeTag.Signer".to_string()),
             },
             TypeName_::ModuleType(mident, sname) => {
-                let address = format_address_hex(mident.value.address);
+                let address = concrete_address_hex(mident, base_ty.loc)?;
                 let modname = mident.value.module;
-                let tparams = format!("[{}]", comma_term(ss, c, base_type_to_typetag)?);
+                let tparams = format!("[{}]", comma_term(ss, c, cached_typetag)?);
                 Ok(format!(
                     "new StructTag(new HexString({}), {}, {}, {})",
                     quote(&address),
@@ -466,8 +1885,14 @@ pub fn base_type_to_typetag(base_ty: &BaseType, c: &mut Context) -> TermResult {
                 ))
             }
         },
-        BaseType_::UnresolvedError => derr!((base_ty.loc, "Received Unresolved Type")),
-        BaseType_::Unreachable => derr!((base_ty.loc, "Received Unresolved Type")),
+        BaseType_::UnresolvedError => {
+            c.reached_unresolved = Some(base_ty.loc);
+            derr!((base_ty.loc, "Received Unresolved Type"))
+        }
+        BaseType_::Unreachable => {
+            c.reached_unreachable = true;
+            derr!((base_ty.loc, "Received Unreachable Type"))
+        }
     }
 }
 
@@ -1,11 +1,20 @@
 use move_compiler::diagnostics::Diagnostic;
 use std::collections::HashSet;
+use std::io;
 
-pub struct TsgenWriter {
+/*
+Completed lines go straight to `sink`, only the line being written is kept, so that a module of
+several megabytes is not also held as one String per line. The sink is an in-memory buffer
+unless the writer is built with_sink
+ */
+pub struct TsgenWriter<W: io::Write = Vec<u8>> {
     margin: usize,
     import_set: HashSet<String>,
     imports: Vec<String>,
-    lines: Vec<String>,
+    sink: W,
+    // first error of the sink, reported by finish()
+    sink_error: Option<io::Error>,
+    current: String,
 }
 
 impl Default for TsgenWriter {
@@ -16,16 +25,72 @@ impl Default for TsgenWriter {
 
 impl TsgenWriter {
     pub fn new() -> Self {
+        Self::with_sink(vec![])
+    }
+
+    /// Flushes the line being written and returns everything written so far
+    pub fn into_string(self) -> String {
+        let buffer = self.finish().expect("Writing to memory cannot fail");
+        String::from_utf8(buffer).expect("Generated code is valid UTF-8")
+    }
+}
+
+impl<W: io::Write> TsgenWriter<W> {
+    pub fn with_sink(sink: W) -> Self {
         Self {
             margin: 0,
             import_set: HashSet::new(),
             imports: vec![],
-            lines: vec![String::new()],
+            sink,
+            sink_error: None,
+            current: String::new(),
+        }
+    }
+
+    /// Flushes the line being written and hands back the sink
+    pub fn finish(mut self) -> io::Result<W> {
+        self.flush_line();
+        match self.sink_error.take() {
+            Some(err) => Err(err),
+            None => self.sink.flush().map(|_| self.sink),
+        }
+    }
+
+    /// A writer that continues the line being written here, and whose output only ends up here
+    /// once committed, so that a statement failing half-way leaves nothing of itself behind
+    pub fn fork(&self) -> TsgenWriter {
+        TsgenWriter {
+            margin: self.margin,
+            current: self.current.clone(),
+            ..TsgenWriter::new()
+        }
+    }
+
+    /// Takes over the output of a fork of this writer, including the line it is writing
+    pub fn commit(&mut self, fork: TsgenWriter) {
+        for line in fork.imports {
+            self.import(line);
+        }
+        if self.sink_error.is_none() {
+            self.sink_error = self.sink.write_all(&fork.sink).err();
         }
+        self.margin = fork.margin;
+        self.current = fork.current;
+    }
+
+    fn flush_line(&mut self) {
+        if self.sink_error.is_none() {
+            let res = self
+                .sink
+                .write_all(self.current.as_bytes())
+                .and_then(|_| self.sink.write_all(b"\n"));
+            self.sink_error = res.err();
+        }
+        self.current.clear();
     }
 
     fn cur(&mut self) -> &mut String {
-        self.lines.last_mut().unwrap()
+        &mut self.current
     }
 
     pub fn import(&mut self, line: String) {
@@ -36,7 +101,7 @@ impl TsgenWriter {
     }
 
     pub fn new_line(&mut self) {
-        self.lines.push(String::new());
+        self.flush_line();
     }
 
     pub fn write(&mut self, s: impl AsRef<str>) {
@@ -57,7 +122,7 @@ impl TsgenWriter {
         self.new_line();
     }
 
-    pub fn indent<F: FnMut(&mut TsgenWriter) -> Result<(), Diagnostic>>(
+    pub fn indent<F: FnMut(&mut Self) -> Result<(), Diagnostic>>(
         &mut self,
         inc: usize,
         mut f: F,
@@ -78,7 +143,7 @@ impl TsgenWriter {
         self.margin -= 2;
     }
 
-    pub fn short_block<F: FnMut(&mut TsgenWriter) -> Result<(), Diagnostic>>(
+    pub fn short_block<F: FnMut(&mut Self) -> Result<(), Diagnostic>>(
         &mut self,
         f: F,
     ) -> Result<(), Diagnostic> {
@@ -88,7 +153,7 @@ impl TsgenWriter {
         Ok(())
     }
 
-    pub fn list<T, F: FnMut(&mut TsgenWriter, T) -> Result<bool, Diagnostic>>(
+    pub fn list<T, F: FnMut(&mut Self, T) -> Result<bool, Diagnostic>>(
         &mut self,
         items: impl std::iter::IntoIterator<Item = T>,
         sep: &str,
@@ -115,11 +180,10 @@ impl TsgenWriter {
     }
 }
 
+// the output so far, for writers on the default in-memory sink
 impl std::fmt::Display for TsgenWriter {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        for line in &self.lines {
-            writeln!(f, "{}", line)?;
-        }
-        Ok(())
+        write!(f, "{}", String::from_utf8_lossy(&self.sink))?;
+        writeln!(f, "{}", self.current)
     }
 }
@@ -1,7 +1,14 @@
+use crate::ast_to_ts::{is_type_signer, script_function_has_valid_parameter};
+use crate::inspect::format_single_type;
+use crate::shared::{format_address_hex, has_payload_builder, Context};
 use itertools::Itertools;
 use move_compiler::expansion::ast::ModuleIdent;
+use std::collections::BTreeSet;
 use std::fmt;
 
+// move-compiler revision the translator is built against, keep in sync with Cargo.toml
+pub const MOVE_COMPILER_REV: &str = "a34266fc6c51bfc669d44f4c0faa337058e7833f";
+
 pub fn generate_package_json(package_name: String, cli: bool, ui: bool) -> (String, String) {
     let ui_dependencies = r###"
     "react": "^18.1.0",
@@ -81,6 +88,36 @@ pub fn generate_package_json(package_name: String, cli: bool, ui: bool) -> (Stri
     ("package.json".to_string(), content)
 }
 
+/// package.json for one package published on its own under `scope`, depending on its siblings
+pub fn generate_scoped_package_json(
+    scope: &String,
+    package_name: &String,
+    dependencies: Option<&BTreeSet<String>>,
+) -> (String, String) {
+    let sibling_dependencies = dependencies
+        .into_iter()
+        .flatten()
+        .map(|dep| format!("    \"{}/{}\": \"0.0.1\",\n", scope, dep))
+        .join("");
+    let content = format!(
+        r###"
+{{
+  "name": "{}/{}",
+  "version": "0.0.1",
+  "main": "index.js",
+  "typings": "index.d.ts",
+  "dependencies": {{
+{}    "aptos": "^1.3.5",
+    "big-integer": "^1.6.51",
+    "@manahippo/move-to-ts": "^0.1.2"
+  }}
+}}
+"###,
+        scope, package_name, sibling_dependencies
+    );
+    (format!("{}/package.json", package_name), content)
+}
+
 pub fn generate_ts_config() -> (String, String) {
     let content = r###"
 {
@@ -104,15 +141,98 @@ pub fn generate_ts_config() -> (String, String) {
     ("tsconfig.json".to_string(), content.to_string())
 }
 
-pub fn generate_jest_config() -> (String, String) {
-    let content = r###"
-module.exports = {
+// hits of each test file under --test-coverage, merged into coverage.json after the run
+pub const COVERAGE_HITS_DIR: &str = ".move-to-ts-coverage";
+// the functions counted by --test-coverage, including those that no test reaches
+pub const COVERAGE_FUNCTIONS_FILE_NAME: &str = "coverage-functions.json";
+
+pub fn generate_jest_config(coverage: bool) -> (String, String) {
+    let hooks = if coverage {
+        r###"  globalSetup: "./jest.coverage-setup.js",
+  globalTeardown: "./jest.coverage-teardown.js",
+"###
+    } else {
+        ""
+    };
+    let content = format!(
+        r###"
+module.exports = {{
   preset: "ts-jest",
   testEnvironment: "node",
   testPathIgnorePatterns: ["dist/*"],
-};
-"###;
-    ("jest.config.js".to_string(), content.to_string())
+{}}};
+"###,
+        hooks
+    );
+    ("jest.config.js".to_string(), content)
+}
+
+/// jest hooks of --test-coverage: the hits of a previous run are cleared before the tests, and
+/// merged into coverage.json after them
+pub fn generate_jest_coverage_hooks() -> Vec<(String, String)> {
+    let setup = format!(
+        r###"const $ = require("@manahippo/move-to-ts");
+module.exports = async () => $.resetCoverageHits("{}");
+"###,
+        COVERAGE_HITS_DIR
+    );
+    let teardown = format!(
+        r###"const $ = require("@manahippo/move-to-ts");
+module.exports = async () => $.mergeCoverageHits("{}", "{}", "coverage.json");
+"###,
+        COVERAGE_HITS_DIR, COVERAGE_FUNCTIONS_FILE_NAME
+    );
+    vec![
+        ("jest.coverage-setup.js".to_string(), setup),
+        ("jest.coverage-teardown.js".to_string(), teardown),
+    ]
+}
+
+/// The .ext.ts of a module with structs, created once next to the module file and owned by the
+/// user afterwards. The module requires it, and the empty interface after each struct class lets
+/// methods declared here merge into the class
+pub fn generate_extension_file(
+    mident: &ModuleIdent,
+    example_struct: &str,
+    c: &Context,
+) -> (String, String) {
+    let file_name = c.module_file_name(mident);
+    let content = format!(
+        r###"/*
+Hand-written extensions of the classes generated in ./{file}.ts. move-to-ts creates this file once
+and never overwrites or prunes it, so methods added here survive regeneration, e.g.
+
+import {{ {name} }} from "./{file}";
+
+declare module "./{file}" {{
+  interface {name} {{
+    describe(): string;
+  }}
+}}
+
+{name}.prototype.describe = function () {{
+  return `{name} of type ${{this.typeTag}}`;
+}};
+*/
+export {{}};
+"###,
+        file = file_name,
+        name = example_struct
+    );
+    (format!("{}.ext.ts", c.module_path(mident)), content)
+}
+
+/// Contents of coverage-functions.json, the fully qualified names of the counted functions
+pub fn generate_coverage_functions(c: &Context) -> (String, String) {
+    let names = c
+        .coverage_functions
+        .iter()
+        .map(|name| format!("  \"{}\"", name))
+        .join(",\n");
+    (
+        COVERAGE_FUNCTIONS_FILE_NAME.to_string(),
+        format!("[\n{}\n]\n", names),
+    )
 }
 
 /*
@@ -139,6 +259,69 @@ pub fn rename(name: &impl fmt::Display) -> String {
     }
 }
 
+pub fn to_camel_case(name: &str) -> String {
+    // leading underscores are kept, every later underscore capitalizes the next character
+    let trimmed = name.trim_start_matches('_');
+    let mut result = name[..name.len() - trimmed.len()].to_string();
+    let mut upper_next = false;
+    for ch in trimmed.chars() {
+        if ch == '_' {
+            upper_next = true;
+        } else if upper_next {
+            result.extend(ch.to_uppercase());
+            upper_next = false;
+        } else {
+            result.push(ch);
+        }
+    }
+    if upper_next {
+        result.push('_');
+    }
+    result
+}
+
+pub fn field_member_name(name: &impl fmt::Display, c: &Context) -> String {
+    // fields are only ever accessed from the module declaring their struct
+    let ts_naming = match c.current_module {
+        Some(mident) => c.uses_ts_naming(&mident),
+        None => false,
+    };
+    base_field_member_name(name, ts_naming)
+}
+
+/// TS member for a field, before telling apart fields of one struct that end up with the same one
+pub fn base_field_member_name(name: &impl fmt::Display, ts_naming: bool) -> String {
+    let renamed = if ts_naming {
+        rename(&to_camel_case(&name.to_string()))
+    } else {
+        rename(name)
+    };
+    // struct fields that would collide with members every generated struct class has
+    match renamed.as_str() {
+        "typeTag" | "constructor" | "getFieldValue" | "getField" => format!("{}__", renamed),
+        _ => renamed,
+    }
+}
+
+/// Keeps a name from escaping or nesting into the output tree when used as one path component
+pub fn sanitize_path_component(name: &str) -> String {
+    let sanitized = name
+        .chars()
+        .map(|ch| {
+            if ch.is_ascii_alphanumeric() || ch == '_' || ch == '-' {
+                ch
+            } else {
+                '_'
+            }
+        })
+        .collect::<String>();
+    if sanitized.is_empty() {
+        "_".to_string()
+    } else {
+        sanitized
+    }
+}
+
 pub fn capitalize(name: &impl fmt::Display) -> String {
     let name_str = format!("{}", name);
     let mut c = name_str.chars();
@@ -148,15 +331,75 @@ pub fn capitalize(name: &impl fmt::Display) -> String {
     }
 }
 
-pub fn generate_index(package_name: &String, modules: &Vec<&ModuleIdent>) -> (String, String) {
-    let filename = format!("{}/index.ts", package_name);
+/// 64-bit FNV-1a, used for content hashes that must stay stable across toolchains
+pub fn fnv1a_hash(content: &str) -> String {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in content.bytes() {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    format!("{:016x}", hash)
+}
+
+/// Entry functions of the package that get a buildPayload_, as (module::name, type param count,
+/// non-signer param count, signature)
+fn package_entry_functions(
+    modules: &Vec<&ModuleIdent>,
+    c: &Context,
+) -> Vec<(String, usize, usize, String)> {
+    let mut entries = vec![];
+    for mi in modules.iter() {
+        let mdef = match c.program.modules.get(mi) {
+            Some(mdef) => mdef,
+            None => continue,
+        };
+        for (fname, func) in mdef.functions.key_cloned_iter() {
+            if !has_payload_builder(func) || !script_function_has_valid_parameter(&func.signature) {
+                continue;
+            }
+            let params = func
+                .signature
+                .parameters
+                .iter()
+                .filter(|(_, ty)| !is_type_signer(ty))
+                .map(|(_, ty)| format_single_type(ty))
+                .collect::<Vec<_>>();
+            let num_tparams = func.signature.type_parameters.len();
+            let name = format!("{}::{}", mi.value.module, fname);
+            let signature = format!("{}<{}>({})", name, num_tparams, params.join(", "));
+            entries.push((name, num_tparams, params.len(), signature));
+        }
+    }
+    entries
+}
+
+/// The generation time for the package index, taken from SOURCE_DATE_EPOCH (seconds) so that
+/// reruns and --check produce the same files; null when it is not set
+fn generation_time() -> String {
+    std::env::var("SOURCE_DATE_EPOCH")
+        .ok()
+        .and_then(|secs| secs.trim().parse::<u64>().ok())
+        .map_or("null".to_string(), |secs| {
+            format!("new Date({})", secs as u128 * 1000)
+        })
+}
+
+pub fn generate_index(
+    package_name: &String,
+    modules: &Vec<&ModuleIdent>,
+    c: &Context,
+) -> (String, String) {
+    let filename = format!(
+        "{}.ts",
+        c.package_index_path(&c.package_dir_by_name(package_name))
+    );
     let exports = modules
         .iter()
         .map(|mi| {
             format!(
                 "export * as {} from './{}';\n",
-                capitalize(&mi.value.module),
-                mi.value.module
+                c.module_namespace(mi),
+                c.module_file_name(mi)
             )
         })
         .collect::<Vec<_>>()
@@ -167,24 +410,70 @@ pub fn generate_index(package_name: &String, modules: &Vec<&ModuleIdent>) -> (St
         .map(|mi| {
             format!(
                 "import * as {} from './{}';\n",
-                capitalize(&mi.value.module),
-                mi.value.module
+                c.module_namespace(mi),
+                c.module_file_name(mi)
             )
         })
         .collect::<Vec<_>>()
         .join("");
 
+    // modules without structs have no loadParsers
     let loads = modules
         .iter()
-        .map(|mi| format!("  {}.loadParsers(repo);", capitalize(&mi.value.module)))
+        .filter(|mi| {
+            c.program
+                .modules
+                .get(mi)
+                .map_or(false, |mdef| !mdef.structs.is_empty())
+        })
+        .map(|mi| format!("  {}.loadParsers(repo);", c.module_namespace(mi)))
         .join("\n");
 
+    let package_address = modules
+        .first()
+        .map(|mi| format_address_hex(mi.value.address))
+        .unwrap_or_default();
+    let entry_functions = package_entry_functions(modules, c);
+    let signatures_hash = fnv1a_hash(
+        &entry_functions
+            .iter()
+            .map(|(_, _, _, signature)| signature)
+            .join("\n"),
+    );
+    let entry_function_decls = entry_functions
+        .iter()
+        .map(|(name, num_tparams, num_params, _)| {
+            format!(
+                "  \"{}\": {{ typeParams: {}, params: {} }},\n",
+                name, num_tparams, num_params
+            )
+        })
+        .join("");
+    let generated_at = generation_time();
+
     let content = format!(
         r###"
-import {{ AptosParserRepo }} from "@manahippo/move-to-ts";
+import * as $ from "@manahippo/move-to-ts";
+import {{ AptosDataCache, AptosParserRepo }} from "@manahippo/move-to-ts";
+import {{ AptosClient, HexString }} from "aptos";
 {}
 {}
 
+export const packageName = "{}";
+export const packageAddress = new HexString("{}");
+export const generatedAt: Date | null = {};
+export const translatorVersion = "{}";
+export const compilerVersion = "{}";
+// hash of the signatures of all entry functions below, changes whenever one of them does
+export const entryFunctionsHash = "{}";
+export const entryFunctions: Record<string, $.EntryFunctionArity> = {{
+{}}};
+
+// compares the entry functions above against the modules currently published at packageAddress
+export async function checkCompatibility(client: AptosClient): Promise<$.CompatibilityReport> {{
+  return await $.checkPackageCompatibility(client, packageAddress, entryFunctions);
+}}
+
 export function loadParsers(repo: AptosParserRepo) {{
 {}
 }}
@@ -195,24 +484,70 @@ export function getPackageRepo(): AptosParserRepo {{
   repo.addDefaultParsers();
   return repo;
 }}
+
+// type strings off-chain may carry padded addresses, this gives the form used by loadParsers
+export const normalizeTypeString = $.normalizeTypeString;
+
+// only contains parsers of structs defined in this package
+const packageParsers = new AptosParserRepo();
+loadParsers(packageParsers);
+
+export function decodeEvent(event: $.MoveEvent, repo: AptosParserRepo): $.DecodedEvent | null {{
+  const typeTag = $.tryParseStructTag(event.type);
+  if (typeTag === null || packageParsers.getParserFromTypeTag(typeTag) === null) {{
+    return null;
+  }}
+  return {{ type: $.normalizeTypeString(event.type), typeTag, value: repo.parse(event.data, typeTag) }};
+}}
+
+// loads resources of accounts into $c, returns warnings for the ones that could not be loaded
+export async function snapshotToCache(
+  client: AptosClient,
+  repo: AptosParserRepo,
+  accounts: HexString[],
+  $c: AptosDataCache
+): Promise<string[]> {{
+  return await $.snapshotAccountsToCache(client, repo, accounts, $c);
+}}
 "###,
-        imports, exports, loads
+        imports,
+        exports,
+        package_name,
+        package_address,
+        generated_at,
+        env!("CARGO_PKG_VERSION"),
+        MOVE_COMPILER_REV,
+        signatures_hash,
+        entry_function_decls,
+        loads
     );
 
     (filename, content)
 }
 
-pub fn generate_topmost_index(packages: &Vec<&String>) -> (String, String) {
+pub fn generate_topmost_index(packages: &Vec<&String>, c: &Context) -> (String, String) {
     let filename = "index.ts".to_string();
     let exports = packages
         .iter()
-        .map(|package_name| format!("export * as {} from './{}';\n", package_name, package_name))
+        .map(|package_name| {
+            format!(
+                "export * as {} from '{}';\n",
+                package_name,
+                c.package_import_path(&c.package_dir_by_name(package_name), false)
+            )
+        })
         .collect::<Vec<_>>()
         .join("");
 
     let imports = packages
         .iter()
-        .map(|package_name| format!("import * as {} from './{}';\n", package_name, package_name))
+        .map(|package_name| {
+            format!(
+                "import * as {} from '{}';\n",
+                package_name,
+                c.package_import_path(&c.package_dir_by_name(package_name), false)
+            )
+        })
         .collect::<Vec<_>>()
         .join("");
 
@@ -240,8 +575,34 @@ export function getProjectRepo(): AptosParserRepo {{
     (filename, content)
 }
 
-pub fn get_table_helper_decl() -> String {
-    r###"
+/// Marks the first line of every file under src/_helpers with the version that generated it, so
+/// that --check can tell a helper left behind by another move-to-ts version from a local edit
+pub const HELPERS_VERSION_MARKER: &str = "// move-to-ts helpers ";
+
+/// First line of every module file, naming the mode it was generated in (see Context::output_mode)
+pub const MODE_MARKER: &str = "// move-to-ts mode: ";
+
+fn get_helpers_header() -> String {
+    format!(
+        "{}{}\n// shared by every package generated into this output tree, do not edit\n",
+        HELPERS_VERSION_MARKER,
+        env!("CARGO_PKG_VERSION")
+    )
+}
+
+/// Contents of _helpers/table.ts, `table_path` being the import path of 0x1::table from there
+pub fn get_table_helper_decl(table_path: &str) -> String {
+    let imports = format!(
+        r###"import * as $ from "@manahippo/move-to-ts";
+import {{AptosParserRepo, FieldDeclType, StructTag, TypeTag}} from "@manahippo/move-to-ts";
+import {{AptosClient}} from "aptos";
+import {{Table}} from "{}";
+"###,
+        table_path
+    );
+    get_helpers_header()
+        + &imports
+        + r###"
 export class TypedTable<K, V> {
   static buildFromField<K, V>(table: Table, field: FieldDeclType): TypedTable<K, V> {
     const tag = field.typeTag;
@@ -279,11 +640,22 @@ export class TypedTable<K, V> {
   }
 }
 "###
-    .to_string()
 }
 
-pub fn get_iterable_table_helper_decl() -> String {
-    r###"
+/// Contents of _helpers/iterable_table.ts, which also needs 0x1::option to walk the entries
+pub fn get_iterable_table_helper_decl(iterable_table_path: &str, option_path: &str) -> String {
+    let imports = format!(
+        r###"import * as $ from "@manahippo/move-to-ts";
+import {{AptosParserRepo, FieldDeclType, StructTag, TypeTag}} from "@manahippo/move-to-ts";
+import {{AptosClient}} from "aptos";
+import {{IterableTable, IterableValue, moduleAddress, moduleName}} from "{}";
+import * as Option from "{}";
+"###,
+        iterable_table_path, option_path
+    );
+    get_helpers_header()
+        + &imports
+        + r###"
 export class TypedIterableTable<K, V> {
   static buildFromField<K, V>(table: IterableTable, field: FieldDeclType): TypedIterableTable<K, V> {
     const tag = field.typeTag;
@@ -326,8 +698,8 @@ export class TypedIterableTable<K, V> {
     const result: [K, V][] = [];
     const cache = new $.DummyCache();
     let next = this.table.head;
-    while(next && await Std.Option.is_some_(next, cache, [this.keyTag])) {
-      const key = await Std.Option.borrow_(next, cache, [this.keyTag]) as K;
+    while(next && await Option.is_some_(next, cache, [this.keyTag])) {
+      const key = await Option.borrow_(next, cache, [this.keyTag]) as K;
       const iterVal = await this.loadEntry(client, repo, key);
       const value = iterVal.val as V;
       result.push([key, value]);
@@ -337,5 +709,186 @@ export class TypedIterableTable<K, V> {
   }
 }
 "###
-        .to_string()
+}
+
+/// Contents of _helpers/primary_fungible_store.ts. Balances of fungible assets live in objects
+/// derived from the owner and the metadata object, so they cannot be looked up by owner alone.
+/// With `concurrent_balance`, a store whose balance moved to ConcurrentFungibleBalance is read
+/// from there
+pub fn get_primary_fungible_store_helper_decl(
+    fungible_asset_path: &str,
+    concurrent_balance: bool,
+) -> String {
+    let imports = format!(
+        r###"import * as $ from "@manahippo/move-to-ts";
+import {{AptosParserRepo, U64, u64}} from "@manahippo/move-to-ts";
+import {{AptosClient, HexString}} from "aptos";
+import {{{}FungibleStore, Metadata}} from "{}";
+"###,
+        if concurrent_balance {
+            "ConcurrentFungibleBalance, "
+        } else {
+            ""
+        },
+        fungible_asset_path
+    );
+    let concurrent_read = if concurrent_balance {
+        r###"
+  if (store.balance.value.isZero()) {
+    try {
+      const concurrent = await repo.loadResource(client, storeAddress, ConcurrentFungibleBalance, []) as ConcurrentFungibleBalance;
+      return $.aggregatorRead(concurrent.balance) as U64;
+    } catch (e) {
+      if ((e as any).status !== 404) {
+        throw e;
+      }
+    }
+  }"###
+    } else {
+        ""
+    };
+    let helpers = r###"
+// same as primary_fungible_store::primary_store_address, an object derived from the owner
+export function primaryStoreAddress(owner: HexString, metadata: HexString): HexString {
+  return $.deriveUserObjectAddress(owner, metadata);
+}
+
+export async function fetchMetadata(client: AptosClient, repo: AptosParserRepo, metadataAddr: HexString): Promise<Metadata> {
+  return await repo.loadResource(client, metadataAddr, Metadata, []) as Metadata;
+}
+
+// 0 when the owner has no primary store for the asset yet
+export async function fetchPrimaryBalance(
+  client: AptosClient,
+  repo: AptosParserRepo,
+  owner: HexString,
+  metadata: HexString
+): Promise<U64> {
+  const storeAddress = primaryStoreAddress(owner, metadata);
+  let store: FungibleStore;
+  try {
+    store = await repo.loadResource(client, storeAddress, FungibleStore, []) as FungibleStore;
+  } catch (e) {
+    if ((e as any).status === 404) {
+      return u64(0);
+    }
+    throw e;
+  }"###;
+    get_helpers_header() + &imports + helpers + concurrent_read + "\n  return store.balance;\n}\n"
+}
+
+/// Contents of _helpers/token_objects.ts, for 0x4::token. Collections and tokens are named
+/// objects, so their addresses follow from the creator and the names. `royalty_path` is the import
+/// path of 0x4::royalty, when it is translated as well
+pub fn get_token_objects_helper_decl(token_path: &str, royalty_path: Option<&str>) -> String {
+    let mut imports = format!(
+        r###"import * as $ from "@manahippo/move-to-ts";
+import {{AptosParserRepo, U8, u8}} from "@manahippo/move-to-ts";
+import {{AptosClient, HexString}} from "aptos";
+import {{Token}} from "{}";
+"###,
+        token_path
+    );
+    if let Some(royalty_path) = royalty_path {
+        imports.push_str(&format!("import {{Royalty}} from \"{}\";\n", royalty_path));
+    }
+    let addresses = r###"
+function utf8(str: string): U8[] {
+  return Array.from(Buffer.from(str, 'utf8')).map(b => u8(b));
+}
+
+// same as collection::create_collection_address
+export function collectionAddress(creator: HexString, collection: string): HexString {
+  return $.deriveObjectAddress(creator, utf8(collection));
+}
+
+// same as token::create_token_address
+export function tokenAddress(creator: HexString, collection: string, name: string): HexString {
+  return $.deriveObjectAddress(creator, utf8(`${collection}::${name}`));
+}
+"###;
+    let load_token = match royalty_path {
+        Some(_) => {
+            r###"
+async function loadRoyalty(client: AptosClient, repo: AptosParserRepo, address: HexString): Promise<Royalty | null> {
+  try {
+    return await repo.loadResource(client, address, Royalty, []) as Royalty;
+  } catch (e) {
+    if ((e as any).status === 404) {
+      return null;
+    }
+    throw e;
+  }
+}
+
+// the Token at objectAddr, carrying the royalty of the token, or else of its collection
+export async function loadToken(client: AptosClient, repo: AptosParserRepo, objectAddr: HexString): Promise<Token> {
+  const token = await repo.loadResource(client, objectAddr, Token, []) as Token;
+  token.$royalty = await loadRoyalty(client, repo, objectAddr) ?? await loadRoyalty(client, repo, token.collection.inner);
+  return token;
+}
+"###
+        }
+        None => {
+            r###"
+export async function loadToken(client: AptosClient, repo: AptosParserRepo, objectAddr: HexString): Promise<Token> {
+  return await repo.loadResource(client, objectAddr, Token, []) as Token;
+}
+"###
+        }
+    };
+    get_helpers_header() + &imports + addresses + load_token
+}
+
+/// Contents of _helpers/struct_base.ts, which the struct classes extend with --compact-structs.
+/// The members every class would otherwise repeat are implemented once, from the static metadata
+/// each class still declares
+pub fn get_struct_base_helper_decl() -> String {
+    get_helpers_header()
+        + r###"import * as $ from "@manahippo/move-to-ts";
+import {AptosParserRepo, FieldDeclType, TypeParamDeclType, TypeTag} from "@manahippo/move-to-ts";
+import {HexString} from "aptos";
+
+export class StructBase {
+  static moduleAddress: HexString;
+  static moduleName: string;
+  static structName: string;
+  static typeParameters: TypeParamDeclType[];
+  static fields: FieldDeclType[];
+  static abilities: string[];
+
+  static get typeParamCount(): number {
+    return this.typeParameters.length;
+  }
+
+  static get phantomTypeParams(): boolean[] {
+    return this.typeParameters.map(param => param.isPhantom);
+  }
+
+  constructor(proto: any, public typeTag: TypeTag) {
+    for (const field of (this.constructor as typeof StructBase).fields) {
+      (this as any)[$.fieldMemberName(field)] = proto[field.name];
+    }
+  }
+
+  // the body of each <Struct>Parser, after its check of the type arguments
+  static parseStruct<T>(
+    this: { new (proto: any, typeTag: TypeTag): T } & typeof StructBase,
+    data: any,
+    typeTag: TypeTag,
+    repo: AptosParserRepo
+  ): T {
+    const proto = $.parseStructProto(data, typeTag, repo, this);
+    return new this(proto, typeTag);
+  }
+
+  static getField(name: string): FieldDeclType {
+    return $.getStructField(this, name);
+  }
+
+  getFieldValue(path: string): unknown {
+    return $.getFieldValueByPath(this, path);
+  }
+}
+"###
 }
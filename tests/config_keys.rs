@@ -0,0 +1,71 @@
+/*
+Every long flag can also be set as a key of move-to-ts.toml, except for the ones that only make
+sense for one run. Each flag listed by --help is written to a config file with a value of the wrong
+type, which must be rejected for its type rather than as an unknown key:  cargo test --test config_keys
+ */
+use std::process::Command;
+
+// flags that select what a run does rather than how the package is translated
+const COMMAND_LINE_ONLY: [&str; 5] = ["help", "version", "path", "print-config", "check"];
+
+fn long_flags() -> Vec<String> {
+    let output = Command::new(env!("CARGO_BIN_EXE_move-to-ts"))
+        .arg("--help")
+        .output()
+        .expect("Failed to run move-to-ts");
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| {
+            let line = line.trim_start();
+            let line = match line.strip_prefix('-') {
+                Some(rest) if !rest.starts_with('-') => rest.get(3..)?,
+                _ => line,
+            };
+            let name = line.strip_prefix("--")?;
+            let end = name
+                .find(|ch: char| ch == ' ' || ch == '<')
+                .unwrap_or(name.len());
+            Some(name[..end].to_string())
+        })
+        .collect()
+}
+
+#[test]
+fn every_long_flag_is_a_config_key() {
+    let flags = long_flags();
+    assert!(
+        flags.iter().any(|flag| flag == "gen-test"),
+        "failed to read the flags from --help: {:?}",
+        flags
+    );
+    let dir = std::env::temp_dir().join(format!("move-to-ts-config-keys-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(dir.join("sources")).unwrap();
+    std::fs::write(
+        dir.join("Move.toml"),
+        "[package]\nname = \"ConfigKeys\"\nversion = \"0.0.1\"\n",
+    )
+    .unwrap();
+    let mut missing = vec![];
+    for flag in flags.iter() {
+        if COMMAND_LINE_ONLY.contains(&flag.as_str()) {
+            continue;
+        }
+        std::fs::write(dir.join("move-to-ts.toml"), format!("{} = {{}}\n", flag)).unwrap();
+        let output = Command::new(env!("CARGO_BIN_EXE_move-to-ts"))
+            .arg("-p")
+            .arg(&dir)
+            .arg("--print-config")
+            .output()
+            .expect("Failed to run move-to-ts");
+        if String::from_utf8_lossy(&output.stdout).contains("unknown field") {
+            missing.push(flag.clone());
+        }
+    }
+    let _ = std::fs::remove_dir_all(&dir);
+    assert!(
+        missing.is_empty(),
+        "flags without a key in move-to-ts.toml: {:?}",
+        missing
+    );
+}
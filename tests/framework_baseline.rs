@@ -0,0 +1,126 @@
+/*
+Translates move-testsuite, and with it the whole aptos-framework revision that its dependencies
+pin, with --skip-function for the entries of framework_baseline.skip only. Any translation error
+or stale skip entry fails the test, the output has to compile with tsc against the runtime in
+typescript/, and a few modules are compared against the snapshots committed under
+move-testsuite/stress/snapshots/framework_baseline. A missing snapshot fails too, and
+UPDATE_SNAPSHOTS=1 records them from this run instead.
+Needs network access for the Move dependencies, and `yarn install` plus `yarn build` in
+typescript/:  cargo test --test framework_baseline -- --ignored
+ */
+#![cfg(unix)]
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+// a module of move-stdlib, and two of aptos-framework that many others depend on
+const SNAPSHOT_MODULES: [&str; 3] = [
+    "std/option",
+    "aptos_framework/coin",
+    "aptos_framework/account",
+];
+
+fn repo_root() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+}
+
+fn skip_args(root: &Path) -> Vec<String> {
+    let skips = root.join("move-testsuite/stress/framework_baseline.skip");
+    std::fs::read_to_string(&skips)
+        .expect("Failed to read framework_baseline.skip")
+        .lines()
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .flat_map(|line| ["--skip-function".to_string(), line.to_string()])
+        .collect()
+}
+
+/// The generated package resolves its dependencies, and the runtime, from typescript/
+fn link_runtime(runtime: &Path, out: &Path) {
+    let node_modules = out.join("node_modules");
+    std::fs::create_dir_all(node_modules.join("@manahippo")).unwrap();
+    for dep in std::fs::read_dir(runtime.join("node_modules")).unwrap() {
+        let dep = dep.unwrap();
+        if dep.file_name() != "@manahippo" {
+            std::os::unix::fs::symlink(dep.path(), node_modules.join(dep.file_name())).unwrap();
+        }
+    }
+    std::os::unix::fs::symlink(runtime, node_modules.join("@manahippo/move-to-ts")).unwrap();
+}
+
+#[test]
+#[ignore = "needs network access for the Move dependencies, and yarn build in typescript/"]
+fn framework_baseline() {
+    let root = repo_root();
+    let runtime = root.join("typescript");
+    let tsc = runtime.join("node_modules/.bin/tsc");
+    assert!(
+        runtime.join("dist/index.d.ts").is_file() && tsc.is_file(),
+        "run yarn install and yarn build in typescript/ first"
+    );
+
+    let out = std::env::temp_dir().join(format!(
+        "move-to-ts-framework-baseline-{}",
+        std::process::id()
+    ));
+    let _ = std::fs::remove_dir_all(&out);
+    let translated = Command::new(env!("CARGO_BIN_EXE_move-to-ts"))
+        .arg("-p")
+        .arg(root.join("move-testsuite"))
+        .arg("-o")
+        .arg(&out)
+        .args(["-n", "framework-baseline"])
+        .args(skip_args(&root))
+        .output()
+        .expect("Failed to run move-to-ts");
+    let log = format!(
+        "{}{}",
+        String::from_utf8_lossy(&translated.stdout),
+        String::from_utf8_lossy(&translated.stderr)
+    );
+    assert!(translated.status.success(), "translation failed:\n{}", log);
+    assert!(
+        !log.contains("matches no translated function"),
+        "remove the stale entries from framework_baseline.skip:\n{}",
+        log
+    );
+
+    link_runtime(&runtime, &out);
+    let compiled = Command::new(&tsc)
+        .arg("-p")
+        .arg(out.join("tsconfig.json"))
+        .arg("--noEmit")
+        .output()
+        .expect("Failed to run tsc");
+    assert!(
+        compiled.status.success(),
+        "the translated aptos-framework does not compile:\n{}",
+        String::from_utf8_lossy(&compiled.stdout)
+    );
+
+    let snapshots = root.join("move-testsuite/stress/snapshots/framework_baseline");
+    let update = std::env::var_os("UPDATE_SNAPSHOTS").is_some();
+    for module in SNAPSHOT_MODULES {
+        let generated = std::fs::read_to_string(out.join(format!("src/{}.ts", module)))
+            .unwrap_or_else(|_| panic!("{} was not translated", module));
+        let name = Path::new(module).file_name().unwrap();
+        let snapshot = snapshots.join(name).with_extension("ts");
+        if update {
+            std::fs::create_dir_all(&snapshots).unwrap();
+            std::fs::write(&snapshot, generated).unwrap();
+            continue;
+        }
+        let expected = std::fs::read_to_string(&snapshot).unwrap_or_else(|_| {
+            panic!(
+                "missing snapshot {}, record it with UPDATE_SNAPSHOTS=1",
+                snapshot.display()
+            )
+        });
+        assert!(
+            expected == generated,
+            "{}.ts differs from its snapshot {}",
+            module,
+            snapshot.display()
+        );
+    }
+    let _ = std::fs::remove_dir_all(&out);
+}
@@ -0,0 +1,110 @@
+/*
+Two modules whose names only differ in case would be written to files that overwrite each other on
+case-insensitive file systems, so the later one gets a suffixed file, which is reported naming both
+modules, and fails the run with --deny-warnings. The package has no dependencies:
+cargo test --test path_collisions
+ */
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+fn package(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!(
+        "move-to-ts-path-collisions-{}-{}",
+        name,
+        std::process::id()
+    ));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(dir.join("sources")).unwrap();
+    std::fs::write(
+        dir.join("Move.toml"),
+        "[package]\nname = \"Collisions\"\nversion = \"0.0.1\"\n\n[addresses]\nCollisions = \"0x5c\"\n",
+    )
+    .unwrap();
+    std::fs::write(
+        dir.join("sources/vaults.move"),
+        "module Collisions::Vault {
+    public fun amount(): u64 { 1 }
+}
+
+module Collisions::vault {
+    public fun amount(): u64 { 2 }
+}
+",
+    )
+    .unwrap();
+    dir
+}
+
+/// Whether the run succeeded, its output, and the names and contents of the module files written
+fn translate(name: &str, args: &[&str]) -> (bool, String, Vec<(String, String)>) {
+    let dir = package(name);
+    let output = Command::new(env!("CARGO_BIN_EXE_move-to-ts"))
+        .arg("-p")
+        .arg(&dir)
+        .arg("-o")
+        .arg(dir.join("ts"))
+        .args(args)
+        .output()
+        .expect("Failed to run move-to-ts");
+    let mut modules = vec![];
+    collect_modules(&dir.join("ts"), &mut modules);
+    modules.sort();
+    let _ = std::fs::remove_dir_all(&dir);
+    let log = format!(
+        "{}{}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    (output.status.success(), log, modules)
+}
+
+fn collect_modules(dir: &Path, modules: &mut Vec<(String, String)>) {
+    for entry in std::fs::read_dir(dir).into_iter().flatten() {
+        let path = entry.unwrap().path();
+        let name = path.file_name().unwrap().to_string_lossy().to_string();
+        if path.is_dir() {
+            collect_modules(&path, modules);
+        } else if name.to_lowercase().starts_with("vault") && name.ends_with(".ts") {
+            modules.push((name, std::fs::read_to_string(&path).unwrap()));
+        }
+    }
+}
+
+fn names_both_modules(log: &str) -> bool {
+    log.lines().any(|line| {
+        line.contains("only differ in case") && line.contains("::Vault") && line.contains("::vault")
+    })
+}
+
+#[test]
+fn case_collision_keeps_both_modules() {
+    let (success, log, modules) = translate("warn", &[]);
+    assert!(success, "a case-only collision failed the run:\n{}", log);
+    assert!(
+        names_both_modules(&log),
+        "expected the collision of Vault and vault to be reported:\n{}",
+        log
+    );
+    let names = modules
+        .iter()
+        .map(|(name, _)| name.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(names, vec!["Vault.ts", "vault-1.ts"], "{}", log);
+    assert!(modules[0].1.contains("u64(\"1\")"), "{}", modules[0].1);
+    assert!(modules[1].1.contains("u64(\"2\")"), "{}", modules[1].1);
+}
+
+#[test]
+fn case_collision_fails_with_deny_warnings() {
+    let (success, log, _) = translate("deny", &["--deny-warnings"]);
+    assert!(
+        !success,
+        "--deny-warnings let the collision through:\n{}",
+        log
+    );
+    assert!(
+        names_both_modules(&log),
+        "expected the collision of Vault and vault to be reported:\n{}",
+        log
+    );
+}
@@ -0,0 +1,89 @@
+/*
+--production leaves the range checks of casts and the widths of arithmetic, which carry its overflow
+checks and shift masks, out of the generated code, and --runtime-checks keeps them. The package has
+no dependencies:  cargo test --test production
+ */
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+const SOURCE: &str = "module Prod::amounts {
+    public fun scaled(amount: u64, shift: u8): u128 {
+        ((amount + 1) as u128) << shift
+    }
+}
+";
+
+/// Translates the package with `args`, and returns the generated file of the amounts module
+fn translate(name: &str, args: &[&str]) -> String {
+    let dir = std::env::temp_dir().join(format!(
+        "move-to-ts-production-{}-{}",
+        name,
+        std::process::id()
+    ));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(dir.join("sources")).unwrap();
+    std::fs::write(
+        dir.join("Move.toml"),
+        "[package]\nname = \"Prod\"\nversion = \"0.0.1\"\n\n[addresses]\nProd = \"0x5d\"\n",
+    )
+    .unwrap();
+    std::fs::write(dir.join("sources/amounts.move"), SOURCE).unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_move-to-ts"))
+        .arg("-p")
+        .arg(&dir)
+        .arg("-o")
+        .arg(dir.join("ts"))
+        .args(args)
+        .output()
+        .expect("Failed to run move-to-ts");
+    assert!(
+        output.status.success(),
+        "translation failed:\n{}{}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let module = find_file(&dir.join("ts"), "amounts.ts").expect("amounts.ts was not written");
+    let content = std::fs::read_to_string(module).unwrap();
+    let _ = std::fs::remove_dir_all(&dir);
+    content
+}
+
+fn find_file(dir: &Path, name: &str) -> Option<PathBuf> {
+    for entry in std::fs::read_dir(dir).ok()? {
+        let path = entry.ok()?.path();
+        if path.is_dir() {
+            if let Some(found) = find_file(&path, name) {
+                return Some(found);
+            }
+        } else if path.file_name().map_or(false, |n| n == name) {
+            return Some(path);
+        }
+    }
+    None
+}
+
+#[test]
+fn checks_are_emitted_by_default() {
+    let content = translate("default", &[]);
+    assert!(content.contains("{ bits: 64 }"), "{}", content);
+    assert!(content.contains("{ bits: 128 }"), "{}", content);
+    assert!(
+        content.contains("u128(") && !content.contains("Unchecked"),
+        "{}",
+        content
+    );
+}
+
+#[test]
+fn production_leaves_checks_out() {
+    let content = translate("production", &["--production"]);
+    assert!(!content.contains("bits:"), "{}", content);
+    assert!(content.contains("$.u128Unchecked("), "{}", content);
+}
+
+#[test]
+fn runtime_checks_keep_them_in_production() {
+    let content = translate("checked", &["--production", "--runtime-checks"]);
+    assert!(content.contains("{ bits: 128 }"), "{}", content);
+    assert!(!content.contains("Unchecked"), "{}", content);
+}
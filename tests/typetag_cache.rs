@@ -0,0 +1,112 @@
+/*
+Struct typetags are memoized in Context by a key with type params replaced by their index, so the
+same type rendered in functions with differently named or ordered type params, and in struct
+fields, must come out as if rendered afresh. The hidden --no-typetag-cache renders every typetag,
+and the output of both runs is compared file by file. The package has no dependencies:
+cargo test --test typetag_cache
+ */
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::process::Command;
+
+const SOURCE: &str = "module Tags::pairs {
+    struct Coin<phantom T> has key, store { value: u64 }
+
+    struct Pair<A, B> has key, store { first: A, second: B }
+
+    struct Pool<phantom X, phantom Y> has key {
+        left: Pair<Coin<X>, Coin<Y>>,
+        right: Pair<Coin<Y>, Coin<X>>,
+    }
+
+    public fun coin<T>(value: u64): Coin<T> { Coin<T> { value } }
+
+    public fun pair<A, B>(first: A, second: B): Pair<A, B> { Pair<A, B> { first, second } }
+
+    public fun publish<X, Y>(account: &signer) {
+        let left = pair(coin<X>(1), coin<Y>(2));
+        let right = pair(coin<Y>(3), coin<X>(4));
+        move_to<Pool<X, Y>>(account, Pool<X, Y> { left, right });
+    }
+
+    public fun publish_swapped<Y, X>(account: &signer) {
+        publish<X, Y>(account);
+        move_to<Pair<Coin<Y>, Coin<X>>>(account, pair(coin<Y>(5), coin<X>(6)));
+    }
+
+    public fun exists_pool<X, Y>(owner: address): bool {
+        exists<Pool<X, Y>>(owner) && exists<Pool<Y, X>>(owner)
+    }
+
+    public fun nested<T>(owner: address): bool {
+        exists<Pair<Coin<T>, Coin<Coin<T>>>>(owner) && exists<Pair<Coin<u64>, Coin<T>>>(owner)
+    }
+}
+";
+
+/// Translates the package with `args` and returns its files by path relative to the output
+fn translate(name: &str, args: &[&str]) -> BTreeMap<String, Vec<u8>> {
+    let dir = std::env::temp_dir().join(format!(
+        "move-to-ts-typetag-cache-{}-{}",
+        name,
+        std::process::id()
+    ));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(dir.join("sources")).unwrap();
+    std::fs::write(
+        dir.join("Move.toml"),
+        "[package]\nname = \"Tags\"\nversion = \"0.0.1\"\n\n[addresses]\nTags = \"0x7a6\"\n",
+    )
+    .unwrap();
+    std::fs::write(dir.join("sources/pairs.move"), SOURCE).unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_move-to-ts"))
+        .arg("-p")
+        .arg(&dir)
+        .arg("-o")
+        .arg(dir.join("ts"))
+        .args(args)
+        .output()
+        .expect("Failed to run move-to-ts");
+    assert!(
+        output.status.success(),
+        "translation failed:\n{}{}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let mut files = BTreeMap::new();
+    collect_files(&dir.join("ts"), &dir.join("ts"), &mut files);
+    let _ = std::fs::remove_dir_all(&dir);
+    files
+}
+
+fn collect_files(root: &Path, dir: &Path, files: &mut BTreeMap<String, Vec<u8>>) {
+    for entry in std::fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        if path.is_dir() {
+            collect_files(root, &path, files);
+        } else {
+            let relative = path.strip_prefix(root).unwrap().display().to_string();
+            files.insert(relative, std::fs::read(&path).unwrap());
+        }
+    }
+}
+
+#[test]
+fn cache_does_not_change_the_output() {
+    let cached = translate("cached", &[]);
+    let uncached = translate("uncached", &["--no-typetag-cache"]);
+    assert_eq!(
+        cached.keys().collect::<Vec<_>>(),
+        uncached.keys().collect::<Vec<_>>()
+    );
+    assert!(cached.keys().any(|path| path.ends_with("pairs.ts")));
+    for (path, content) in cached.iter() {
+        assert!(
+            content == &uncached[path],
+            "{} differs with the cache:\n{}\nwithout it:\n{}",
+            path,
+            String::from_utf8_lossy(content),
+            String::from_utf8_lossy(&uncached[path])
+        );
+    }
+}
@@ -0,0 +1,98 @@
+/*
+--emit-examples writes a commented call of buildPayload_ and of the translated function above each
+payload builder, awaiting the latter once in --asynchronous mode. The package has no dependencies:
+cargo test --test usage_examples
+ */
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+const SOURCE: &str = "module Examples::vault {
+    public entry fun deposit(_account: &signer, to: address, amount: u64, memo: vector<u8>) {
+        let _ = to;
+        let _ = amount;
+        let _ = memo;
+    }
+}
+";
+
+/// Translates the package with `args`, and returns the generated file of the vault module
+fn translate(name: &str, args: &[&str]) -> String {
+    let dir = std::env::temp_dir().join(format!(
+        "move-to-ts-examples-{}-{}",
+        name,
+        std::process::id()
+    ));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(dir.join("sources")).unwrap();
+    std::fs::write(
+        dir.join("Move.toml"),
+        "[package]\nname = \"Examples\"\nversion = \"0.0.1\"\n\n[addresses]\nExamples = \"0xe7\"\n",
+    )
+    .unwrap();
+    std::fs::write(dir.join("sources/vault.move"), SOURCE).unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_move-to-ts"))
+        .arg("-p")
+        .arg(&dir)
+        .arg("-o")
+        .arg(dir.join("ts"))
+        .arg("--emit-examples")
+        .args(args)
+        .output()
+        .expect("Failed to run move-to-ts");
+    assert!(
+        output.status.success(),
+        "translation failed:\n{}{}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let module = find_file(&dir.join("ts"), "vault.ts").expect("vault.ts was not written");
+    let content = std::fs::read_to_string(module).unwrap();
+    let _ = std::fs::remove_dir_all(&dir);
+    content
+}
+
+fn find_file(dir: &Path, name: &str) -> Option<PathBuf> {
+    for entry in std::fs::read_dir(dir).ok()? {
+        let path = entry.ok()?.path();
+        if path.is_dir() {
+            if let Some(found) = find_file(&path, name) {
+                return Some(found);
+            }
+        } else if path.file_name().map_or(false, |n| n == name) {
+            return Some(path);
+        }
+    }
+    None
+}
+
+#[test]
+fn example_calls_the_function() {
+    let content = translate("sync", &[]);
+    assert!(
+        content.contains("//   const payload = buildPayload_deposit("),
+        "{}",
+        content
+    );
+    assert!(
+        content.contains("//     new HexString(\"0x1\"),"),
+        "{}",
+        content
+    );
+    assert!(
+        content.contains("//     $.strToU8(\"example\"),"),
+        "{}",
+        content
+    );
+    assert!(
+        content.contains("//   deposit_(new HexString(\"0x1\"), new HexString(\"0x1\"), u64(\"1000000\"), $.strToU8(\"example\"), $c);"),
+        "{}",
+        content
+    );
+}
+
+#[test]
+fn async_example_awaits_the_function_once() {
+    let content = translate("async", &["--asynchronous"]);
+    assert!(content.contains("//   await deposit_("), "{}", content);
+    assert!(!content.contains("await await"), "{}", content);
+}
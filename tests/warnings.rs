@@ -0,0 +1,115 @@
+/*
+Exit codes of runs that only warn and of runs that fail. Warnings are printed without failing the
+run, unless --deny-warnings is given, while blocking errors always fail it. The packages have no
+dependencies, so they translate offline:  cargo test --test warnings
+ */
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+/// A package named `name` with one source file, and a named address of the same name set to
+/// `address`
+fn package(name: &str, address: &str, source: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!(
+        "move-to-ts-warnings-{}-{}",
+        name,
+        std::process::id()
+    ));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(dir.join("sources")).unwrap();
+    std::fs::write(
+        dir.join("Move.toml"),
+        format!(
+            "[package]\nname = \"{}\"\nversion = \"0.0.1\"\n\n[addresses]\n{} = \"{}\"\n",
+            name, name, address
+        ),
+    )
+    .unwrap();
+    std::fs::write(dir.join("sources/main.move"), source).unwrap();
+    dir
+}
+
+fn translate(dir: &Path, args: &[&str]) -> (Output, String) {
+    let output = Command::new(env!("CARGO_BIN_EXE_move-to-ts"))
+        .arg("-p")
+        .arg(dir)
+        .arg("-o")
+        .arg(dir.join("ts"))
+        .args(args)
+        .output()
+        .expect("Failed to run move-to-ts");
+    let log = format!(
+        "{}{}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    (output, log)
+}
+
+// struct parameters rule out buildPayload_, so the #[cmd] only gets a warning
+const WARNING_ONLY: &str = "module WarnOnly::pairs {
+    struct Pair has copy, drop, store { first: u64, second: u64 }
+
+    #[cmd]
+    public entry fun set_pair(_account: &signer, _pair: Pair) {}
+}
+";
+
+#[test]
+fn warnings_do_not_fail_the_run() {
+    let dir = package("WarnOnly", "0x5e", WARNING_ONLY);
+    let (output, log) = translate(&dir, &[]);
+    assert!(
+        output.status.success(),
+        "a warning failed the run:\n{}",
+        log
+    );
+    assert!(
+        log.contains("No cli command is generated for set_pair"),
+        "expected the warning about set_pair:\n{}",
+        log
+    );
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn deny_warnings_fails_the_run() {
+    let dir = package(
+        "DenyWarnings",
+        "0x5e",
+        &WARNING_ONLY.replace("WarnOnly", "DenyWarnings"),
+    );
+    let (output, log) = translate(&dir, &["--deny-warnings"]);
+    assert!(
+        !output.status.success(),
+        "--deny-warnings let a warning through:\n{}",
+        log
+    );
+    assert!(
+        log.contains("No cli command is generated for set_pair"),
+        "expected the warning about set_pair:\n{}",
+        log
+    );
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn errors_fail_the_run() {
+    // the module's address is used as a value, which needs it to have one
+    let source = "module Unassigned::pool {
+    public fun owner(): address { @Unassigned }
+}
+";
+    let dir = package("Unassigned", "_", source);
+    let (output, log) = translate(&dir, &[]);
+    assert!(
+        !output.status.success(),
+        "an error did not fail the run:\n{}",
+        log
+    );
+    assert!(
+        log.contains("named address 'Unassigned' has no value"),
+        "expected the error about Unassigned:\n{}",
+        log
+    );
+    let _ = std::fs::remove_dir_all(&dir);
+}
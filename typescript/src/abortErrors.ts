@@ -0,0 +1,107 @@
+import bigInt from "big-integer";
+import { U64 } from "./builtinTypes";
+import { normalizeAddress } from "./typeTag";
+
+/*
+Every module with error constants gets a <Module>Error class extending MoveAbortError, and a
+<Module>Error_<CONSTANT> subclass per constant, which `abort <CONSTANT>` throws. The message is
+the abort code alone, as with $.abortCode, so code matching keeps working next to instanceof.
+The subclasses register themselves, so that a failed simulation can rethrow the abort of its
+vm_status as the class of the aborting module
+*/
+export class MoveAbortError extends Error {
+  constructor(public code: U64, public moduleName: string, public constantName: string) {
+    super(`${code.value.toString()}`);
+    this.name = constantName;
+    Object.setPrototypeOf(this, new.target.prototype);
+  }
+}
+
+type AbortErrorClass = new () => MoveAbortError;
+
+// by <address>::<module>::<decimal code>, more than one where constants of a module share a code
+const abortErrorClasses = new Map<string, AbortErrorClass[]>();
+
+function abortErrorKey(moduleName: string, code: string): string {
+  const [address, module] = moduleName.split("::");
+  return `${normalizeAddress(address)}::${module}::${code}`;
+}
+
+export function registerAbortErrors(classes: AbortErrorClass[]) {
+  for (const cls of classes) {
+    const error = new cls();
+    const key = abortErrorKey(error.moduleName, error.code.value.toString());
+    // a module loaded twice registers the same constants again
+    const classes = (abortErrorClasses.get(key) ?? []).filter(other => new other().constantName !== error.constantName);
+    abortErrorClasses.set(key, [...classes, cls]);
+  }
+}
+
+// e.g. "Move abort in 0x1::coin: EINSUFFICIENT_BALANCE(0x10006): ..." or "Move abort in 0x7::m: 0x3"
+function parseVmStatusAbort(vmStatus: string): { moduleName: string; constantName?: string; code: string } | null {
+  const match = vmStatus.match(/Move abort in (0x[0-9a-fA-F]+::\w+): (?:(\w+)\()?(0x[0-9a-fA-F]+|\d+)/);
+  if (!match) {
+    return null;
+  }
+  const code = match[3].startsWith("0x") ? bigInt(match[3].slice(2), 16).toString() : match[3];
+  return { moduleName: match[1], constantName: match[2], code };
+}
+
+// the decimal abort code of a vm_status, null when the transaction failed some other way
+export function abortCodeFromVmStatus(vmStatus: string): string | null {
+  return parseVmStatusAbort(vmStatus)?.code ?? null;
+}
+
+/*
+Codes built with std::error carry a category in the bits above the lowest 16, e.g.
+error::invalid_argument(EINSUFFICIENT) aborts with 0x10003, and the error constant in the reason
+below them. Module is the aborting module with its address normalized
+*/
+export interface AbortDetails {
+  moduleName: string;
+  code: string;
+  category: number;
+  reason: number;
+}
+
+export function abortDetailsFromVmStatus(vmStatus: string): AbortDetails | null {
+  const abort = parseVmStatusAbort(vmStatus);
+  if (!abort) {
+    return null;
+  }
+  const [address, module] = abort.moduleName.split("::");
+  const code = bigInt(abort.code);
+  return {
+    moduleName: `${normalizeAddress(address)}::${module}`,
+    code: abort.code,
+    category: code.shiftRight(16).toJSNumber(),
+    reason: code.and(0xffff).toJSNumber(),
+  };
+}
+
+// the decimal abort code of an error thrown by the translated code, null for any other error
+export function abortCodeFromError(e: any): string | null {
+  if (e instanceof MoveAbortError) {
+    return e.code.value.toString();
+  }
+  // $.abortCode throws the code as the message
+  return e instanceof Error && /^\d+$/.test(e.message) ? e.message : null;
+}
+
+export function abortErrorFromVmStatus(vmStatus: string): MoveAbortError | null {
+  const abort = parseVmStatusAbort(vmStatus);
+  if (!abort) {
+    return null;
+  }
+  // a code with a category names the constant in its reason
+  const reason = bigInt(abort.code).and(0xffff).toString();
+  const classes = abortErrorClasses.get(abortErrorKey(abort.moduleName, abort.code))
+    ?? abortErrorClasses.get(abortErrorKey(abort.moduleName, reason))
+    ?? [];
+  // the node names the constant when the module was published with its error map, otherwise a
+  // code that several constants share cannot tell them apart
+  const errors = classes
+    .map(cls => new cls())
+    .filter(error => abort.constantName === undefined || error.constantName === abort.constantName);
+  return errors.length === 1 ? errors[0] : null;
+}
@@ -2,9 +2,11 @@ import { AptosClient, HexString, Types } from "aptos";
 import bigInt from "big-integer";
 import { U128, U64 } from "./builtinTypes";
 import { AptosParserRepo, StructInfoType } from "./parserRepo";
-import { getTypeTagFullname, parseMoveStructTag, parseTypeTagOrThrow, StructTag, TypeTag } from "./typeTag";
+import { getTypeTagFullname, normalizeAddress, parseMoveStructTag, parseTypeTagOrThrow, StructTag, TypeTag } from "./typeTag";
 import stringify from "json-stable-stringify";
 import { DeleteResource, WriteResource } from "aptos/dist/generated";
+import { AptosClock, SystemClock } from "./clock";
+import { AptosRandomness, CryptoRandomness } from "./randomness";
 
 
 export interface ITable {
@@ -38,9 +40,19 @@ export interface AptosDataCache {
   table_remove_box(table: ITable, key: any): IBox;
   table_destroy_empty_box(table: ITable): void;
   table_drop_unchecked_box(table: ITable): void;
+
+  // Clock
+  getClock(): AptosClock;
+  setClock(clock: AptosClock): void;
+
+  // Randomness
+  getRandomness(): AptosRandomness;
+  setRandomness(randomness: AptosRandomness): void;
 }
 
 export class DummyCache implements AptosDataCache {
+  clock: AptosClock = new SystemClock();
+  randomness: AptosRandomness = new CryptoRandomness();
   // synchronous builtins
   exists(_tag: TypeTag, _address: HexString): boolean {
     throw new Error("DummyCache does not support 'exists'");
@@ -98,6 +110,20 @@ export class DummyCache implements AptosDataCache {
   table_drop_unchecked_box(table: ITable) {
     throw new Error("Not implemented");
   }
+  // clock
+  getClock(): AptosClock {
+    return this.clock;
+  }
+  setClock(clock: AptosClock) {
+    this.clock = clock;
+  }
+  // randomness
+  getRandomness(): AptosRandomness {
+    return this.randomness;
+  }
+  setRandomness(randomness: AptosRandomness) {
+    this.randomness = randomness;
+  }
 }
 
 class AccountCache {
@@ -160,40 +186,44 @@ class AccountCache {
 // caches all data locally and never attempts to retrieve anything from chain
 // only suited for running tests
 export class AptosLocalCache implements AptosDataCache {
-  // maps account address (HexString.hex()) to AccountCache
+  // maps account address (normalizeAddress) to AccountCache
   public accounts: Map<string, AccountCache>;
   public tables: Map<string, Map<string, IBox>>;
   nextTableHandle: number;
+  clock: AptosClock;
+  randomness: AptosRandomness;
   constructor() {
     this.accounts = new Map();
     this.tables = new Map();
     this.nextTableHandle = 1;
+    this.clock = new SystemClock(this);
+    this.randomness = new CryptoRandomness();
   }
   // synchronous builtins
   exists(tag: TypeTag, address: HexString): boolean {
-    const account = this.accounts.get(address.hex());
+    const account = this.accounts.get(normalizeAddress(address));
     if (!account) {
       return false;
     }
     return account.has(tag);
   }
   move_to(tag: TypeTag, address: HexString, resource: any): void {
-    let account = this.accounts.get(address.hex());
+    let account = this.accounts.get(normalizeAddress(address));
     if (!account) {
       account = new AccountCache(address);
-      this.accounts.set(address.hex(), account);
+      this.accounts.set(normalizeAddress(address), account);
     }
     account.set(tag, resource);
   }
   move_from<T>(tag: TypeTag, address: HexString): T {
-    let account = this.accounts.get(address.hex());
+    let account = this.accounts.get(normalizeAddress(address));
     if (!account) {
       throw new Error(`Resource ${getTypeTagFullname(tag)} does not exist for ${address.hex()}`);
     }
     return account.move_from(tag);
   }
   borrow_global<T>(tag: TypeTag, address: HexString): T {
-    let account = this.accounts.get(address.hex());
+    let account = this.accounts.get(normalizeAddress(address));
     if (!account) {
       throw new Error(`Resource ${getTypeTagFullname(tag)} does not exist for ${address.hex()}`);
     }
@@ -278,6 +308,58 @@ export class AptosLocalCache implements AptosDataCache {
   table_drop_unchecked_box(table: ITable) {
     // NOP
   }
+  // clock
+  getClock(): AptosClock {
+    return this.clock;
+  }
+  setClock(clock: AptosClock) {
+    this.clock = clock;
+  }
+  // randomness
+  getRandomness(): AptosRandomness {
+    return this.randomness;
+  }
+  setRandomness(randomness: AptosRandomness) {
+    this.randomness = randomness;
+  }
+}
+
+// copies arrays, maps, typed arrays and objects (keeping their class), so that nothing reachable
+// from the copy is shared with the original. `seen` keeps shared references shared in the copy
+function deepClone<T>(value: T, seen: Map<any, any>): T {
+  if (value === null || typeof value !== 'object') {
+    return value;
+  }
+  if (seen.has(value)) {
+    return seen.get(value);
+  }
+  if (value instanceof Map) {
+    const result = new Map();
+    seen.set(value, result);
+    value.forEach((v, k) => result.set(k, deepClone(v, seen)));
+    return result as any;
+  }
+  if (Array.isArray(value)) {
+    const result: any[] = [];
+    seen.set(value, result);
+    value.forEach(v => result.push(deepClone(v, seen)));
+    return result as any;
+  }
+  if (ArrayBuffer.isView(value)) {
+    return (value as any).slice();
+  }
+  const result = Object.create(Object.getPrototypeOf(value));
+  seen.set(value, result);
+  for (const key of Object.keys(value)) {
+    result[key] = deepClone((value as any)[key], seen);
+  }
+  return result;
+}
+
+// copy of a test cache, e.g. the state after a #[test_setup] function, so that each test starts
+// from it without seeing what other tests wrote
+export function cloneCache(cache: AptosLocalCache): AptosLocalCache {
+  return deepClone(cache, new Map());
 }
 
 // caches data locally, and attempts to fetch from chain when needed
@@ -290,10 +372,10 @@ export class AptosSyncedCache extends AptosLocalCache {
   }
   // asynchronous builtins
   async exists_async(tag: TypeTag, address: HexString): Promise<boolean> {
-    let account = this.accounts.get(address.hex());
+    let account = this.accounts.get(normalizeAddress(address));
     if (!account) {
       account = new AccountCache(address);
-      this.accounts.set(address.hex(), account);
+      this.accounts.set(normalizeAddress(address), account);
     }
     return account.has_async(tag, this.repo, this.client);
   }
@@ -304,10 +386,10 @@ export class AptosSyncedCache extends AptosLocalCache {
     throw new Error("move_from not supported by AptosSyncedCache");
   }
   async borrow_global_async<T>(tag: TypeTag, address: HexString): Promise<T> {
-    let account = this.accounts.get(address.hex());
+    let account = this.accounts.get(normalizeAddress(address));
     if (!account) {
       account = new AccountCache(address);
-      this.accounts.set(address.hex(), account);
+      this.accounts.set(normalizeAddress(address), account);
     }
     return await account.get_async(tag, this.repo, this.client) as unknown as T;
   }
@@ -317,6 +399,38 @@ export class AptosSyncedCache extends AptosLocalCache {
 }
 
 
+/*
+Load all parseable resources of given accounts into $c, using the same typeTags that translated functions use to access
+them. Resources whose types have no parser in repo are skipped, and reported in the returned warnings.
+*/
+export async function snapshotAccountsToCache(
+  client: AptosClient,
+  repo: AptosParserRepo,
+  accounts: HexString[],
+  $c: AptosDataCache,
+): Promise<string[]> {
+  const warnings: string[] = [];
+  for(const address of accounts) {
+    const resources = await client.getAccountResources(address);
+    for(const resource of resources) {
+      const typeTag = parseMoveStructTag(resource.type);
+      if (!repo.getParserFromTypeTag(typeTag)) {
+        warnings.push(`${address.hex()}: no parser for ${getTypeTagFullname(typeTag)}`);
+        continue;
+      }
+      try{
+        const value = repo.parse(resource.data, typeTag);
+        $c.move_to(typeTag, address, value);
+      }
+      catch(e) {
+        warnings.push(`${address.hex()}: failed to load ${getTypeTagFullname(typeTag)}: ${e}`);
+      }
+    }
+  }
+  return warnings;
+}
+
+
 export type UpdateType = 'update' | 'delete';
 export type ListenerType = {
   id: string; 
@@ -496,6 +610,6 @@ export class AptosResourceCache {
   Computes ResourceKey from owner address and resource TypeTag
   */
   getResourceKey(ownerAddress: HexString, typeTag: TypeTag) {
-    return `${ownerAddress.hex()}/${getTypeTagFullname(typeTag)}`;
+    return `${normalizeAddress(ownerAddress)}/${getTypeTagFullname(typeTag)}`;
   }
 }
\ No newline at end of file
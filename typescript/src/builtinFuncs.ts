@@ -3,7 +3,7 @@ import bigInt, { isInstance } from "big-integer";
 import { U8, U64, U128, UnsignedInt, takeBigInt } from "./builtinTypes";
 import { HexString } from "aptos";
 import stringify from "json-stable-stringify";
-import { StructInfoType } from "./parserRepo";
+import { fieldMemberName, StructInfoType } from "./parserRepo";
 
 export function abortCode(code: any) {
   if (code instanceof U64) {
@@ -31,6 +31,19 @@ export function u128(from: UnsignedInt<any> | bigInt.BigInteger | string | numbe
   return new U128(takeBigInt(from));
 }
 
+// casts for code generated with --production, which skip the range check of the constructors
+export function u8Unchecked(from: UnsignedInt<any>): U8 {
+  return Object.assign(Object.create(U8.prototype), { value: from.value });
+}
+
+export function u64Unchecked(from: UnsignedInt<any>): U64 {
+  return Object.assign(Object.create(U64.prototype), { value: from.value });
+}
+
+export function u128Unchecked(from: UnsignedInt<any>): U128 {
+  return Object.assign(Object.create(U128.prototype), { value: from.value });
+}
+
 export function deep_eq(lhs: any, rhs: any): boolean {
   return stringify(lhs) === stringify(rhs);
 }
@@ -113,8 +126,8 @@ export function veq(lhs: any, rhs: any): boolean {
 export function copy<T>(val: T): T {
   const v = val as unknown as any;
   if (val instanceof HexString) {
-    // address & signer are immutable
-    return val;
+    // $.set assigns addresses in place, so a copy cannot share the original
+    return new HexString(val.hex()) as unknown as T;
   }
   else if (typeof val === 'boolean') {
     return val
@@ -129,8 +142,7 @@ export function copy<T>(val: T): T {
     let proto = Object();
     const structInfo = v.constructor as StructInfoType;
     for (const field of structInfo.fields) {
-      const fname = field.name;
-      proto[fname] = copy(v[fname]);
+      proto[field.name] = copy(v[fieldMemberName(field)]);
     }
     let copied = new structInfo(proto, v.typeTag);
     return copied;
@@ -173,6 +185,17 @@ function printerReplacer(key: string, val: any) {
       const bytes = val.bytes as U8[];
       return u8str(bytes);
     }
+    else if (tagFullname === '0x1::fixed_point32::FixedPoint32') {
+      return fixedPointToDecimalString(val.value, 32, 8);
+    }
+    else if (tagFullname === '0x1::fixed_point64::FixedPoint64') {
+      return fixedPointToDecimalString(val.value, 64, 8);
+    }
+    else if (tag.getParamlessName() === '0x1::option::Option') {
+      // same as isNone()/getOrNull() of the generated class
+      const vec = val.vec as any[];
+      return vec.length === 0 ? "None" : { Some: vec[0] };
+    }
     else if (tagFullname === '0x1::type_info::TypeInfo') {
       const account_address = (val.account_address as HexString).toShortString();
       const module_name = u8str(val.module_name as U8[]);
@@ -228,7 +251,8 @@ export function set(lhs: any, rhs: any) {
     // struct set
     const structInfo = lhs.constructor as StructInfoType;
     for(const field of structInfo.fields) {
-      lhs[field.name] = copy(rhs[field.name]);
+      const member = fieldMemberName(field);
+      lhs[member] = copy(rhs[member]);
     }
   }
 }
@@ -269,6 +293,95 @@ export function payloadArg(val: any) {
   }
 }
 
+/*
+0x1::fixed_point32 and 0x1::fixed_point64 store a number as an integer with 32 or 64 fractional bits.
+toNumber() on the generated classes loses precision past 2^53, toDecimalString() does not and
+truncates like fixed_point32::multiply_u64 does
+*/
+export function fixedPointToNumber(raw: UnsignedInt<any>, fractionalBits: number): number {
+  const { quotient, remainder } = raw.value.divmod(bigInt.one.shiftLeft(fractionalBits));
+  return quotient.toJSNumber() + remainder.toJSNumber() / Math.pow(2, fractionalBits);
+}
+
+export function fixedPointToDecimalString(raw: UnsignedInt<any>, fractionalBits: number, precision: number): string {
+  const integer = raw.value.shiftRight(fractionalBits);
+  if (precision <= 0) {
+    return integer.toString();
+  }
+  const fraction = raw.value.subtract(integer.shiftLeft(fractionalBits));
+  const digits = fraction.multiply(bigInt(10).pow(precision)).shiftRight(fractionalBits);
+  return `${integer.toString()}.${digits.toString().padStart(precision, "0")}`;
+}
+
+export function fixedPointFromNumber(n: number, fractionalBits: number): bigInt.BigInteger {
+  if (!Number.isFinite(n) || n < 0 || n >= Number.MAX_SAFE_INTEGER) {
+    throw new Error(`Cannot represent ${n} as a fixed point number`);
+  }
+  const integer = Math.floor(n);
+  let raw = bigInt(integer);
+  let fraction = n - integer;
+  // 32 bits at a time, so that every step stays a safe integer
+  for (let remaining = fractionalBits; remaining > 0; remaining -= 32) {
+    const step = Math.min(32, remaining);
+    fraction *= Math.pow(2, step);
+    const chunk = remaining === step ? Math.round(fraction) : Math.floor(fraction);
+    raw = raw.shiftLeft(step).add(chunk);
+    fraction -= chunk;
+  }
+  return raw;
+}
+
+export type PayloadArgType = "bool" | "address" | "u8" | "u64" | "u128";
+
+/*
+Arguments of buildPayload_ functions, which may come from plain JS callers passing numbers, bigints or
+strings for integers and strings for addresses. Anything else is rejected with the Move name of the
+parameter, instead of failing later while the transaction is serialized
+*/
+export function coercePayloadArg(val: any, expected: PayloadArgType, param: string) {
+  const fail = () => new Error(`Parameter ${param} expects ${expected} but received: ${typeof val === "object" ? val?.constructor?.name : typeof val} ${val}`);
+  if (expected === "bool") {
+    if (typeof val !== "boolean") {
+      throw fail();
+    }
+    return val;
+  }
+  if (expected === "address") {
+    if (val instanceof HexString) {
+      return val.toShortString();
+    }
+    if (typeof val !== "string" || !/^(0x)?[0-9a-fA-F]+$/.test(val)) {
+      throw fail();
+    }
+    return new HexString(val).toShortString();
+  }
+  let value: bigInt.BigInteger;
+  if (val instanceof UnsignedInt) {
+    value = val.value;
+  }
+  else if (typeof val === "number" && Number.isSafeInteger(val)) {
+    value = bigInt(val);
+  }
+  else if (typeof val === "bigint") {
+    value = bigInt(val.toString());
+  }
+  else if (typeof val === "string" && /^[0-9]+$/.test(val)) {
+    value = bigInt(val);
+  }
+  else {
+    throw fail();
+  }
+  try {
+    if (expected === "u8") {
+      return new U8(value).toJsNumber();
+    }
+    return (expected === "u64" ? new U64(value) : new U128(value)).value.toString();
+  }
+  catch (e) {
+    throw new Error(`Parameter ${param} expects ${expected} but ${value} is out of range`);
+  }
+}
+
 export function u8ArrayArg(val: U8[]): string {
   const uint8array = new Uint8Array(Array.from(val.map(u => u.toJsNumber())));
   return HexString.fromUint8Array(uint8array).hex();
@@ -284,6 +397,9 @@ export function moveValueToOpenApiObject(val: any, typeTag: TypeTag): any {
   else if (val instanceof HexString) {
     return val.hex();
   }
+  else if (typeof val === 'boolean') {
+    return val;
+  }
   // vector
   else if (val instanceof Array) {
     if (!(typeTag instanceof VectorTag)) {
@@ -310,7 +426,7 @@ export function moveValueToOpenApiObject(val: any, typeTag: TypeTag): any {
     for (const field of structInfo.fields) {
       const name = field.name;
       const fieldTag = substituteTypeParams(field.typeTag, typeTag.typeParams);
-      result[name] = moveValueToOpenApiObject(val[field.name], fieldTag);
+      result[name] = moveValueToOpenApiObject(val[fieldMemberName(field)], fieldTag);
     }
     return result;
   }
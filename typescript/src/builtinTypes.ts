@@ -21,6 +21,36 @@ export function takeBigInt(from: UnsignedInt<any> | bigInt.BigInteger | string |
   }
 }
 
+/*
+Thrown where the VM aborts with ARITHMETIC_ERROR: on overflow and underflow, on division by zero and
+on shifts by at least the width of the operand
+*/
+export class ArithmeticError extends Error {
+  constructor(message: string) {
+    super(`ARITHMETIC_ERROR: ${message}`);
+    this.name = "ArithmeticError";
+    Object.setPrototypeOf(this, ArithmeticError.prototype);
+  }
+}
+
+// width of the operands of an arithmetic operation, which generated code passes to get the checks
+// of the VM whatever the class of the operands
+export interface ArithmeticWidth {
+  bits: number;
+}
+
+function checkFits(value: bigInt.BigInteger, width: ArithmeticWidth | undefined, describe: () => string) {
+  if (width && (value.lt(bigInt.zero) || value.geq(bigInt.one.shiftLeft(width.bits)))) {
+    throw new ArithmeticError(`${describe()} does not fit in u${width.bits}`);
+  }
+}
+
+function checkShift(amount: bigInt.BigInteger, width: ArithmeticWidth | undefined) {
+  if (width && amount.geq(width.bits)) {
+    throw new ArithmeticError(`cannot shift a u${width.bits} by ${amount}`);
+  }
+}
+
 export class UnsignedInt<T extends IntInterface<T>> {
   public value: bigInt.BigInteger;
   constructor(value: bigInt.BigInteger | UnsignedInt<any> | string | number) {
@@ -64,30 +94,41 @@ export class UnsignedInt<T extends IntInterface<T>> {
   xor(other: T): T {
     return other.make(this.value.xor(other.value));
   }
-  add(other: T): T {
-    return other.make(this.value.add(other.value));
+  add(other: T, width?: ArithmeticWidth): T {
+    const value = this.value.add(other.value);
+    checkFits(value, width, () => `${this.value} + ${other.value}`);
+    return other.make(value);
   }
-  sub(other: T): T {
-    return other.make(this.value.subtract(other.value));
+  sub(other: T, width?: ArithmeticWidth): T {
+    const value = this.value.subtract(other.value);
+    checkFits(value, width, () => `${this.value} - ${other.value}`);
+    return other.make(value);
   }
-  mul(other: T): T {
-    return other.make(this.value.multiply(other.value));
+  mul(other: T, width?: ArithmeticWidth): T {
+    const value = this.value.multiply(other.value);
+    checkFits(value, width, () => `${this.value} * ${other.value}`);
+    return other.make(value);
   }
-  div(other: T): T {
+  div(other: T, _width?: ArithmeticWidth): T {
     if (other.value.eq(bigInt.zero)) {
-      throw new Error("Division by 0");
+      throw new ArithmeticError("Division by 0");
     }
     return other.make(this.value.divide(other.value));
   }
-  mod(other: T): T {
+  mod(other: T, _width?: ArithmeticWidth): T {
+    if (other.value.eq(bigInt.zero)) {
+      throw new ArithmeticError("Modulo by 0");
+    }
     return other.make(this.value.mod(other.value));
   }
-  shl(other: UnsignedInt<any>): UnsignedInt<T> {
-    // FIXME: need to match this with move's implementation
-    return this.make(this.value.shiftLeft(other.value));
+  shl(other: UnsignedInt<any>, width?: ArithmeticWidth): UnsignedInt<T> {
+    checkShift(other.value, width);
+    const value = this.value.shiftLeft(other.value);
+    // bits shifted out are dropped, as in the VM
+    return this.make(width ? value.and(bigInt.one.shiftLeft(width.bits).subtract(1)) : value);
   }
-  shr(other: UnsignedInt<any>): UnsignedInt<T> {
-    // FIXME: need to match this with move's implementation
+  shr(other: UnsignedInt<any>, width?: ArithmeticWidth): UnsignedInt<T> {
+    checkShift(other.value, width);
     return this.make(this.value.shiftRight(other.value));
   }
   lt(other: T): boolean {
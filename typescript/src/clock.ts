@@ -0,0 +1,92 @@
+import { HexString } from "aptos";
+import bigInt from "big-integer";
+import { U64 } from "./builtinTypes";
+import { StructTag, TypeTag } from "./typeTag";
+
+/*
+Source of time and block height for translated calls into 0x1::timestamp and 0x1::block
+*/
+export interface AptosClock {
+  nowMicroseconds(): U64;
+  nowSeconds(): U64;
+  blockHeight(): U64;
+  setMicroseconds(micros: U64): void;
+  setSeconds(secs: U64): void;
+  fastForwardSeconds(secs: U64): void;
+}
+
+const MICRO_CONVERSION_FACTOR = bigInt(1000000);
+
+// the part of AptosDataCache that SystemClock reads the block height from
+export interface ResourceReader {
+  exists(tag: TypeTag, address: HexString): boolean;
+  borrow_global<T>(tag: TypeTag, address: HexString): T;
+}
+
+const BLOCK_RESOURCE_TAG = new StructTag(new HexString("0x1"), "block", "BlockResource", []);
+
+/*
+follows wall-clock time, cannot be modified. There is no wall-clock block height, so it is read from
+0x1::block::BlockResource in the cache, and is 0 when that is missing. With strictNatives, which
+mirrors --strict-natives, a missing BlockResource throws instead
+*/
+export class SystemClock implements AptosClock {
+  constructor(public cache?: ResourceReader, public strictNatives = false) {}
+  nowMicroseconds(): U64 {
+    return new U64(bigInt(Date.now()).multiply(1000));
+  }
+  nowSeconds(): U64 {
+    return new U64(this.nowMicroseconds().value.divide(MICRO_CONVERSION_FACTOR));
+  }
+  blockHeight(): U64 {
+    const address = BLOCK_RESOURCE_TAG.address;
+    if (this.cache && this.cache.exists(BLOCK_RESOURCE_TAG, address)) {
+      const resource = this.cache.borrow_global<{ height: U64 }>(BLOCK_RESOURCE_TAG, address);
+      return new U64(resource.height.value);
+    }
+    if (this.strictNatives) {
+      throw new Error("0x1::block::BlockResource does not exist, set the block height with a FrozenClock");
+    }
+    return new U64(bigInt.zero);
+  }
+  setMicroseconds(_micros: U64) {
+    throw new Error("SystemClock cannot be modified, use a FrozenClock instead");
+  }
+  setSeconds(_secs: U64) {
+    throw new Error("SystemClock cannot be modified, use a FrozenClock instead");
+  }
+  fastForwardSeconds(_secs: U64) {
+    throw new Error("SystemClock cannot be modified, use a FrozenClock instead");
+  }
+}
+
+// only moves when told to, suited for tests
+export class FrozenClock implements AptosClock {
+  public micros: bigInt.BigInteger;
+  public height: bigInt.BigInteger;
+  constructor(micros: U64 | number = 0, height: U64 | number = 0) {
+    this.micros = typeof micros === 'number' ? bigInt(micros) : micros.value;
+    this.height = typeof height === 'number' ? bigInt(height) : height.value;
+  }
+  nowMicroseconds(): U64 {
+    return new U64(this.micros);
+  }
+  nowSeconds(): U64 {
+    return new U64(this.micros.divide(MICRO_CONVERSION_FACTOR));
+  }
+  blockHeight(): U64 {
+    return new U64(this.height);
+  }
+  setMicroseconds(micros: U64) {
+    this.micros = micros.value;
+  }
+  setSeconds(secs: U64) {
+    this.micros = secs.value.multiply(MICRO_CONVERSION_FACTOR);
+  }
+  fastForwardSeconds(secs: U64) {
+    this.micros = this.micros.add(secs.value.multiply(MICRO_CONVERSION_FACTOR));
+  }
+  setBlockHeight(height: U64) {
+    this.height = height.value;
+  }
+}
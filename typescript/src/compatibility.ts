@@ -0,0 +1,70 @@
+import { AptosClient, HexString, Types } from "aptos";
+
+/*
+Shape of an entry function as seen by generated code, keyed by "module::function"
+*/
+export type EntryFunctionArity = {
+  typeParams: number;
+  // excluding the signer
+  params: number;
+};
+
+export type ArityMismatch = {
+  name: string;
+  expected: EntryFunctionArity;
+  actual: EntryFunctionArity;
+};
+
+export type CompatibilityReport = {
+  compatible: boolean;
+  // generated, but not exposed as an entry function on-chain
+  missingFunctions: string[];
+  arityMismatches: ArityMismatch[];
+};
+
+function isSignerParam(param: string) {
+  return param === 'signer' || param === '&signer';
+}
+
+/*
+Compare the entry functions a generated package knows about against the ABIs currently published at
+`address`. Functions added on-chain since generation are not reported, as the SDK never calls them
+*/
+export async function checkPackageCompatibility(
+  client: AptosClient,
+  address: HexString,
+  entryFunctions: Record<string, EntryFunctionArity>,
+): Promise<CompatibilityReport> {
+  const modules = await client.getAccountModules(address);
+  const onChain: Record<string, EntryFunctionArity> = {};
+  for (const module of modules) {
+    if (!module.abi) {
+      continue;
+    }
+    for (const func of module.abi.exposed_functions as Types.MoveFunction[]) {
+      if (!func.is_entry) {
+        continue;
+      }
+      onChain[`${module.abi.name}::${func.name}`] = {
+        typeParams: func.generic_type_params.length,
+        params: func.params.filter(p => !isSignerParam(p)).length,
+      };
+    }
+  }
+  const missingFunctions: string[] = [];
+  const arityMismatches: ArityMismatch[] = [];
+  for (const [name, expected] of Object.entries(entryFunctions)) {
+    const actual = onChain[name];
+    if (!actual) {
+      missingFunctions.push(name);
+    }
+    else if (actual.typeParams !== expected.typeParams || actual.params !== expected.params) {
+      arityMismatches.push({ name, expected, actual });
+    }
+  }
+  return {
+    compatible: missingFunctions.length === 0 && arityMismatches.length === 0,
+    missingFunctions,
+    arityMismatches,
+  };
+}
@@ -0,0 +1,78 @@
+/*
+Call counts of translated functions under --test-coverage, keyed by 0x..::module::function like
+coverage-functions.json. fs is required lazily, so that the runtime still loads in browsers
+*/
+const hits = new Map<string, number>();
+
+export function hitCoverage(name: string) {
+  hits.set(name, (hits.get(name) ?? 0) + 1);
+}
+
+export function coverageHits(): Map<string, number> {
+  return hits;
+}
+
+function fileNameOf(testFile: string): string {
+  return `${testFile.replace(/[^A-Za-z0-9_]/g, "_")}.json`;
+}
+
+// afterAll of a generated test file
+export function writeCoverageHits(dir: string, testFile: string) {
+  // eslint-disable-next-line @typescript-eslint/no-var-requires
+  const fs = require("fs");
+  const counts: Record<string, number> = {};
+  hits.forEach((count, name) => {
+    counts[name] = count;
+  });
+  fs.mkdirSync(dir, { recursive: true });
+  fs.writeFileSync(`${dir}/${fileNameOf(testFile)}`, JSON.stringify(counts));
+}
+
+// jest globalSetup, so that hits of a previous run are not counted again
+export function resetCoverageHits(dir: string) {
+  // eslint-disable-next-line @typescript-eslint/no-var-requires
+  const fs = require("fs");
+  if (!fs.existsSync(dir)) {
+    return;
+  }
+  for (const file of fs.readdirSync(dir)) {
+    fs.unlinkSync(`${dir}/${file}`);
+  }
+}
+
+export interface CoverageReport {
+  functions: Record<string, number>;
+  neverHit: string[];
+}
+
+// sums the hits of all test files over the functions the translator listed
+export function mergeCoverage(functions: string[], hitsByFile: Record<string, number>[]): CoverageReport {
+  const counts: Record<string, number> = {};
+  for (const name of functions) {
+    counts[name] = 0;
+  }
+  for (const fileHits of hitsByFile) {
+    for (const name of Object.keys(fileHits)) {
+      counts[name] = (counts[name] ?? 0) + fileHits[name];
+    }
+  }
+  const neverHit = Object.keys(counts).filter(name => counts[name] === 0).sort();
+  return { functions: counts, neverHit };
+}
+
+// jest globalTeardown: writes coverage.json and prints the functions no test reached
+export function mergeCoverageHits(dir: string, functionsPath: string, outPath: string) {
+  // eslint-disable-next-line @typescript-eslint/no-var-requires
+  const fs = require("fs");
+  const functions: string[] = JSON.parse(fs.readFileSync(functionsPath, "utf8"));
+  const hitsByFile = fs.existsSync(dir)
+    ? fs.readdirSync(dir).map((file: string) => JSON.parse(fs.readFileSync(`${dir}/${file}`, "utf8")))
+    : [];
+  const report = mergeCoverage(functions, hitsByFile);
+  fs.writeFileSync(outPath, JSON.stringify(report, null, 2));
+  const total = Object.keys(report.functions).length;
+  console.log(`\nMove function coverage: ${total - report.neverHit.length}/${total} functions hit, see ${outPath}`);
+  if (report.neverHit.length > 0) {
+    console.log(`Never hit:\n${report.neverHit.map(name => `  ${name}`).join("\n")}`);
+  }
+}
@@ -0,0 +1,170 @@
+import { AptosAccount, AptosClient, HexString, Types } from "aptos";
+import { UserTransaction, WriteSetChange_DeleteResource, WriteSetChange_WriteResource } from "aptos/dist/generated";
+import { abortCodeFromError, abortCodeFromVmStatus } from "./abortErrors";
+import { AptosLocalCache, snapshotAccountsToCache } from "./aptosDataCache";
+import { UnsignedInt } from "./builtinTypes";
+import { AptosParserRepo, FieldDeclType, fieldMemberName } from "./parserRepo";
+import { moveTraceOf } from "./trace";
+import { simulatePayloadTx } from "./txSender";
+import { getTypeTagFullname, isSameAddress, normalizeAddress, parseMoveStructTag } from "./typeTag";
+
+/*
+One entry function call, run both by the translated code against a snapshot of `addresses` and by
+the node in a simulation. Generated by --gen-difftest, see src/difftest
+*/
+export interface DiffTestCase {
+  name: string;
+  payload: Types.TransactionPayload;
+  runLocal($c: AptosLocalCache): Promise<void> | void;
+  // accounts to snapshot beforehand and whose resources get compared afterwards
+  addresses: HexString[];
+}
+
+export interface DiffTestReport {
+  name: string;
+  // empty when both runs agree
+  mismatches: string[];
+  // resources the snapshot or the comparison had to skip, e.g. for lack of a parser
+  warnings: string[];
+}
+
+function showValue(value: any): string {
+  if (value instanceof UnsignedInt) {
+    return value.value.toString();
+  }
+  if (value instanceof HexString) {
+    return normalizeAddress(value);
+  }
+  if (Array.isArray(value)) {
+    return `[${value.map(showValue).join(", ")}]`;
+  }
+  if (value !== null && typeof value === 'object' && value.constructor?.structName) {
+    return value.constructor.structName;
+  }
+  return `${value}`;
+}
+
+/*
+Field-level differences between what the node left (decoded by the generated parsers) and what the
+translated code left, each prefixed with the path of the field. Structs are walked through the
+static `fields` of their generated class
+*/
+export function diffValues(node: any, local: any, path: string): string[] {
+  const mismatch = () => [`${path}: node has ${showValue(node)}, translated code has ${showValue(local)}`];
+  if (node instanceof UnsignedInt || local instanceof UnsignedInt) {
+    if (!(node instanceof UnsignedInt && local instanceof UnsignedInt) || !node.value.eq(local.value)) {
+      return mismatch();
+    }
+    return [];
+  }
+  if (node instanceof HexString || local instanceof HexString) {
+    if (!(node instanceof HexString && local instanceof HexString) || !isSameAddress(node, local)) {
+      return mismatch();
+    }
+    return [];
+  }
+  if (Array.isArray(node) || Array.isArray(local)) {
+    if (!(Array.isArray(node) && Array.isArray(local))) {
+      return mismatch();
+    }
+    if (node.length !== local.length) {
+      return [`${path}: node has ${node.length} elements, translated code has ${local.length}`];
+    }
+    return node.flatMap((elem, i) => diffValues(elem, local[i], `${path}[${i}]`));
+  }
+  if (node !== null && typeof node === 'object' && local !== null && typeof local === 'object') {
+    const fields: FieldDeclType[] | undefined = node.constructor?.fields;
+    if (!fields) {
+      // not a generated struct class, e.g. a table handle
+      return [];
+    }
+    if (node.constructor !== local.constructor) {
+      return mismatch();
+    }
+    return fields.flatMap(field => {
+      const member = fieldMemberName(field);
+      return diffValues(node[member], local[member], `${path}.${field.name}`);
+    });
+  }
+  return node === local ? [] : mismatch();
+}
+
+/*
+Seeds a local cache with the resources of testCase.addresses, runs the translated call on it,
+simulates the same payload on the node, and compares the outcomes: whether and with which code
+both aborted, and every resource the node wrote to or deleted from those addresses
+*/
+export async function runDiffTest(
+  client: AptosClient,
+  repo: AptosParserRepo,
+  account: AptosAccount,
+  testCase: DiffTestCase,
+): Promise<DiffTestReport> {
+  const $c = new AptosLocalCache();
+  const warnings = await snapshotAccountsToCache(client, repo, testCase.addresses, $c);
+  const mismatches: string[] = [];
+
+  let localAbort: string | null = null;
+  let localCode: string | null = null;
+  // the Move frames of the local abort under --trace, shown along with it
+  let localTrace = "";
+  try {
+    await testCase.runLocal($c);
+  }
+  catch(e) {
+    localAbort = e instanceof Error ? e.message : `${e}`;
+    localCode = abortCodeFromError(e);
+    localTrace = moveTraceOf(e).map(frame => `\n${frame}`).join("");
+  }
+  const tx = await simulatePayloadTx(client, account, testCase.payload) as UserTransaction;
+  const nodeAbort = tx.success ? null : tx.vm_status;
+
+  if (nodeAbort !== null || localAbort !== null) {
+    if (nodeAbort === null || localAbort === null) {
+      mismatches.push(`abort: node ${nodeAbort ?? "succeeded"}, translated code ${localAbort ?? "succeeded"}${localTrace}`);
+    }
+    // a failure that is not a Move abort on either side cannot be confirmed to agree
+    else {
+      const nodeCode = abortCodeFromVmStatus(nodeAbort);
+      if (nodeCode === null || localCode === null || nodeCode !== localCode) {
+        mismatches.push(`abort code: node ${nodeAbort}, translated code ${localAbort}${localTrace}`);
+      }
+    }
+    return { name: testCase.name, mismatches, warnings };
+  }
+
+  const isCompared = (address: string) => testCase.addresses.some(a => isSameAddress(a, address));
+  for (const change of tx.changes) {
+    if (change.type === 'write_resource') {
+      const wr = change as WriteSetChange_WriteResource;
+      if (!isCompared(wr.address)) {
+        continue;
+      }
+      const address = new HexString(wr.address);
+      const typeTag = parseMoveStructTag(wr.data.type);
+      const path = `${getTypeTagFullname(typeTag)}@${normalizeAddress(address)}`;
+      if (!repo.getParserFromTypeTag(typeTag)) {
+        warnings.push(`${path}: no parser, not compared`);
+        continue;
+      }
+      if (!$c.exists(typeTag, address)) {
+        mismatches.push(`${path}: written by the node, missing after the translated code`);
+        continue;
+      }
+      const nodeValue = repo.parse(wr.data.data, typeTag);
+      mismatches.push(...diffValues(nodeValue, $c.borrow_global(typeTag, address), path));
+    }
+    else if (change.type === 'delete_resource') {
+      const dr = change as WriteSetChange_DeleteResource;
+      if (!isCompared(dr.address)) {
+        continue;
+      }
+      const address = new HexString(dr.address);
+      const typeTag = parseMoveStructTag(dr.resource);
+      if ($c.exists(typeTag, address)) {
+        mismatches.push(`${getTypeTagFullname(typeTag)}@${normalizeAddress(address)}: deleted by the node, kept by the translated code`);
+      }
+    }
+  }
+  return { name: testCase.name, mismatches, warnings };
+}
@@ -1,8 +1,19 @@
+export * from "./abortErrors";
 export * from "./aptosDataCache";
 export * from "./builtinTypes";
 export * from "./builtinFuncs";
+export * from "./clock";
+export * from "./compatibility";
+export * from "./coverage";
+export * from "./difftest";
+export * from "./invariants";
+export * from "./mathFuncs";
 export * from "./nativeFuncs";
+export * from "./ord";
 export * from "./jsonTypes";
 export * from "./parserRepo";
+export * from "./randomness";
+export * from "./tableItems";
+export * from "./trace";
 export * from "./txSender";
 export * from "./typeTag";
\ No newline at end of file
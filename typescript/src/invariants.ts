@@ -0,0 +1,31 @@
+import { HexString } from "aptos";
+
+/*
+Asserts a module guards with one of its bool constants, e.g. `if (PARANOID) { assert!(...) }` or
+`assert!(PARANOID && cond, code)`, run when invariantChecks says so: as in Move by default, or as
+last set with setInvariantChecks for the constant, named <address>::<module>::<NAME>. The default
+is the constant for the if, and true for the assert, whose condition still reads the constant.
+--production leaves these checks out of the generated code
+*/
+
+const switches = new Map<string, boolean>();
+
+// the same constant, whatever the form of its address
+function constantKey(constant: string): string {
+  const [address, ...rest] = constant.split("::");
+  if (rest.length !== 2) {
+    throw new Error(`Expected <address>::<module>::<NAME>, got ${constant}`);
+  }
+  return [new HexString(address).toShortString(), ...rest].join("::");
+}
+
+export function setInvariantChecks(constant: string, enabled: boolean) {
+  switches.set(constantKey(constant), enabled);
+}
+
+export function invariantChecks(constant: string, value: boolean): boolean {
+  if (switches.size === 0) {
+    return value;
+  }
+  return switches.get(constantKey(constant)) ?? value;
+}
@@ -0,0 +1,83 @@
+import bigInt from "big-integer";
+import { ArithmeticError, U128, U64 } from "./builtinTypes";
+
+/*
+Fast paths of the helpers of 0x1::math64 and 0x1::math128, which translated calls to them go to
+unless --no-math-inline is given. They compute on the big integers at once instead of running the
+loops of the translated bodies, and throw ArithmeticError where those overflow
+*/
+
+function average(a: bigInt.BigInteger, b: bigInt.BigInteger): bigInt.BigInteger {
+  return a.lt(b) ? a.add(b.subtract(a).divide(2)) : b.add(a.subtract(b).divide(2));
+}
+
+// the translated loop squares up to the result, so it overflows exactly when the result does
+function pow(n: bigInt.BigInteger, e: bigInt.BigInteger, bits: number): bigInt.BigInteger {
+  if (e.isZero()) {
+    return bigInt.one;
+  }
+  if (n.leq(bigInt.one)) {
+    return n;
+  }
+  // from 2 on, the power leaves the width before the exponent reaches it
+  const value = e.lt(bits) ? n.pow(e) : null;
+  if (value === null || value.geq(bigInt.one.shiftLeft(bits))) {
+    throw new ArithmeticError(`${n} ** ${e} does not fit in u${bits}`);
+  }
+  return value;
+}
+
+// rounded down, like the translated Newton iterations
+function sqrt(x: bigInt.BigInteger): bigInt.BigInteger {
+  if (x.isZero()) {
+    return bigInt.zero;
+  }
+  let root = bigInt.one.shiftLeft(Math.ceil(x.bitLength().toJSNumber() / 2));
+  for (;;) {
+    const next = root.add(x.divide(root)).shiftRight(1);
+    if (next.geq(root)) {
+      return root;
+    }
+    root = next;
+  }
+}
+
+export function aptos_std_math64_max(a: U64, b: U64): U64 {
+  return new U64(bigInt.max(a.value, b.value));
+}
+
+export function aptos_std_math64_min(a: U64, b: U64): U64 {
+  return new U64(bigInt.min(a.value, b.value));
+}
+
+export function aptos_std_math64_average(a: U64, b: U64): U64 {
+  return new U64(average(a.value, b.value));
+}
+
+export function aptos_std_math64_pow(n: U64, e: U64): U64 {
+  return new U64(pow(n.value, e.value, 64));
+}
+
+export function aptos_std_math64_sqrt(x: U64): U64 {
+  return new U64(sqrt(x.value));
+}
+
+export function aptos_std_math128_max(a: U128, b: U128): U128 {
+  return new U128(bigInt.max(a.value, b.value));
+}
+
+export function aptos_std_math128_min(a: U128, b: U128): U128 {
+  return new U128(bigInt.min(a.value, b.value));
+}
+
+export function aptos_std_math128_average(a: U128, b: U128): U128 {
+  return new U128(average(a.value, b.value));
+}
+
+export function aptos_std_math128_pow(n: U128, e: U128): U128 {
+  return new U128(pow(n.value, e.value, 128));
+}
+
+export function aptos_std_math128_sqrt(x: U128): U128 {
+  return new U128(sqrt(x.value));
+}
@@ -1,13 +1,13 @@
 import { HexString } from "aptos";
 import { AptosDataCache, IBox, ITable } from "./aptosDataCache";
-import { U128, U64, U8 } from "./builtinTypes";
+import { U128, U64, U8, UnsignedInt } from "./builtinTypes";
 import { AtomicTypeTag, getTypeParamsString, getTypeTagFullname, parseTypeTagOrThrow, StructTag, substituteTypeParams, TypeParamIdx, TypeTag, VectorTag } from "./typeTag";
 import * as sha from "sha.js";
 import { SHA3 } from "sha3";
 import bigInt from "big-integer";
 import * as elliptic from "elliptic";
-import { AptosParserRepo, FieldDeclType, parseStructProto, StructInfoType, TypeParamDeclType } from "./parserRepo";
-import { strToU8, u64, u8str } from "./builtinFuncs";
+import { AptosParserRepo, FieldDeclType, fieldMemberName, parseStructProto, StructInfoType, TypeParamDeclType } from "./parserRepo";
+import { abortCode, strToU8, u64, u8str } from "./builtinFuncs";
 import { BCS } from "aptos/dist/transaction_builder";
 
 
@@ -36,7 +36,7 @@ export function serializeStruct(serializer: BCS.Serializer, v: any, tag: StructT
   const structDef = ctor as unknown as StructInfoType;
   for (const field of structDef.fields) {
     const concreteTag = substituteTypeParams(field.typeTag, tag.typeParams);
-    serializeMoveValue(serializer, v[field.name], concreteTag);
+    serializeMoveValue(serializer, v[fieldMemberName(field)], concreteTag);
   }
 }
 
@@ -291,6 +291,51 @@ export function aptos_framework_account_create_signer(addr: HexString, $c: Aptos
   return addr;
 }
 
+const DERIVE_RESOURCE_ACCOUNT_SCHEME = 255;
+const OBJECT_FROM_SEED_ADDRESS_SCHEME = 254;
+const OBJECT_DERIVED_SCHEME = 252;
+
+// sha3_256(bcs(source) ++ seed ++ scheme), as done by the framework's address derivation
+export function deriveAddress(source: HexString, seed: U8[], scheme: number): HexString {
+  // bcs of an address is its 32 bytes, left-padded with zeros
+  const sourceHex = source.toShortString().substring(2).padStart(64, '0');
+  const dataBuffer = Buffer.concat([
+    Buffer.from(sourceHex, 'hex'),
+    Buffer.from(seed.map(u => u.value.toJSNumber())),
+    Buffer.from([scheme]),
+  ]);
+  const outputBuffer = new SHA3(256).update(dataBuffer).digest();
+  return HexString.fromUint8Array(new Uint8Array(outputBuffer));
+}
+
+export function aptos_framework_account_create_resource_address(source: HexString, seed: U8[], $c: AptosDataCache): HexString {
+  return deriveAddress(source, seed, DERIVE_RESOURCE_ACCOUNT_SCHEME);
+}
+
+// object::create_object_address, which also gives named objects such as collections and tokens
+export function deriveObjectAddress(source: HexString, seed: U8[]): HexString {
+  return deriveAddress(source, seed, OBJECT_FROM_SEED_ADDRESS_SCHEME);
+}
+
+export function aptos_framework_object_create_object_address(source: HexString, seed: U8[], $c: AptosDataCache): HexString {
+  return deriveObjectAddress(source, seed);
+}
+
+// object::create_user_derived_object_address, which also gives the primary fungible store of an owner
+export function deriveUserObjectAddress(source: HexString, deriveFrom: HexString): HexString {
+  const deriveFromHex = deriveFrom.toShortString().substring(2).padStart(64, '0');
+  const seed = Array.from(Buffer.from(deriveFromHex, 'hex')).map(b => new U8(bigInt(b)));
+  return deriveAddress(source, seed, OBJECT_DERIVED_SCHEME);
+}
+
+export function aptos_framework_object_create_user_derived_object_address(source: HexString, deriveFrom: HexString, $c: AptosDataCache): HexString {
+  return deriveUserObjectAddress(source, deriveFrom);
+}
+
+export function aptos_framework_object_create_user_derived_object_address_impl(source: HexString, deriveFrom: HexString, $c: AptosDataCache): HexString {
+  return deriveUserObjectAddress(source, deriveFrom);
+}
+
 class ModuleMetadata 
 {
   static moduleAddress = new HexString("0x1");
@@ -566,3 +611,153 @@ export function aptos_std_type_info_type_name($c: AptosDataCache, tags: TypeTag[
     bytes: strToU8(name),
   }, newTag)
 }
+
+/*
+Aggregators are counters the VM updates in parallel, outside of the resource holding them. For
+simulation they are plain arithmetic: aggregator_v2 works on the value field of the struct and
+aggregator (v1), whose value lives in a table, on one kept on the object. The generated modules only
+call these without --strict-natives
+*/
+export interface IAggregatorV2 {
+  value: UnsignedInt<any>;
+  max_value: UnsignedInt<any>;
+}
+
+export function aggregatorZero(tag: TypeTag): U64 | U128 {
+  if (tag === AtomicTypeTag.U64) {
+    return new U64(bigInt.zero);
+  }
+  if (tag === AtomicTypeTag.U128) {
+    return new U128(bigInt.zero);
+  }
+  throw new Error(`Aggregators hold u64 or u128, not ${getTypeTagFullname(tag)}`);
+}
+
+export function aggregatorMax(tag: TypeTag): U64 | U128 {
+  const zero = aggregatorZero(tag);
+  return zero instanceof U64 ? new U64(U64.MAX) : new U128(U128.MAX);
+}
+
+export function aggregatorRead(aggregator: { value: UnsignedInt<any> }): UnsignedInt<any> {
+  return aggregator.value.copy();
+}
+
+export function aggregatorTryAdd(aggregator: IAggregatorV2, value: UnsignedInt<any>): boolean {
+  const sum = aggregator.value.value.add(value.value);
+  if (sum.gt(aggregator.max_value.value)) {
+    return false;
+  }
+  aggregator.value.$set(aggregator.value.make(sum));
+  return true;
+}
+
+export function aggregatorTrySub(aggregator: IAggregatorV2, value: UnsignedInt<any>): boolean {
+  if (aggregator.value.value.lt(value.value)) {
+    return false;
+  }
+  aggregator.value.$set(aggregator.value.make(aggregator.value.value.subtract(value.value)));
+  return true;
+}
+
+export function aggregatorIsAtLeast(aggregator: IAggregatorV2, min: UnsignedInt<any>): boolean {
+  return aggregator.value.value.geq(min.value);
+}
+
+// 0x1::aggregator::Aggregator declares handle, key and limit, the value is kept next to them
+export interface IAggregatorV1 {
+  limit: U128;
+  $value?: U128;
+}
+
+export function aggregatorV1Read(aggregator: IAggregatorV1): U128 {
+  return new U128(aggregator.$value?.value ?? bigInt.zero);
+}
+
+// same abort codes as aggregator::add and aggregator::sub: EAGGREGATOR_OVERFLOW and EAGGREGATOR_UNDERFLOW
+export function aggregatorV1Add(aggregator: IAggregatorV1, value: U128) {
+  const sum = aggregatorV1Read(aggregator).value.add(value.value);
+  if (sum.gt(aggregator.limit.value)) {
+    throw abortCode(new U64(bigInt(0x020001)));
+  }
+  aggregator.$value = new U128(sum);
+}
+
+export function aggregatorV1Sub(aggregator: IAggregatorV1, value: U128) {
+  const current = aggregatorV1Read(aggregator).value;
+  if (current.lt(value.value)) {
+    throw abortCode(new U64(bigInt(0x020002)));
+  }
+  aggregator.$value = new U128(current.subtract(value.value));
+}
+
+// 0x1::randomness, calls into it are routed here and draw from the cache's AptosRandomness
+export function aptos_framework_randomness_u8_integer($c: AptosDataCache): U8 {
+  return new U8($c.getRandomness().nextBits(8));
+}
+
+export function aptos_framework_randomness_u64_integer($c: AptosDataCache): U64 {
+  return new U64($c.getRandomness().nextBits(64));
+}
+
+export function aptos_framework_randomness_u128_integer($c: AptosDataCache): U128 {
+  return new U128($c.getRandomness().nextBits(128));
+}
+
+// like the Move implementation: a 256-bit sample modulo the size of the range
+function randomnessRange<T extends UnsignedInt<any>>(minIncl: T, maxExcl: T, $c: AptosDataCache): T {
+  if (maxExcl.value.leq(minIncl.value)) {
+    throw new Error(`Empty randomness range [${minIncl.value}, ${maxExcl.value})`);
+  }
+  const sample = $c.getRandomness().nextBits(256).mod(maxExcl.value.subtract(minIncl.value));
+  return minIncl.make(minIncl.value.add(sample)) as T;
+}
+
+export function aptos_framework_randomness_u8_range(minIncl: U8, maxExcl: U8, $c: AptosDataCache): U8 {
+  return randomnessRange(minIncl, maxExcl, $c);
+}
+
+export function aptos_framework_randomness_u64_range(minIncl: U64, maxExcl: U64, $c: AptosDataCache): U64 {
+  return randomnessRange(minIncl, maxExcl, $c);
+}
+
+export function aptos_framework_randomness_u128_range(minIncl: U128, maxExcl: U128, $c: AptosDataCache): U128 {
+  return randomnessRange(minIncl, maxExcl, $c);
+}
+
+export function aptos_framework_randomness_bytes(n: U64, $c: AptosDataCache): U8[] {
+  const result: U8[] = [];
+  for (let i = 0; i < n.value.toJSNumber(); i++) {
+    result.push(aptos_framework_randomness_u8_integer($c));
+  }
+  return result;
+}
+
+// Fisher-Yates shuffle of [0, n), in the same order as the Move implementation
+export function aptos_framework_randomness_permutation(n: U64, $c: AptosDataCache): U64[] {
+  const values: U64[] = [];
+  for (let i = 0; i < n.value.toJSNumber(); i++) {
+    values.push(u64(i));
+  }
+  for (let tail = values.length - 1; tail > 0; tail--) {
+    const pop = aptos_framework_randomness_u64_range(u64(0), u64(tail + 1), $c).value.toJSNumber();
+    [values[pop], values[tail]] = [values[tail], values[pop]];
+  }
+  return values;
+}
+
+export function aptos_framework_randomness_initialize_for_testing(_framework: HexString, $c: AptosDataCache) {
+  // nothing to set up, the cache always has a source of randomness
+}
+
+export function aptos_framework_randomness_set_seed(seed: U8[], $c: AptosDataCache) {
+  $c.getRandomness().setSeed(seed);
+}
+
+export function aptos_framework_randomness_fetch_and_increment_txn_counter($c: AptosDataCache): U8[] {
+  return aptos_framework_randomness_bytes(u64(32), $c);
+}
+
+export function aptos_framework_randomness_is_unbiasable($c: AptosDataCache): boolean {
+  // translated code is not called from other modules that could test-and-abort
+  return true;
+}
@@ -0,0 +1,24 @@
+import { HexString } from "aptos";
+import bigInt from "big-integer";
+import { UnsignedInt } from "./builtinTypes";
+
+/*
+The comparators generated for #[ord] structs compare each field with compareValues, and fall
+through to the next field on a tie. Integers compare as big integers, never as JS numbers, and
+addresses compare as the numbers they encode, so that 0x2 sorts before 0x10
+*/
+
+function addressValue(address: HexString): bigInt.BigInteger {
+  const hex = address.noPrefix();
+  return hex === "" ? bigInt.zero : bigInt(hex, 16);
+}
+
+export function compareValues(a: UnsignedInt<any> | HexString, b: UnsignedInt<any> | HexString): number {
+  if (a instanceof UnsignedInt && b instanceof UnsignedInt) {
+    return a.value.compare(b.value);
+  }
+  if (a instanceof HexString && b instanceof HexString) {
+    return addressValue(a).compare(addressValue(b));
+  }
+  throw new Error(`Cannot compare ${a} with ${b}`);
+}
@@ -1,4 +1,4 @@
-import { AtomicTypeTag, getTypeTagParamlessName, StructTag, TypeTag, TypeParamIdx, VectorTag, getTypeTagFullname, parseTypeTagOrThrow, substituteTypeParams } from "./typeTag";
+import { AtomicTypeTag, getTypeTagParamlessName, StructTag, TypeTag, TypeParamIdx, VectorTag, getTypeTagFullname, parseTypeTagOrThrow, substituteTypeParams, tryParseStructTag, isSameAddress, normalizeTypeString } from "./typeTag";
 import { AptosClient, HexString } from "aptos";
 import bigInt from "big-integer";
 import { U128, U64, U8 } from "./builtinTypes";
@@ -11,6 +11,14 @@ export type TypeParamDeclType = {
 export type FieldDeclType = {
   name: string;
   typeTag: TypeTag;
+  // set when the class member holding this field differs from its on-chain name
+  memberName?: string;
+  // set on the fields of stubs of excluded modules, whose instances keep the data as is in raw
+  opaque?: boolean;
+}
+
+export function fieldMemberName(field: FieldDeclType): string {
+  return field.memberName ?? field.name;
 }
 export interface StructInfoType {
   moduleAddress: HexString;
@@ -18,15 +26,66 @@ export interface StructInfoType {
   structName: string;
   typeParameters: TypeParamDeclType[];
   fields: FieldDeclType[];
+  // emitted on generated classes, absent on hand-written ones
+  typeParamCount?: number;
+  phantomTypeParams?: boolean[];
+  abilities?: string[];
   new(proto: any, typeTag: TypeTag): any;
   load?(repo: AptosParserRepo, client: AptosClient, address: HexString, typeParams: TypeTag[]): Promise<any>;
 }
 
+export type Ability = "copy" | "drop" | "store" | "key";
+
+export function hasAbility(struct: StructInfoType, ability: Ability): boolean {
+  return (struct.abilities ?? []).includes(ability);
+}
+
+// resources are the structs that can be loaded from an account
+export function isResource(struct: StructInfoType): boolean {
+  return hasAbility(struct, "key");
+}
+
+export function getStructField(struct: StructInfoType, name: string): FieldDeclType {
+  const field = struct.fields.find(f => f.name === name);
+  if (!field) {
+    throw new Error(`${struct.structName} has no field ${name}, its fields are: ${struct.fields.map(f => f.name).join(", ")}`);
+  }
+  return field;
+}
+
+// tables keep their entries on-chain, out of reach of a field path
+const TABLE_MODULES = ["table", "table_with_length", "iterable_table", "smart_table"];
+
+/*
+Value at a dotted path of field names, e.g. "pool.reserve.value", starting from an instance of a
+generated struct class and going through nested ones
+*/
+export function getFieldValueByPath(value: any, path: string): unknown {
+  let current = value;
+  let walked = value?.constructor?.structName ?? "value";
+  for (const name of path.split(".")) {
+    if (Array.isArray(current) || current instanceof AptosVectorU8) {
+      throw new Error(`${walked} is a vector, cannot get its field ${name}`);
+    }
+    const struct = current?.constructor as StructInfoType | undefined;
+    if (!struct || !struct.fields) {
+      throw new Error(`${walked} is not a struct, cannot get its field ${name}`);
+    }
+    if (isSameAddress(struct.moduleAddress, "0x1") && TABLE_MODULES.includes(struct.moduleName)) {
+      throw new Error(`${walked} is a table, cannot get its field ${name}`);
+    }
+    const field = getStructField(struct, name);
+    current = field.opaque ? current.raw?.[field.name] : current[fieldMemberName(field)];
+    walked = `${walked}.${name}`;
+  }
+  return current;
+}
+
 export function parseStructProto(data: any, typeTag: TypeTag, repo: AptosParserRepo, struct: StructInfoType): any {
   if(!(typeTag instanceof StructTag)) {
     throw new Error(`${struct.structName} expects a StructTag as typeTag but received: ${typeTag}`);
   }
-  if(typeTag.address.hex() !== struct.moduleAddress.hex()) {
+  if(!isSameAddress(typeTag.address, struct.moduleAddress)) {
     throw new Error(`${struct.structName} expects a moduleAddress of ${struct.moduleAddress} but received: ${typeTag.address}.`);
   }
   if(typeTag.module !== struct.moduleName) {
@@ -35,9 +94,12 @@ export function parseStructProto(data: any, typeTag: TypeTag, repo: AptosParserR
   if(typeTag.name !== struct.structName) {
     throw new Error(`${struct.structName} expects a struct name of "${struct.structName}" but received: ${typeTag.name}`);
   }
+  if(isSameAddress(typeTag.address, '0x1') && typeTag.module === 'aggregator_v2') {
+    data = aggregatorV2Data(data, typeTag);
+  }
   if(typeof data !== "object") {
     // could be 0x1::string::String
-    if (typeTag.address.toShortString() === '0x1' && typeTag.module === 'string' && typeTag.name === 'String') {
+    if (isSameAddress(typeTag.address, '0x1') && typeTag.module === 'string' && typeTag.name === 'String') {
       // return the proto object directly
       const bytes = strToU8(data);
       const proto = { bytes };
@@ -70,6 +132,45 @@ export function parseStructProto(data: any, typeTag: TypeTag, repo: AptosParserR
   return proto;
 }
 
+/*
+The API returns an aggregator_v2 Aggregator or AggregatorSnapshot either as the struct, or as just its
+current value. The latter is expanded into the struct, with an unbounded max_value when missing
+*/
+function aggregatorV2Data(data: any, typeTag: StructTag): any {
+  const proto = typeof data === "object" ? { ...data } : { value: data };
+  if(typeTag.name === 'Aggregator' && !('max_value' in proto)) {
+    const elementTag = typeTag.typeParams[0];
+    proto.max_value = (elementTag === AtomicTypeTag.U64 ? U64.MAX : U128.MAX).toString();
+  }
+  return proto;
+}
+
+export type MoveEvent = {
+  type: string;
+  data: any;
+}
+
+export type DecodedEvent = {
+  type: string;
+  typeTag: StructTag;
+  value: any;
+}
+
+/*
+Parse event data into struct's class if event.type refers to that struct, otherwise return null
+*/
+export function decodeStructEvent<T>(event: MoveEvent, struct: StructInfoType, repo: AptosParserRepo): T | null {
+  const typeTag = tryParseStructTag(event.type);
+  if (typeTag === null) {
+    return null;
+  }
+  if (!isSameAddress(typeTag.address, struct.moduleAddress) || typeTag.module !== struct.moduleName || typeTag.name !== struct.structName) {
+    return null;
+  }
+  const proto = parseStructProto(event.data, typeTag, repo, struct);
+  return new struct(proto, typeTag) as T;
+}
+
 export type ParserFunc = (data: any, typeTag: TypeTag, repo: AptosParserRepo) => any;
 
 export function U8Parser(data: any, typeTag: TypeTag, _repo: AptosParserRepo):U8 {
@@ -88,24 +189,39 @@ export function U8Parser(data: any, typeTag: TypeTag, _repo: AptosParserRepo):U8
   return new U8(bigInt(data));
 }
 
+/*
+The fullnode sends u64 and u128 values as JSON strings, but some endpoints and older nodes send small
+ones as numbers. Both are turned into a decimal string here, and numbers that JSON.parse could have
+rounded are rejected instead of being parsed into a wrong value
+*/
+export function unsignedIntData(data: any, parserName: string): string {
+  if(typeof data === "string") {
+    if(!/^[0-9]+$/.test(data)) {
+      throw new Error(`${parserName} expects a decimal integer but received: ${data}`);
+    }
+    return data;
+  }
+  if(typeof data === "number") {
+    if(!Number.isSafeInteger(data) || data < 0) {
+      throw new Error(`${parserName} received ${data} as a number, which may have lost precision, expected a string`);
+    }
+    return data.toString();
+  }
+  throw new Error(`${parserName} expects string or number type as data but received: ${typeof data}`);
+}
+
 export function U64Parser(data: any, typeTag: TypeTag, _repo: AptosParserRepo): U64 {
   if(typeTag !== AtomicTypeTag.U64) {
     throw new Error(`U64Parser cannot parse type: ${getTypeTagParamlessName(typeTag)}`);
   }
-  if(typeof data !== "string") {
-    throw new Error(`U64Parser expects string type as data but received: ${typeof data}`);
-  }
-  return new U64(bigInt(data));
+  return new U64(bigInt(unsignedIntData(data, "U64Parser")));
 }
 
 export function U128Parser(data: any, typeTag: TypeTag, _repo: AptosParserRepo): U128 {
   if(typeTag !== AtomicTypeTag.U128) {
     throw new Error(`U128Parser cannot parse type: ${getTypeTagParamlessName(typeTag)}`);
   }
-  if(typeof data !== "string") {
-    throw new Error(`U128Parser expects string type as data but received: ${typeof data}`);
-  }
-  return new U128(bigInt(data));
+  return new U128(bigInt(unsignedIntData(data, "U128Parser")));
 }
 
 export function BoolParser(data: any, typeTag: TypeTag, _repo: AptosParserRepo): boolean {
@@ -238,7 +354,7 @@ export class AptosParserRepo {
     return parser(data, typeTag, this);
   }
   getParserFromParamlessName(paramlessName: string): null | ParserFunc {
-    const parser = this.paramlessNameToParser[paramlessName];
+    const parser = this.paramlessNameToParser[normalizeTypeString(paramlessName)];
     if (parser) {
       return parser;
     }
@@ -249,7 +365,8 @@ export class AptosParserRepo {
     return this.getParserFromParamlessName(paramlessName);
   }
   addParser(paramlessName: string, parser: ParserFunc) {
-    this.paramlessNameToParser[paramlessName] = parser;
+    // keys are kept in canonical form so that lookups tolerate both short and padded addresses
+    this.paramlessNameToParser[normalizeTypeString(paramlessName)] = parser;
   }
   addDefaultParsers() {
     // load the defaults
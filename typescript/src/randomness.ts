@@ -0,0 +1,66 @@
+import bigInt from "big-integer";
+import { SHA3 } from "sha3";
+import { U8 } from "./builtinTypes";
+
+/*
+Source of randomness for translated calls into 0x1::randomness
+*/
+export interface AptosRandomness {
+  // uniformly random integer in [0, 2^bits), bits is a multiple of 8
+  nextBits(bits: number): bigInt.BigInteger;
+  setSeed(seed: U8[]): void;
+}
+
+function bytesToBigInt(bytes: Uint8Array): bigInt.BigInteger {
+  return bytes.reduce((acc, b) => acc.shiftLeft(8).add(b), bigInt.zero);
+}
+
+function cryptoRandomBytes(numBytes: number): Uint8Array {
+  const webCrypto = (globalThis as any).crypto;
+  if (webCrypto && webCrypto.getRandomValues) {
+    return webCrypto.getRandomValues(new Uint8Array(numBytes));
+  }
+  // eslint-disable-next-line @typescript-eslint/no-var-requires
+  return new Uint8Array(require("crypto").randomBytes(numBytes));
+}
+
+// draws from the platform's cryptographic RNG, cannot be seeded
+export class CryptoRandomness implements AptosRandomness {
+  nextBits(bits: number): bigInt.BigInteger {
+    return bytesToBigInt(cryptoRandomBytes(bits / 8));
+  }
+  setSeed(_seed: U8[]) {
+    throw new Error("CryptoRandomness cannot be seeded, use a SeededRandomness instead");
+  }
+}
+
+// 8 big-endian bytes
+function seedFromNumber(seed: number): U8[] {
+  const hex = bigInt(seed).toString(16).padStart(16, '0');
+  return Array.from(Buffer.from(hex, 'hex')).map(b => new U8(bigInt(b)));
+}
+
+// sha3-256 of the seed and a counter, reproducible across runs, suited for tests
+export class SeededRandomness implements AptosRandomness {
+  public seed: Uint8Array;
+  public counter: number;
+  constructor(seed: U8[] | number = 0) {
+    this.seed = new Uint8Array(0);
+    this.counter = 0;
+    this.setSeed(typeof seed === 'number' ? seedFromNumber(seed) : seed);
+  }
+  nextBits(bits: number): bigInt.BigInteger {
+    const bytes: number[] = [];
+    while (bytes.length < bits / 8) {
+      const counterBytes = Buffer.alloc(8);
+      counterBytes.writeUInt32LE(this.counter++, 0);
+      const digest = new SHA3(256).update(Buffer.concat([Buffer.from(this.seed), counterBytes])).digest();
+      bytes.push(...Array.from(digest));
+    }
+    return bytesToBigInt(new Uint8Array(bytes.slice(0, bits / 8)));
+  }
+  setSeed(seed: U8[]) {
+    this.seed = new Uint8Array(seed.map(u => u.value.toJSNumber()));
+    this.counter = 0;
+  }
+}
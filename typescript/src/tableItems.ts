@@ -0,0 +1,96 @@
+import { AptosClient, HexString } from "aptos";
+import { moveValueToOpenApiObject } from "./builtinFuncs";
+import { UnsignedInt } from "./builtinTypes";
+import { AptosParserRepo, FieldDeclType, fieldMemberName, getStructField, StructInfoType } from "./parserRepo";
+import { getTypeTagFullname, isTypeTagConcrete, parseTypeTagOrThrow, StructTag, substituteTypeParams, TypeTag } from "./typeTag";
+
+/*
+What the table items API needs to read one entry of a 0x1::table::Table field: the handle, the key
+and value type strings, and the key in its JSON form. Generated classes build it in
+tableItemRequest_<field>, from the field's type tag with the type parameters of the instance
+substituted, so that tables keyed or valued by a type parameter resolve to the actual types
+*/
+export interface TableItemRequest {
+  handle: string;
+  keyType: string;
+  valueType: string;
+  key: any;
+}
+
+// older frameworks keep the handle of a table as a u128, newer ones as an address
+function handleString(handle: UnsignedInt<any> | HexString): string {
+  return handle instanceof HexString ? handle.hex() : handle.value.toString();
+}
+
+// the field's type tag with the type parameters of the instance substituted, checked to be the
+// table type `name` with concrete key and value types
+function resolveTableTag(field: FieldDeclType, typeParams: TypeTag[], name: string): StructTag {
+  const tag = substituteTypeParams(field.typeTag, typeParams);
+  if (!(tag instanceof StructTag) || tag.getParamlessName() !== name || tag.typeParams.length !== 2) {
+    throw new Error(`Field ${field.name} is not a ${name}`);
+  }
+  if (!isTypeTagConcrete(tag)) {
+    throw new Error(`Field ${field.name} has unresolved type parameters: ${getTypeTagFullname(tag)}`);
+  }
+  return tag;
+}
+
+export function tableItemRequest(
+  handle: UnsignedInt<any> | HexString,
+  field: FieldDeclType,
+  typeParams: TypeTag[],
+  key: any
+): TableItemRequest {
+  const [keyTag, valueTag] = resolveTableTag(field, typeParams, "0x1::table::Table").typeParams;
+  return {
+    handle: handleString(handle),
+    keyType: getTypeTagFullname(keyTag),
+    valueType: getTypeTagFullname(valueTag),
+    key: moveValueToOpenApiObject(key, keyTag),
+  };
+}
+
+// an IterableTable keeps its entries in the Table of its TableWithLength
+function iterableTableHandle(table: any): UnsignedInt<any> | HexString {
+  let current = table;
+  for (const name of ["inner", "inner", "handle"]) {
+    current = current[fieldMemberName(getStructField(current.constructor as StructInfoType, name))];
+  }
+  return current;
+}
+
+/*
+The same for an 0x1::iterable_table::IterableTable field, whose entries are IterableValue<K, V>
+structs linking to the previous and next key; fetchIterableTableValue returns the V inside
+*/
+export function iterableTableItemRequest(table: any, field: FieldDeclType, typeParams: TypeTag[], key: any): TableItemRequest {
+  const tag = resolveTableTag(field, typeParams, "0x1::iterable_table::IterableTable");
+  const [keyTag, valueTag] = tag.typeParams;
+  const entryTag = new StructTag(tag.address, tag.module, "IterableValue", [keyTag, valueTag]);
+  return {
+    handle: handleString(iterableTableHandle(table)),
+    keyType: getTypeTagFullname(keyTag),
+    valueType: getTypeTagFullname(entryTag),
+    key: moveValueToOpenApiObject(key, keyTag),
+  };
+}
+
+async function fetchRawTableItem(client: AptosClient, request: TableItemRequest): Promise<any> {
+  const rawVal = await client.getTableItem(request.handle, {
+    key_type: request.keyType,
+    value_type: request.valueType,
+    key: request.key,
+  });
+  return rawVal.data;
+}
+
+export async function fetchTableItem(client: AptosClient, repo: AptosParserRepo, request: TableItemRequest): Promise<any> {
+  const data = await fetchRawTableItem(client, request);
+  return repo.parse(data, parseTypeTagOrThrow(request.valueType));
+}
+
+export async function fetchIterableTableValue(client: AptosClient, repo: AptosParserRepo, request: TableItemRequest): Promise<any> {
+  const data = await fetchRawTableItem(client, request);
+  const entryTag = parseTypeTagOrThrow(request.valueType) as StructTag;
+  return repo.parse(data.val, entryTag.typeParams[1]);
+}
@@ -0,0 +1,42 @@
+import { HexString } from "aptos";
+import { hasAbility, isResource } from "../parserRepo";
+import { TypeTag } from "../typeTag";
+import { assert } from "../utils";
+
+class Vault {
+  static moduleAddress = new HexString("0xa1");
+  static moduleName = "fixture";
+  static structName = "Vault";
+  static typeParameters = [];
+  static fields = [];
+  static abilities: string[] = ["key"];
+  constructor(_proto: any, public typeTag: TypeTag) {}
+}
+
+class Receipt {
+  static moduleAddress = new HexString("0xa1");
+  static moduleName = "fixture";
+  static structName = "Receipt";
+  static typeParameters = [];
+  static fields = [];
+  static abilities: string[] = ["copy", "drop", "store"];
+  constructor(_proto: any, public typeTag: TypeTag) {}
+}
+
+// hand-written, without abilities
+class Legacy {
+  static moduleAddress = new HexString("0xa1");
+  static moduleName = "fixture";
+  static structName = "Legacy";
+  static typeParameters = [];
+  static fields = [];
+  constructor(_proto: any, public typeTag: TypeTag) {}
+}
+
+export function test() {
+  assert(isResource(Vault) && !hasAbility(Vault, "store"), "expect Vault to be a resource without store");
+  assert(!isResource(Receipt), "expect Receipt not to be a resource");
+  assert(hasAbility(Receipt, "copy") && hasAbility(Receipt, "drop") && hasAbility(Receipt, "store"), "expect the abilities of Receipt");
+  assert(!isResource(Legacy) && !hasAbility(Legacy, "copy"), "expect a class without abilities to have none");
+  console.log("Passed: struct abilities");
+}
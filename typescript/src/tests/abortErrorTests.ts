@@ -0,0 +1,28 @@
+import { abortDetailsFromVmStatus, abortErrorFromVmStatus, MoveAbortError, registerAbortErrors } from "../abortErrors";
+import { u64 } from "../builtinFuncs";
+import { assert } from "../utils";
+
+// as generated for EINSUFFICIENT of the AbortErrors fixture
+class AbortErrorsError_EINSUFFICIENT extends MoveAbortError {
+  constructor() { super(u64(3), "0x12345::AbortErrors", "EINSUFFICIENT"); }
+}
+
+export function test() {
+  registerAbortErrors([AbortErrorsError_EINSUFFICIENT]);
+  // error::invalid_argument(EINSUFFICIENT), reported with the address padded
+  const vmStatus = `Move abort in 0x${"0".repeat(59)}12345::AbortErrors: EINSUFFICIENT(0x10003): insufficient`;
+  const details = abortDetailsFromVmStatus(vmStatus);
+  assert(details !== null && details.moduleName === "0x12345::AbortErrors", `unexpected module: ${details?.moduleName}`);
+  assert(details!.code === "65539", `expect the decimal code, got ${details!.code}`);
+  assert(details!.category === 1 && details!.reason === 3, `expect category 1 and reason 3, got ${details!.category} and ${details!.reason}`);
+
+  const error = abortErrorFromVmStatus(vmStatus);
+  assert(error instanceof AbortErrorsError_EINSUFFICIENT, `expect EINSUFFICIENT from the reason, got ${error}`);
+  const plain = abortErrorFromVmStatus("Move abort in 0x12345::AbortErrors: 0x3");
+  assert(plain instanceof AbortErrorsError_EINSUFFICIENT, `expect EINSUFFICIENT from the code, got ${plain}`);
+
+  assert(abortErrorFromVmStatus("Move abort in 0x7a::market: 0x3") === null, "expect unregistered modules to map to nothing");
+  assert(abortDetailsFromVmStatus("OUT_OF_GAS") === null, "expect other statuses to have no details");
+  assert(abortErrorFromVmStatus("OUT_OF_GAS") === null, "expect other statuses to map to nothing");
+  console.log("Passed: abort error classes");
+}
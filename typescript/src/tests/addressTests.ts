@@ -0,0 +1,34 @@
+import { HexString } from "aptos";
+import { AptosLocalCache } from "../aptosDataCache";
+import { strToU8 } from "../builtinFuncs";
+import { aptos_framework_account_create_resource_address, aptos_framework_object_create_object_address, deriveObjectAddress } from "../nativeFuncs";
+import { assert } from "../utils";
+
+export function test() {
+  const $c = new AptosLocalCache();
+  // expected values are sha3_256(source ++ seed ++ scheme), computed independently of this runtime
+  const cases: [string, string, string, string][] = [
+    ["0x1", "",
+      "0x914ed009b2a4325e1d4c6f167a52e9d49e7e9b8aa82c8af2ab43ddc260c50c7f",
+      "0x9f065b8d53f96a958c9a68fee35862404cdb976388c7c4374fa19474287700db"],
+    ["0x1", "hippo",
+      "0xebb9ffcd7a395407e26bec5285a6c2ff6f0593009d909d2befcc279b3e78d739",
+      "0x9ad7b78b2bf93980cab7b98e1508077a00152f0e4c5128028159f6612939ed50"],
+  ];
+  for (const [source, seed, resourceAddress, objectAddress] of cases) {
+    const resource = aptos_framework_account_create_resource_address(new HexString(source), strToU8(seed), $c);
+    assert(resource.hex() === resourceAddress, `resource address of ${source}/${seed}: ${resource.hex()}`);
+    const object = aptos_framework_object_create_object_address(new HexString(source), strToU8(seed), $c);
+    assert(object.hex() === objectAddress, `object address of ${source}/${seed}: ${object.hex()}`);
+  }
+  // collection and token objects of 0x4, as derived by the --framework-extras helpers
+  const named: [string, string][] = [
+    ["Aptos Monkeys", "0x6e8111e1db0dcf209309b3e48ae71340ca4e4f0544d2fff43df7ae4f952d340a"],
+    ["Aptos Monkeys::Monkey #1", "0x8c9c72f9433738571ec02d69fb5dc8a811289aa69efe904f971652b79b632938"],
+  ];
+  for (const [seed, objectAddress] of named) {
+    const object = deriveObjectAddress(new HexString("0xcafe"), strToU8(seed));
+    assert(object.hex() === objectAddress, `object address of 0xcafe/${seed}: ${object.hex()}`);
+  }
+  console.log("Passed: create_resource_address / create_object_address");
+}
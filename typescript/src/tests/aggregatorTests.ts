@@ -0,0 +1,58 @@
+import { HexString } from "aptos";
+import { u64, u128 } from "../builtinFuncs";
+import { U128, U64 } from "../builtinTypes";
+import { aggregatorMax, aggregatorRead, aggregatorTryAdd, aggregatorTrySub, aggregatorV1Add, aggregatorV1Read, aggregatorZero } from "../nativeFuncs";
+import { AptosParserRepo, FieldDeclType, parseStructProto, TypeParamDeclType } from "../parserRepo";
+import { AtomicTypeTag, StructTag, TypeParamIdx, TypeTag } from "../typeTag";
+import { assert } from "../utils";
+
+class Aggregator {
+  static moduleAddress = new HexString("0x1");
+  static moduleName = "aggregator_v2";
+  static structName = "Aggregator";
+  static typeParameters: TypeParamDeclType[] = [{ name: "IntElement", isPhantom: false }];
+  static fields: FieldDeclType[] = [
+    { name: "value", typeTag: new TypeParamIdx(0) },
+    { name: "max_value", typeTag: new TypeParamIdx(0) },
+  ];
+  value: any;
+  max_value: any;
+  constructor(proto: any, public typeTag: TypeTag) {
+    this.value = proto['value'];
+    this.max_value = proto['max_value'];
+  }
+  static AggregatorParser(data: any, typeTag: TypeTag, repo: AptosParserRepo): Aggregator {
+    const proto = parseStructProto(data, typeTag, repo, Aggregator);
+    return new Aggregator(proto, typeTag);
+  }
+}
+
+export function test() {
+  const aggregator = { value: aggregatorZero(AtomicTypeTag.U64), max_value: u64(10) };
+  assert(aggregatorTryAdd(aggregator, u64(7)), "expect add within the max to succeed");
+  assert(!aggregatorTryAdd(aggregator, u64(4)), "expect add past the max to fail");
+  assert(aggregatorRead(aggregator).eq(u64(7)), "expect the failed add to leave the value");
+  assert(aggregatorTrySub(aggregator, u64(7)) && !aggregatorTrySub(aggregator, u64(1)), "expect sub to stop at 0");
+  assert(aggregatorMax(AtomicTypeTag.U128).eq(new U128(U128.MAX)), "expect unbounded u128 max");
+
+  const v1 = { limit: u128(5) };
+  aggregatorV1Add(v1, u128(5));
+  assert(aggregatorV1Read(v1).eq(u128(5)), "expect v1 value to be kept on the object");
+  let threw = false;
+  try {
+    aggregatorV1Add(v1, u128(1));
+  } catch (e) {
+    threw = true;
+  }
+  assert(threw, "expect v1 add past the limit to abort");
+
+  // the API may return the struct or only its value
+  const repo = new AptosParserRepo();
+  repo.addParser("0x1::aggregator_v2::Aggregator", Aggregator.AggregatorParser);
+  const tag = new StructTag(new HexString("0x1"), "aggregator_v2", "Aggregator", [AtomicTypeTag.U64]);
+  const full = repo.parse({ value: "3", max_value: "9" }, tag) as Aggregator;
+  assert(full.value.eq(u64(3)) && full.max_value.eq(u64(9)), "expect the struct form to be parsed");
+  const simple = repo.parse("3", tag) as Aggregator;
+  assert(simple.value.eq(u64(3)) && (simple.max_value as U64).value.eq(U64.MAX), "expect the value form to be parsed as unbounded");
+  console.log("Passed: aggregators");
+}
@@ -0,0 +1,31 @@
+import { u128, u64, u8 } from "../builtinFuncs";
+import { ArithmeticError, U64, U8 } from "../builtinTypes";
+import { assert } from "../utils";
+
+function throwsArithmetic(f: () => unknown, what: string) {
+  try {
+    f();
+  }
+  catch (e) {
+    assert(e instanceof ArithmeticError, `expect an ArithmeticError for ${what}, got ${e}`);
+    assert((e as Error).message.startsWith("ARITHMETIC_ERROR"), `unexpected message: ${(e as Error).message}`);
+    return;
+  }
+  throw new Error(`expect ${what} to abort`);
+}
+
+export function test() {
+  const max64 = u64(U64.MAX);
+  assert(max64.sub(u64(1), { bits: 64 }).add(u64(1), { bits: 64 }).eq(max64), "expect MAX - 1 + 1 == MAX");
+  throwsArithmetic(() => max64.add(u64(1), { bits: 64 }), "u64 overflow");
+  throwsArithmetic(() => u128(U64.MAX).mul(u128(U64.MAX).add(u128(2), { bits: 128 }), { bits: 128 }), "u128 overflow");
+  throwsArithmetic(() => u64(1).sub(u64(2), { bits: 64 }), "underflow");
+  throwsArithmetic(() => u64(1).div(u64(0), { bits: 64 }), "division by zero");
+  throwsArithmetic(() => u64(1).mod(u64(0), { bits: 64 }), "modulo by zero");
+  throwsArithmetic(() => u8(1).shl(u8(8), { bits: 8 }), "shift by the width");
+
+  // bits shifted out to the left are dropped
+  const shifted = u8(U8.MAX).shl(u8(1), { bits: 8 });
+  assert(shifted.value.toJSNumber() === 254, `expect 254, got ${shifted.value}`);
+  console.log("Passed: checked arithmetic");
+}
@@ -0,0 +1,81 @@
+import { AptosClient, HexString } from "aptos";
+import { UserTransaction } from "aptos/dist/generated";
+import { u64, u128 } from "../builtinFuncs";
+import { U128, U64 } from "../builtinTypes";
+import { AptosParserRepo, FieldDeclType, parseStructProto, TypeParamDeclType } from "../parserRepo";
+import { takeSimulationValue } from "../txSender";
+import { AtomicTypeTag, StructTag, TypeTag } from "../typeTag";
+import { assert } from "../utils";
+
+// 2^53 + 1, the first integer a JSON number cannot hold
+const ABOVE_SAFE = "9007199254740993";
+const MAX_U128 = "340282366920938463463374607431768211455";
+
+class Reserve {
+  static moduleAddress = new HexString("0xa1");
+  static moduleName = "fixture";
+  static structName = "Reserve";
+  static typeParameters: TypeParamDeclType[] = [];
+  static fields: FieldDeclType[] = [
+    { name: "amount", typeTag: AtomicTypeTag.U64 },
+    { name: "supply", typeTag: AtomicTypeTag.U128 },
+  ];
+  amount: U64;
+  supply: U128;
+  constructor(proto: any, public typeTag: TypeTag) {
+    this.amount = proto['amount'] as U64;
+    this.supply = proto['supply'] as U128;
+  }
+  static ReserveParser(data: any, typeTag: TypeTag, repo: AptosParserRepo): Reserve {
+    const proto = parseStructProto(data, typeTag, repo, Reserve);
+    return new Reserve(proto, typeTag);
+  }
+}
+
+const reserveTag = new StructTag(Reserve.moduleAddress, Reserve.moduleName, Reserve.structName, []);
+
+function checkReserve(reserve: Reserve, amount: string, supply: string, path: string) {
+  assert(reserve.amount.value.toString() === amount, `expect ${path} to keep amount ${amount}, got ${reserve.amount.value}`);
+  assert(reserve.supply.value.toString() === supply, `expect ${path} to keep supply ${supply}, got ${reserve.supply.value}`);
+}
+
+export async function test() {
+  const repo = new AptosParserRepo();
+  repo.addParser("0xa1::fixture::Reserve", Reserve.ReserveParser);
+
+  // load
+  const client = {
+    getAccountResource: async (_address: HexString, _tag: any) => ({ data: { amount: ABOVE_SAFE, supply: MAX_U128 } }),
+    getTableItem: async (_handle: string, _request: any) => ({ data: MAX_U128 }),
+  } as unknown as AptosClient;
+  const loaded = await repo.loadResource(client, new HexString("0x1234"), Reserve, []) as Reserve;
+  checkReserve(loaded, ABOVE_SAFE, MAX_U128, "load");
+
+  // small values may come as numbers
+  const small = repo.parse({ amount: 42, supply: 7 }, reserveTag) as Reserve;
+  checkReserve(small, "42", "7", "numbers");
+  let threw = false;
+  try {
+    repo.parse({ amount: 9007199254740993, supply: "0" }, reserveTag);
+  } catch (e) {
+    threw = true;
+  }
+  assert(threw, "expect a number above 2^53 to be rejected");
+
+  // query_, whose result comes from the simulated write set
+  const tx = {
+    success: true,
+    changes: [{
+      type: "write_resource",
+      data: { type: { address: "0xa1", module: "fixture", name: "Reserve" }, data: { amount: ABOVE_SAFE, supply: MAX_U128 } },
+    }],
+  } as unknown as UserTransaction;
+  checkReserve(takeSimulationValue<Reserve>(tx, reserveTag, repo), ABOVE_SAFE, MAX_U128, "query");
+
+  // table entry, the way TypedTable.loadEntry parses it
+  const rawVal = await client.getTableItem("0x1", { key_type: "u64", value_type: "u128", key: ABOVE_SAFE });
+  const entry = repo.parse(rawVal.data, AtomicTypeTag.U128) as U128;
+  assert(entry.value.eq(u128(MAX_U128).value), "expect table entry to keep the maximum u128");
+  assert(u64(ABOVE_SAFE).value.toString() === ABOVE_SAFE, "expect u64 to take the string form");
+  console.log("Passed: u64/u128 values above 2^53");
+}
@@ -0,0 +1,31 @@
+import { HexString } from "aptos";
+import { AptosLocalCache } from "../aptosDataCache";
+import { u64 } from "../builtinFuncs";
+import { U64 } from "../builtinTypes";
+import { SystemClock } from "../clock";
+import { StructTag } from "../typeTag";
+import { assert } from "../utils";
+
+class BlockResource {
+  constructor(public height: U64) {}
+}
+
+export function test() {
+  const $c = new AptosLocalCache();
+  assert($c.getClock().blockHeight().value.eq(0), "expect height 0 without a BlockResource");
+
+  const strict = new SystemClock($c, true);
+  let threw = false;
+  try {
+    strict.blockHeight();
+  } catch (e) {
+    threw = true;
+  }
+  assert(threw, "expect strict natives to throw without a BlockResource");
+
+  const tag = new StructTag(new HexString("0x1"), "block", "BlockResource", []);
+  $c.move_to(tag, new HexString("0x1"), new BlockResource(u64(42)));
+  assert($c.getClock().blockHeight().value.eq(42), "expect the height of the BlockResource");
+  assert(strict.blockHeight().value.eq(42), "expect strict natives to read the BlockResource too");
+  console.log("Passed: clock");
+}
@@ -0,0 +1,35 @@
+import { HexString } from "aptos";
+import { AptosLocalCache, cloneCache } from "../aptosDataCache";
+import { u64 } from "../builtinFuncs";
+import { U64 } from "../builtinTypes";
+import { FrozenClock } from "../clock";
+import { SeededRandomness } from "../randomness";
+import { StructTag } from "../typeTag";
+import { assert } from "../utils";
+
+class Counter {
+  constructor(public value: U64, public history: U64[]) {}
+}
+
+export function test() {
+  const tag = new StructTag(new HexString("0xa1"), "fixture", "Counter", []);
+  const admin = new HexString("0x1234");
+  const setup = new AptosLocalCache();
+  setup.setClock(new FrozenClock(5));
+  setup.setRandomness(new SeededRandomness(1));
+  setup.move_to(tag, admin, new Counter(u64(0), [u64(0)]));
+
+  const first = cloneCache(setup);
+  const counter = first.borrow_global_mut<Counter>(tag, admin);
+  assert(counter instanceof Counter && counter.value instanceof U64, "expect the copy to keep the classes");
+  counter.value = u64(1);
+  counter.history.push(u64(1));
+  (first.getClock() as FrozenClock).setMicroseconds(u64(9));
+
+  const second = cloneCache(setup);
+  const untouched = second.borrow_global<Counter>(tag, admin);
+  assert(untouched.value.value.eq(0) && untouched.history.length === 1, "expect a mutated copy to leave the setup alone");
+  assert(second.getClock().nowMicroseconds().value.eq(5), "expect the clock to be copied too");
+  assert(second.getRandomness().nextBits(64).eq(setup.getRandomness().nextBits(64)), "expect the same random sequence");
+  console.log("Passed: cloneCache");
+}
@@ -0,0 +1,18 @@
+import { coverageHits, hitCoverage, mergeCoverage } from "../coverage";
+import { assert } from "../utils";
+
+export function test() {
+  hitCoverage("0x1::pool::swap");
+  hitCoverage("0x1::pool::swap");
+  assert(coverageHits().get("0x1::pool::swap") === 2, "expect hits to be counted");
+
+  const functions = ["0x1::pool::swap", "0x1::pool::add_liquidity", "0x1::pool::remove_liquidity"];
+  const report = mergeCoverage(functions, [
+    { "0x1::pool::swap": 2 },
+    { "0x1::pool::swap": 1, "0x1::pool::add_liquidity": 4 },
+  ]);
+  assert(report.functions["0x1::pool::swap"] === 3, "expect hits of all test files to be summed");
+  assert(report.functions["0x1::pool::add_liquidity"] === 4, "expect hits of a single file to be kept");
+  assert(report.neverHit.join(",") === "0x1::pool::remove_liquidity", `expect only remove_liquidity never hit, got ${report.neverHit}`);
+  console.log("Passed: coverage merge");
+}
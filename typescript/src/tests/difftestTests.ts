@@ -0,0 +1,120 @@
+import { AptosAccount, AptosClient, HexString } from "aptos";
+import { u64 } from "../builtinFuncs";
+import { U64 } from "../builtinTypes";
+import { diffValues, runDiffTest } from "../difftest";
+import { AptosParserRepo, FieldDeclType, parseStructProto, TypeParamDeclType } from "../parserRepo";
+import { AtomicTypeTag, StructTag, TypeTag, VectorTag } from "../typeTag";
+import { assert } from "../utils";
+
+class Vault {
+  static moduleAddress = new HexString("0xa1");
+  static moduleName = "fixture";
+  static structName = "Vault";
+  static typeParameters: TypeParamDeclType[] = [];
+  static fields: FieldDeclType[] = [
+    { name: "balance", typeTag: AtomicTypeTag.U64 },
+    { name: "owner", typeTag: AtomicTypeTag.Address },
+    { name: "history", typeTag: new VectorTag(AtomicTypeTag.U64) },
+  ];
+  balance: U64;
+  owner: HexString;
+  history: U64[];
+  constructor(proto: any, public typeTag: TypeTag) {
+    this.balance = proto['balance'] as U64;
+    this.owner = proto['owner'] as HexString;
+    this.history = proto['history'] as U64[];
+  }
+  static VaultParser(data: any, typeTag: TypeTag, repo: AptosParserRepo): Vault {
+    const proto = parseStructProto(data, typeTag, repo, Vault);
+    return new Vault(proto, typeTag);
+  }
+}
+
+const vaultTag = new StructTag(Vault.moduleAddress, Vault.moduleName, Vault.structName, []);
+const moveVaultTag = { address: "0xa1", module: "fixture", name: "Vault", generic_type_params: [] };
+
+function vault(balance: number, owner: string, history: number[]) {
+  return new Vault({ balance: u64(balance), owner: new HexString(owner), history: history.map(h => u64(h)) }, vaultTag);
+}
+
+function mockClient(resources: any[], simulated: any): AptosClient {
+  return {
+    getAccountResources: async (_address: HexString) => resources,
+    generateTransaction: async (_sender: HexString, payload: any) => payload,
+    simulateTransaction: async (_account: AptosAccount, _request: any) => [simulated],
+  } as unknown as AptosClient;
+}
+
+export async function test() {
+  // field-level diff
+  assert(diffValues(vault(5, "0x01", [1, 2]), vault(5, "0x1", [1, 2]), "Vault").length === 0, "expect equal vaults to match");
+  const diffs = diffValues(vault(5, "0x1", [1, 2]), vault(4, "0x1", [1, 3]), "Vault");
+  assert(diffs.length === 2, `expect 2 mismatches, got ${diffs}`);
+  assert(diffs[0] === "Vault.balance: node has 5, translated code has 4", `unexpected mismatch: ${diffs[0]}`);
+  assert(diffs[1].startsWith("Vault.history[1]:"), `unexpected mismatch: ${diffs[1]}`);
+  const lengthDiff = diffValues(vault(5, "0x1", [1]), vault(5, "0x1", []), "Vault");
+  assert(lengthDiff[0] === "Vault.history: node has 1 elements, translated code has 0", `unexpected mismatch: ${lengthDiff}`);
+
+  const repo = new AptosParserRepo();
+  repo.addParser("0xa1::fixture::Vault", Vault.VaultParser);
+  const account = new AptosAccount();
+  const user = new HexString("0xb0b");
+  const before = [{ type: moveVaultTag, data: { balance: "10", owner: "0xb0b", history: [] } }];
+  const withdraw = (amount: number) => ($c: any) => {
+    const v = $c.borrow_global(vaultTag, user) as Vault;
+    if (v.balance.value.toJSNumber() < amount) {
+      throw new Error("65537");
+    }
+    v.balance = u64(v.balance.value.toJSNumber() - amount);
+    v.history.push(u64(amount));
+  };
+
+  // both runs agree
+  const agreed = await runDiffTest(mockClient(before, {
+    success: true,
+    vm_status: "Executed successfully",
+    changes: [{ type: "write_resource", address: "0xb0b", data: { type: moveVaultTag, data: { balance: "7", owner: "0xb0b", history: ["3"] } } }],
+  }), repo, account, { name: "withdraw", payload: {} as any, runLocal: withdraw(3), addresses: [user] });
+  assert(agreed.mismatches.length === 0, `expect no mismatch, got ${agreed.mismatches}`);
+
+  // the node leaves a different balance
+  const diverged = await runDiffTest(mockClient(before, {
+    success: true,
+    vm_status: "Executed successfully",
+    changes: [{ type: "write_resource", address: "0xb0b", data: { type: moveVaultTag, data: { balance: "6", owner: "0xb0b", history: ["3"] } } }],
+  }), repo, account, { name: "withdraw", payload: {} as any, runLocal: withdraw(3), addresses: [user] });
+  assert(diverged.mismatches.length === 1, `expect 1 mismatch, got ${diverged.mismatches}`);
+  assert(diverged.mismatches[0].startsWith("0xa1::fixture::Vault@0xb0b.balance:"), `unexpected mismatch: ${diverged.mismatches[0]}`);
+
+  // same abort code, written differently
+  const aborted = await runDiffTest(mockClient(before, {
+    success: false,
+    vm_status: "Move abort in 0xa1::fixture: EINSUFFICIENT(0x10001): ",
+    changes: [],
+  }), repo, account, { name: "withdraw", payload: {} as any, runLocal: withdraw(30), addresses: [user] });
+  assert(aborted.mismatches.length === 0, `expect matching abort codes, got ${aborted.mismatches}`);
+
+  const onlyNodeAborted = await runDiffTest(mockClient(before, {
+    success: false,
+    vm_status: "Move abort in 0xa1::fixture: EINSUFFICIENT(0x10001): ",
+    changes: [],
+  }), repo, account, { name: "withdraw", payload: {} as any, runLocal: withdraw(3), addresses: [user] });
+  assert(onlyNodeAborted.mismatches.length === 1 && onlyNodeAborted.mismatches[0].startsWith("abort:"), `expect an abort mismatch, got ${onlyNodeAborted.mismatches}`);
+
+  // failures that are not Move aborts carry no code, so they cannot be confirmed to agree
+  const failing = () => { throw new TypeError("Cannot read properties of undefined (reading 'value')"); };
+  const bothFailed = await runDiffTest(mockClient(before, {
+    success: false,
+    vm_status: "OUT_OF_GAS",
+    changes: [],
+  }), repo, account, { name: "withdraw", payload: {} as any, runLocal: failing, addresses: [user] });
+  assert(bothFailed.mismatches.length === 1 && bothFailed.mismatches[0].startsWith("abort code:"), `expect an abort code mismatch, got ${bothFailed.mismatches}`);
+
+  const nodeFailed = await runDiffTest(mockClient(before, {
+    success: false,
+    vm_status: "Execution failed in 0xa1::fixture::withdraw at code offset 12: ARITHMETIC_ERROR",
+    changes: [],
+  }), repo, account, { name: "withdraw", payload: {} as any, runLocal: withdraw(30), addresses: [user] });
+  assert(nodeFailed.mismatches.length === 1 && nodeFailed.mismatches[0].startsWith("abort code:"), `expect an abort code mismatch, got ${nodeFailed.mismatches}`);
+  console.log("Passed: difftest");
+}
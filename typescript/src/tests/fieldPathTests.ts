@@ -0,0 +1,53 @@
+import { HexString } from "aptos";
+import { u64 } from "../builtinFuncs";
+import { U64 } from "../builtinTypes";
+import { FieldDeclType, getFieldValueByPath, getStructField } from "../parserRepo";
+import { AtomicTypeTag, StructTag } from "../typeTag";
+import { assert } from "../utils";
+
+function throws(f: () => unknown, message: string) {
+  try {
+    f();
+  }
+  catch (e) {
+    assert((e as Error).message === message, `unexpected error: ${(e as Error).message}`);
+    return;
+  }
+  throw new Error(`expect error: ${message}`);
+}
+
+function structClass(moduleName: string, structName: string, fields: FieldDeclType[]) {
+  return class {
+    static moduleAddress = new HexString("0xa1");
+    static moduleName = moduleName;
+    static structName = structName;
+    static typeParameters = [];
+    static fields = fields;
+    constructor(proto: any) {
+      Object.assign(this, proto);
+    }
+  };
+}
+
+const Coin = structClass("fixture", "Coin", [{ name: "value", typeTag: AtomicTypeTag.U64 }]);
+const coinTag = new StructTag(new HexString("0xa1"), "fixture", "Coin", []);
+// type is held by the type__ member, and the stub of an excluded module keeps its data in raw
+const Pool = structClass("fixture", "Pool", [
+  { name: "coin", typeTag: coinTag },
+  { name: "type", typeTag: AtomicTypeTag.U8, memberName: "type__" },
+  { name: "config", typeTag: AtomicTypeTag.U64, opaque: true },
+]);
+
+export function test() {
+  const pool = new Pool({ coin: new Coin({ value: u64(7) }), type__: 2, raw: { config: "9" } });
+  assert((getFieldValueByPath(pool, "coin.value") as U64).value.eq(7), "expect the value of the nested coin");
+  assert(getFieldValueByPath(pool, "type") === 2, "expect a renamed member to be found by field name");
+  assert(getFieldValueByPath(pool, "config") === "9", "expect opaque fields to be read from raw");
+  assert(getStructField(Pool, "type").memberName === "type__", "expect the field metadata");
+
+  throws(() => getFieldValueByPath(pool, "coin.amount"), "Coin has no field amount, its fields are: value");
+  throws(() => getFieldValueByPath(pool, "coin.value.inner"), "Pool.coin.value is not a struct, cannot get its field inner");
+  throws(() => getFieldValueByPath([1, 2], "value"), "value is a vector, cannot get its field value");
+  throws(() => getFieldValueByPath({ value: 1 }, "value"), "value is not a struct, cannot get its field value");
+  console.log("Passed: field paths");
+}
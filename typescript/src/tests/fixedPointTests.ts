@@ -0,0 +1,31 @@
+import { fixedPointFromNumber, fixedPointToDecimalString, fixedPointToNumber, u64, u128 } from "../builtinFuncs";
+import { assert } from "../utils";
+
+// raw values and expected digits come from Move: fixed_point32::create_from_rational(1, 3) and
+// multiply_u64(100000000, x), see move-testsuite/sources/FixedPoints.move. fixed_point64 does the
+// same with 64 fractional bits
+export function test() {
+  const third32 = u64("1431655765");
+  assert(fixedPointToDecimalString(third32, 32, 8) === "0.33333333", "expect 1/3 with 8 digits");
+  assert(fixedPointToDecimalString(third32, 32, 0) === "0", "expect precision 0 to drop the fraction");
+  assert(Math.abs(fixedPointToNumber(third32, 32) - 1 / 3) < 1e-9, "expect toNumber to be close to 1/3");
+
+  const half5 = u64("10737418240");
+  assert(fixedPointToDecimalString(half5, 32, 8) === "2.50000000", "expect 5/2 with 8 digits");
+  assert(fixedPointToNumber(half5, 32) === 2.5, "expect toNumber of 5/2");
+  assert(fixedPointFromNumber(2.5, 32).toString() === "10737418240", "expect fromNumber to invert toNumber");
+
+  const third64 = u128("6148914691236517205");
+  assert(fixedPointToDecimalString(third64, 64, 18) === "0.333333333333333333", "expect 1/3 with 18 digits");
+  assert(fixedPointFromNumber(2.5, 64).toString() === "46116860184273879040", "expect fromNumber with 64 bits");
+  assert(fixedPointToNumber(u128("46116860184273879040"), 64) === 2.5, "expect toNumber with 64 bits");
+
+  let threw = false;
+  try {
+    fixedPointFromNumber(-1, 32);
+  } catch (e) {
+    threw = true;
+  }
+  assert(threw, "expect negative numbers to be rejected");
+  console.log("Passed: fixed point conversions");
+}
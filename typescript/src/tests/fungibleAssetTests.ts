@@ -0,0 +1,24 @@
+import { HexString } from "aptos";
+import { DummyCache } from "../aptosDataCache";
+import { aptos_framework_object_create_user_derived_object_address, deriveUserObjectAddress } from "../nativeFuncs";
+import { assert } from "../utils";
+
+// sha3_256(owner ++ metadata ++ 0xfc), computed independently of the runtime
+const CASES: [string, string, string][] = [
+  ["0x1", "0xa", "0xc6d3d69a9810647845a5ca5ebe905256dc37327c1c39c1d673de00caaac0e3a8"],
+  [
+    "0xcafe",
+    "0xbae207659db88bea0cbead6da0ed00aac12edcdda169e591cd41c94180b46f3b",
+    "0xea89e2d8ae5a3552ef9bee40e91a864b1410be05cc455fb7f635318a48120901",
+  ],
+];
+
+export function test() {
+  for (const [owner, metadata, expected] of CASES) {
+    const derived = deriveUserObjectAddress(new HexString(owner), new HexString(metadata));
+    assert(derived.hex() === expected, `expect primary store of ${owner} for ${metadata} at ${expected}, got ${derived.hex()}`);
+    const native = aptos_framework_object_create_user_derived_object_address(new HexString(owner), new HexString(metadata), new DummyCache());
+    assert(native.hex() === expected, "expect object::create_user_derived_object_address to agree");
+  }
+  console.log("Passed: primary fungible store addresses");
+}
@@ -1,4 +1,26 @@
 import { test } from "./typeTagTests";
+import { test as snapshotTest } from "./snapshotTests";
+import { test as addressTest } from "./addressTests";
+import { test as normalizeTest } from "./normalizeTests";
+import { test as bigIntJsonTest } from "./bigIntJsonTests";
+import { test as payloadArgTest } from "./payloadArgTests";
+import { test as fixedPointTest } from "./fixedPointTests";
+import { test as aggregatorTest } from "./aggregatorTests";
+import { test as randomnessTest } from "./randomnessTests";
+import { test as fungibleAssetTest } from "./fungibleAssetTests";
+import { test as cloneCacheTest } from "./cloneCacheTests";
+import { test as coverageTest } from "./coverageTests";
+import { test as difftestTest } from "./difftestTests";
+import { test as abilityTest } from "./abilityTests";
+import { test as fieldPathTest } from "./fieldPathTests";
+import { test as arithmeticTest } from "./arithmeticTests";
+import { test as traceTest } from "./traceTests";
+import { test as ordTest } from "./ordTests";
+import { test as tableItemTest } from "./tableItemTests";
+import { test as abortErrorTest } from "./abortErrorTests";
+import { test as invariantTest } from "./invariantTests";
+import { test as mathTest } from "./mathTests";
+import { test as clockTest } from "./clockTests";
 
 
 
@@ -8,6 +30,28 @@ const main = async () => {
   switch (fname) {
     case "test": 
       await test();
+      await snapshotTest();
+      addressTest();
+      normalizeTest();
+      await bigIntJsonTest();
+      payloadArgTest();
+      fixedPointTest();
+      aggregatorTest();
+      randomnessTest();
+      fungibleAssetTest();
+      cloneCacheTest();
+      coverageTest();
+      await difftestTest();
+      abilityTest();
+      fieldPathTest();
+      arithmeticTest();
+      traceTest();
+      ordTest();
+      tableItemTest();
+      abortErrorTest();
+      invariantTest();
+      mathTest();
+      clockTest();
       break;
     default:
       console.log(`Unrecognized command: ${fname}`);
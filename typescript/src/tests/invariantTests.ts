@@ -0,0 +1,25 @@
+import { invariantChecks, setInvariantChecks } from "../invariants";
+import { assert } from "../utils";
+
+export function test() {
+  const paranoid = "0x12345::vault::PARANOID";
+  assert(invariantChecks(paranoid, true), "expect checks to follow the constant by default");
+  assert(!invariantChecks(paranoid, false), "expect a false constant to skip the checks");
+
+  setInvariantChecks("0x0000000000000000000000000000000000000000000000000000000000012345::vault::PARANOID", false);
+  assert(!invariantChecks(paranoid, true), "expect the switch to apply whatever the form of the address");
+  assert(invariantChecks("0x12345::pool::PARANOID", true), "expect other constants to keep their value");
+
+  setInvariantChecks(paranoid, true);
+  assert(invariantChecks(paranoid, false), "expect checks guarded by a false constant to be switched on");
+
+  let message = "";
+  try {
+    setInvariantChecks("vault::PARANOID", true);
+  }
+  catch (e) {
+    message = (e as Error).message;
+  }
+  assert(message.includes("<address>::<module>::<NAME>"), `expect a malformed name to throw, got ${message}`);
+  console.log("Passed: invariant checks");
+}
@@ -0,0 +1,58 @@
+import { u128, u64 } from "../builtinFuncs";
+import { ArithmeticError, U128, U64 } from "../builtinTypes";
+import {
+  aptos_std_math128_pow, aptos_std_math128_sqrt, aptos_std_math64_average, aptos_std_math64_max, aptos_std_math64_min,
+  aptos_std_math64_pow, aptos_std_math64_sqrt,
+} from "../mathFuncs";
+import { assert } from "../utils";
+
+const W64 = { bits: 64 };
+
+// math64::pow as translated, with the checked arithmetic of the generated code
+function translatedPow(n: U64, e: U64): U64 {
+  if (e.eq(u64(0))) {
+    return u64(1);
+  }
+  let p = u64(1);
+  while (e.gt(u64(1))) {
+    if (e.mod(u64(2), W64).eq(u64(1))) {
+      p = p.mul(n, W64);
+    }
+    e = e.div(u64(2), W64);
+    n = n.mul(n, W64);
+  }
+  return p.mul(n, W64);
+}
+
+function outcome(f: () => U64 | U128): string {
+  try {
+    return f().value.toString();
+  }
+  catch (e) {
+    if (e instanceof ArithmeticError) {
+      return "ArithmeticError";
+    }
+    throw e;
+  }
+}
+
+export function test() {
+  const samples = ["0", "1", "2", "3", "7", "15", "16", "17", "255", "4294967295", "4294967296", "9223372036854775808", U64.MAX.toString()];
+  for (const a of samples) {
+    for (const b of samples) {
+      const [x, y] = [u64(a), u64(b)];
+      const pow = outcome(() => aptos_std_math64_pow(x, y));
+      assert(pow === outcome(() => translatedPow(x, y)), `pow(${a}, ${b}) differs from the translated loop: ${pow}`);
+      assert(aptos_std_math64_max(x, y).value.eq(x.value.gt(y.value) ? x.value : y.value), `max(${a}, ${b})`);
+      assert(aptos_std_math64_min(x, y).value.eq(x.value.lt(y.value) ? x.value : y.value), `min(${a}, ${b})`);
+      assert(aptos_std_math64_average(x, y).value.eq(x.value.add(y.value).divide(2)), `average(${a}, ${b})`);
+    }
+    // the largest root whose square does not exceed the value
+    const root = aptos_std_math64_sqrt(u64(a)).value;
+    assert(root.square().leq(a) && root.add(1).square().gt(a), `sqrt(${a}) is ${root}`);
+  }
+  assert(aptos_std_math128_pow(u128(2), u128(127)).value.eq(U128.MAX.add(1).divide(2)), "expect 2 ** 127 to fit in u128");
+  assert(outcome(() => aptos_std_math128_pow(u128(2), u128(128))) === "ArithmeticError", "expect 2 ** 128 to overflow");
+  assert(aptos_std_math128_sqrt(u128(U128.MAX)).value.eq(U64.MAX), "expect the root of u128::MAX to be u64::MAX");
+  console.log("Passed: math fast paths");
+}
@@ -0,0 +1,45 @@
+import { HexString } from "aptos";
+import { u64 } from "../builtinFuncs";
+import { U64 } from "../builtinTypes";
+import { AptosParserRepo, FieldDeclType, parseStructProto, TypeParamDeclType } from "../parserRepo";
+import { AtomicTypeTag, normalizeTypeString, parseTypeTagOrThrow, StructTag, TypeTag } from "../typeTag";
+import { assert } from "../utils";
+
+class Counter {
+  static moduleAddress = new HexString("0xa1");
+  static moduleName = "fixture";
+  static structName = "Counter";
+  static typeParameters: TypeParamDeclType[] = [];
+  static fields: FieldDeclType[] = [{ name: "value", typeTag: AtomicTypeTag.U64 }];
+  value: U64;
+  constructor(proto: any, public typeTag: TypeTag) {
+    this.value = proto['value'] as U64;
+  }
+  static CounterParser(data: any, typeTag: TypeTag, repo: AptosParserRepo): Counter {
+    const proto = parseStructProto(data, typeTag, repo, Counter);
+    return new Counter(proto, typeTag);
+  }
+}
+
+export function test() {
+  const padded = "0x00000000000000000000000000000000000000000000000000000000000000a1";
+  assert(normalizeTypeString(`${padded}::fixture::Counter`) === "0xa1::fixture::Counter", "expect padded address to be shortened");
+  assert(
+    normalizeTypeString("0x0001::coin::CoinStore<0x00A1::fixture::Counter>") === "0x1::coin::CoinStore<0xa1::fixture::Counter>",
+    "expect nested addresses to be normalized"
+  );
+  assert(
+    normalizeTypeString("0x01::pool0x01::Pool") === "0x1::pool0x01::Pool",
+    "expect hex inside identifiers to be left alone"
+  );
+
+  // registered with the short form, as loadParsers does
+  const repo = new AptosParserRepo();
+  repo.addParser("0xa1::fixture::Counter", Counter.CounterParser);
+  repo.addDefaultParsers();
+  const tag = parseTypeTagOrThrow(`${padded}::fixture::Counter`) as StructTag;
+  assert(repo.getParserFromTypeTag(tag) !== null, "expect padded type to find the short-form parser");
+  const counter = repo.parse({ value: "5" }, tag) as Counter;
+  assert(counter.value.eq(u64(5)), "expect Counter to be parsed from padded type");
+  console.log("Passed: padded address lookup");
+}
@@ -0,0 +1,17 @@
+import { HexString } from "aptos";
+import { u128 } from "../builtinFuncs";
+import { compareValues } from "../ord";
+import { assert } from "../utils";
+
+export function test() {
+  // both above 2^53, where JS numbers stop telling them apart
+  const big = u128("9007199254740993");
+  const bigger = u128("9007199254740994");
+  assert(compareValues(big, bigger) < 0, "expect big < bigger");
+  assert(compareValues(bigger, big) > 0, "expect bigger > big");
+  assert(compareValues(big, u128("9007199254740993")) === 0, "expect equal integers to tie");
+
+  assert(compareValues(new HexString("0x2"), new HexString("0x10")) < 0, "expect 0x2 < 0x10");
+  assert(compareValues(new HexString("0x0"), new HexString("0x00")) === 0, "expect 0x0 == 0x00");
+  console.log("Passed: ord comparators");
+}
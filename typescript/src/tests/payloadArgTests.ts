@@ -0,0 +1,37 @@
+import { HexString } from "aptos";
+import { coercePayloadArg, moveValueToOpenApiObject, u64, u8 } from "../builtinFuncs";
+import { AtomicTypeTag, VectorTag } from "../typeTag";
+import { assert } from "../utils";
+
+function throwsWith(f: () => any, needle: string): boolean {
+  try {
+    f();
+  } catch (e) {
+    return (e as Error).message.includes(needle);
+  }
+  return false;
+}
+
+export function test() {
+  // BigInt is not in the es2016 lib this package is built against
+  const nativeBigInt = (global as any).BigInt;
+  assert(coercePayloadArg(u64(5), "u64", "amount") === "5", "expect U64 to be accepted");
+  assert(coercePayloadArg(5, "u64", "amount") === "5", "expect number to be accepted");
+  assert(coercePayloadArg(nativeBigInt("18446744073709551615"), "u64", "amount") === "18446744073709551615", "expect bigint to be accepted");
+  assert(coercePayloadArg("340282366920938463463374607431768211455", "u128", "supply") === "340282366920938463463374607431768211455", "expect string to be accepted");
+  assert(coercePayloadArg(7, "u8", "decimals") === 7 && coercePayloadArg(u8(7), "u8", "decimals") === 7, "expect u8 as a number");
+  assert(coercePayloadArg("0x0001", "address", "to") === "0x1", "expect address string to be shortened");
+  assert(coercePayloadArg(new HexString("0x1"), "address", "to") === "0x1", "expect HexString to be accepted");
+  assert(coercePayloadArg(true, "bool", "flag") === true, "expect bool to be accepted");
+
+  assert(throwsWith(() => coercePayloadArg(1.5, "u64", "amount"), "Parameter amount expects u64"), "expect fraction to be rejected");
+  assert(throwsWith(() => coercePayloadArg(2 ** 60, "u64", "amount"), "Parameter amount expects u64"), "expect unsafe number to be rejected");
+  assert(throwsWith(() => coercePayloadArg("18446744073709551616", "u64", "amount"), "out of range"), "expect overflow to be rejected");
+  assert(throwsWith(() => coercePayloadArg(1, "address", "to"), "Parameter to expects address"), "expect number address to be rejected");
+  assert(throwsWith(() => coercePayloadArg("yes", "bool", "flag"), "Parameter flag expects bool"), "expect string bool to be rejected");
+  const flags = moveValueToOpenApiObject([true, false], new VectorTag(AtomicTypeTag.Bool));
+  assert(JSON.stringify(flags) === "[true,false]", "expect bools to be kept as open api values");
+  const admins = moveValueToOpenApiObject([new HexString("0x1"), new HexString("0xb0b")], new VectorTag(AtomicTypeTag.Address));
+  assert(JSON.stringify(admins) === '["0x1","0xb0b"]', "expect addresses as hex strings");
+  console.log("Passed: coercePayloadArg");
+}
@@ -0,0 +1,62 @@
+import { u8, u64 } from "../builtinFuncs";
+import { AptosLocalCache } from "../aptosDataCache";
+import {
+  aptos_framework_randomness_bytes,
+  aptos_framework_randomness_permutation,
+  aptos_framework_randomness_set_seed,
+  aptos_framework_randomness_u128_integer,
+  aptos_framework_randomness_u64_integer,
+  aptos_framework_randomness_u64_range,
+} from "../nativeFuncs";
+import { CryptoRandomness, SeededRandomness } from "../randomness";
+import { assert } from "../utils";
+
+function draw($c: AptosLocalCache): string {
+  return [
+    aptos_framework_randomness_u64_integer($c).value.toString(),
+    aptos_framework_randomness_u128_integer($c).value.toString(),
+    aptos_framework_randomness_bytes(u64(5), $c).map(b => b.value.toString()).join(","),
+  ].join("/");
+}
+
+export function test() {
+  const $c = new AptosLocalCache();
+  assert($c.getRandomness() instanceof CryptoRandomness, "expect crypto randomness by default");
+  let threw = false;
+  try {
+    $c.getRandomness().setSeed([u8(1)]);
+  } catch (e) {
+    threw = true;
+  }
+  assert(threw, "expect CryptoRandomness to refuse a seed");
+
+  $c.setRandomness(new SeededRandomness(42));
+  const first = draw($c);
+  $c.setRandomness(new SeededRandomness(42));
+  assert(draw($c) === first, "expect the same seed to give the same values");
+  $c.setRandomness(new SeededRandomness(43));
+  assert(draw($c) !== first, "expect another seed to give other values");
+
+  // randomness::set_seed in a Move test reseeds the cache's randomness
+  aptos_framework_randomness_set_seed([u8(7), u8(7)], $c);
+  const reseeded = draw($c);
+  aptos_framework_randomness_set_seed([u8(7), u8(7)], $c);
+  assert(draw($c) === reseeded, "expect set_seed to restart the sequence");
+
+  for (let i = 0; i < 50; i++) {
+    const v = aptos_framework_randomness_u64_range(u64(10), u64(13), $c);
+    assert(v.value.geq(10) && v.value.lt(13), `expect u64_range to stay in [10, 13), got ${v.value}`);
+  }
+  threw = false;
+  try {
+    aptos_framework_randomness_u64_range(u64(5), u64(5), $c);
+  } catch (e) {
+    threw = true;
+  }
+  assert(threw, "expect an empty range to throw");
+
+  const permutation = aptos_framework_randomness_permutation(u64(10), $c).map(v => v.value.toJSNumber());
+  const sorted = [...permutation].sort((a, b) => a - b);
+  assert(sorted.join(",") === "0,1,2,3,4,5,6,7,8,9", `expect a permutation of 0..9, got ${permutation}`);
+  console.log("Passed: randomness natives");
+}
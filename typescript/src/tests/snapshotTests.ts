@@ -0,0 +1,29 @@
+import { AptosClient, HexString } from "aptos";
+import { AptosLocalCache, snapshotAccountsToCache } from "../aptosDataCache";
+import { u64 } from "../builtinFuncs";
+import { AptosParserRepo } from "../parserRepo";
+import { StructTag, TypeTag } from "../typeTag";
+import { assert } from "../utils";
+
+export async function test() {
+  const owner = new HexString("0x1234");
+  const knownTag = new StructTag(new HexString("0x1"), "fixture", "Counter", []);
+  // stands in for a fullnode serving a fixed snapshot
+  const client = {
+    getAccountResources: async (_address: HexString) => [
+      { type: { address: "0x1", module: "fixture", name: "Counter", generic_type_params: [] }, data: { value: "7" } },
+      { type: { address: "0x1", module: "fixture", name: "Unknown", generic_type_params: [] }, data: {} },
+    ],
+  } as unknown as AptosClient;
+
+  const repo = new AptosParserRepo();
+  repo.addParser("0x1::fixture::Counter", (data: any, typeTag: TypeTag) => ({ value: u64(data.value), typeTag }));
+
+  const $c = new AptosLocalCache();
+  const warnings = await snapshotAccountsToCache(client, repo, [owner], $c);
+  assert(warnings.length === 1, "expect the unknown resource to be reported");
+  assert($c.exists(knownTag, owner), "expect Counter to be loaded into cache");
+  const counter = $c.borrow_global<any>(knownTag, owner);
+  assert(counter.value.eq(u64(7)), "expect Counter to be parsed");
+  console.log("Passed: snapshotAccountsToCache");
+}
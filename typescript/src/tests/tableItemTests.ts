@@ -0,0 +1,23 @@
+import { HexString } from "aptos";
+import { u128, u64 } from "../builtinFuncs";
+import { FieldDeclType } from "../parserRepo";
+import { tableItemRequest } from "../tableItems";
+import { AtomicTypeTag, StructTag, TypeTag } from "../typeTag";
+import { assert } from "../utils";
+
+const tableOf = (keyTag: TypeTag, valueTag: TypeTag) =>
+  new StructTag(new HexString("0x1"), "table", "Table", [keyTag, valueTag]);
+
+export function test() {
+  const byId: FieldDeclType = { name: "by_id", typeTag: tableOf(AtomicTypeTag.U64, AtomicTypeTag.U128) };
+  const request = tableItemRequest(u128(12), byId, [], u64(42));
+  assert(request.handle === "12", `expect the u128 handle as a decimal string, got ${request.handle}`);
+  assert(request.keyType === "u64" && request.valueType === "u128", `unexpected types ${request.keyType}, ${request.valueType}`);
+  assert(request.key === "42", `expect u64 keys as strings, got ${request.key}`);
+
+  const byOwner: FieldDeclType = { name: "by_owner", typeTag: tableOf(AtomicTypeTag.Address, AtomicTypeTag.U64) };
+  const ownerRequest = tableItemRequest(new HexString("0xab"), byOwner, [], new HexString("0xb0b"));
+  assert(ownerRequest.handle === "0xab", `expect the address handle as hex, got ${ownerRequest.handle}`);
+  assert(ownerRequest.key === "0xb0b", `expect address keys as hex, got ${ownerRequest.key}`);
+  console.log("Passed: table item requests");
+}
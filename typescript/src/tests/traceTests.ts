@@ -0,0 +1,24 @@
+import { ArithmeticError } from "../builtinTypes";
+import { moveTraceOf, traceCall } from "../trace";
+import { assert } from "../utils";
+
+export function test() {
+  // the same object is rethrown, with its class
+  const arithmetic = new ArithmeticError("overflow");
+  assert(traceCall(arithmetic, "0x1::m::f", {}) === arithmetic, "expect the same error back");
+  assert(arithmetic instanceof ArithmeticError && arithmetic.message === "ARITHMETIC_ERROR: overflow", `unexpected message: ${arithmetic.message}`);
+  assert(moveTraceOf(arithmetic).join() === "  at 0x1::m::f()", `unexpected frames: ${moveTraceOf(arithmetic)}`);
+  // codes that are not Errors are left alone
+  assert(traceCall(42, "0x1::m::f", {}) === 42, "expect a non-Error to be rethrown as is");
+
+  // long values and deep recursion stay bounded
+  const long = new Error("1");
+  traceCall(long, "0x1::m::f", { v: "9".repeat(200) });
+  assert(moveTraceOf(long)[0].length < 100, `expect a truncated value, got ${moveTraceOf(long)[0].length} characters`);
+  const deep = new Error("1");
+  for (let i = 0; i < 100; i++) {
+    traceCall(deep, "0x1::m::f", {});
+  }
+  assert(moveTraceOf(deep).length === 33, `expect 32 frames and an ellipsis, got ${moveTraceOf(deep).length} lines`);
+  console.log("Passed: trace");
+}
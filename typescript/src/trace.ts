@@ -0,0 +1,56 @@
+import { HexString } from "aptos";
+import { UnsignedInt } from "./builtinTypes";
+
+/*
+Under --trace, each generated function rethrows what it throws through traceCall, which adds a line
+naming the Move function and its primitive arguments to the moveTrace of the error, and to its
+stack for display. The message is left alone, as expected_failure checks match the abort code in
+it, and the error itself is rethrown, so that catch handlers still see the same object
+*/
+const MAX_FRAMES = 32;
+const MAX_VALUE_LENGTH = 66;
+
+export type TracedError = Error & { moveTrace?: string[] };
+
+function showArg(value: any): string {
+  let shown: string;
+  if (value instanceof UnsignedInt) {
+    shown = value.value.toString();
+  }
+  else if (value instanceof HexString) {
+    shown = value.hex();
+  }
+  else {
+    shown = `${value}`;
+  }
+  return shown.length > MAX_VALUE_LENGTH ? `${shown.slice(0, MAX_VALUE_LENGTH)}...` : shown;
+}
+
+export function traceCall(e: unknown, name: string, args: Record<string, any>): unknown {
+  if (!(e instanceof Error)) {
+    return e;
+  }
+  const traced = e as TracedError;
+  const frames = traced.moveTrace ?? [];
+  let frame: string | null = null;
+  if (frames.length < MAX_FRAMES) {
+    const shownArgs = Object.keys(args).map(arg => `${arg}=${showArg(args[arg])}`).join(", ");
+    frame = `  at ${name}(${shownArgs})`;
+  }
+  else if (frames.length === MAX_FRAMES) {
+    frame = "  ...";
+  }
+  if (frame !== null) {
+    frames.push(frame);
+    if (traced.stack !== undefined) {
+      traced.stack += `\n${frame}`;
+    }
+  }
+  traced.moveTrace = frames;
+  return e;
+}
+
+// the Move frames traceCall recorded on an error, innermost first, or an empty list
+export function moveTraceOf(e: unknown): string[] {
+  return e instanceof Error ? (e as TracedError).moveTrace ?? [] : [];
+}
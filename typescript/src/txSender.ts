@@ -1,7 +1,25 @@
-import { AptosClient, AptosAccount, Types } from "aptos";
+import { AptosClient, AptosAccount, HexString, TransactionBuilderEd25519, TxnBuilderTypes, Types } from "aptos";
 import { Transaction, UserTransaction, WriteSetChange_WriteResource } from "aptos/dist/generated";
+import { abortErrorFromVmStatus } from "./abortErrors";
 import { AptosParserRepo, StructInfoType } from "./parserRepo";
-import { StructTag } from "./typeTag";
+import { isSameAddress, StructTag } from "./typeTag";
+
+/*
+Entry functions of a module, as listed by its ALL_ENTRY_FUNCTIONS. Kinds are Move types with
+vectors spelled out, e.g. "u64", "address" or "vector-u8"
+*/
+export type EntryParamDescriptor = {
+  name: string;
+  kind: string;
+};
+
+export type EntryFunctionDescriptor = {
+  name: string;
+  buildPayload: (...args: any[]) => Types.TransactionPayload;
+  typeParamCount: number;
+  // excluding the signers
+  params: EntryParamDescriptor[];
+};
 
 export async function sendAndWait(
   client: AptosClient,
@@ -63,27 +81,73 @@ export async function sendPayloadTx(
   console.log(txDetails);
 }
 
+// enough to simulate a transaction, for front ends that have no access to the private key
+export type SimulationKeys = {
+  publicKey: string;
+  address: HexString;
+};
+
+export type GasOptions = {
+  maxGas?: number;
+  gasPrice?: number;
+};
+
+function gasOptionsToExtraArgs(options?: GasOptions) {
+  const extraArgs: { max_gas_amount: string; gas_unit_price?: string } = {
+    max_gas_amount: `${options?.maxGas ?? 1000}`,
+  };
+  if (options?.gasPrice !== undefined) {
+    extraArgs.gas_unit_price = `${options.gasPrice}`;
+  }
+  return extraArgs;
+}
+
+export async function simulatePayloadTx(
+  client: AptosClient,
+  account: AptosAccount | SimulationKeys,
+  payload: Types.TransactionPayload,
+  max_gas?: number
+): Promise<Types.UserTransaction>;
+export async function simulatePayloadTx(
+  client: AptosClient,
+  account: AptosAccount | SimulationKeys,
+  payload: Types.TransactionPayload,
+  options?: GasOptions
+): Promise<Types.UserTransaction>;
 export async function simulatePayloadTx(
   client: AptosClient, 
-  account: AptosAccount, 
+  account: AptosAccount | SimulationKeys, 
   payload: Types.TransactionPayload, 
-  max_gas=1000
+  gas: number | GasOptions = {}
 ){
-  const txnRequest = await client.generateTransaction(account.address(), payload, {max_gas_amount: `${max_gas}`});
-  const outputs = await client.simulateTransaction(account, txnRequest);
+  const options = typeof gas === "number" ? { maxGas: gas } : gas;
+  // not instanceof, the app may load a different copy of the aptos package
+  if (!("publicKey" in account)) {
+    const txnRequest = await client.generateTransaction(account.address(), payload, gasOptionsToExtraArgs(options));
+    const outputs = await client.simulateTransaction(account, txnRequest);
+    return outputs[0];
+  }
+  const txnRequest = await client.generateTransaction(account.address, payload, gasOptionsToExtraArgs(options));
+  // the same all-zero signature simulateTransaction uses, the fullnode does not verify it
+  const builder = new TransactionBuilderEd25519(
+    () => new TxnBuilderTypes.Ed25519Signature(new Uint8Array(64)),
+    HexString.ensure(account.publicKey).toUint8Array()
+  );
+  const outputs = await client.submitBCSSimulation(builder.sign(txnRequest));
   return outputs[0];
 }
 
 export function takeSimulationValue<T>(tx: UserTransaction, tag: StructTag, repo: AptosParserRepo): T {
   if (!tx.success) {
-    throw new Error("Simulation failed");
+    // the class of the aborting module's error constant, when that module was translated
+    throw abortErrorFromVmStatus(tx.vm_status) ?? new Error("Simulation failed");
   }
   const valueData = tx.changes.filter(change => {
     if (change.type !== 'write_resource') {
       return false;
     }
     const wr = change as WriteSetChange_WriteResource;
-    return wr.data.type.address === tag.address.toShortString() && wr.data.type.module == tag.module && wr.data.type.name === tag.name;
+    return isSameAddress(wr.data.type.address, tag.address) && wr.data.type.module == tag.module && wr.data.type.name === tag.name;
   })
   if (valueData.length === 0) {
     throw new Error("Did not find output resource");
@@ -22,11 +22,11 @@ export class StructTag {
   }
   getFullname(): string {
     const typeParamString = getTypeParamsString(this.typeParams);
-    return `${this.address.hex()}::${this.module}::${this.name}${typeParamString}`;
+    return `${normalizeAddress(this.address)}::${this.module}::${this.name}${typeParamString}`;
   }
 
   getParamlessName(): string {
-    return `${this.address.hex()}::${this.module}::${this.name}`;
+    return `${normalizeAddress(this.address)}::${this.module}::${this.name}`;
   }
 
   getAptosMoveTypeTag(): MoveStructTag {
@@ -39,6 +39,25 @@ export class StructTag {
   }
 }
 
+/*
+Canonical (short, lowercase) form of an address, e.g. 0x1 for 0x0000...0001. This is the form used by
+the generated code, so addresses should go through here before being compared or used as keys
+*/
+export function normalizeAddress(address: HexString | string): string {
+  const hex = typeof address === 'string' ? new HexString(address).noPrefix() : address.noPrefix();
+  const trimmed = hex.toLowerCase().replace(/^0+/, '');
+  return `0x${trimmed === '' ? '0' : trimmed}`;
+}
+
+export function isSameAddress(a: HexString | string, b: HexString | string): boolean {
+  return normalizeAddress(a) === normalizeAddress(b);
+}
+
+// rewrites every address inside a type string, e.g. from an API response, into its canonical form
+export function normalizeTypeString(typeString: string): string {
+  return typeString.replace(/\b0x[0-9a-fA-F]+\b/g, normalizeAddress);
+}
+
 export class VectorTag {
   constructor(
     public elementType: TypeTag
@@ -250,6 +269,16 @@ export function parseResourceType(fullname: string): StructTag {
   return result;
 }
 
+export function tryParseStructTag(fullname: string): StructTag | null {
+  try {
+    const tag = parseTypeTagOrThrow(fullname);
+    return tag instanceof StructTag ? tag : null;
+  }
+  catch (e) {
+    return null;
+  }
+}
+
 export function substituteTypeParams(toSubstitute: TypeTag, typeParams: TypeTag[]): TypeTag {
   if(toSubstitute instanceof StructTag) {
     let params = toSubstitute.typeParams.map(p=>substituteTypeParams(p, typeParams));